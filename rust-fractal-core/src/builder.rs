@@ -0,0 +1,189 @@
+use crate::renderer::{FractalRenderer, PixelOrder};
+use crate::util::data_export::ExportType;
+use crate::util::{ComplexArbitrary, FloatExtended, extended_to_string_long};
+
+use config::{Config, File, FileFormat, Value};
+
+const DEFAULT_SETTINGS: &str = include_str!("../data/default.toml");
+
+/// Builds a renderer without writing the settings files. The defaults are those of `default.toml`, except that
+/// nothing is printed while rendering and the frames are not added to the history. Any setting without a typed
+/// setter can be given with [`setting`].
+///
+/// ```no_run
+/// use rust_fractal_core::FractalRendererBuilder;
+///
+/// let mut renderer = FractalRendererBuilder::new()
+///     .image_size(1920, 1080)
+///     .centre("-1.7490930", "0.0000217")
+///     .zoom("1E5")
+///     .iterations(20000)
+///     .filename_template("output/spiral")
+///     .build();
+///
+/// renderer.render();
+/// ```
+///
+/// [`setting`]: FractalRendererBuilder::setting
+pub struct FractalRendererBuilder {
+    settings: Config,
+}
+
+impl FractalRendererBuilder {
+    pub fn new() -> Self {
+        let mut settings = Config::default();
+
+        settings.merge(File::from_str(DEFAULT_SETTINGS, FileFormat::Toml)).unwrap();
+        settings.set("show_output", false).unwrap();
+        settings.set("history", false).unwrap();
+
+        FractalRendererBuilder {
+            settings,
+        }
+    }
+
+    /// Starts from existing settings, such as those read from an options and a location file.
+    pub fn from_settings(settings: Config) -> Self {
+        FractalRendererBuilder {
+            settings,
+        }
+    }
+
+    /// Sets any of the keys read from the settings files.
+    pub fn setting<T: Into<Value>>(mut self, key: &str, value: T) -> Self {
+        self.settings.set(key, value).unwrap();
+        self
+    }
+
+    pub fn image_width(self, image_width: usize) -> Self {
+        self.setting("image_width", image_width as i64)
+    }
+
+    pub fn image_height(self, image_height: usize) -> Self {
+        self.setting("image_height", image_height as i64)
+    }
+
+    pub fn image_size(self, image_width: usize, image_height: usize) -> Self {
+        self.image_width(image_width).image_height(image_height)
+    }
+
+    /// The centre as decimal strings, which can have as many digits as the zoom needs.
+    pub fn centre(self, real: &str, imag: &str) -> Self {
+        self.setting("real", real).setting("imag", imag)
+    }
+
+    /// The centre as an arbitrary precision value, all of its digits are kept.
+    pub fn centre_arbitrary(self, centre: &ComplexArbitrary) -> Self {
+        let real = centre.real().to_string_radix(10, None);
+        let imag = centre.imag().to_string_radix(10, None);
+
+        self.centre(&real, &imag)
+    }
+
+    /// Renders the Julia set with the seed instead of the Mandelbrot set, the centre is then the location in the
+    /// Julia set.
+    pub fn julia_seed(self, seed: &ComplexArbitrary) -> Self {
+        let real = seed.real().to_string_radix(10, None);
+        let imag = seed.imag().to_string_radix(10, None);
+
+        self.setting("julia_real", real).setting("julia_imag", imag)
+    }
+
+    /// The zoom in the same format as the location files, for example `"1.5E1000"`.
+    pub fn zoom(self, zoom: &str) -> Self {
+        self.setting("zoom", zoom)
+    }
+
+    pub fn zoom_extended(self, zoom: FloatExtended) -> Self {
+        self.zoom(&extended_to_string_long(zoom))
+    }
+
+    pub fn iterations(self, maximum_iteration: usize) -> Self {
+        self.setting("iterations", maximum_iteration as i64)
+    }
+
+    /// The rotation in degrees.
+    pub fn rotate(self, rotate: f64) -> Self {
+        self.setting("rotate", rotate)
+    }
+
+    /// The colours of the palette, which are interpolated between. The palette repeats every span of iterations.
+    pub fn palette(self, colours: &[(u8, u8, u8)]) -> Self {
+        let values = colours.iter()
+            .flat_map(|&(r, g, b)| vec![r as i64, g as i64, b as i64])
+            .collect::<Vec<i64>>();
+
+        self.setting("palette", values)
+    }
+
+    /// Chooses one of the built in palettes by name, such as `"fire"` or `"ocean"`, which is used unless the colours
+    /// are also given.
+    pub fn palette_name(self, name: &str) -> Self {
+        self.setting("palette_name", name)
+    }
+
+    pub fn palette_iteration_span(self, palette_iteration_span: f64) -> Self {
+        self.setting("palette_iteration_span", palette_iteration_span)
+    }
+
+    pub fn palette_offset(self, palette_offset: f64) -> Self {
+        self.setting("palette_offset", palette_offset)
+    }
+
+    pub fn approximation_order(self, approximation_order: usize) -> Self {
+        self.setting("approximation_order", approximation_order as i64)
+    }
+
+    pub fn export(self, export_type: ExportType) -> Self {
+        let export = match export_type {
+            ExportType::Color => "png",
+            ExportType::Raw => "exr",
+            ExportType::Both => "both",
+            ExportType::Gui => "gui"
+        };
+
+        self.setting("export", export)
+    }
+
+    /// The template of the output files, see `default.toml` for the fields it can contain.
+    pub fn filename_template(self, filename_template: &str) -> Self {
+        self.setting("filename_template", filename_template)
+    }
+
+    /// The number of frames of a sequence zooming out by the zoom scale each frame.
+    pub fn frames(self, frames: usize, zoom_scale: f64) -> Self {
+        self.setting("frames", frames as i64).setting("zoom_scale", zoom_scale)
+    }
+
+    pub fn pixel_order(self, pixel_order: PixelOrder) -> Self {
+        let pixel_order = match pixel_order {
+            PixelOrder::Scanline => "scanline",
+            PixelOrder::Random => "random",
+            PixelOrder::Spiral => "spiral",
+            PixelOrder::Hilbert => "hilbert",
+            PixelOrder::Tiled => "tiled"
+        };
+
+        self.setting("pixel_order", pixel_order)
+    }
+
+    /// Renders on a pool of this many threads, owned by the renderer. Zero uses every core.
+    pub fn threads(self, threads: usize) -> Self {
+        self.setting("threads", threads as i64)
+    }
+
+    /// Prints the table of stage timings while rendering, as the command line renderer does in verbose mode.
+    pub fn show_output(self, show_output: bool) -> Self {
+        self.setting("show_output", show_output)
+    }
+
+    pub fn build(self) -> FractalRenderer {
+        FractalRenderer::new(self.settings)
+    }
+}
+
+impl Default for FractalRendererBuilder {
+    fn default() -> Self {
+        FractalRendererBuilder::new()
+    }
+}
@@ -0,0 +1,39 @@
+//! The rendering core of rust-fractal, a mandelbrot set renderer using perturbation theory and series approximation.
+//!
+//! A render is configured with the same settings as the command line renderer, either directly or through the
+//! builder, and the results are written to the files named by the settings or read from the data export afterwards:
+//!
+//! ```no_run
+//! use rust_fractal_core::FractalRendererBuilder;
+//!
+//! let mut renderer = FractalRendererBuilder::new()
+//!     .centre("-0.75", "0.1")
+//!     .zoom("1E10")
+//!     .iterations(10000)
+//!     .build();
+//!
+//! renderer.render();
+//!
+//! let iterations = renderer.data_export.lock().iterations.clone();
+//! ```
+//!
+//! The items exported from the crate root are the stable interface. The modules are public so that the command
+//! line renderer and other tools can reach the internals, but their contents may change between minor versions.
+//!
+//! The arbitrary precision values use rug (GMP and MPFR) with the default `mpfr` feature. Without it, for example for
+//! wasm32, `FloatArbitrary` and `ComplexArbitrary` are the slower pure Rust floats of `util::arbitrary`, which have
+//! the same methods as rug for the operations the renderer uses.
+
+pub mod renderer;
+pub mod builder;
+pub mod util;
+pub mod math;
+
+pub use config::Config;
+
+pub use renderer::{FractalRenderer, PixelOrder, Recentre, KeyframeIterations};
+pub use builder::FractalRendererBuilder;
+pub use math::{Reference, SeriesApproximation, Perturbation};
+pub use util::data_export::{DataExport, ExportType, ColoringType, DataType, HeightmapType, RenderedImage, RawChannels, CompletedRegion};
+pub use util::{ComplexArbitrary, ComplexExtended, ComplexFixed, FloatArbitrary, FloatExtended, FractalType, PixelData};
+pub use util::{ProgressReport, RenderStage, RenderStatistics, RenderParameters};
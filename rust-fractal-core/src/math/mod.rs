@@ -3,7 +3,7 @@ mod perturbation;
 mod series_approximation;
 mod root_finding;
 
-pub use reference::Reference;
+pub use reference::{Reference, ReferenceIteration};
 pub use perturbation::Perturbation;
 pub use series_approximation::SeriesApproximation;
 pub use root_finding::{BoxPeriod, BallMethod, get_nucleus, get_nucleus_position};
\ No newline at end of file
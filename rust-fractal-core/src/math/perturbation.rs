@@ -16,7 +16,13 @@ const ESCAPE_RADIUS: f64 = 1e16;
 pub struct Perturbation {}
 
 impl Perturbation {
+    // The series approximation is only evaluated in the initial pass. The correction passes reuse the deltas stored
+    // in the pixel data, which are rebased onto the glitch resolving reference in resolve_glitches. A Julia set
+    // has no series approximation, so its pixels start from the deltas they were generated with.
     pub fn iterate(pixel_data: &mut [PixelData], reference: &Reference, pixels_complete: &Arc<AtomicUsize>, stop_flag: &Arc<AtomicBool>, data_export: Arc<Mutex<DataExport>>, delta_pixel: FloatExtended, scale: usize, chunk_size: usize, _fractal_type: FractalType, data_type: DataType, series_approximation: &SeriesApproximation, initial: bool) {
+        // The derivative of a Julia set is taken with respect to the starting value rather than c
+        let derivative_increment = if reference.julia { 0.0 } else { 1.0f64 };
+
         match data_type {
             DataType::Iteration => {
                 pixel_data.par_chunks_mut(chunk_size)
@@ -32,7 +38,7 @@ impl Perturbation {
                             break;
                         };
 
-                        if initial {
+                        if initial && !reference.julia {
                             pixel.delta_current = series_approximation.evaluate(pixel.delta_reference, pixel.iteration);
                         }
     
@@ -189,7 +195,7 @@ impl Perturbation {
                             break;
                         };
 
-                        if initial {
+                        if initial && !reference.julia {
                             pixel.delta_current = series_approximation.evaluate(pixel.delta_reference, pixel.iteration);
                             pixel.derivative_current = series_approximation.evaluate_derivative(pixel.delta_reference, pixel.iteration);
                         }
@@ -201,7 +207,7 @@ impl Perturbation {
     
                         // Scaled factors and reference values for the scaled double implementation
                         let mut scaled_scale_factor_1 = 1.0f64.ldexp(pixel.delta_current.exponent);
-                        let mut scaled_scale_factor_2 = 1.0f64.ldexp(-pixel.derivative_current.exponent);
+                        let mut scaled_scale_factor_2 = derivative_increment.ldexp(-pixel.derivative_current.exponent);
                         let mut scaled_delta_reference = 1.0f64.ldexp(pixel.delta_reference.exponent - pixel.delta_current.exponent) * pixel.delta_reference.mantissa;
     
                         // Get the reference slice that is worked on
@@ -315,7 +321,7 @@ impl Perturbation {
                                 }
     
                                 pixel.derivative_current *= (reference.reference_data_extended[val1 + additional_iterations] + pixel.delta_current) * 2.0;
-                                pixel.derivative_current += ComplexExtended::new2(1.0, 0.0, 0);
+                                pixel.derivative_current += ComplexExtended::new2(derivative_increment, 0.0, 0);
     
                                 pixel.delta_current *= reference.reference_data_extended[val1 + additional_iterations] * 2.0 + pixel.delta_current;
                                 pixel.delta_current += pixel.delta_reference;
@@ -334,7 +340,7 @@ impl Perturbation {
                             pixel.derivative_current.reduce();
     
                             scaled_scale_factor_1 = 1.0f64.ldexp(pixel.delta_current.exponent);
-                            scaled_scale_factor_2 = 1.0f64.ldexp(-pixel.derivative_current.exponent);
+                            scaled_scale_factor_2 = derivative_increment.ldexp(-pixel.derivative_current.exponent);
                             scaled_delta_reference = 1.0f64.ldexp(pixel.delta_reference.exponent - pixel.delta_current.exponent) * pixel.delta_reference.mantissa;
                         }
     
@@ -359,7 +365,7 @@ impl Perturbation {
                             break;
                         };
 
-                        if initial {
+                        if initial && !reference.julia {
                             pixel.delta_current = series_approximation.evaluate(pixel.delta_reference, pixel.iteration);
                         }
     
@@ -521,7 +527,7 @@ impl Perturbation {
                             break;
                         };
 
-                        if initial {
+                        if initial && !reference.julia {
                             pixel.delta_current = series_approximation.evaluate(pixel.delta_reference, pixel.iteration);
                             pixel.derivative_current = series_approximation.evaluate_derivative(pixel.delta_reference, pixel.iteration);
                         }
@@ -533,7 +539,7 @@ impl Perturbation {
     
                         // Scaled factors and reference values for the scaled double implementation
                         let mut scaled_scale_factor_1 = 1.0f64.ldexp(pixel.delta_current.exponent);
-                        let mut scaled_scale_factor_2 = 1.0f64.ldexp(-pixel.derivative_current.exponent);
+                        let mut scaled_scale_factor_2 = derivative_increment.ldexp(-pixel.derivative_current.exponent);
                         let mut scaled_delta_reference = 1.0f64.ldexp(pixel.delta_reference.exponent - pixel.delta_current.exponent) * pixel.delta_reference.mantissa;
     
                         // Get the reference slice that is worked on
@@ -657,7 +663,7 @@ impl Perturbation {
                                 }
 
                                 pixel.derivative_current *= (reference.reference_data_extended[val1 + additional_iterations] + pixel.delta_current) * 2.0;
-                                pixel.derivative_current += ComplexExtended::new2(1.0, 0.0, 0);
+                                pixel.derivative_current += ComplexExtended::new2(derivative_increment, 0.0, 0);
     
                                 pixel.delta_current *= reference.reference_data_extended[val1 + additional_iterations] * 2.0 + pixel.delta_current;
                                 pixel.delta_current += pixel.delta_reference;
@@ -676,7 +682,7 @@ impl Perturbation {
                             pixel.derivative_current.reduce();
 
                             scaled_scale_factor_1 = 1.0f64.ldexp(pixel.delta_current.exponent);
-                            scaled_scale_factor_2 = 1.0f64.ldexp(-pixel.derivative_current.exponent);
+                            scaled_scale_factor_2 = derivative_increment.ldexp(-pixel.derivative_current.exponent);
                             scaled_delta_reference = 1.0f64.ldexp(pixel.delta_reference.exponent - pixel.delta_current.exponent) * pixel.delta_reference.mantissa;
                         }
 
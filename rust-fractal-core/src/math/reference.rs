@@ -17,6 +17,8 @@ pub struct Reference {
     pub data_storage_interval: usize,
     pub high_precision_data: Vec<ComplexArbitrary>,
     pub glitch_tolerance: f64,
+    // For a Julia set the orbit starts at the centre and c is the seed, otherwise both start at the centre
+    pub julia: bool,
 }
 
 #[derive(Clone)]
@@ -39,7 +41,18 @@ impl Reference {
             zoom,
             data_storage_interval,
             high_precision_data: Vec::new(),
-            glitch_tolerance
+            glitch_tolerance,
+            julia: false,
+        }
+    }
+
+    // The centre of the view. The orbit of a Julia set starts at the centre, which is the first of the stored values
+    // once the reference has been run.
+    pub fn get_location(&self) -> ComplexArbitrary {
+        if self.julia {
+            self.high_precision_data.first().cloned().unwrap_or_else(|| self.z.clone())
+        } else {
+            self.c.clone()
         }
     }
 
@@ -132,7 +145,10 @@ impl Reference {
         let reference_c = self.c.clone();
         let reference_z = self.high_precision_data[(iteration - self.start_iteration) / self.data_storage_interval].clone();
 
-        Reference::new(reference_z, reference_c, iteration_reference, self.maximum_iteration, 1, self.glitch_tolerance, self.zoom)
+        let mut reference = Reference::new(reference_z, reference_c, iteration_reference, self.maximum_iteration, 1, self.glitch_tolerance, self.zoom);
+        reference.julia = self.julia;
+
+        reference
     }
 
     // This is for use when getting new references others with full data
@@ -159,7 +175,10 @@ impl Reference {
         *reference_z.mut_real() += &temp2 * &temp;
         *reference_z.mut_imag() += &temp3 * &temp;
 
-        Reference::new(reference_z, reference_c, iteration, self.maximum_iteration, 1, self.glitch_tolerance, self.zoom)
+        let mut reference = Reference::new(reference_z, reference_c, iteration, self.maximum_iteration, 1, self.glitch_tolerance, self.zoom);
+        reference.julia = self.julia;
+
+        reference
     }
 }
 
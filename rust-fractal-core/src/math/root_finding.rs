@@ -1,5 +1,4 @@
 use std::{cmp::max};
-use std::time::Instant;
 
 use std::sync::Arc;
 use std::sync::atomic::{Ordering, AtomicUsize, AtomicBool};
@@ -121,6 +120,7 @@ pub struct BallMethod {
     pub point_z: ComplexExtended,
     pub point_dz: ComplexExtended,
     pub period: usize,
+    pub found: bool,
 }
 
 impl BallMethod {
@@ -134,14 +134,14 @@ impl BallMethod {
             point_c,
             point_z: point_c,
             point_dz: ComplexExtended::new2(1.0, 0.0, 0),
-            period: 1
+            period: 1,
+            found: false
         }
     }
 
     pub fn find_period(&mut self, reference: &Reference) {
-        let time = Instant::now();
-
         self.period = 1;
+        self.found = false;
 
         for k in 0..reference.current_iteration {
             self.radius_dz = self.point_dz.norm();
@@ -155,7 +155,7 @@ impl BallMethod {
 
             if self.radius_radius > self.radius_z {
                 self.period = k + 1;
-                println!("ball method period is: {}", self.period);
+                self.found = true;
                 break;
             }
 
@@ -170,8 +170,6 @@ impl BallMethod {
             self.point_z += self.point_c;
             self.point_z.reduce();
         }
-
-        println!("period: {}ms", time.elapsed().as_millis());
     }
 }
 
@@ -4,7 +4,7 @@ use crate::math::reference::Reference;
 use crate::util::float_extended::FloatExtended;
 use rayon::prelude::*;
 
-use std::cmp::max;
+use std::cmp::{max, min};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicUsize, AtomicBool, Ordering};
 
@@ -25,6 +25,7 @@ pub struct SeriesApproximation {
     pub tiled: bool,
     pub enabled: bool,
     pub valid_iteration_probe_multiplier: f32,
+    pub maximum_skip: f64,
     pub data_storage_interval: usize,
     pub fractal_type: FractalType
 }
@@ -37,6 +38,7 @@ impl SeriesApproximation {
         tiled: bool, 
         enabled: bool,
         valid_iteration_probe_multiplier: f32,
+        maximum_skip: f64,
         data_storage_interval: usize,
         fractal_type: FractalType) -> Self {
 
@@ -58,6 +60,7 @@ impl SeriesApproximation {
             tiled,
             enabled,
             valid_iteration_probe_multiplier,
+            maximum_skip,
             data_storage_interval,
             fractal_type
         }
@@ -339,6 +342,20 @@ impl SeriesApproximation {
         self.valid_iterations = valid_iterations;
         self.interpolate_probes();
 
+        // The skip can be limited to a fraction of the reference, in case the probes overestimate it for a location
+        if self.maximum_skip < 1.0 {
+            let maximum_skip = (self.maximum_skip.max(0.0) * center_reference.current_iteration as f64) as usize;
+
+            // Rounded down to an iteration which has stored reference data
+            let maximum_skip = self.data_storage_interval * ((maximum_skip.max(1) - 1) / self.data_storage_interval) + 1;
+
+            self.min_valid_iteration = min(self.min_valid_iteration, maximum_skip);
+
+            for valid_iteration in self.valid_iterations.iter_mut().chain(self.valid_interpolation.iter_mut()) {
+                *valid_iteration = min(*valid_iteration, maximum_skip);
+            }
+        }
+
         self.max_valid_iteration = if self.tiled {
             *self.valid_interpolation.iter().max().unwrap()
         } else {
@@ -0,0 +1,3890 @@
+use crate::util::{ComplexArbitrary, FloatArbitrary, ComplexFixed, FractalType, PixelData, ProgressCounters, ProgressReport, RenderStage, ComplexExtended, FloatExtended, data_export::*, extended_to_string_long, extended_to_string_short, get_palette, get_approximation_terms, get_delta_top_left, get_exponential_map_delta, get_exponential_map_scale_factor, get_zoom_scale_between, parse_resolution, parse_zoom, pixel_to_complex, string_to_extended, to_extended};
+use crate::util::mesh_export::MeshType;
+use crate::util::distributed::{WorkUnit, run_coordinator};
+use crate::util::history::{HistoryEntry, append_history};
+use crate::util::log::{log_event, log_warning};
+use crate::util::affinity::{get_pinned_cpus, pin_current_thread};
+use crate::util::reference_orbit::ReferenceOrbit;
+use crate::util::{RenderStatistics, MemoryEstimate, LocationAnalysis, Easing, CameraPath, SequenceManifest, RenderCheckpoint, ReferenceCache, RenderParameters, ColoringPlugin, Validation, statistics::get_peak_memory, linear_interpolation_between_zoom, srgb_to_linear, linear_to_srgb};
+use crate::math::{SeriesApproximation, Perturbation, Reference, ReferenceIteration, BoxPeriod, BallMethod, get_nucleus, get_nucleus_position};
+
+use std::{sync::{atomic::AtomicBool}, time::Duration};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::cmp::{min, max};
+
+use rand::seq::SliceRandom;
+use rand_distr::Distribution;
+use rand::{SeedableRng, rngs::StdRng};
+
+use rayon::prelude::*;
+use rayon::ThreadPool;
+use config::{Config, File, FileFormat};
+use serde_json::json;
+
+use std::thread;
+use std::sync::{Arc, mpsc};
+use std::sync::atomic::{Ordering, AtomicUsize};
+use std::collections::BTreeMap;
+use std::f64::consts::PI;
+
+// The clock of std panics on wasm32, where the time is taken from the browser instead
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use instant::Instant;
+
+use parking_lot::Mutex;
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum PixelOrder {
+    Scanline,
+    Random,
+    Spiral,
+    Hilbert,
+    Tiled
+}
+
+// The side length of the tiles which the pixels are iterated in with the tiled pixel order
+pub const TILE_SIZE: usize = 64;
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum Recentre {
+    None,
+    Iteration,
+    Nucleus
+}
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum KeyframeIterations {
+    Constant,
+    Depth,
+    Measured
+}
+
+/// Renders a location, or a sequence of frames zooming out from it, with the settings of the options and location files.
+pub struct FractalRenderer {
+    pub image_width: usize,
+    pub image_height: usize,
+    pub total_pixels: usize,
+    pub rotate: f64,
+    pub zoom: FloatExtended,
+    pub auto_adjust_iterations: bool,
+    pub automatic_iterations: bool,
+    pub automatic_iterations_threshold: f64,
+    pub automatic_iterations_limit: usize,
+    automatic_continuation: Option<Vec<bool>>,
+    pub maximum_iteration: usize,
+    pub glitch_percentage: f64,
+    pub data_export: Arc<Mutex<DataExport>>,
+    pub remaining_frames: usize,
+    frame_offset: usize,
+    pub zoom_scale_factor: f64,
+    pub center_reference: Reference,
+    pub series_approximation: SeriesApproximation,
+    pub period_finding: BoxPeriod,
+    render_indices: Vec<usize>,
+    pub remove_centre: bool,
+    pub pixel_data_type: DataType,
+    pub jitter: bool,
+    pub jitter_factor: f64,
+    show_output: bool,
+    pub progress: ProgressCounters,
+    pub render_time: u128,
+    pub fractal_type: FractalType,
+    pub root_zoom_factor: f64,
+    pub name: String,
+    pub filename_template: String,
+    pub overwrite_protection: bool,
+    pub export_statistics: bool,
+    pub statistics: RenderStatistics,
+    glitched_pixels_per_pass: Mutex<Vec<usize>>,
+    pub log_json: bool,
+    log_stage: Mutex<Option<(RenderStage, Instant)>>,
+    pub exponential_map: bool,
+    pub end_zoom: FloatExtended,
+    pub resume: bool,
+    pub parallel_keyframes: usize,
+    pub parallel_keyframe_zoom: f64,
+    pub keyframe_iterations: KeyframeIterations,
+    initial_maximum_iteration: usize,
+    initial_zoom: FloatExtended,
+    pub zoom_easing: Easing,
+    pub rotate_easing: Easing,
+    rotate_start: f64,
+    rotate_end: Option<f64>,
+    pub palette_easing: Easing,
+    palette_start: (f32, f32),
+    palette_end: (Option<f32>, Option<f32>),
+    sequence_frames: usize,
+    sequence_start_zoom: FloatExtended,
+    sequence_end_zoom: Option<FloatExtended>,
+    pub camera_path: Option<CameraPath>,
+    pub motion_blur_samples: usize,
+    pub motion_blur_shutter: f64,
+    pub recentre: Recentre,
+    pub recentre_radius: f64,
+    pub image_offset: (f64, f64),
+    pub crop_margin: usize,
+    pub supersampling: usize,
+    pub pixel_chunk_size: usize,
+    pub solid_guessing: bool,
+    pub solid_guessing_tile: usize,
+    pub progressive: bool,
+    pub progressive_export: bool,
+    pub pixel_order: PixelOrder,
+    pub adaptive_antialiasing_samples: usize,
+    pub adaptive_antialiasing_threshold: f64,
+    pub samples_per_pixel: usize,
+    pub jitter_seed: Option<u64>,
+    pub deterministic: bool,
+    pub memory_limit: usize,
+    pub threads: usize,
+    thread_pool: Option<Arc<ThreadPool>>,
+    pub stop_flag: Arc<AtomicBool>,
+    pub cancelled: bool,
+    pub checkpoint_interval: usize,
+    pub continue_from: Option<String>,
+    pub reference_cache: Option<ReferenceCache>,
+    current_filename: String,
+    progress_sender: Mutex<Option<mpsc::Sender<ProgressReport>>>,
+    region_sender: Mutex<Option<mpsc::Sender<CompletedRegion>>>,
+    save_output: bool,
+    pub manifest: Option<SequenceManifest>,
+    pub zoom_in: bool,
+    pub reverse_numbering: bool,
+    settings: Config,
+    pub parameters: RenderParameters,
+}
+
+impl FractalRenderer {
+    /// Creates a renderer if the settings are valid, otherwise returns the problems with them.
+    pub fn try_new(settings: Config) -> Result<Self, Validation> {
+        let validation = Validation::check(&settings);
+
+        if validation.is_valid() {
+            Ok(FractalRenderer::new(settings))
+        } else {
+            Err(validation)
+        }
+    }
+
+    /// Creates a renderer from the settings, which use the same keys as `default.toml` and the location files.
+    pub fn new(mut settings: Config) -> Self {
+        FractalRenderer::apply_output_directory(&mut settings);
+        FractalRenderer::apply_region(&mut settings);
+        FractalRenderer::apply_view_fit(&mut settings);
+
+        let parameters = RenderParameters::from_config(&settings);
+
+        let exponential_map = parameters.exponential_map;
+
+        // The image is rendered at a multiple of the output size when supersampling
+        let supersampling = max(parameters.supersampling, 1);
+
+        // The margin is added around the requested image size, the scale of the inner crop window is unchanged
+        let crop_margin = FractalRenderer::get_crop_margin(&settings, exponential_map) * supersampling;
+        let image_width = parameters.image_width * supersampling + 2 * crop_margin;
+        let image_height = parameters.image_height * supersampling + 2 * crop_margin;
+
+        let (offset_x, offset_y) = parameters.get_image_offset();
+        let image_offset = (offset_x * supersampling as f64, offset_y * supersampling as f64);
+
+        let rotate = parameters.rotate.to_radians();
+        let maximum_iteration = parameters.iterations;
+
+        let initial_zoom = parameters.zoom.to_ascii_uppercase();
+        let center_real = parameters.real.clone();
+        let center_imag = parameters.imag.clone();
+
+        let approximation_order = parameters.approximation_order;
+        let glitch_percentage = parameters.glitch_percentage;
+        let frame_offset = parameters.frame_offset;
+
+        let (remaining_frames, zoom_scale_factor, end_zoom) = FractalRenderer::get_sequence_range(&settings, &initial_zoom, parameters.end_zoom.as_deref(), exponential_map, image_width, image_height);
+
+        let display_glitches = parameters.display_glitches;
+
+        let auto_adjust_iterations = parameters.auto_adjust_iterations;
+        let series_approximation_tiled = parameters.series_approximation_tiled;
+        // The series approximation is derived for the Mandelbrot set, so it is not used for Julia sets
+        let series_approximation_enabled = parameters.series_approximation_enabled && !FractalRenderer::is_julia(&parameters);
+
+        let probe_sampling = parameters.probe_sampling;
+
+        let palette_iteration_span = parameters.palette_iteration_span as f32;
+        let palette_offset = parameters.palette_offset as f32;
+        let palette_cyclic = parameters.palette_cyclic;
+
+        let distance_color = parameters.distance_color;
+
+        let lighting = parameters.lighting;
+
+        let lighting_direction = parameters.lighting_direction as f32;
+        let lighting_azimuth = parameters.lighting_azimuth as f32;
+        let lighting_opacity = parameters.lighting_opacity as f32;
+        let lighting_ambient = parameters.lighting_ambient as f32;
+        let lighting_diffuse = parameters.lighting_diffuse as f32;
+        let lighting_specular = parameters.lighting_specular as f32;
+        let lighting_shininess = parameters.lighting_shininess;
+
+        let distance_transition = parameters.distance_transition as f32;
+
+        let valid_iteration_probe_multiplier = parameters.valid_iteration_probe_multiplier as f32;
+        let glitch_tolerance = parameters.glitch_tolerance;
+        let data_storage_interval = parameters.data_storage_interval;
+
+        let coloring_type = match parameters.coloring_type.to_ascii_uppercase().as_ref() {
+            "SMOOTH_ITERATION" | "SMOOTH" => ColoringType::SmoothIteration,
+            "STEP_ITERATION" | "STEP" => ColoringType::StepIteration,
+            "DISTANCE" => ColoringType::Distance,
+            "STRIPE" => ColoringType::Stripe,
+            "DISTANCE_STRIPE" => ColoringType::DistanceStripe,
+            _ => ColoringType::SmoothIteration
+        };
+
+        let pixel_data_type = match coloring_type {
+            ColoringType::SmoothIteration | ColoringType::StepIteration => DataType::Iteration,
+            ColoringType::Stripe => DataType::Stripe,
+            ColoringType::DistanceStripe => DataType::DistanceStripe,
+            _ => DataType::Distance
+        };
+
+        let stripe_scale = parameters.stripe_scale as f32;
+
+        let heightmap_type = match parameters.heightmap.to_ascii_uppercase().as_ref() {
+            "ITERATION" | "SMOOTH" => HeightmapType::Iteration,
+            "DISTANCE" => HeightmapType::Distance,
+            _ => HeightmapType::None
+        };
+
+        let mesh_type = match parameters.mesh.to_ascii_uppercase().as_ref() {
+            "OBJ" => MeshType::Obj,
+            "STL" => MeshType::Stl,
+            _ => MeshType::None
+        };
+
+        let mesh_scale = parameters.mesh_scale as f32;
+        let mesh_decimation = parameters.mesh_decimation;
+
+        let additional_resolutions = FractalRenderer::parse_additional_resolutions(&settings);
+
+        let jitter = parameters.jitter;
+        let jitter_factor = parameters.jitter_factor;
+        let show_output = parameters.show_output;
+        let log_json = parameters.log_format.eq_ignore_ascii_case("json");
+
+        let name = parameters.name.clone();
+        let filename_template = parameters.filename_template.clone();
+        let manifest = FractalRenderer::get_manifest(&settings, &name);
+        let overwrite_protection = parameters.overwrite_protection;
+        let export_statistics = parameters.export_statistics;
+        let export_glitch_map = parameters.export_glitch_map;
+        let resume = parameters.resume;
+
+        let reference_cache = if parameters.reference_cache {
+            Some(ReferenceCache::new(&parameters.reference_cache_directory))
+        } else {
+            None
+        };
+        let parallel_keyframes = parameters.parallel_keyframes;
+        let parallel_keyframe_zoom = parameters.parallel_keyframe_zoom;
+        let keyframe_iterations = FractalRenderer::parse_keyframe_iterations(&settings);
+        let zoom_easing = Easing::from_settings(&settings, "zoom_easing", "zoom_curve");
+        let rotate_easing = Easing::from_settings(&settings, "rotate_easing", "rotate_curve");
+        let rotate_end = parameters.end_rotate.map(|angle| angle.to_radians());
+        // The centre of a frame is only the previous frame scaled down when every frame zooms by the same factor
+        // without turning or moving
+        let remove_centre = parameters.remove_centre && !exponential_map && zoom_easing == Easing::Linear && rotate_end.is_none()
+            && FractalRenderer::parse_recentre(&settings) == Recentre::None;
+        let palette_easing = Easing::from_settings(&settings, "palette_easing", "palette_curve");
+        let palette_end = (
+            parameters.end_palette_iteration_span.map(|span| span as f32), 
+            parameters.end_palette_offset.map(|offset| offset as f32));
+        let sequence_end_zoom = parameters.end_zoom.as_ref().map(|zoom| string_to_extended(&zoom.to_ascii_uppercase()));
+        
+        let export_type = match parameters.export.to_ascii_uppercase().as_ref() {
+            "GUI" => ExportType::Gui,
+            "RAW" | "EXR" => ExportType::Raw,
+            "BOTH" => ExportType::Both,
+            _ => ExportType::Color
+        };
+
+        let (palette_buffer, palette_interpolated_buffer) = get_palette(&settings);
+
+        let mut zoom = string_to_extended(&initial_zoom);
+        let precision = FractalRenderer::get_precision(zoom, image_width, image_height);
+
+        let center_location = ComplexArbitrary::with_val(
+            precision,
+            ComplexArbitrary::parse("(".to_owned() + &center_real + "," + &center_imag + ")").expect("provided location not valid"));
+        let auto_approximation = get_approximation_terms(approximation_order, parameters.approximation_aggressiveness, image_width, image_height);
+
+        let julia_seed = FractalRenderer::get_julia_seed(&parameters, precision);
+
+        let mut reference = Reference::new(center_location.clone(), 
+            julia_seed.clone().unwrap_or(center_location), 
+            1, 
+            maximum_iteration, 
+            data_storage_interval,
+            glitch_tolerance,
+            zoom);
+
+        reference.julia = julia_seed.is_some();
+
+        let fractal_type = FractalType::Mandelbrot2;
+
+        let series_approximation = SeriesApproximation::new_central(auto_approximation, 
+            maximum_iteration, 
+            FloatExtended::new(0.0, 0), 
+            probe_sampling,
+            series_approximation_tiled,
+            series_approximation_enabled,
+            valid_iteration_probe_multiplier,
+            parameters.approximation_maximum_skip,
+            data_storage_interval,
+            fractal_type);
+
+        let temporary_delta = ComplexExtended::new2(0.0, 0.0, 0);
+
+        let period_finding = BoxPeriod::new(temporary_delta, [temporary_delta, temporary_delta, temporary_delta, temporary_delta]);
+
+        let progressive = parameters.progressive;
+        let progressive_export = parameters.progressive_export;
+        let pixel_order = FractalRenderer::parse_pixel_order(&settings);
+
+        let render_indices = FractalRenderer::generate_render_indices(image_width, image_height, remove_centre, zoom_scale_factor, export_type == ExportType::Gui || progressive, pixel_order);
+
+        // Change the zoom level to the correct one for the frame offset
+        for _ in 0..frame_offset {
+            zoom.mantissa /= zoom_scale_factor;
+            zoom.reduce();
+        }
+
+        let data_export = Arc::new(Mutex::new(
+            DataExport::new(
+                    image_width, 
+                    image_height, 
+                    display_glitches, 
+                    palette_buffer, 
+                    palette_interpolated_buffer, 
+                    palette_cyclic, 
+                    palette_iteration_span, 
+                    palette_offset, 
+                    distance_transition, 
+                    stripe_scale,
+                    distance_color,
+                    lighting,
+                    coloring_type, 
+                    pixel_data_type, 
+                    fractal_type, 
+                    export_type)
+        ));
+
+        data_export.lock().heightmap_type = heightmap_type;
+        data_export.lock().mesh_type = mesh_type;
+        data_export.lock().mesh_scale = mesh_scale;
+        data_export.lock().mesh_decimation = mesh_decimation;
+        data_export.lock().additional_resolutions = additional_resolutions;
+        data_export.lock().export_glitch_map = export_glitch_map;
+        data_export.lock().export_histogram = parameters.export_histogram;
+        data_export.lock().histogram_bins = parameters.histogram_bins;
+        data_export.lock().export_reference_orbit = parameters.export_reference_orbit;
+        data_export.lock().reference_orbit_overlay = parameters.reference_orbit_overlay;
+        data_export.lock().reference_orbit_points = parameters.reference_orbit_points;
+        data_export.lock().raw_channels = parameters.raw_channels.clone();
+        data_export.lock().glitch_colour = FractalRenderer::get_glitch_colour(&parameters);
+        data_export.lock().glitch_overlay = parameters.glitch_overlay;
+        data_export.lock().progressive = progressive;
+        data_export.lock().supersampling = supersampling;
+        data_export.lock().reconstruction_filter = FractalRenderer::parse_reconstruction_filter(&settings);
+        data_export.lock().coloring_plugin = ColoringPlugin::load_shared(&parameters.coloring_plugin);
+        data_export.lock().change_lighting(lighting_direction, lighting_azimuth, lighting_opacity, lighting_ambient, lighting_diffuse, lighting_specular, lighting_shininess);
+
+        FractalRenderer {
+            image_width,
+            image_height,
+            total_pixels: render_indices.len(),
+            rotate,
+            zoom,
+            auto_adjust_iterations,
+            automatic_iterations: parameters.automatic_iterations,
+            automatic_iterations_threshold: parameters.automatic_iterations_threshold,
+            automatic_iterations_limit: parameters.automatic_iterations_limit,
+            automatic_continuation: None,
+            maximum_iteration,
+            glitch_percentage,
+            data_export,
+            remaining_frames,
+            frame_offset,
+            zoom_scale_factor,
+            center_reference: reference,
+            series_approximation,
+            period_finding,
+            render_indices,
+            remove_centre,
+            pixel_data_type,
+            jitter,
+            jitter_factor,
+            show_output,
+            progress: ProgressCounters::new(maximum_iteration),
+            render_time: 0,
+            fractal_type,
+            root_zoom_factor: 0.0,
+            name,
+            filename_template,
+            overwrite_protection,
+            export_statistics,
+            statistics: RenderStatistics::default(),
+            glitched_pixels_per_pass: Mutex::new(Vec::new()),
+            log_json,
+            log_stage: Mutex::new(None),
+            exponential_map,
+            end_zoom,
+            resume,
+            parallel_keyframes,
+            parallel_keyframe_zoom,
+            keyframe_iterations,
+            initial_maximum_iteration: maximum_iteration,
+            initial_zoom: zoom,
+            zoom_easing,
+            rotate_easing,
+            rotate_start: rotate,
+            rotate_end,
+            palette_easing,
+            palette_start: (palette_iteration_span, palette_offset),
+            palette_end,
+            sequence_frames: remaining_frames,
+            sequence_start_zoom: string_to_extended(&initial_zoom),
+            sequence_end_zoom,
+            camera_path: CameraPath::from_settings(&settings),
+            motion_blur_samples: parameters.motion_blur_samples,
+            motion_blur_shutter: parameters.motion_blur_shutter,
+            recentre: FractalRenderer::parse_recentre(&settings),
+            recentre_radius: parameters.recentre_radius,
+            image_offset,
+            crop_margin,
+            supersampling,
+            pixel_chunk_size: parameters.pixel_chunk_size,
+            solid_guessing: parameters.solid_guessing,
+            solid_guessing_tile: parameters.solid_guessing_tile,
+            progressive,
+            progressive_export,
+            pixel_order,
+            adaptive_antialiasing_samples: parameters.adaptive_antialiasing_samples,
+            adaptive_antialiasing_threshold: parameters.adaptive_antialiasing_threshold,
+            samples_per_pixel: max(parameters.samples_per_pixel, 1),
+            jitter_seed: FractalRenderer::get_jitter_seed(&settings),
+            deterministic: parameters.deterministic,
+            memory_limit: parameters.memory_limit,
+            threads: parameters.threads,
+            thread_pool: FractalRenderer::build_thread_pool(parameters.threads, settings.get_bool("pin_threads").unwrap_or(false)),
+            stop_flag: Arc::new(AtomicBool::new(false)),
+            cancelled: false,
+            checkpoint_interval: parameters.checkpoint_interval,
+            continue_from: parameters.continue_from.clone(),
+            reference_cache,
+            current_filename: String::new(),
+            progress_sender: Mutex::new(None),
+            region_sender: Mutex::new(None),
+            save_output: true,
+            manifest,
+            zoom_in: parameters.sequence_direction.to_ascii_uppercase() == "IN",
+            reverse_numbering: parameters.reverse_numbering,
+            settings,
+            parameters,
+        }
+    }
+
+    /// Renders the current zoom into the data export, saving it to the filename if outputs are enabled. The reference
+    /// and series approximation are only calculated when the frame index is zero, later frames reuse them.
+    pub fn render_frame(&mut self, frame_index: usize, filename: String, stop_flag: Arc<AtomicBool>) {
+        self.progress.reset();
+        self.cancelled = false;
+        self.current_filename = filename.clone();
+
+        if self.log_json {
+            self.end_stage();
+
+            log_event("info", "frame_start", json!({
+                "frame": frame_index,
+                "zoom": extended_to_string_short(self.zoom),
+                "filename": filename,
+            }));
+
+            self.set_stage(RenderStage::Reference);
+        }
+
+        // The reporter stops when this is dropped at the end of the frame
+        let _progress_reporter = self.progress_sender.lock().clone().map(|sender| {
+            self.progress.spawn_reporter(extended_to_string_short(self.zoom), self.render_indices.len(), Duration::from_millis(250), sender)
+        });
+        
+        if self.show_output {
+            print!(" {:<15}", extended_to_string_short(self.zoom));
+            std::io::stdout().flush().unwrap();
+        };
+
+        let frame_time = Instant::now();
+        let approximation_time = Instant::now();
+
+        let (tx, rx) = mpsc::channel();
+
+        if self.show_output {
+            let thread_counter_1 = self.progress.reference.clone();
+            let thread_counter_2 = self.progress.series_approximation.clone();
+            let thread_counter_3 = self.progress.reference_maximum.clone();
+            let thread_counter_4 = self.progress.series_validation.clone();
+            print!("|               ");
+
+            thread::spawn(move || {
+                loop {
+                    match rx.try_recv() {
+                        Ok(_) => {
+                            break;
+                        },
+                        Err(_) => {
+                            // 40% weighting to first reference, 40% to SA calculation, 20% to SA checking
+                            let mut percentage_complete = 0.0;
+
+                            // println!("{} {} {}", thread_counter_1.get(), thread_counter_2.get(), thread_counter_3.get());
+
+                            percentage_complete += 45.0 * thread_counter_1.load(Ordering::Relaxed) as f64 / thread_counter_3.load(Ordering::Relaxed) as f64;
+                            percentage_complete += 45.0 * thread_counter_2.load(Ordering::Relaxed) as f64 / thread_counter_3.load(Ordering::Relaxed) as f64;
+                            percentage_complete += 10.0 * thread_counter_4.load(Ordering::Relaxed) as f64 / 2.0;
+
+                            print!("\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08{:^14}", format!("{:.2}%", percentage_complete));
+                            std::io::stdout().flush().unwrap();
+                        }
+                    };
+
+                    thread::sleep(Duration::from_millis(100));
+                };
+            });
+        };
+
+        self.statistics = RenderStatistics::default();
+        self.glitched_pixels_per_pass.lock().clear();
+
+        if frame_index == 0 {
+            self.data_export.lock().maximum_iteration = self.maximum_iteration;
+
+            self.adjust_precision();
+
+            let reference_time = Instant::now();
+
+            // The cache is keyed by c, which for a Julia set is the seed rather than the centre
+            let cached_reference = match &self.reference_cache {
+                Some(cache) if !self.center_reference.julia => cache.load_reference(&mut self.center_reference, self.fractal_type),
+                _ => false
+            };
+
+            if cached_reference {
+                self.progress.reference.store(self.center_reference.current_iteration, Ordering::SeqCst);
+                self.progress.reference_maximum.store(self.center_reference.current_iteration, Ordering::SeqCst);
+            } else {
+                self.center_reference.run(&self.progress.reference, &self.progress.reference_maximum, &stop_flag, self.fractal_type);
+            }
+
+            self.statistics.reference_time = reference_time.elapsed().as_millis();
+
+            if self.stop_rendering(&stop_flag, frame_time) {
+                tx.send(()).unwrap();
+                return;
+            };
+
+            if let (Some(cache), false, false) = (&self.reference_cache, cached_reference, self.center_reference.julia) {
+                cache.save_reference(&self.center_reference, self.fractal_type);
+            }
+            
+            self.set_stage(RenderStage::SeriesApproximation);
+            self.series_approximation.maximum_iteration = self.center_reference.current_iteration;
+
+            let cached_approximation = match &self.reference_cache {
+                Some(cache) if self.series_approximation.enabled => cache.load_approximation(&self.center_reference, &mut self.series_approximation),
+                _ => false
+            };
+
+            if cached_approximation {
+                self.progress.series_approximation.store(self.series_approximation.maximum_iteration, Ordering::SeqCst);
+            } else {
+                self.series_approximation.generate_approximation(&self.center_reference, &self.progress.series_approximation, &stop_flag);
+
+                if let (Some(cache), true, false) = (&self.reference_cache, self.series_approximation.enabled, stop_flag.load(Ordering::SeqCst)) {
+                    cache.save_approximation(&self.center_reference, &self.series_approximation);
+                }
+            }
+        } else {
+            self.set_stage(RenderStage::SeriesApproximation);
+
+            let mut export = self.data_export.lock();
+
+            // If the image width/height changes intraframe (GUI) we need to regenerate some things
+            if export.image_width != self.image_width || export.image_height != self.image_height {
+                self.render_indices = FractalRenderer::generate_render_indices(self.image_width, self.image_height, self.remove_centre, self.zoom_scale_factor, export.export_type == ExportType::Gui || self.progressive, self.pixel_order);
+
+                export.centre_removed = self.remove_centre;
+                export.image_width = self.image_width;
+                export.image_height = self.image_height;
+            }
+            
+            export.clear_buffers();
+
+            drop(export);
+
+            // Check to see if the series approximation order has changed intraframe
+            if self.series_approximation.enabled && self.series_approximation.order != self.series_approximation.generated_order {
+                self.series_approximation.min_valid_iteration = 1;
+                self.series_approximation.generate_approximation(&self.center_reference, &self.progress.series_approximation, &stop_flag);
+            }
+        }
+
+        self.pixel_data_type = match self.data_export.lock().coloring_type {
+            ColoringType::SmoothIteration | ColoringType::StepIteration => DataType::Iteration,
+            ColoringType::Stripe => DataType::Stripe,
+            ColoringType::DistanceStripe => DataType::DistanceStripe,
+            _ => DataType::Distance
+        };
+
+        if self.stop_rendering(&stop_flag, frame_time) {
+            tx.send(()).unwrap();
+            return;
+        };
+        
+        let cos_rotate = self.rotate.cos();
+        let sin_rotate = self.rotate.sin();
+
+        // The exponential map is sampled out to the corners of the final video frame
+        let exponential_map_radius = 4.0 / self.zoom.mantissa;
+
+        let delta_pixel = if self.exponential_map {
+            // Use the size of the outermost pixels
+            2.0 * PI * exponential_map_radius / self.image_width as f64
+        } else {
+            4.0 / ((self.image_height - 1 - 2 * self.crop_margin) as f64 * self.zoom.mantissa)
+        };
+
+        let delta_pixel_cos = delta_pixel * cos_rotate;
+        let delta_pixel_sin = delta_pixel * sin_rotate;
+
+        let delta_top_left = self.get_image_delta_top_left(delta_pixel, cos_rotate, sin_rotate);
+        let delta_pixel_extended = FloatExtended::new(delta_pixel, -self.zoom.exponent);
+
+        let minimum_dimension = min(self.image_width, self.image_height);
+
+        self.series_approximation.delta_pixel_square = if minimum_dimension < 1000 {
+            let fixed_delta_pixel_extended = FloatExtended::new(4.0 / (999.0 * self.zoom.mantissa), -self.zoom.exponent);
+            
+            fixed_delta_pixel_extended * fixed_delta_pixel_extended
+        } else {
+            delta_pixel_extended * delta_pixel_extended
+        };
+
+        // Used for placing the probe points
+        if self.exponential_map {
+            // The probes are placed over the square containing the whole exponential map
+            self.series_approximation.check_approximation(
+                ComplexFixed::new(-exponential_map_radius, -exponential_map_radius), 
+                -self.zoom.exponent, 
+                1.0, 
+                0.0, 
+                2.0 * exponential_map_radius / self.image_height as f64, 
+                self.image_height,
+                self.image_height,
+                &self.center_reference,
+                &self.progress.series_validation);
+        } else {
+            self.series_approximation.check_approximation(
+                delta_top_left, 
+                -self.zoom.exponent, 
+                cos_rotate, 
+                sin_rotate, 
+                delta_pixel, 
+                self.image_width,
+                self.image_height,
+                &self.center_reference,
+                &self.progress.series_validation);
+        }
+
+        self.progress.min_series_approximation.store(self.series_approximation.min_valid_iteration, Ordering::SeqCst);
+        self.progress.max_series_approximation.store(self.series_approximation.max_valid_iteration, Ordering::SeqCst);
+
+        tx.send(()).unwrap();
+
+        self.statistics.approximation_time = approximation_time.elapsed().as_millis() - self.statistics.reference_time;
+
+        if self.stop_rendering(&stop_flag, frame_time) {
+            return;
+        };
+
+        if self.show_output {
+            print!("\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08{:<15}", approximation_time.elapsed().as_millis());
+            print!("| {:<15}", self.series_approximation.min_valid_iteration);
+            print!("| {:<6}", self.series_approximation.order);
+            print!("| {:<15}", self.maximum_iteration);
+            std::io::stdout().flush().unwrap();
+        };
+
+        if self.remove_centre != self.data_export.lock().centre_removed {
+            let progressive = self.data_export.lock().export_type == ExportType::Gui || self.progressive;
+
+            self.render_indices = FractalRenderer::generate_render_indices(self.image_width, self.image_height, self.remove_centre, self.zoom_scale_factor, progressive, self.pixel_order);
+            self.data_export.lock().centre_removed = self.remove_centre;
+        }
+
+        let complex_default = ComplexExtended::new2(1.0, 0.0, 0);
+        let sampling_resolution_width = (self.series_approximation.probe_sampling - 1) as f64 / self.image_width as f64;
+        let sampling_resolution_height = (self.series_approximation.probe_sampling - 1) as f64 / self.image_height as f64;
+
+        let normal = rand_distr::Normal::new(0.0, self.jitter_factor).unwrap();
+
+        let generate_pixel = |index: &usize| {
+            let mut i = (index % self.image_width) as f64;
+            let mut j = (index / self.image_width) as f64;
+
+            let chosen_iteration = if self.series_approximation.enabled {
+                if self.series_approximation.tiled && !self.exponential_map {
+                    let test1 = (i * sampling_resolution_width).floor() as usize;
+                    let test2 = (j * sampling_resolution_height).floor() as usize;
+
+                    let index = test2 * (self.series_approximation.probe_sampling - 1) + test1;
+
+                    self.series_approximation.valid_interpolation[index]
+                } else {
+                    self.series_approximation.min_valid_iteration
+                }
+            } else {
+                1
+            };
+
+            if self.jitter {
+                let (offset_i, offset_j) = self.get_sample_offset(&normal, *index, 0);
+
+                i += offset_i;
+                j += offset_j;
+            }
+
+            let element = if self.exponential_map {
+                get_exponential_map_delta(i, j, self.image_width, exponential_map_radius, self.rotate)
+            } else {
+                ComplexFixed::new(
+                    i * delta_pixel_cos - j * delta_pixel_sin + delta_top_left.re, 
+                    i * delta_pixel_sin + j * delta_pixel_cos + delta_top_left.im
+                )
+            };
+
+            let point_delta = ComplexExtended::new(element, -self.zoom.exponent);
+
+            PixelData {
+                index: *index,
+                iteration: chosen_iteration,
+                delta_reference: self.get_delta_reference(point_delta),
+                delta_current: point_delta,
+                derivative_current: complex_default,
+                glitched: false,
+                z_norm: 0.0,
+                stripe_storage: [ComplexFixed::new(0.0, 0.0); 4],
+                stripe_iteration: 0,
+            }
+        };
+
+        let iteration_time = Instant::now();
+        self.set_stage(RenderStage::Iteration);
+
+        let total_pixels = self.render_indices.len() as f64;
+
+        let (tx, rx) = mpsc::channel();
+
+        if self.show_output {
+            let thread_counter = Arc::clone(&self.progress.iteration);
+            print!("|               ");
+
+            thread::spawn(move || {
+                loop {
+                    thread::sleep(Duration::from_millis(100));
+                    match rx.try_recv() {
+                        Ok(_) => {
+                            break;
+                        },
+                        Err(_) => {
+                            print!("\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08{:^14}", format!("{:.2}%", 100.0 * thread_counter.load(Ordering::SeqCst) as f64 / total_pixels));
+                            std::io::stdout().flush().unwrap();
+                        }
+                    };
+                };
+            });
+        };
+
+        let number_pixels = self.render_indices.len();
+
+        let pixel_chunk_size = if self.pixel_chunk_size == 0 {
+            max(number_pixels, 1)
+        } else {
+            self.pixel_chunk_size
+        };
+
+        let mut pixel_data = Vec::new();
+
+        // Solid guessing needs every pixel of the image to be rendered in this frame
+        let solid_guessing = self.solid_guessing && !self.exponential_map && !self.remove_centre;
+
+        // Checkpoints are written between the pixel chunks, so the chunk size sets how often they can be saved
+        let mut checkpoint = if self.checkpoint_interval > 0 && self.save_output && !solid_guessing {
+            Some(self.get_checkpoint(&filename))
+        } else {
+            None
+        };
+
+        let mut completed = checkpoint.as_ref()
+            .filter(|checkpoint| checkpoint.completed.contains(&true))
+            .map(|checkpoint| checkpoint.completed.clone());
+
+        // The pass before the automatic iterations were raised is kept, so only the pixels which reached the maximum
+        // iteration are iterated again
+        let automatic_continuation = self.automatic_continuation.take();
+
+        // A previous export of the frame with fewer iterations only needs its unescaped pixels iterated again
+        if completed.is_none() && !solid_guessing {
+            completed = automatic_continuation.or_else(|| self.get_continuation());
+
+            if let (Some(checkpoint), Some(completed)) = (&mut checkpoint, &completed) {
+                checkpoint.completed = completed.clone();
+            }
+        }
+
+        let resumed_indices;
+
+        let render_indices = match &completed {
+            Some(completed) => {
+                resumed_indices = self.render_indices.iter()
+                    .filter(|&&index| !completed[index])
+                    .copied()
+                    .collect::<Vec<usize>>();
+
+                &resumed_indices[..]
+            }
+            _ => &self.render_indices[..]
+        };
+
+        // The scaled passes for the progressive preview only work on the whole image
+        let values = if pixel_chunk_size >= number_pixels && render_indices.len() == number_pixels {
+            vec![16usize, 8, 4, 2, 1]
+        } else {
+            vec![1usize]
+        };
+
+        if solid_guessing {
+            pixel_data = self.guess_rectangles(&generate_pixel, delta_pixel_extended, &stop_flag);
+
+            // The guessed rectangles are only filled in at the end, so the whole image is sent at once
+            if !stop_flag.load(Ordering::SeqCst) {
+                self.send_rows(0..number_pixels);
+            }
+        } else {
+            // The glitched pixels of a resumed frame are iterated again so that they can be corrected
+            if render_indices.len() < number_pixels {
+                let export = self.data_export.lock();
+
+                let mut glitched_data = self.render_indices.iter()
+                    .filter(|&&index| export.glitched[index])
+                    .map(&generate_pixel)
+                    .collect::<Vec<PixelData>>();
+
+                drop(export);
+
+                self.progress.iteration.fetch_add(number_pixels - render_indices.len() - glitched_data.len(), Ordering::SeqCst);
+
+                let chunk_size = max(glitched_data.len() / 512, 8);
+
+                Perturbation::iterate(&mut glitched_data, &self.center_reference, &self.progress.iteration, &stop_flag, self.data_export.clone(), delta_pixel_extended, 1, chunk_size, self.fractal_type, self.pixel_data_type, &self.series_approximation, true);
+
+                glitched_data.retain(|packet| {
+                    packet.glitched
+                });
+
+                pixel_data.append(&mut glitched_data);
+            }
+
+            let mut checkpoint_time = Instant::now();
+            let streaming = self.region_sender.lock().is_some();
+
+            // The pixels are generated, iterated and exported a chunk at a time so that only the glitched pixels
+            // of the whole image need to be kept. With no chunk size set the whole image is a single chunk.
+            for indices in render_indices.chunks(pixel_chunk_size) {
+                let mut chunk_data = indices.par_iter().map(&generate_pixel).collect::<Vec<PixelData>>();
+
+                if stop_flag.load(Ordering::SeqCst) {
+                    break;
+                }
+
+                let mut previous_value = 0;
+
+                for &value in values.iter() {
+                    let end_value = chunk_data.len() / (value * value);
+                    let chunk_size = max((end_value - previous_value) / 512, 8);
+
+                    if self.pixel_order == PixelOrder::Tiled {
+                        self.iterate_tiles(&mut chunk_data[previous_value..end_value], &stop_flag, delta_pixel_extended, value);
+                    } else if streaming {
+                        // The other orders are iterated in tiles while streaming, as their pixels are spread over
+                        // the whole image and no region would be complete until the end of the chunk
+                        let tiles_x = (self.image_width + TILE_SIZE - 1) / TILE_SIZE;
+
+                        chunk_data[previous_value..end_value].sort_by_key(|pixel| (FractalRenderer::get_tile(pixel.index, self.image_width, tiles_x), pixel.index));
+
+                        self.iterate_tiles(&mut chunk_data[previous_value..end_value], &stop_flag, delta_pixel_extended, value);
+                    } else {
+                        Perturbation::iterate(&mut chunk_data[previous_value..end_value], &self.center_reference, &self.progress.iteration, &stop_flag, self.data_export.clone(), delta_pixel_extended, value, chunk_size, self.fractal_type, self.pixel_data_type, &self.series_approximation, true);
+                    }
+
+                    previous_value = end_value;
+
+                    // Each coarse stage is complete for the whole image, with the pixels filled from the coarse results
+                    if self.progressive && self.progressive_export && self.save_output && value > 1 && value < 16 {
+                        self.data_export.lock().save_preview(&filename, value);
+                    }
+                }
+
+                // A partially iterated chunk is not marked as complete
+                if stop_flag.load(Ordering::SeqCst) {
+                    break;
+                }
+
+                chunk_data.retain(|packet| {
+                    packet.glitched
+                });
+
+                pixel_data.append(&mut chunk_data);
+
+                if let Some(checkpoint) = &mut checkpoint {
+                    for &index in indices {
+                        checkpoint.completed[index] = true;
+                    }
+
+                    if checkpoint_time.elapsed().as_secs() >= self.checkpoint_interval as u64 {
+                        checkpoint.save(&self.data_export.lock());
+                        checkpoint_time = Instant::now();
+                    }
+                }
+            }
+
+            // The glitched pixels are kept in the checkpoint, so the correction can restart without iterating the image again
+            if let Some(checkpoint) = &checkpoint {
+                if !stop_flag.load(Ordering::SeqCst) {
+                    checkpoint.save(&self.data_export.lock());
+                }
+            }
+        }
+
+        tx.send(()).unwrap();
+
+        self.statistics.iteration_time = iteration_time.elapsed().as_millis();
+
+        if self.stop_rendering(&stop_flag, frame_time) {
+            return;
+        };
+
+        if self.show_output {
+            print!("\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08{:<15}", iteration_time.elapsed().as_millis());
+            std::io::stdout().flush().unwrap();
+        };
+        
+        let correction_time = Instant::now();
+        self.set_stage(RenderStage::Correction);
+
+        // Remove all non-glitched points from the remaining points
+        pixel_data.retain(|packet| {
+            packet.glitched
+        });
+
+        let glitched_pixels = pixel_data.len() as f64;
+        self.progress.glitched_maximum.fetch_add(pixel_data.len(), Ordering::SeqCst);
+
+        let complete_pixels = total_pixels - glitched_pixels;
+
+        let (tx, rx) = mpsc::channel();
+
+        if self.show_output {
+            let thread_counter = Arc::clone(&self.progress.iteration);
+            print!("|               ");
+
+            thread::spawn(move || {
+                loop {
+                    thread::sleep(Duration::from_millis(100));
+                    match rx.try_recv() {
+                        Ok(_) => {
+                            break;
+                        },
+                        Err(_) => {
+                            print!("\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08{:^14}", format!("{:.2}%", 100.0 * (thread_counter.load(Ordering::Relaxed) as f64 - complete_pixels) / glitched_pixels));
+                            std::io::stdout().flush().unwrap();
+                        }
+                    };
+                };
+            });
+        };
+
+        // Goes through all glitches and solved them - no need for glitch percentage at this time
+        if pixel_data.len() > 0 {
+            self.resolve_glitches(&mut pixel_data, &stop_flag, frame_time, delta_pixel_extended, None, 0);
+
+            if !stop_flag.load(Ordering::SeqCst) {
+                self.send_rows(pixel_data.iter().map(|pixel| pixel.index));
+            }
+        }
+
+        if self.samples_per_pixel > 1 && !stop_flag.load(Ordering::SeqCst) {
+            let indices = (0..self.image_width * self.image_height).collect::<Vec<usize>>();
+            self.add_jittered_samples(&indices, self.samples_per_pixel, &stop_flag, delta_pixel_extended);
+        } else if self.adaptive_antialiasing_samples > 0 && !stop_flag.load(Ordering::SeqCst) {
+            let indices = self.get_high_contrast_pixels();
+            self.add_jittered_samples(&indices, self.adaptive_antialiasing_samples + 1, &stop_flag, delta_pixel_extended);
+        }
+
+        tx.send(()).unwrap();
+
+        self.statistics.correction_time = correction_time.elapsed().as_millis();
+
+        if self.show_output {
+            print!("\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08{:<15}", correction_time.elapsed().as_millis());
+            print!("| {:<6}", self.progress.reference_count.load(Ordering::SeqCst));
+            std::io::stdout().flush().unwrap();
+        };
+        
+        // Too many pixels reached the automatic iterations, so the frame is rendered again with more
+        if frame_index == 0 && self.automatic_iterations && !stop_flag.load(Ordering::SeqCst) && self.raise_automatic_iterations() {
+            self.render_frame(frame_index, filename, stop_flag);
+            return;
+        }
+
+        if self.log_json {
+            for (pass, glitched_pixels) in self.glitched_pixels_per_pass.lock().iter().enumerate() {
+                log_event("info", "glitch_pass", json!({
+                    "zoom": extended_to_string_short(self.zoom),
+                    "pass": pass,
+                    "glitched_pixels": glitched_pixels,
+                }));
+            }
+        }
+
+        let saving_time = Instant::now();
+        self.set_stage(RenderStage::Saving);
+
+        // Motion blurred frames are saved once all of the samples have been accumulated
+        if self.save_output {
+            let metadata = self.get_output_metadata();
+            let mut export = self.data_export.lock();
+
+            // The orbit of the central reference is only decimated when it is exported or drawn over the image
+            if export.export_reference_orbit || export.reference_orbit_overlay {
+                export.reference_orbit = Some(ReferenceOrbit::new(&self.center_reference, export.reference_orbit_points));
+            }
+
+            export.save(&filename, self.series_approximation.order, &extended_to_string_long(self.zoom), &metadata);
+        }
+
+        let trap = self.data_export.lock().coloring_plugin.as_ref().and_then(|plugin| plugin.lock().take_trap());
+
+        if let Some(trap) = trap {
+            log_warning(self.log_json, &format!("the coloring plugin trapped, and those pixels have the glitch colour: {}", trap));
+        }
+
+        if let Some(checkpoint) = &checkpoint {
+            checkpoint.remove();
+        }
+
+        self.render_time = frame_time.elapsed().as_millis();
+
+        self.statistics.saving_time = saving_time.elapsed().as_millis();
+        self.statistics.completed_pixels = self.render_indices.len();
+        self.update_statistics();
+
+        if self.export_statistics && self.save_output {
+            self.statistics.save(&filename);
+        }
+
+        if self.log_json {
+            self.end_stage();
+
+            log_event("info", "frame_end", json!({
+                "zoom": extended_to_string_short(self.zoom),
+                "filename": filename,
+                "time": self.render_time as u64,
+                "statistics": self.statistics.to_json(),
+            }));
+        }
+
+        if self.show_output {
+            println!("| {:<15}", frame_time.elapsed().as_millis());
+            std::io::stdout().flush().unwrap();
+        }
+    }
+
+    // Recursive glitch solving by glitch levels
+    // Start with a central reference that has ALL data stored for each iteration past the min skip
+    pub fn resolve_glitches(&self, pixel_data: &mut [PixelData], stop_flag: &Arc<AtomicBool>, frame_time: Instant, delta_pixel_extended: FloatExtended, previous_reference: Option<Reference>, depth: usize) {
+        // Keep track of the number of glitched pixels at each level of correction
+        {
+            let mut glitched_pixels_per_pass = self.glitched_pixels_per_pass.lock();
+
+            if glitched_pixels_per_pass.len() <= depth {
+                glitched_pixels_per_pass.resize(depth + 1, 0);
+            }
+
+            glitched_pixels_per_pass[depth] += pixel_data.len();
+        }
+
+        // Store the number of correction passes each pixel has needed
+        {
+            let mut export = self.data_export.lock();
+
+            for pixel in pixel_data.iter() {
+                export.correction_passes[pixel.index] = depth as u32 + 1;
+            }
+        }
+
+        // The bins are kept in iteration order so that the correction does not depend on the hash of the keys
+        let mut iteration_map: BTreeMap<usize, Vec<PixelData>> = BTreeMap::new();
+
+        // Sort into bins to process
+        pixel_data.iter()
+            .for_each(|pixel| {
+                match iteration_map.get_mut(&pixel.iteration) {
+                    Some(pixels) => {
+                        pixels.push(pixel.clone());
+                    }
+                    None => {
+                        iteration_map.insert(pixel.iteration, vec![pixel.clone()]);
+                    }
+                }
+            });
+
+        let previous_reference = match previous_reference {
+            Some(reference) => {
+                reference
+            },
+            None => {
+                let mut lowest_iteration = usize::MAX;
+                let mut highest_iteration = 0;
+        
+                iteration_map.iter().for_each(|(&index, _)| {
+                    if index < lowest_iteration {
+                        lowest_iteration = index;
+                    }
+
+                    if index > highest_iteration {
+                        highest_iteration = index;
+                    }
+                });
+
+                let mut previous_reference = self.center_reference.get_central_glitch_resolving_reference(lowest_iteration);
+
+                // Modify the precision of the reference to the current image scale
+                let radius = delta_pixel_extended * self.image_width as f64;
+                let precision = max(64, -radius.exponent + 64) as u32;
+
+                previous_reference.c.set_prec(precision);
+                previous_reference.z.set_prec(precision);
+
+                let previous_maximum_iteration = previous_reference.maximum_iteration;
+
+                // We cap this to avoid running the reference too long when the central point has a large amount of iterations relative to the rest of the pixels
+                previous_reference.maximum_iteration = min(highest_iteration + 10000, previous_maximum_iteration);
+
+                previous_reference.run(&Arc::new(AtomicUsize::new(0)), &Arc::new(AtomicUsize::new(0)), &stop_flag, self.fractal_type);
+
+                // Reset the maximum iteration of the reference back to normal to make it seem the reference escaped early
+                previous_reference.maximum_iteration = previous_maximum_iteration;
+
+                previous_reference
+            }
+        };
+
+        if stop_flag.load(Ordering::SeqCst) {
+            return;
+        };
+
+        iteration_map.par_iter_mut()
+            .for_each(|(iteration, pixel_data)| {
+                // Ties are broken by the pixel index, so the same reference is chosen regardless of the pixel order
+                let glitch_reference_pixel = pixel_data.iter().min_by(|i, j| {
+                    i.z_norm.partial_cmp(&j.z_norm).unwrap().then(i.index.cmp(&j.index))
+                }).unwrap().clone();
+
+                let mut glitch_reference = previous_reference.get_glitch_resolving_reference(*iteration, glitch_reference_pixel.delta_reference, glitch_reference_pixel.delta_current);
+                glitch_reference.run(&Arc::new(AtomicUsize::new(0)), &Arc::new(AtomicUsize::new(0)), &stop_flag, self.fractal_type);
+
+                self.progress.reference_count.fetch_add(1, Ordering::SeqCst);
+    
+                if stop_flag.load(Ordering::SeqCst) {
+                    return;
+                };
+
+                pixel_data.par_iter_mut()
+                    .for_each(|pixel| {
+                        pixel.glitched = false;
+                        pixel.delta_current -= glitch_reference_pixel.delta_current;
+                        pixel.delta_reference -= glitch_reference_pixel.delta_reference;
+                });
+
+                let chunk_size = max(pixel_data.len() / 512, 4);
+                // println!("chunk size: {}", chunk_size);
+
+                Perturbation::iterate(pixel_data, &glitch_reference, &self.progress.iteration, &stop_flag, self.data_export.clone(), delta_pixel_extended, 1, chunk_size, self.fractal_type, self.pixel_data_type, &self.series_approximation, false);
+
+                pixel_data.retain(|packet| {
+                    packet.glitched
+                });
+
+                if pixel_data.len() > 0 {
+                    self.resolve_glitches(pixel_data, stop_flag, frame_time, delta_pixel_extended, Some(glitch_reference), depth + 1)
+                }
+            });
+    }
+
+    // Mariani-Silver rectangle guessing. The borders of each rectangle are iterated first, and if they are all interior
+    // (or all the same iteration with step colouring) the inside is filled without iterating. Otherwise the rectangle
+    // is split into four until it is small enough to iterate every pixel. The glitched pixels are returned.
+    pub fn guess_rectangles<F: Fn(&usize) -> PixelData + Sync>(&self, generate_pixel: &F, delta_pixel_extended: FloatExtended, stop_flag: &Arc<AtomicBool>) -> Vec<PixelData> {
+        let tile_size = max(self.solid_guessing_tile, 8);
+        let step_colouring = self.data_export.lock().coloring_type == ColoringType::StepIteration;
+
+        let tiles = (0..self.image_height).step_by(tile_size)
+            .flat_map(|y| (0..self.image_width).step_by(tile_size).map(move |x| (x, y)))
+            .collect::<Vec<_>>();
+
+        tiles.into_par_iter().flat_map(|(tile_x, tile_y)| {
+            let tile_width = min(tile_size, self.image_width - tile_x);
+            let tile_height = min(tile_size, self.image_height - tile_y);
+
+            // The computed pixels of this tile, as the iteration and whether it glitched
+            let mut computed: Vec<Option<(usize, bool)>> = vec![None; tile_width * tile_height];
+            let mut glitched = Vec::new();
+
+            let iterate_pixels = |positions: Vec<(usize, usize)>, computed: &mut Vec<Option<(usize, bool)>>| -> Vec<PixelData> {
+                let mut pixels = positions.iter()
+                    .filter(|(x, y)| computed[y * tile_width + x].is_none())
+                    .map(|(x, y)| generate_pixel(&((tile_y + y) * self.image_width + tile_x + x)))
+                    .collect::<Vec<PixelData>>();
+
+                let chunk_size = max(pixels.len(), 1);
+
+                Perturbation::iterate(&mut pixels, &self.center_reference, &self.progress.iteration, stop_flag, self.data_export.clone(), delta_pixel_extended, 1, chunk_size, self.fractal_type, self.pixel_data_type, &self.series_approximation, true);
+
+                for pixel in &pixels {
+                    let x = pixel.index % self.image_width - tile_x;
+                    let y = pixel.index / self.image_width - tile_y;
+
+                    computed[y * tile_width + x] = Some((pixel.iteration, pixel.glitched));
+                }
+
+                pixels
+            };
+
+            let mut rectangles = vec![(0, 0, tile_width - 1, tile_height - 1)];
+
+            while let Some((x0, y0, x1, y1)) = rectangles.pop() {
+                if stop_flag.load(Ordering::SeqCst) {
+                    break;
+                }
+
+                // Small rectangles are iterated completely
+                if x1 - x0 < 4 || y1 - y0 < 4 {
+                    let positions = (y0..=y1).flat_map(|y| (x0..=x1).map(move |x| (x, y))).collect::<Vec<_>>();
+
+                    glitched.extend(iterate_pixels(positions, &mut computed).into_iter().filter(|pixel| pixel.glitched));
+                    continue;
+                }
+
+                let border = (y0..=y1).flat_map(|y| (x0..=x1).map(move |x| (x, y)))
+                    .filter(|&(x, y)| x == x0 || x == x1 || y == y0 || y == y1)
+                    .collect::<Vec<_>>();
+
+                let border_pixels = iterate_pixels(border.clone(), &mut computed);
+                glitched.extend(border_pixels.iter().filter(|pixel| pixel.glitched).cloned());
+
+                let first = computed[y0 * tile_width + x0];
+
+                let uniform = border.iter().all(|(x, y)| {
+                    match (computed[y * tile_width + x], first) {
+                        (Some((iteration, false)), Some((first_iteration, false))) => {
+                            iteration == first_iteration && (iteration >= self.maximum_iteration || step_colouring)
+                        },
+                        _ => false
+                    }
+                });
+
+                // Any newly iterated border pixel has the same iteration data as the inside
+                let template = border_pixels.iter().find(|pixel| !pixel.glitched).filter(|_| uniform);
+
+                if let Some(template) = template {
+                    let filled = (y0 + 1..y1).flat_map(|y| (x0 + 1..x1).map(move |x| (x, y))).map(|(x, y)| {
+                        let mut pixel = template.clone();
+                        pixel.index = (tile_y + y) * self.image_width + tile_x + x;
+
+                        computed[y * tile_width + x] = Some((pixel.iteration, false));
+                        pixel
+                    }).collect::<Vec<PixelData>>();
+
+                    self.data_export.lock().export_pixels(&filled, &self.center_reference, delta_pixel_extended, 1);
+                    self.progress.iteration.fetch_add(filled.len(), Ordering::Relaxed);
+                } else {
+                    let xm = (x0 + x1) / 2;
+                    let ym = (y0 + y1) / 2;
+
+                    rectangles.push((x0, y0, xm, ym));
+                    rectangles.push((xm, y0, x1, ym));
+                    rectangles.push((x0, ym, xm, y1));
+                    rectangles.push((xm, ym, x1, y1));
+                }
+            }
+
+            glitched
+        }).collect()
+    }
+
+    // The pixels where any channel differs from one of the neighbouring pixels by more than the threshold
+    pub fn get_high_contrast_pixels(&self) -> Vec<usize> {
+        let export = self.data_export.lock();
+        let threshold = self.adaptive_antialiasing_threshold as i32;
+
+        (0..self.image_width * self.image_height).into_par_iter().filter(|&k| {
+            let i = k % self.image_width;
+            let j = k / self.image_width;
+
+            let neighbours = [
+                (i > 0, k.wrapping_sub(1)), 
+                (i + 1 < self.image_width, k + 1), 
+                (j > 0, k.wrapping_sub(self.image_width)), 
+                (j + 1 < self.image_height, k + self.image_width)];
+
+            neighbours.iter().any(|&(valid, neighbour)| {
+                valid && (0..3).any(|channel| {
+                    (export.buffer[3 * k + channel] as i32 - export.buffer[3 * neighbour + channel] as i32).abs() > threshold
+                })
+            })
+        }).collect()
+    }
+
+    // With a seed the offsets come from a generator seeded by the pixel and sample, so they are the same no matter
+    // which order or machine the pixels are rendered in
+    pub fn get_sample_offset<D: Distribution<f64>>(&self, distribution: &D, index: usize, sample: usize) -> (f64, f64) {
+        match self.jitter_seed {
+            Some(seed) => {
+                let mut rng = StdRng::seed_from_u64(seed ^ (index as u64).wrapping_mul(0x9E3779B97F4A7C15) ^ (sample as u64).wrapping_mul(0xC2B2AE3D27D4EB4F));
+
+                (distribution.sample(&mut rng), distribution.sample(&mut rng))
+            },
+            None => {
+                let mut rng = rand::thread_rng();
+
+                (distribution.sample(&mut rng), distribution.sample(&mut rng))
+            }
+        }
+    }
+
+    // Replaces the colour of the pixels with the average of jittered samples in linear light. Every sample is placed
+    // uniformly over the pixel, so the centre which was rendered first is only kept if all of the samples glitch.
+    // The samples are exported into a copy of the data so that the raw iteration data is not changed. The colours are
+    // averaged rather than the iterations, as the palette is not linear in the iteration. Averaging the iterations of
+    // samples on either side of a palette wrap or of the interior would give a colour which none of them have.
+    pub fn add_jittered_samples(&self, indices: &[usize], samples: usize, stop_flag: &Arc<AtomicBool>, delta_pixel_extended: FloatExtended) {
+        if indices.is_empty() {
+            return;
+        }
+
+        let cos_rotate = self.rotate.cos();
+        let sin_rotate = self.rotate.sin();
+
+        let exponential_map_radius = 4.0 / self.zoom.mantissa;
+
+        let delta_pixel = if self.exponential_map {
+            2.0 * PI * exponential_map_radius / self.image_width as f64
+        } else {
+            4.0 / ((self.image_height - 1 - 2 * self.crop_margin) as f64 * self.zoom.mantissa)
+        };
+
+        let delta_top_left = self.get_image_delta_top_left(delta_pixel, cos_rotate, sin_rotate);
+
+        let chosen_iteration = if self.series_approximation.enabled {
+            self.series_approximation.min_valid_iteration
+        } else {
+            1
+        };
+
+        let mut accumulation = vec![([0.0f32; 3], 0.0f32); indices.len()];
+
+        let sample_export = Arc::new(Mutex::new(self.data_export.lock().clone()));
+        let uniform = rand_distr::Uniform::new(-0.5, 0.5);
+
+        for sample in 0..samples {
+            let mut pixel_data = indices.par_iter().map(|&index| {
+                let (offset_i, offset_j) = self.get_sample_offset(&uniform, index, sample);
+
+                let i = (index % self.image_width) as f64 + offset_i;
+                let j = (index / self.image_width) as f64 + offset_j;
+
+                let element = if self.exponential_map {
+                    get_exponential_map_delta(i, j, self.image_width, exponential_map_radius, self.rotate)
+                } else {
+                    ComplexFixed::new(
+                        i * delta_pixel * cos_rotate - j * delta_pixel * sin_rotate + delta_top_left.re, 
+                        i * delta_pixel * sin_rotate + j * delta_pixel * cos_rotate + delta_top_left.im
+                    )
+                };
+
+                let point_delta = ComplexExtended::new(element, -self.zoom.exponent);
+
+                PixelData {
+                    index,
+                    iteration: chosen_iteration,
+                    delta_reference: self.get_delta_reference(point_delta),
+                    delta_current: point_delta,
+                    derivative_current: ComplexExtended::new2(1.0, 0.0, 0),
+                    glitched: false,
+                    z_norm: 0.0,
+                    stripe_storage: [ComplexFixed::new(0.0, 0.0); 4],
+                    stripe_iteration: 0,
+                }
+            }).collect::<Vec<PixelData>>();
+
+            let chunk_size = max(pixel_data.len() / 512, 4);
+
+            Perturbation::iterate(&mut pixel_data, &self.center_reference, &Arc::new(AtomicUsize::new(0)), stop_flag, sample_export.clone(), delta_pixel_extended, 1, chunk_size, self.fractal_type, self.pixel_data_type, &self.series_approximation, true);
+
+            if stop_flag.load(Ordering::SeqCst) {
+                return;
+            }
+
+            // Glitched samples are not corrected, they are left out of the average instead
+            let export = sample_export.lock();
+
+            for (pixel, (total, weight)) in pixel_data.iter().zip(accumulation.iter_mut()) {
+                if !pixel.glitched {
+                    for channel in 0..3 {
+                        total[channel] += srgb_to_linear(export.buffer[3 * pixel.index + channel]);
+                    }
+
+                    *weight += 1.0;
+                }
+            }
+        }
+
+        let mut export = self.data_export.lock();
+
+        for (&index, (total, weight)) in indices.iter().zip(accumulation.into_iter()).filter(|(_, (_, weight))| *weight > 0.0) {
+            for channel in 0..3 {
+                export.buffer[3 * index + channel] = linear_to_srgb(total[channel] / weight);
+            }
+        }
+    }
+
+    // The parameters which are embedded in the exported images so that they can be reproduced
+    pub fn get_metadata(&self) -> Vec<(String, String)> {
+        let export = self.data_export.lock();
+
+        let fractal_type = match self.fractal_type {
+            FractalType::Mandelbrot2 => "mandelbrot2",
+            FractalType::Mandelbrot3 => "mandelbrot3"
+        };
+
+        let location = self.center_reference.get_location();
+
+        let palette = export.palette_buffer.iter().map(|colour| {
+            let (r, g, b, _) = colour.rgba_u8();
+            format!("{}, {}, {}", r, g, b)
+        }).collect::<Vec<String>>().join(", ");
+
+        let mut metadata = vec![
+            (String::from("real"), location.real().to_string_radix(10, None)),
+            (String::from("imag"), location.imag().to_string_radix(10, None)),
+            (String::from("zoom"), extended_to_string_long(self.zoom)),
+            (String::from("iterations"), self.maximum_iteration.to_string()),
+            (String::from("rotate"), self.rotate.to_degrees().to_string()),
+            (String::from("fractal_type"), String::from(fractal_type)),
+            (String::from("palette"), palette),
+            (String::from("palette_iteration_span"), export.palette_iteration_span.to_string()),
+            (String::from("palette_offset"), export.palette_offset.to_string()),
+            (String::from("supersampling"), self.supersampling.to_string()),
+            (String::from("crop_margin"), (self.crop_margin / self.supersampling).to_string()),
+            (String::from("crop_window"), format!("{}, {}, {}, {}", 
+                self.crop_margin / self.supersampling, 
+                self.crop_margin / self.supersampling, 
+                (self.image_width - 2 * self.crop_margin) / self.supersampling, 
+                (self.image_height - 2 * self.crop_margin) / self.supersampling)),
+            (String::from("samples_per_pixel"), self.samples_per_pixel.to_string()),
+        ];
+
+        // The seed is only needed to reproduce jittered samples
+        if let Some(seed) = self.jitter_seed {
+            metadata.push((String::from("jitter_seed"), seed.to_string()));
+        }
+
+        if self.deterministic {
+            metadata.push((String::from("deterministic"), String::from("true")));
+        }
+
+        if self.center_reference.julia {
+            metadata.push((String::from("julia_real"), self.center_reference.c.real().to_string_radix(10, None)));
+            metadata.push((String::from("julia_imag"), self.center_reference.c.imag().to_string_radix(10, None)));
+        }
+
+        metadata
+    }
+
+    // The saved images also contain all of the parameters, so that the whole render can be reproduced or compared.
+    // These are not part of the checkpoint metadata, which would otherwise depend on settings such as resume.
+    pub fn get_output_metadata(&self) -> Vec<(String, String)> {
+        let mut metadata = self.get_metadata();
+        metadata.push((String::from("parameters"), self.parameters.to_json()));
+
+        metadata
+    }
+
+    // The deterministic mode fixes the seed if none is given, so that the output is identical across runs and thread counts
+    pub fn get_jitter_seed(settings: &Config) -> Option<u64> {
+        match settings.get_int("jitter_seed") {
+            Ok(seed) => Some(seed as u64),
+            Err(_) if settings.get_bool("deterministic").unwrap_or(false) => Some(0),
+            Err(_) => None
+        }
+    }
+
+    pub fn parse_reconstruction_filter(settings: &Config) -> ReconstructionFilter {
+        match settings.get_str("supersampling_filter").unwrap_or_else(|_| String::from("LANCZOS")).to_ascii_uppercase().as_ref() {
+            "BOX" => ReconstructionFilter::Box,
+            "TRIANGLE" | "TENT" => ReconstructionFilter::Triangle,
+            "GAUSSIAN" => ReconstructionFilter::Gaussian,
+            _ => ReconstructionFilter::Lanczos
+        }
+    }
+
+    // The margin is given in pixels on each side, the exponential map has no edges so it is not used
+    pub fn get_crop_margin(settings: &Config, exponential_map: bool) -> usize {
+        if exponential_map {
+            0
+        } else {
+            settings.get_int("crop_margin").unwrap_or(0) as usize
+        }
+    }
+
+    // The output directory is written back to the settings, so that the renderers created from copies of the settings,
+    // such as for parallel keyframes, save to the same folder. The folder of the run is normally chosen by the front-end,
+    // and otherwise the renderer chooses its own.
+    pub fn apply_output_directory(settings: &mut Config) {
+        FractalRenderer::apply_run_folder(settings, &get_run_folder());
+
+        let parameters = RenderParameters::from_config(settings);
+
+        if let Some(output_dir) = &parameters.output_dir {
+            let (directory, filename_template) = resolve_output_directory(&parameters.filename_template, output_dir);
+
+            settings.set("filename_template", filename_template).unwrap();
+            settings.set("output_dir", directory).unwrap();
+        }
+    }
+
+    /// Moves the output directory into the folder of the run when `output_subfolder` is set. Front-ends call this once
+    /// with the folder from `get_run_folder`, so that every renderer of a batch or queue saves to the same folder.
+    pub fn apply_run_folder(settings: &mut Config, run_folder: &str) {
+        let parameters = RenderParameters::from_config(settings);
+
+        if let (Some(output_dir), true) = (&parameters.output_dir, parameters.output_subfolder) {
+            settings.set("output_dir", Path::new(output_dir).join(run_folder).to_string_lossy().to_string()).unwrap();
+            settings.set("output_subfolder", false).unwrap();
+        }
+    }
+
+    // The zooms are written back fitted to the height, so that the renderers created from copies of the settings,
+    // such as for parallel keyframes or a camera path, do not scale them for the view fit a second time
+    pub fn apply_view_fit(settings: &mut Config) {
+        let parameters = RenderParameters::from_config(settings);
+
+        settings.set("zoom", parameters.zoom.clone()).unwrap();
+
+        if let Some(end_zoom) = &parameters.end_zoom {
+            settings.set("end_zoom", end_zoom.clone()).unwrap();
+        }
+
+        settings.set("view_fit", "height").unwrap();
+    }
+
+    // A region of the view is rendered as an image of its own, centred on the middle of the region with the zoom
+    // scaled so that the pixels are the same size as in the full view. The settings are rewritten to this view and the
+    // region is cleared, so that reading them again does not crop a second time.
+    pub fn apply_region(settings: &mut Config) {
+        let parameters = RenderParameters::from_config(settings);
+
+        let (x, y, width, height) = match parameters.get_region() {
+            Some(region) if !parameters.exponential_map => region,
+            _ => return
+        };
+
+        let zoom = string_to_extended(&parameters.zoom.to_ascii_uppercase());
+        let precision = FractalRenderer::get_precision(zoom, parameters.image_width, parameters.image_height);
+
+        let centre = ComplexArbitrary::with_val(precision,
+            ComplexArbitrary::parse(format!("({},{})", parameters.real, parameters.imag)).expect("provided location not valid"));
+
+        let centre = pixel_to_complex(&centre,
+            zoom,
+            parameters.image_width,
+            parameters.image_height,
+            parameters.rotate.to_radians(),
+            x as f64 + 0.5 * (width - 1) as f64,
+            y as f64 + 0.5 * (height - 1) as f64);
+
+        let scale = (parameters.image_height.max(2) - 1) as f64 / (height.max(2) - 1) as f64;
+
+        settings.set("real", centre.real().to_string_radix(10, None)).unwrap();
+        settings.set("imag", centre.imag().to_string_radix(10, None)).unwrap();
+        settings.set("zoom", extended_to_string_long(zoom * scale)).unwrap();
+
+        if let Some(end_zoom) = &parameters.end_zoom {
+            settings.set("end_zoom", extended_to_string_long(string_to_extended(&end_zoom.to_ascii_uppercase()) * scale)).unwrap();
+        }
+
+        settings.set("image_width", width as i64).unwrap();
+        settings.set("image_height", height as i64).unwrap();
+        settings.set("view_fit", "height").unwrap();
+        settings.set("region", Vec::<f64>::new()).unwrap();
+    }
+
+    pub fn update_statistics(&mut self) {
+        self.statistics.frame_time = self.render_time;
+        self.statistics.zoom = extended_to_string_long(self.zoom);
+        self.statistics.image_width = self.image_width;
+        self.statistics.image_height = self.image_height;
+        self.statistics.skipped_iterations = self.series_approximation.min_valid_iteration;
+        self.statistics.maximum_skipped_iterations = self.series_approximation.max_valid_iteration;
+        self.statistics.approximation_order = self.series_approximation.order;
+        self.statistics.maximum_iteration = self.maximum_iteration;
+        self.statistics.reference_count = self.progress.reference_count.load(Ordering::SeqCst);
+        self.statistics.glitched_pixels_per_pass = self.glitched_pixels_per_pass.lock().clone();
+        self.statistics.reference_iteration = self.center_reference.current_iteration;
+        self.statistics.precision = self.center_reference.c.prec().0;
+        self.statistics.peak_memory = get_peak_memory();
+        self.statistics.interestingness = self.data_export.lock().get_interestingness();
+    }
+
+    // The only place the stage is changed, so that the progress counters and the JSON log always agree. With the JSON
+    // log each stage is logged when it starts and when it ends, with how long it took
+    fn set_stage(&self, stage: RenderStage) {
+        self.progress.stage.store(stage as usize, Ordering::SeqCst);
+
+        if self.log_json {
+            self.end_stage();
+
+            log_event("info", "stage_start", json!({
+                "zoom": extended_to_string_short(self.zoom),
+                "stage": format!("{:?}", stage).to_ascii_lowercase(),
+            }));
+
+            *self.log_stage.lock() = Some((stage, Instant::now()));
+        }
+    }
+
+    fn end_stage(&self) {
+        if let Some((stage, start_time)) = self.log_stage.lock().take() {
+            log_event("info", "stage_end", json!({
+                "zoom": extended_to_string_short(self.zoom),
+                "stage": format!("{:?}", stage).to_ascii_lowercase(),
+                "time": start_time.elapsed().as_millis() as u64,
+            }));
+        }
+    }
+
+    pub fn stop_rendering(&mut self, stop_flag: &Arc<AtomicBool>, frame_time: Instant) -> bool {
+        if stop_flag.load(Ordering::SeqCst) {
+            self.cancelled = true;
+            self.render_time = frame_time.elapsed().as_millis();
+
+            // The counters are reset below, so the progress of the frame is kept for the partial output
+            self.statistics.cancelled = true;
+            self.statistics.completed_pixels = self.progress.iteration.load(Ordering::SeqCst);
+            self.update_statistics();
+
+            if self.log_json {
+                self.end_stage();
+
+                log_event("warning", "frame_cancelled", json!({
+                    "zoom": extended_to_string_short(self.zoom),
+                    "time": self.render_time as u64,
+                    "completed_pixels": self.statistics.completed_pixels,
+                }));
+            }
+
+            self.progress.reset();
+            stop_flag.store(false, Ordering::SeqCst);
+
+            if self.show_output {
+                println!();
+            }
+
+            true
+        } else {
+            false
+        }
+    }
+
+    // Saves whatever the cancelled frame had accumulated, along with its statistics. The pixels which were not
+    // reached are left black, and the reference iteration shows how far the reference calculation got.
+    pub fn save_partial_output(&mut self) {
+        if !self.cancelled || self.current_filename.is_empty() {
+            return;
+        }
+
+        let filename = format!("{}_partial", self.current_filename);
+        let metadata = self.get_output_metadata();
+
+        self.data_export.lock().save(&filename, self.series_approximation.order, &extended_to_string_long(self.zoom), &metadata);
+        self.statistics.save(&filename);
+
+        if self.show_output {
+            println!("partial output saved to {}", filename);
+        }
+    }
+
+    /// The reports of each frame are sent to the returned receiver while it is rendering.
+    pub fn get_progress_receiver(&self) -> mpsc::Receiver<ProgressReport> {
+        let (tx, rx) = mpsc::channel();
+        *self.progress_sender.lock() = Some(tx);
+
+        rx
+    }
+
+    /// The regions of each frame are sent to the returned receiver as soon as they have been iterated, so that a
+    /// preview can be drawn or the image streamed before the frame is complete. Each tile is sent as it finishes, and
+    /// the pixels are iterated in tiles while there is a receiver whatever the pixel order.
+    pub fn get_region_receiver(&self) -> mpsc::Receiver<CompletedRegion> {
+        let (tx, rx) = mpsc::channel();
+        *self.region_sender.lock() = Some(tx);
+
+        rx
+    }
+
+    // The region is only copied out of the export when there is a receiver for it
+    pub fn send_region(&self, x: usize, y: usize, width: usize, height: usize) {
+        let sender = match self.region_sender.lock().clone() {
+            Some(sender) => sender,
+            None => return
+        };
+
+        let region = self.data_export.lock().get_region(x, y, width, height);
+
+        // The receiver being dropped stops the regions being sent
+        if sender.send(region).is_err() {
+            *self.region_sender.lock() = None;
+        }
+    }
+
+    // Sends the full rows between the smallest and largest of the pixel indices
+    pub fn send_rows<I: Iterator<Item = usize>>(&self, indices: I) {
+        if self.region_sender.lock().is_none() {
+            return;
+        }
+
+        let (first, last) = indices.fold((usize::MAX, 0), |(first, last), index| (min(first, index), max(last, index)));
+
+        if first <= last {
+            let first_row = first / self.image_width;
+            let last_row = last / self.image_width;
+
+            self.send_region(0, first_row, self.image_width, last_row - first_row + 1);
+        }
+    }
+
+    /// A host application can set the returned flag to stop the current frame, and any remaining frames of a sequence.
+    /// The data which was completed before stopping is kept in the data export.
+    pub fn get_stop_flag(&self) -> Arc<AtomicBool> {
+        self.stop_flag.clone()
+    }
+
+    // find the period given a box
+    pub fn find_period(&mut self) {
+        self.period_finding.find_period(&self.center_reference);
+        // self.period_finding.find_atom_domain_period(&self.center_reference);
+    }
+
+    // Returns true if the maximum iterations has been increased
+    pub fn adjust_iterations(&mut self) -> bool {
+        if self.auto_adjust_iterations {
+            if 4 * self.series_approximation.max_valid_iteration > self.maximum_iteration {
+                self.maximum_iteration *= 3;
+                self.maximum_iteration /= 2;
+                return true;
+            }
+
+            if 8 * self.series_approximation.max_valid_iteration < self.maximum_iteration {
+                self.maximum_iteration *= 3;
+                self.maximum_iteration /= 4;
+
+                if self.maximum_iteration < 1000 {
+                    self.maximum_iteration = 1000;
+                }
+
+                self.data_export.lock().maximum_iteration = self.maximum_iteration;
+                
+                if self.center_reference.current_iteration > self.maximum_iteration {
+                    self.center_reference.current_iteration = self.maximum_iteration;
+                }
+
+                self.center_reference.maximum_iteration = self.maximum_iteration;
+            }
+        }
+        false
+    }
+
+    // Raises the maximum iteration if more than the threshold fraction of the pixels reached it, and the escaped
+    // pixels came close enough to it that the rest are not all interior. The escaped pixels are kept for the next pass
+    // and the reference is run again from the start to the new maximum iteration.
+    pub fn raise_automatic_iterations(&mut self) -> bool {
+        let export = self.data_export.lock();
+
+        let capped = export.iterations.iter()
+            .zip(export.glitched.iter())
+            .filter(|(&iteration, &glitched)| iteration as usize >= self.maximum_iteration && !glitched)
+            .count();
+
+        let maximum_escaped = export.get_maximum_escaped_iteration();
+
+        if (capped as f64) <= self.automatic_iterations_threshold * export.iterations.len() as f64
+            || 2 * maximum_escaped <= self.maximum_iteration
+            || 4 * self.maximum_iteration > self.automatic_iterations_limit {
+            return false;
+        }
+
+        self.automatic_continuation = Some(export.iterations.iter()
+            .map(|&iteration| (iteration as usize) < self.maximum_iteration)
+            .collect());
+
+        drop(export);
+
+        let previous_maximum_iteration = self.maximum_iteration;
+
+        self.maximum_iteration *= 4;
+        self.initial_maximum_iteration *= 4;
+        self.set_location(self.center_reference.get_location());
+
+        if self.show_output {
+            println!("| {} pixels reached {} iterations, continuing with {}", capped, previous_maximum_iteration, self.maximum_iteration);
+        }
+
+        true
+    }
+
+    // Reduces the maximum iteration for the next keyframe, either proportionally to the zoom depth or
+    // from the highest escaped iteration in the previous keyframe. The maximum iteration is never increased.
+    pub fn scale_keyframe_iterations(&mut self, previous_rendered: bool) {
+        let new_maximum_iteration = match self.keyframe_iterations {
+            KeyframeIterations::Depth => {
+                let initial_depth = self.initial_zoom.mantissa.log2() + self.initial_zoom.exponent as f64;
+                let current_depth = self.zoom.mantissa.log2() + self.zoom.exponent as f64;
+
+                if initial_depth > 0.0 {
+                    (self.initial_maximum_iteration as f64 * current_depth.max(0.0) / initial_depth) as usize
+                } else {
+                    self.maximum_iteration
+                }
+            },
+            KeyframeIterations::Measured if previous_rendered => {
+                // Leave some headroom as the next frame will contain slightly deeper detail near the edges
+                2 * self.data_export.lock().get_maximum_escaped_iteration()
+            },
+            _ => self.maximum_iteration
+        };
+
+        let new_maximum_iteration = min(self.maximum_iteration, max(new_maximum_iteration, 1000));
+
+        if new_maximum_iteration < self.maximum_iteration {
+            self.maximum_iteration = new_maximum_iteration;
+            self.data_export.lock().maximum_iteration = new_maximum_iteration;
+
+            if self.center_reference.current_iteration > new_maximum_iteration {
+                self.center_reference.current_iteration = new_maximum_iteration;
+            }
+
+            self.center_reference.maximum_iteration = new_maximum_iteration;
+        }
+    }
+
+    // When an easing is used the zoom of each frame is interpolated between the start and end zoom, rather than
+    // using a constant scale factor. This requires both the end zoom and the total number of frames to be given.
+    pub fn get_eased_zoom(&self, frame: usize) -> Option<FloatExtended> {
+        match self.sequence_end_zoom {
+            Some(end_zoom) if self.zoom_easing != Easing::Linear && self.sequence_frames > 1 && self.sequence_frames < usize::MAX && !self.exponential_map => {
+                let factor = self.zoom_easing.apply(frame as f64 / (self.sequence_frames - 1) as f64);
+
+                Some(linear_interpolation_between_zoom(self.sequence_start_zoom, end_zoom, factor))
+            },
+            _ => None
+        }
+    }
+
+    // The rotation is interpolated between the start and end angles over the sequence
+    pub fn get_frame_rotation(&self, frame: usize) -> f64 {
+        match self.rotate_end {
+            Some(rotate_end) if self.sequence_frames > 1 && self.sequence_frames < usize::MAX => {
+                let factor = self.rotate_easing.apply(frame as f64 / (self.sequence_frames - 1) as f64);
+
+                self.rotate_start + (rotate_end - self.rotate_start) * factor
+            },
+            _ => self.rotate_start
+        }
+    }
+
+    // Returns the palette iteration span and offset, each is interpolated over the sequence if an end value is given
+    pub fn get_frame_palette(&self, frame: usize) -> (f32, f32) {
+        let factor = if self.sequence_frames > 1 && self.sequence_frames < usize::MAX {
+            self.palette_easing.apply(frame as f64 / (self.sequence_frames - 1) as f64) as f32
+        } else {
+            0.0
+        };
+
+        let palette_iteration_span = match self.palette_end.0 {
+            Some(end) => self.palette_start.0 + (end - self.palette_start.0) * factor,
+            None => self.palette_start.0
+        };
+
+        let palette_offset = match self.palette_end.1 {
+            Some(end) => self.palette_start.1 + (end - self.palette_start.1) * factor,
+            None => self.palette_start.1
+        };
+
+        (palette_iteration_span, palette_offset)
+    }
+
+    pub fn apply_frame_palette(&mut self, frame: usize) {
+        if self.palette_end.0.is_none() && self.palette_end.1.is_none() {
+            return;
+        }
+
+        let (palette_iteration_span, palette_offset) = self.get_frame_palette(frame);
+
+        let mut export = self.data_export.lock();
+        export.palette_iteration_span = palette_iteration_span;
+        export.palette_offset = palette_offset;
+    }
+
+    pub fn is_julia(parameters: &RenderParameters) -> bool {
+        parameters.julia_real.is_some() && parameters.julia_imag.is_some()
+    }
+
+    // A Julia set is rendered when both parts of the seed are given
+    pub fn get_julia_seed(parameters: &RenderParameters, precision: u32) -> Option<ComplexArbitrary> {
+        match (&parameters.julia_real, &parameters.julia_imag) {
+            (Some(julia_real), Some(julia_imag)) => Some(ComplexArbitrary::with_val(
+                precision,
+                ComplexArbitrary::parse("(".to_owned() + julia_real + "," + julia_imag + ")").expect("provided julia seed not valid"))),
+            _ => None
+        }
+    }
+
+    // The components which are missing are taken from the default red, and the rest are clamped to a byte
+    pub fn get_glitch_colour(parameters: &RenderParameters) -> [u8; 3] {
+        let mut colour = [255, 0, 0];
+
+        for (component, &value) in colour.iter_mut().zip(parameters.glitch_colour.iter()) {
+            *component = value.clamp(0, 255) as u8;
+        }
+
+        colour
+    }
+
+    pub fn parse_recentre(settings: &Config) -> Recentre {
+        match settings.get_str("recentre").unwrap_or_else(|_| String::from("NONE")).to_ascii_uppercase().as_ref() {
+            "ITERATION" => Recentre::Iteration,
+            "NUCLEUS" => Recentre::Nucleus,
+            _ => Recentre::None
+        }
+    }
+
+    /// Moves the centre of the render, the reference is recalculated with the first frame.
+    pub fn set_location(&mut self, location: ComplexArbitrary) {
+        // A Julia set keeps its seed
+        let reference_c = if self.center_reference.julia {
+            self.center_reference.c.clone()
+        } else {
+            location.clone()
+        };
+
+        let julia = self.center_reference.julia;
+
+        self.center_reference = Reference::new(location, 
+            reference_c, 
+            1, 
+            self.maximum_iteration, 
+            self.center_reference.data_storage_interval,
+            self.center_reference.glitch_tolerance,
+            self.zoom);
+
+        self.center_reference.julia = julia;
+    }
+
+    // The offset of each pixel is added to c for the Mandelbrot set. A Julia set has the same c for every pixel, and
+    // only the starting value of the orbit is offset.
+    pub fn get_delta_reference(&self, point_delta: ComplexExtended) -> ComplexExtended {
+        if self.center_reference.julia {
+            ComplexExtended::new2(0.0, 0.0, 0)
+        } else {
+            point_delta
+        }
+    }
+
+    /// Renders the view moved from the reference by the offset in pixels, without writing any files. The reference and
+    /// series approximation of the previous call are reused, so neighbouring views at the same zoom only iterate their
+    /// pixels. The first call, or the first after the location is set, calculates them.
+    pub fn render_offset_to_buffer(&mut self, offset_x: f64, offset_y: f64) -> RenderedImage {
+        let frame_index = if self.center_reference.high_precision_data.is_empty() { 0 } else { 1 };
+
+        self.image_offset = (offset_x, offset_y);
+
+        self.save_output = false;
+        self.in_thread_pool(|renderer| renderer.render_frame(frame_index, String::new(), renderer.stop_flag.clone()));
+        self.save_output = true;
+
+        self.data_export.lock().get_image_buffer(false)
+    }
+
+    /// Renders the current zoom without writing any files and returns the colour image, and the raw channels if they
+    /// are requested. The reference is calculated again, so the centre and zoom can be changed between calls.
+    pub fn render_to_buffer(&mut self, include_raw: bool) -> RenderedImage {
+        self.save_output = false;
+        self.in_thread_pool(|renderer| renderer.render_frame(0, String::new(), renderer.stop_flag.clone()));
+        self.save_output = true;
+
+        self.data_export.lock().get_image_buffer(include_raw)
+    }
+
+    // Renders the keyframe without saving and moves the centre to the highest iteration region within the radius,
+    // which is given as a fraction of the image height. Each keyframe of a sequence is re-centred before it is rendered.
+    pub fn recentre_on_iteration(&mut self) {
+        if self.exponential_map {
+            return;
+        }
+
+        self.save_output = false;
+        self.render_frame(0, String::new(), self.stop_flag.clone());
+        self.save_output = true;
+
+        if self.cancelled {
+            return;
+        }
+
+        let centre_x = 0.5 * (self.image_width - 1) as f64;
+        let centre_y = 0.5 * (self.image_height - 1) as f64;
+        let radius = self.recentre_radius * self.image_height as f64;
+
+        let (offset_x, offset_y) = {
+            let export = self.data_export.lock();
+
+            let candidates = (0..self.image_height).flat_map(|j| (0..self.image_width).map(move |i| (i, j)))
+                .filter(|&(i, j)| {
+                    let k = j * self.image_width + i;
+                    let distance = ((i as f64 - centre_x).powi(2) + (j as f64 - centre_y).powi(2)).sqrt();
+
+                    distance <= radius && !export.glitched[k]
+                })
+                .collect::<Vec<_>>();
+
+            let highest = candidates.iter().map(|&(i, j)| export.iterations[j * self.image_width + i]).max().unwrap_or(0);
+
+            // Take the centroid of the highest pixels so that an interior region is centred
+            let highest_pixels = candidates.iter()
+                .filter(|&&(i, j)| export.iterations[j * self.image_width + i] == highest)
+                .collect::<Vec<_>>();
+
+            if highest_pixels.is_empty() {
+                (0.0, 0.0)
+            } else {
+                let total_x = highest_pixels.iter().map(|(i, _)| *i as f64).sum::<f64>();
+                let total_y = highest_pixels.iter().map(|(_, j)| *j as f64).sum::<f64>();
+
+                (total_x / highest_pixels.len() as f64 - centre_x, total_y / highest_pixels.len() as f64 - centre_y)
+            }
+        };
+
+        if offset_x.abs() < 0.5 && offset_y.abs() < 0.5 {
+            return;
+        }
+
+        let delta_pixel = 4.0 / ((self.image_height - 1 - 2 * self.crop_margin) as f64 * self.zoom.mantissa);
+        let (sin_rotate, cos_rotate) = self.rotate.sin_cos();
+
+        let delta = ComplexExtended::new2(
+            (offset_x * cos_rotate - offset_y * sin_rotate) * delta_pixel, 
+            (offset_x * sin_rotate + offset_y * cos_rotate) * delta_pixel, 
+            -self.zoom.exponent);
+
+        let mut location = self.center_reference.get_location();
+        let precision = location.prec().0;
+
+        let mut delta_real = FloatArbitrary::with_val(precision, delta.mantissa.re);
+        let mut delta_imag = FloatArbitrary::with_val(precision, delta.mantissa.im);
+
+        delta_real <<= delta.exponent;
+        delta_imag <<= delta.exponent;
+
+        *location.mut_real() += delta_real;
+        *location.mut_imag() += delta_imag;
+
+        if self.show_output {
+            println!(" {:<15}| re-centred by ({:.1}, {:.1}) pixels", extended_to_string_short(self.zoom), offset_x, offset_y);
+        }
+
+        self.set_location(location);
+    }
+
+    // Finds the period of the nearest nucleus within the radius with the ball method, then moves the centre to
+    // the nucleus with newton's method if it is inside the radius
+    pub fn recentre_on_nucleus(&mut self) {
+        // The nucleus is found for the Mandelbrot set, which does not apply to a Julia set
+        if self.center_reference.julia {
+            return;
+        }
+
+        let stop_flag = Arc::new(AtomicBool::new(false));
+
+        self.center_reference.run(&self.progress.reference, &self.progress.reference_maximum, &stop_flag, self.fractal_type);
+
+        let radius = FloatExtended::new(2.0 * self.recentre_radius / self.zoom.mantissa, -self.zoom.exponent);
+
+        let mut ball_method = BallMethod::new(radius, ComplexExtended::new2(0.0, 0.0, 0));
+        ball_method.find_period(&self.center_reference);
+
+        let location = self.center_reference.c.clone();
+        let precision = location.prec();
+
+        let nucleus = get_nucleus(location.clone(), 
+            ball_method.period, 
+            Arc::new(AtomicUsize::new(0)), 
+            Arc::new(AtomicUsize::new(0)), 
+            stop_flag, 
+            Arc::new(Mutex::new(ComplexExtended::new2(0.0, 0.0, 0))));
+
+        match nucleus {
+            Some(nucleus) if to_extended(&(nucleus.clone() - &location)).norm() <= radius => {
+                if self.show_output {
+                    println!(" {:<15}| re-centred on period {} nucleus", extended_to_string_short(self.zoom), ball_method.period);
+                }
+
+                self.set_location(ComplexArbitrary::with_val(precision, nucleus));
+            },
+            _ => {
+                // The reference is reset so that it is calculated as normal with the first frame
+                if self.show_output {
+                    println!(" {:<15}| no nucleus found within the re-centring radius", extended_to_string_short(self.zoom));
+                }
+
+                self.set_location(location);
+            }
+        }
+    }
+
+    // Runs the reference and series approximation of the first frame, and looks for the period and minibrot of the
+    // view, without iterating any of the pixels. The suggested iterations follow the automatic adjustment, which keeps
+    // the maximum between four and eight times the skipped iterations, and leave room for a few periods of the minibrot.
+    pub fn analyse_location(&mut self) -> LocationAnalysis {
+        let stop_flag = Arc::new(AtomicBool::new(false));
+
+        self.adjust_precision();
+        self.center_reference.run(&self.progress.reference, &self.progress.reference_maximum, &stop_flag, self.fractal_type);
+
+        let mut analysis = LocationAnalysis {
+            zoom: extended_to_string_short(self.zoom),
+            precision: FractalRenderer::get_precision(self.zoom, self.image_width, self.image_height),
+            reference_iteration: self.center_reference.current_iteration,
+            reference_escaped: self.center_reference.current_iteration < self.maximum_iteration,
+            ..LocationAnalysis::default()
+        };
+
+        // The period and nucleus are of the Mandelbrot set, which do not apply to a Julia set
+        if !self.center_reference.julia {
+            let radius = FloatExtended::new(2.0 * self.image_width.max(self.image_height) as f64 / (self.image_height as f64 * self.zoom.mantissa), -self.zoom.exponent);
+
+            let mut ball_method = BallMethod::new(radius, ComplexExtended::new2(0.0, 0.0, 0));
+            ball_method.find_period(&self.center_reference);
+
+            if ball_method.found {
+                analysis.period = Some(ball_method.period);
+
+                let location = self.center_reference.c.clone();
+
+                let nucleus = get_nucleus(location.clone(), 
+                    ball_method.period, 
+                    Arc::new(AtomicUsize::new(0)), 
+                    Arc::new(AtomicUsize::new(0)), 
+                    stop_flag.clone(), 
+                    Arc::new(Mutex::new(ComplexExtended::new2(0.0, 0.0, 0))));
+
+                if let Some(nucleus) = nucleus {
+                    let distance = to_extended(&(nucleus.clone() - &location)).norm() * self.zoom / 2.0;
+                    let (minibrot_zoom, _) = get_nucleus_position(nucleus, ball_method.period);
+
+                    analysis.minibrot_zoom = Some(extended_to_string_short(minibrot_zoom));
+                    analysis.minibrot_distance = Some(distance.to_float());
+                }
+            }
+        }
+
+        // The series approximation is checked over the image in the same way as for the first frame. Each candidate
+        // order is tried, and the fastest of those which skip within 1% of the most iterations is suggested, as the
+        // higher orders take longer to generate and check for little gain. The configured order is run last.
+        analysis.approximation_order = self.series_approximation.order;
+
+        if self.series_approximation.enabled {
+            let configured_order = self.series_approximation.order;
+
+            let mut orders = vec![4, 8, 16, 32, 64];
+            orders.retain(|order| *order != configured_order);
+            orders.push(configured_order);
+
+            for order in orders {
+                let approximation_time = Instant::now();
+
+                self.series_approximation.order = order;
+                self.check_series_approximation(&stop_flag);
+
+                analysis.approximation_orders.push((order, self.series_approximation.min_valid_iteration, approximation_time.elapsed().as_millis()));
+            }
+
+            let most_skipped = analysis.approximation_orders.iter().map(|(_, skipped, _)| *skipped).max().unwrap_or(0);
+
+            analysis.suggested_order = analysis.approximation_orders.iter()
+                .filter(|(_, skipped, _)| *skipped as f64 >= 0.99 * most_skipped as f64)
+                .min_by_key(|(_, _, time)| *time)
+                .map(|(order, _, _)| *order);
+
+            analysis.approximation_orders.sort_by_key(|(order, _, _)| *order);
+
+            analysis.skipped_iterations = self.series_approximation.min_valid_iteration;
+        }
+
+        let suggested = max(6 * self.series_approximation.max_valid_iteration, 4 * analysis.period.unwrap_or(0));
+
+        // Rounded up to two significant figures
+        let scale = 10usize.pow((suggested.max(1) as f64).log10().floor().max(1.0) as u32 - 1);
+        analysis.suggested_iterations = max(1000, (suggested + scale - 1) / scale * scale);
+
+        analysis
+    }
+
+    // Generates the series approximation of the current order and checks it over the image
+    fn check_series_approximation(&mut self, stop_flag: &Arc<AtomicBool>) {
+        self.series_approximation.maximum_iteration = self.center_reference.current_iteration;
+        self.series_approximation.generate_approximation(&self.center_reference, &self.progress.series_approximation, stop_flag);
+
+        let delta_pixel = 4.0 / ((self.image_height - 1 - 2 * self.crop_margin) as f64 * self.zoom.mantissa);
+        let delta_pixel_extended = FloatExtended::new(delta_pixel, -self.zoom.exponent);
+        let delta_top_left = self.get_image_delta_top_left(delta_pixel, self.rotate.cos(), self.rotate.sin());
+
+        self.series_approximation.delta_pixel_square = delta_pixel_extended * delta_pixel_extended;
+
+        self.series_approximation.check_approximation(
+            delta_top_left, 
+            -self.zoom.exponent, 
+            self.rotate.cos(), 
+            self.rotate.sin(), 
+            delta_pixel, 
+            self.image_width,
+            self.image_height,
+            &self.center_reference,
+            &self.progress.series_validation);
+    }
+
+    pub fn parse_keyframe_iterations(settings: &Config) -> KeyframeIterations {
+        match settings.get_str("keyframe_iterations").unwrap_or_else(|_| String::from("CONSTANT")).to_ascii_uppercase().as_ref() {
+            "DEPTH" => KeyframeIterations::Depth,
+            "MEASURED" => KeyframeIterations::Measured,
+            _ => KeyframeIterations::Constant
+        }
+    }
+
+    /// Renders every frame given by the settings, choosing between a single image, a sequence, keyframes or a camera path.
+    pub fn render(&mut self) {
+        self.in_thread_pool(|renderer| renderer.render_sequence());
+    }
+
+    // Runs the work on the pool of the renderer, if it has one, and otherwise on the current pool. Child renderers
+    // are given zero threads, so they share the pool of the renderer which created them.
+    fn in_thread_pool<T: Send>(&mut self, work: impl FnOnce(&mut Self) -> T + Send) -> T {
+        match self.thread_pool.clone() {
+            Some(pool) => pool.install(|| work(self)),
+            None => work(self)
+        }
+    }
+
+    pub fn build_thread_pool(threads: usize, pin_threads: bool) -> Option<Arc<ThreadPool>> {
+        if threads == 0 && !pin_threads {
+            return None;
+        }
+
+        // Zero threads lets rayon use every core
+        let mut builder = rayon::ThreadPoolBuilder::new().num_threads(threads);
+
+        // The workers are pinned node by node, so the pixel chunks they first write tend to be placed in local memory
+        if pin_threads {
+            let cpus = get_pinned_cpus();
+
+            builder = builder.start_handler(move |index| {
+                pin_current_thread(cpus[index % cpus.len()]);
+            });
+        }
+
+        builder.build().ok().map(Arc::new)
+    }
+
+    fn render_sequence(&mut self) {
+        // Print out the status information
+        if self.show_output {
+            println!(" {:<15}| {:<15}| {:<15}| {:<6}| {:<15}| {:<15}| {:<15}| {:<6}| {:<15}", "Zoom", "Approx [ms]", "Skipped [it]", "Order", "Maximum [it]", "Iteration [ms]", "Correct [ms]", "Ref", "Frame [ms]");
+        };
+
+        if !self.enforce_memory_limit() {
+            return;
+        }
+
+        if self.camera_path.is_some() {
+            self.render_camera_path();
+            return;
+        }
+
+        if let Some(zoom) = self.get_eased_zoom(self.frame_offset) {
+            self.zoom = zoom;
+        }
+
+        match self.recentre {
+            Recentre::Iteration => self.recentre_on_iteration(),
+            Recentre::Nucleus => self.recentre_on_nucleus(),
+            Recentre::None => {}
+        }
+
+        let total_frames = if self.reverse_numbering || self.zoom_in {
+            self.get_sequence_plan().len()
+        } else {
+            0
+        };
+
+        if self.zoom_in {
+            self.render_zoom_in();
+            return;
+        }
+
+        let mut count = 0;
+        let mut rendered = 0;
+
+        self.rotate = self.get_frame_rotation(self.frame_offset);
+        self.apply_frame_palette(self.frame_offset);
+
+        while self.remaining_frames > 0 && self.zoom > self.end_zoom {
+            // Shallow keyframes are cheap enough that they can be rendered independently at the same time
+            if self.parallel_keyframes > 1 && self.zoom.to_float() < self.parallel_keyframe_zoom {
+                self.render_parallel_keyframes(count, total_frames);
+                break;
+            }
+
+            let index = self.get_output_index(count, total_frames);
+
+            let filename = resolve_filename_template(&self.filename_template, 
+                &self.name, 
+                index, 
+                &extended_to_string_short(self.zoom), 
+                self.maximum_iteration);
+
+            // When resuming, keyframes which already exist are skipped
+            // The first frame which is rendered will calculate the reference
+            if self.resume && self.is_frame_complete(index, &filename) {
+                if self.show_output {
+                    println!(" {:<15}| skipped, {} already exists", extended_to_string_short(self.zoom), filename);
+                }
+            } else {
+                let filename = if self.overwrite_protection {
+                    get_unused_filename(&filename)
+                } else {
+                    filename
+                };
+
+                // The first keyframe was re-centred before the loop, the others are re-centred on their own view
+                if count > 0 {
+                    match self.recentre {
+                        Recentre::Iteration => self.recentre_on_iteration(),
+                        Recentre::Nucleus => self.recentre_on_nucleus(),
+                        Recentre::None => {}
+                    }
+
+                    if self.cancelled {
+                        return;
+                    }
+                }
+
+                // A re-centred keyframe has a new reference, which is calculated as for the first frame
+                let frame_index = if self.recentre == Recentre::None { rendered } else { 0 };
+
+                if self.motion_blur_samples > 1 {
+                    let next_zoom = self.get_next_zoom(count + self.frame_offset);
+
+                    self.render_blurred_frame(frame_index, filename.clone(), next_zoom);
+                    rendered += self.motion_blur_samples;
+                } else {
+                    self.render_frame(frame_index, filename.clone(), self.stop_flag.clone());
+                    rendered += 1;
+                }
+
+                // The partial data of the cancelled frame is left in the export buffers
+                if self.cancelled {
+                    return;
+                }
+
+                self.record_frame(index, self.zoom, self.maximum_iteration, &filename);
+            }
+
+            self.zoom.mantissa /= self.zoom_scale_factor;
+            self.zoom.reduce();
+
+            if let Some(zoom) = self.get_eased_zoom(count + 1 + self.frame_offset) {
+                self.zoom = zoom;
+            }
+
+            self.rotate = self.get_frame_rotation(count + 1 + self.frame_offset);
+            self.apply_frame_palette(count + 1 + self.frame_offset);
+            self.adjust_precision();
+
+            if self.keyframe_iterations != KeyframeIterations::Constant {
+                self.scale_keyframe_iterations(rendered > 0);
+            }
+
+            if self.zoom.to_float() < 1e10 {
+                // Set these to start from the beginning
+                self.series_approximation.valid_iteration_probe_multiplier = 1.0;
+
+                // SA has some problems with precision with lots of terms at lot zoom levels
+                if self.series_approximation.order > 8 {
+                    // Overwrite the series approximation order
+                    self.series_approximation.order = 8;
+
+                    // If nothing has been rendered yet the approximation will be generated with the first frame
+                    if rendered > 0 {
+                        self.series_approximation.maximum_iteration = self.center_reference.current_iteration;
+                        self.series_approximation.generate_approximation(&self.center_reference, &self.progress.series_approximation, &Arc::new(AtomicBool::new(false)));
+                    }
+                }
+
+                // Logic in here to automatically adjust the maximum number of iterations
+                // This is done arbitrarily and could be done in a config file if required
+                if self.auto_adjust_iterations && self.maximum_iteration > 10000 {
+                    let new_iteration_value = 10000;
+
+                    if self.center_reference.current_iteration >= 10000 {
+                        self.center_reference.current_iteration = new_iteration_value;
+                    };
+
+                    self.center_reference.maximum_iteration = new_iteration_value;
+                    self.maximum_iteration = new_iteration_value;
+                }
+            } else if rendered > 0 && self.series_approximation.min_valid_iteration < 1000 && self.series_approximation.order > 16 {
+                    self.series_approximation.order = 16;
+                    self.series_approximation.generate_approximation(&self.center_reference, &self.progress.series_approximation, &Arc::new(AtomicBool::new(false)));
+            } else if rendered > 0 && self.series_approximation.min_valid_iteration < 10000 && self.series_approximation.order > 32 {
+                self.series_approximation.order = 32;
+                self.series_approximation.generate_approximation(&self.center_reference, &self.progress.series_approximation, &Arc::new(AtomicBool::new(false)));
+            }
+            
+            self.remaining_frames -= 1;
+            count += 1;
+        }
+    }
+
+    // The zoom of every remaining frame of the sequence, in the order from deepest to shallowest
+    pub fn get_sequence_plan(&self) -> Vec<FloatExtended> {
+        let mut plan = Vec::new();
+        let mut zoom = self.zoom;
+        let mut index = 0;
+
+        while index < self.remaining_frames && zoom > self.end_zoom {
+            plan.push(zoom);
+
+            zoom.mantissa /= self.zoom_scale_factor;
+            zoom.reduce();
+
+            if let Some(eased_zoom) = self.get_eased_zoom(index + 1 + self.frame_offset) {
+                zoom = eased_zoom;
+            }
+
+            index += 1;
+        }
+
+        plan
+    }
+
+    // The manifest is written next to the output unless a filename is given
+    pub fn get_manifest(settings: &Config, name: &str) -> Option<SequenceManifest> {
+        if !settings.get_bool("manifest").unwrap_or(false) {
+            return None;
+        }
+
+        let filename = settings.get_str("manifest_filename").unwrap_or_else(|_| String::from("output/{name}_manifest.json"));
+
+        Some(SequenceManifest::new(&filename.replace("{name}", name), name))
+    }
+
+    // When there is a manifest, a frame is only complete if its files match the recorded checksums
+    pub fn is_frame_complete(&self, index: usize, filename: &str) -> bool {
+        match &self.manifest {
+            Some(manifest) => manifest.is_complete(index),
+            None => output_exists(filename)
+        }
+    }
+
+    // The number of bits needed to resolve the pixels of the image at the zoom
+    pub fn get_precision(zoom: FloatExtended, image_width: usize, image_height: usize) -> u32 {
+        let delta_pixel = (-2.0 * (4.0 / image_height as f64 - 2.0) / zoom) / image_height as f64;
+
+        // The furthest pixel is along the longer side, which is the height of a portrait image
+        let radius = delta_pixel * max(image_width, image_height) as f64;
+
+        max(64, -radius.exponent + 64) as u32
+    }
+
+    // Matches the precision of the centre to the current zoom. Before the reference is run the precision is only
+    // raised, as the frames of a zoom in sequence reuse the reference of the first and shallowest frame. Afterwards
+    // it is only reduced, for the stored high precision iterations that the glitch references of shallower frames
+    // start from.
+    pub fn adjust_precision(&mut self) {
+        let precision = FractalRenderer::get_precision(self.zoom, self.image_width, self.image_height);
+        let current_precision = self.center_reference.c.prec().0;
+
+        // The orbit has already been calculated if there is stored data
+        let reference_calculated = !self.center_reference.high_precision_data.is_empty();
+
+        if reference_calculated && precision < current_precision {
+            self.center_reference.c.set_prec(precision);
+
+            for value in self.center_reference.high_precision_data.iter_mut() {
+                value.set_prec(precision);
+            }
+        } else if !reference_calculated && precision > current_precision {
+            if self.show_output || self.log_json {
+                log_warning(self.log_json, &format!("the zoom needs {} bits of precision but the centre only has {}, the precision has been raised", precision, current_precision));
+            }
+
+            self.center_reference.c.set_prec(precision);
+            self.center_reference.z.set_prec(precision);
+        }
+    }
+
+    // When resuming, the completed pixels of a matching checkpoint are restored into the data export
+    pub fn get_checkpoint(&self, filename: &str) -> RenderCheckpoint {
+        let metadata = self.get_metadata();
+        let checkpoint_filename = format!("{}.checkpoint", filename);
+
+        if self.resume {
+            if let Some(checkpoint) = RenderCheckpoint::load(&checkpoint_filename, &metadata, &mut self.data_export.lock()) {
+                return checkpoint;
+            }
+        }
+
+        RenderCheckpoint::new(&checkpoint_filename, &metadata, self.image_width * self.image_height)
+    }
+
+    // Restores the raw export given to continue from if it is of the current frame and was rendered with at most
+    // the current maximum iteration. The reference is always run again, to the new maximum iteration.
+    pub fn get_continuation(&self) -> Option<Vec<bool>> {
+        let filename = self.continue_from.as_ref()?;
+        let mut export = self.data_export.lock();
+
+        let maximum_iteration = export.load_raw(filename, &extended_to_string_long(self.zoom))?;
+
+        if maximum_iteration > self.maximum_iteration {
+            export.clear_buffers();
+            return None;
+        }
+
+        let completed = export.iterations.iter()
+            .map(|&iteration| iteration < maximum_iteration as u32)
+            .collect::<Vec<bool>>();
+
+        for (k, &completed) in completed.iter().enumerate() {
+            if completed {
+                export.colour_index(k, 1);
+            }
+        }
+
+        Some(completed)
+    }
+
+    pub fn record_frame(&mut self, index: usize, zoom: FloatExtended, maximum_iteration: usize, filename: &str) {
+        if let Some(manifest) = &mut self.manifest {
+            let files = self.data_export.lock().get_output_files(filename);
+
+            manifest.add_frame(index, &extended_to_string_long(zoom), maximum_iteration, filename, &files);
+            manifest.save();
+        }
+
+        if self.parameters.history {
+            // The export is locked again for the parameters, so the files are found first
+            let files = self.data_export.lock().get_output_files(filename);
+            let entry = HistoryEntry::new(filename, &files, self.get_frame_parameters(zoom, maximum_iteration), self.get_frame_statistics(zoom));
+
+            append_history(&self.parameters.history_file, &entry);
+        }
+    }
+
+    // The parameters which render the frame on its own, with the location, iterations, rotation and palette it was
+    // rendered with rather than those at the start of the sequence
+    pub fn get_frame_parameters(&self, zoom: FloatExtended, maximum_iteration: usize) -> RenderParameters {
+        let location = self.center_reference.get_location();
+        let export = self.data_export.lock();
+
+        RenderParameters {
+            real: location.real().to_string_radix(10, None),
+            imag: location.imag().to_string_radix(10, None),
+            zoom: extended_to_string_long(zoom),
+            iterations: maximum_iteration,
+            automatic_iterations: false,
+            rotate: self.rotate.to_degrees(),
+            frames: 1,
+            frame_offset: 0,
+            end_zoom: None,
+            end_rotate: None,
+            end_palette_iteration_span: None,
+            end_palette_offset: None,
+            palette_iteration_span: export.palette_iteration_span as f64,
+            palette_offset: export.palette_offset as f64,
+            recentre: String::from("none"),
+            ..self.parameters.clone()
+        }
+    }
+
+    // The statistics are only those of the frame if it was the last one rendered by this renderer
+    pub fn get_frame_statistics(&self, zoom: FloatExtended) -> Option<serde_json::Value> {
+        if self.statistics.zoom == extended_to_string_long(zoom) {
+            Some(self.statistics.to_json())
+        } else {
+            None
+        }
+    }
+
+    // The number used in the filename, which can count down so that assemblers expecting zoom in order work
+    pub fn get_output_index(&self, count: usize, total_frames: usize) -> usize {
+        if self.reverse_numbering {
+            self.frame_offset + total_frames - 1 - count
+        } else {
+            self.frame_offset + count
+        }
+    }
+
+    // Renders the sequence from the shallowest frame to the deepest. The reference is still at the
+    // deepest precision, but the approximation order is only reduced for the shallow frames.
+    pub fn render_zoom_in(&mut self) {
+        let plan = self.get_sequence_plan();
+
+        let original_order = self.series_approximation.order;
+        let original_probe_multiplier = self.series_approximation.valid_iteration_probe_multiplier;
+
+        let mut rendered = 0;
+
+        for (count, zoom) in plan.iter().enumerate().rev() {
+            self.zoom = *zoom;
+            self.rotate = self.get_frame_rotation(count + self.frame_offset);
+            self.apply_frame_palette(count + self.frame_offset);
+
+            let index = self.get_output_index(count, plan.len());
+
+            let filename = resolve_filename_template(&self.filename_template, 
+                &self.name, 
+                index, 
+                &extended_to_string_short(self.zoom), 
+                self.maximum_iteration);
+
+            if self.resume && self.is_frame_complete(index, &filename) {
+                if self.show_output {
+                    println!(" {:<15}| skipped, {} already exists", extended_to_string_short(self.zoom), filename);
+                }
+
+                continue;
+            }
+
+            // SA has some problems with precision with lots of terms at low zoom levels
+            let (order, probe_multiplier) = if self.zoom.to_float() < 1e10 {
+                (min(original_order, 8), 1.0)
+            } else {
+                (original_order, original_probe_multiplier)
+            };
+
+            self.series_approximation.valid_iteration_probe_multiplier = probe_multiplier;
+
+            if order != self.series_approximation.order {
+                self.series_approximation.order = order;
+
+                // If nothing has been rendered yet the approximation will be generated with the first frame
+                if rendered > 0 {
+                    self.series_approximation.maximum_iteration = self.center_reference.current_iteration;
+                    self.series_approximation.generate_approximation(&self.center_reference, &self.progress.series_approximation, &Arc::new(AtomicBool::new(false)));
+                }
+            }
+
+            let filename = if self.overwrite_protection {
+                get_unused_filename(&filename)
+            } else {
+                filename
+            };
+
+            if self.motion_blur_samples > 1 {
+                let next_zoom = self.get_next_zoom(count + self.frame_offset);
+
+                self.render_blurred_frame(rendered, filename.clone(), next_zoom);
+                rendered += self.motion_blur_samples;
+            } else {
+                self.render_frame(rendered, filename.clone(), self.stop_flag.clone());
+                rendered += 1;
+            }
+
+            if self.cancelled {
+                return;
+            }
+
+            self.record_frame(index, self.zoom, self.maximum_iteration, &filename);
+        }
+
+        self.remaining_frames = 0;
+    }
+
+    // The maximum iteration which a frame at the zoom would be rendered with, following the sequence logic
+    pub fn get_estimated_iterations(&self, zoom: FloatExtended) -> usize {
+        let mut iterations = if self.keyframe_iterations == KeyframeIterations::Depth {
+            let initial_depth = self.initial_zoom.mantissa.log2() + self.initial_zoom.exponent as f64;
+            let current_depth = zoom.mantissa.log2() + zoom.exponent as f64;
+
+            if initial_depth > 0.0 {
+                min(self.maximum_iteration, (self.initial_maximum_iteration as f64 * current_depth.max(0.0) / initial_depth) as usize)
+            } else {
+                self.maximum_iteration
+            }
+        } else {
+            self.maximum_iteration
+        };
+
+        if self.auto_adjust_iterations && zoom.to_float() < 1e10 {
+            iterations = min(iterations, 10000);
+        }
+
+        max(iterations, 1)
+    }
+
+    // Walks the sequence without rendering it, the render time is estimated from a tiny render of the deepest frame,
+    // calibration_size pixels wide, with the reference only run to calibration_iterations. The time to iterate a frame
+    // is assumed to scale with the pixels and maximum iteration, and the reference and approximation time with the
+    // maximum iteration. The older calibration_scale is used instead if it is set.
+    pub fn estimate_sequence(&mut self) {
+        let plan = self.get_sequence_plan();
+
+        if plan.is_empty() {
+            log_warning(self.log_json, "the sequence has no frames to render");
+            return;
+        }
+
+        let calibration_scale = match self.settings.get_float("calibration_scale") {
+            Ok(scale) => scale.clamp(0.01, 1.0),
+            Err(_) => (self.settings.get_int("calibration_size").unwrap_or(64) as f64 / self.image_width as f64).min(1.0)
+        };
+
+        let calibration_iterations = min(self.maximum_iteration, max(self.settings.get_int("calibration_iterations").unwrap_or(100000), 1000) as usize);
+        let mut settings = self.settings.clone();
+
+        settings.set("image_width", max(16, (self.image_width as f64 * calibration_scale) as i64)).unwrap();
+        settings.set("image_height", max(16, (self.image_height as f64 * calibration_scale) as i64)).unwrap();
+        settings.set("iterations", calibration_iterations as i64).unwrap();
+        settings.set("auto_adjust_iterations", false).unwrap();
+        settings.set("crop_margin", 0i64).unwrap();
+        settings.set("frames", 1i64).unwrap();
+        settings.set("frame_offset", 0i64).unwrap();
+        settings.set("show_output", false).unwrap();
+        settings.set("manifest", false).unwrap();
+        settings.set("history", false).unwrap();
+        settings.set("recentre", "none").unwrap();
+        settings.set("threads", 0i64).unwrap();
+        settings.set("pin_threads", false).unwrap();
+
+        let mut calibration = FractalRenderer::new(settings);
+        calibration.zoom = plan[0];
+        calibration.save_output = false;
+        calibration.render_frame(0, String::new(), Arc::new(AtomicBool::new(false)));
+
+        let calibration_pixels = (calibration.image_width * calibration.image_height) as f64;
+        let frame_pixels = (self.image_width * self.image_height) as f64;
+
+        let setup_scale = self.maximum_iteration as f64 / max(calibration.maximum_iteration, 1) as f64;
+        let setup_time = (calibration.statistics.reference_time + calibration.statistics.approximation_time) as f64 * setup_scale;
+        let pixel_time = (calibration.statistics.iteration_time + calibration.statistics.correction_time) as f64 / calibration_pixels;
+
+        // The compressed size of the colour output is estimated from the calibration image
+        let colour_bytes_per_pixel = {
+            let export = calibration.data_export.lock();
+            let mut encoded = Vec::new();
+
+            image::png::PngEncoder::new(&mut encoded)
+                .encode(&export.buffer, export.image_width as u32, export.image_height as u32, image::ColorType::Rgb8)
+                .unwrap();
+
+            encoded.len() as f64 / calibration_pixels
+        };
+
+        let export_type = self.data_export.lock().export_type;
+
+        let raw_bytes_per_pixel = self.data_export.lock().get_raw_bytes_per_pixel();
+
+        let bytes_per_pixel = match export_type {
+            ExportType::Color => colour_bytes_per_pixel,
+            ExportType::Raw => raw_bytes_per_pixel,
+            ExportType::Both => colour_bytes_per_pixel + raw_bytes_per_pixel,
+            ExportType::Gui => 0.0
+        };
+
+        let reference_iterations = max(calibration.maximum_iteration, 1) as f64;
+
+        println!(" {:<6}| {:<15}| {:<10}| {:<15}| {:<15}| {:<15}", "Frame", "Zoom", "Precision", "Maximum [it]", "Estimate [ms]", "Size [MB]");
+
+        let mut total_time = setup_time;
+        let mut total_bytes = 0.0;
+
+        for (count, zoom) in plan.iter().enumerate() {
+            let precision = FractalRenderer::get_precision(*zoom, self.image_width, self.image_height);
+
+            let iterations = self.get_estimated_iterations(*zoom);
+
+            let frame_time = pixel_time * frame_pixels * iterations as f64 / reference_iterations;
+            let frame_bytes = bytes_per_pixel * frame_pixels;
+
+            total_time += frame_time;
+            total_bytes += frame_bytes;
+
+            println!(" {:<6}| {:<15}| {:<10}| {:<15}| {:<15.0}| {:<15.2}", 
+                self.get_output_index(count, plan.len()), 
+                extended_to_string_short(*zoom), 
+                precision, 
+                iterations, 
+                frame_time, 
+                frame_bytes / 1e6);
+        }
+
+        println!("Estimated {} frames taking {:.1} hours and {:.2} GB, calibrated from a {}x{} render to {} iterations in {} ms.", 
+            plan.len(), 
+            total_time / 3.6e6, 
+            total_bytes / 1e9, 
+            calibration.image_width, 
+            calibration.image_height, 
+            calibration.maximum_iteration, 
+            calibration.render_time);
+
+        println!("Estimated peak memory of {:.0} MB.", self.estimate_memory(self.pixel_chunk_size).total() as f64 / 1e6);
+    }
+
+    // Estimates the peak memory of the deepest frame from the parameters. The reference stores every iteration in double
+    // precision and every data storage interval at full precision, which the series approximation also uses.
+    pub fn estimate_memory(&self, pixel_chunk_size: usize) -> MemoryEstimate {
+        let total_pixels = self.image_width * self.image_height;
+        let pixels_in_flight = if pixel_chunk_size == 0 { self.render_indices.len() } else { min(pixel_chunk_size, self.render_indices.len()) };
+
+        let precision = FractalRenderer::get_precision(self.zoom, self.image_width, self.image_height) as usize;
+
+        let stored_iterations = self.maximum_iteration / max(self.center_reference.data_storage_interval, 1) + 1;
+
+        // The pixel data, colour, iteration, smooth, stripe, distance, glitch and correction pass channels
+        let mut export = total_pixels * (3 + 4 + 4 + 4 + 8 + 1 + 4);
+
+        // Additional samples are exported into a copy of the data
+        if self.samples_per_pixel > 1 || self.adaptive_antialiasing_samples > 0 {
+            export *= 2;
+        }
+
+        MemoryEstimate {
+            pixel_state: pixels_in_flight * std::mem::size_of::<PixelData>() + self.render_indices.len() * std::mem::size_of::<usize>(),
+            reference: self.maximum_iteration * std::mem::size_of::<ReferenceIteration>() + stored_iterations * (2 * precision / 8 + 64),
+            series_approximation: stored_iterations * (self.series_approximation.order + 1) * std::mem::size_of::<ComplexExtended>(),
+            export,
+        }
+    }
+
+    // The memory limit is in megabytes. When it would be exceeded the pixels are rendered in chunks which fit, and if even
+    // the reference and export buffers do not fit the render is refused.
+    pub fn enforce_memory_limit(&mut self) -> bool {
+        if self.memory_limit == 0 {
+            return true;
+        }
+
+        let limit = self.memory_limit * 1_000_000;
+        let estimate = self.estimate_memory(self.pixel_chunk_size);
+
+        if estimate.total() <= limit {
+            return true;
+        }
+
+        let index_memory = self.render_indices.len() * std::mem::size_of::<usize>();
+        let available = limit.saturating_sub(estimate.fixed() + index_memory);
+
+        // Chunks smaller than this spend most of the time on the overhead of each chunk
+        let pixel_chunk_size = available / std::mem::size_of::<PixelData>();
+
+        if pixel_chunk_size < 4096 {
+            if self.log_json {
+                log_event("error", "memory_limit", json!({
+                    "estimated_mb": estimate.total() as f64 / 1e6,
+                    "limit_mb": self.memory_limit,
+                }));
+            } else {
+                println!("The render needs an estimated {:.0} MB, which is over the memory limit of {} MB.", estimate.total() as f64 / 1e6, self.memory_limit);
+            }
+
+            return false;
+        }
+
+        if self.show_output {
+            println!("The render needs an estimated {:.0} MB, so the pixels are rendered in chunks of {} to stay under the memory limit of {} MB.", 
+                estimate.total() as f64 / 1e6, 
+                pixel_chunk_size, 
+                self.memory_limit);
+        }
+
+        self.pixel_chunk_size = pixel_chunk_size;
+
+        true
+    }
+
+    // The zoom of the frame after the current one in the sequence
+    pub fn get_next_zoom(&self, frame: usize) -> FloatExtended {
+        self.get_eased_zoom(frame + 1).unwrap_or_else(|| {
+            let mut zoom = self.zoom;
+            zoom.mantissa /= self.zoom_scale_factor;
+            zoom.reduce();
+            zoom
+        })
+    }
+
+    // Renders a number of samples between this frame and the next and averages them in linear light
+    // The shutter is the fraction of the interval between the frames which is covered
+    pub fn render_blurred_frame(&mut self, frame_index: usize, filename: String, next_zoom: FloatExtended) {
+        let frame_zoom = self.zoom;
+        let mut accumulation = vec![0.0f32; self.image_width * self.image_height * 3];
+
+        self.save_output = false;
+
+        for sample in 0..self.motion_blur_samples {
+            let factor = self.motion_blur_shutter * sample as f64 / self.motion_blur_samples as f64;
+
+            self.zoom = linear_interpolation_between_zoom(frame_zoom, next_zoom, factor);
+            self.render_frame(frame_index + sample, filename.clone(), self.stop_flag.clone());
+
+            if self.cancelled {
+                self.save_output = true;
+                self.zoom = frame_zoom;
+                return;
+            }
+
+            for (total, &value) in accumulation.iter_mut().zip(self.data_export.lock().buffer.iter()) {
+                *total += srgb_to_linear(value);
+            }
+        }
+
+        self.save_output = true;
+        self.zoom = frame_zoom;
+
+        {
+            let mut export = self.data_export.lock();
+
+            for (value, total) in export.buffer.iter_mut().zip(accumulation.into_iter()) {
+                *value = linear_to_srgb(total / self.motion_blur_samples as f32);
+            }
+        }
+
+        let metadata = self.get_output_metadata();
+        self.data_export.lock().save(&filename, self.series_approximation.order, &extended_to_string_long(self.zoom), &metadata);
+    }
+
+    // Renders the frames evenly spaced along the camera path, each frame needs its own reference
+    pub fn render_camera_path(&mut self) {
+        let frames = if self.remaining_frames == usize::MAX {
+            1
+        } else {
+            self.remaining_frames
+        };
+
+        for frame in 0..frames {
+            let position = if frames > 1 {
+                frame as f64 / (frames - 1) as f64
+            } else {
+                0.0
+            };
+
+            let (location, zoom, rotate) = self.camera_path.as_ref().unwrap().get_frame(position);
+
+            let filename = resolve_filename_template(&self.filename_template, 
+                &self.name, 
+                frame + self.frame_offset, 
+                &extended_to_string_short(zoom), 
+                self.maximum_iteration);
+
+            if self.resume && self.is_frame_complete(frame + self.frame_offset, &filename) {
+                continue;
+            }
+
+            let filename = if self.overwrite_protection {
+                get_unused_filename(&filename)
+            } else {
+                filename
+            };
+
+            let mut settings = self.settings.clone();
+
+            settings.set("real", location.real().to_string_radix(10, None)).unwrap();
+            settings.set("imag", location.imag().to_string_radix(10, None)).unwrap();
+            settings.set("zoom", extended_to_string_long(zoom)).unwrap();
+            settings.set("rotate", rotate.to_degrees()).unwrap();
+            settings.set("frames", 1i64).unwrap();
+            settings.set("frame_offset", 0i64).unwrap();
+            settings.set("filename_template", filename.clone()).unwrap();
+            settings.set("overwrite_protection", false).unwrap();
+            settings.set("manifest", false).unwrap();
+            settings.set("history", false).unwrap();
+            settings.set("resume", false).unwrap();
+            settings.set("show_output", false).unwrap();
+            settings.set("path", Vec::<config::Value>::new()).unwrap();
+            settings.set("threads", 0i64).unwrap();
+            settings.set("pin_threads", false).unwrap();
+
+            let mut renderer = FractalRenderer::new(settings);
+            renderer.stop_flag = self.stop_flag.clone();
+            *renderer.progress_sender.lock() = self.progress_sender.lock().clone();
+            *renderer.region_sender.lock() = self.region_sender.lock().clone();
+            renderer.render();
+
+            if renderer.cancelled {
+                self.cancelled = true;
+                return;
+            }
+
+            if self.show_output {
+                println!(" {:<15}| {} ms", extended_to_string_short(zoom), renderer.render_time);
+            }
+
+            self.record_frame(frame + self.frame_offset, zoom, renderer.maximum_iteration, &filename);
+        }
+    }
+
+    // Splits the remaining frames of the sequence into work units, each with the settings to render that frame on its
+    // own. A single frame is split into tiles instead when the tile size is set.
+    pub fn get_work_units(&self) -> Vec<WorkUnit> {
+        let plan = self.get_sequence_plan();
+        let total_frames = plan.len();
+
+        let location = self.center_reference.get_location();
+
+        let real = location.real().to_string_radix(10, None);
+        let imag = location.imag().to_string_radix(10, None);
+
+        let tile_size = self.get_distributed_tile_size(total_frames);
+
+        plan.iter().enumerate().flat_map(|(count, zoom)| {
+            let frame = count + self.frame_offset;
+            let index = self.get_output_index(count, total_frames);
+
+            let filename = resolve_filename_template(&self.filename_template, 
+                &self.name, 
+                index, 
+                &extended_to_string_short(*zoom), 
+                self.maximum_iteration);
+
+            if self.resume && self.is_frame_complete(index, &filename) {
+                return Vec::new();
+            }
+
+            let mut settings = self.settings.clone();
+
+            settings.set("rotate", self.get_frame_rotation(frame).to_degrees()).unwrap();
+
+            let (palette_iteration_span, palette_offset) = self.get_frame_palette(frame);
+            settings.set("palette_iteration_span", palette_iteration_span as f64).unwrap();
+            settings.set("palette_offset", palette_offset as f64).unwrap();
+
+            settings.set("real", real.clone()).unwrap();
+            settings.set("imag", imag.clone()).unwrap();
+            settings.set("zoom", extended_to_string_long(*zoom)).unwrap();
+            settings.set("frames", 1i64).unwrap();
+            settings.set("frame_offset", 0i64).unwrap();
+            settings.set("filename_template", filename.clone()).unwrap();
+            settings.set("overwrite_protection", false).unwrap();
+            settings.set("resume", false).unwrap();
+            settings.set("parallel_keyframes", 0i64).unwrap();
+            settings.set("manifest", false).unwrap();
+            settings.set("history", false).unwrap();
+            settings.set("show_output", false).unwrap();
+
+            match tile_size {
+                Some(tile_size) => self.get_tile_units(index, *zoom, &filename, settings, tile_size),
+                None => vec![WorkUnit {
+                    index,
+                    zoom: extended_to_string_long(*zoom),
+                    maximum_iteration: self.maximum_iteration,
+                    files: self.data_export.lock().get_output_files(&filename),
+                    filename,
+                    settings: settings.try_into::<serde_json::Value>().unwrap(),
+                    tile: None,
+                    reference_files: Vec::new(),
+                }]
+            }
+        }).collect()
+    }
+
+    // Only a single frame is split into tiles, as the reference of each frame is calculated by the coordinator
+    fn get_distributed_tile_size(&self, total_frames: usize) -> Option<usize> {
+        let tile_size = max(self.settings.get_int("distributed_tile_size").unwrap_or(0), 0) as usize;
+
+        if tile_size == 0 {
+            return None;
+        }
+
+        if total_frames > 1 || self.exponential_map {
+            log_warning(self.log_json, "distributed_tile_size only splits a single frame which is not an exponential map, the frames are rendered whole");
+            return None;
+        }
+
+        if self.data_export.lock().export_type != ExportType::Color {
+            log_warning(self.log_json, "a frame split into tiles is only saved as a colour image");
+        }
+
+        Some(max(tile_size, 2))
+    }
+
+    // The tiles all have the same size, so that they have the same reference and series approximation, and the tiles
+    // on the right and bottom edges are cropped when the frame is put together. Each tile keeps the centre of the frame
+    // and is moved to its place with the image offset, with the zoom raised so that the pixels are the same size as
+    // in the whole frame. The tiles are only saved as colour images.
+    fn get_tile_units(&self, index: usize, zoom: FloatExtended, filename: &str, mut settings: Config, tile_size: usize) -> Vec<WorkUnit> {
+        let frame_width = self.parameters.image_width;
+        let frame_height = self.parameters.image_height;
+
+        let scale = (frame_height * self.supersampling - 1) as f64 / (tile_size * self.supersampling - 1) as f64;
+
+        settings.set("image_width", tile_size as i64).unwrap();
+        settings.set("image_height", tile_size as i64).unwrap();
+        settings.set("zoom", extended_to_string_long(zoom * scale)).unwrap();
+        settings.set("iterations", self.maximum_iteration as i64).unwrap();
+        settings.set("auto_adjust_iterations", false).unwrap();
+        settings.set("crop_margin", 0i64).unwrap();
+        settings.set("remove_centre", false).unwrap();
+        settings.set("export", "colour").unwrap();
+        settings.set("export_histogram", false).unwrap();
+        settings.set("export_reference_orbit", false).unwrap();
+        settings.set("heightmap", "none").unwrap();
+        settings.set("mesh", "none").unwrap();
+        settings.set("additional_resolutions", Vec::<config::Value>::new()).unwrap();
+        settings.set("reference_cache", true).unwrap();
+        settings.set("reference_cache_directory", self.parameters.reference_cache_directory.clone()).unwrap();
+
+        let tiles_x = (frame_width + tile_size - 1) / tile_size;
+        let tiles_y = (frame_height + tile_size - 1) / tile_size;
+
+        (0..tiles_y).flat_map(|tile_y| (0..tiles_x).map(move |tile_x| (tile_x * tile_size, tile_y * tile_size))).map(|(x, y)| {
+            let tile_filename = format!("{}_tile_{}_{}.png", filename, x, y);
+
+            let offset_x = x as f64 + 0.5 * (tile_size as f64 - frame_width as f64);
+            let offset_y = y as f64 + 0.5 * (tile_size as f64 - frame_height as f64);
+
+            let mut settings = settings.clone();
+            settings.set("image_offset", vec![offset_x, offset_y]).unwrap();
+            settings.set("filename_template", tile_filename.clone()).unwrap();
+
+            WorkUnit {
+                index,
+                zoom: extended_to_string_long(zoom),
+                maximum_iteration: self.maximum_iteration,
+                filename: filename.to_owned(),
+                files: vec![tile_filename],
+                settings: settings.try_into::<serde_json::Value>().unwrap(),
+                tile: Some((x, y)),
+                reference_files: Vec::new(),
+            }
+        }).collect()
+    }
+
+    // The tiles are copied into the frame, cropping those over the edges, and removed once the frame is saved
+    fn save_tiled_frame(&self, filename: &str, tiles: &[(usize, usize, String)]) {
+        let frame_width = self.parameters.image_width;
+        let frame_height = self.parameters.image_height;
+
+        let mut buffer = vec![0u8; 3 * frame_width * frame_height];
+
+        for (x, y, tile_filename) in tiles {
+            let tile = match image::open(tile_filename) {
+                Ok(tile) => tile.to_rgb8(),
+                Err(error) => {
+                    log_warning(self.log_json, &format!("could not read the tile {}: {}", tile_filename, error));
+                    continue;
+                }
+            };
+
+            let width = min(tile.width() as usize, frame_width - x);
+            let height = min(tile.height() as usize, frame_height - y);
+
+            for j in 0..height {
+                let start = 3 * j * tile.width() as usize;
+                let frame_start = 3 * ((y + j) * frame_width + x);
+
+                buffer[frame_start..(frame_start + 3 * width)].copy_from_slice(&tile.as_raw()[start..(start + 3 * width)]);
+            }
+        }
+
+        let colour_filename = match filename.split_terminator('.').last() {
+            Some("jpg") | Some("jpeg") | Some("png") => filename.to_owned(),
+            _ => filename.to_owned() + ".png"
+        };
+
+        DataExport::write_colour_buffer(&colour_filename, &buffer, frame_width, frame_height, &self.get_metadata());
+
+        for (_, _, tile_filename) in tiles {
+            fs::remove_file(tile_filename).ok();
+        }
+    }
+
+    // The frames are rendered by the workers which connect to the address, and recorded here as they are returned
+    pub fn render_distributed(&mut self, address: &str) {
+        if let Some(zoom) = self.get_eased_zoom(self.frame_offset) {
+            self.zoom = zoom;
+        }
+
+        match self.recentre {
+            Recentre::Iteration => self.recentre_on_iteration(),
+            Recentre::Nucleus => self.recentre_on_nucleus(),
+            Recentre::None => {}
+        }
+
+        let mut units = self.get_work_units();
+        let mut tiles = Vec::new();
+
+        let tiled = units.first().map_or(false, |unit| unit.tile.is_some());
+        let total_units = units.len();
+
+        // The first tile is rendered here, which calculates the reference and series approximation into the cache to
+        // be sent to the workers with the other tiles
+        if tiled && units.len() > 1 {
+            let first = units.remove(0);
+
+            let mut settings = Config::default();
+            settings.merge(File::from_str(&first.settings.to_string(), FileFormat::Json)).unwrap();
+
+            let mut renderer = FractalRenderer::new(settings);
+            renderer.stop_flag = self.stop_flag.clone();
+            renderer.render();
+
+            if renderer.cancelled {
+                self.cancelled = true;
+                return;
+            }
+
+            let reference_files = match &renderer.reference_cache {
+                Some(cache) if !renderer.center_reference.julia => {
+                    let mut files = vec![cache.get_reference_filename(&renderer.center_reference, renderer.fractal_type)];
+
+                    if renderer.series_approximation.enabled {
+                        files.push(cache.get_approximation_filename(&renderer.center_reference, &renderer.series_approximation));
+                    }
+
+                    files
+                },
+                _ => Vec::new()
+            };
+
+            for unit in units.iter_mut() {
+                unit.reference_files = reference_files.clone();
+            }
+
+            if let Some((x, y)) = first.tile {
+                tiles.push((x, y, first.files[0].clone()));
+            }
+        }
+
+        let mut received = total_units - units.len();
+
+        if self.show_output {
+            if tiled {
+                println!("Waiting for workers on {} to render {} tiles.", address, units.len());
+            } else {
+                println!("Waiting for workers on {} to render {} frames.", address, units.len());
+            }
+        }
+
+        run_coordinator(address, units, |unit, files| {
+            if self.show_output {
+                println!(" {:<15}| {} files received", extended_to_string_short(string_to_extended(&unit.zoom)), files.len());
+            }
+
+            if let Some((x, y)) = unit.tile {
+                received += 1;
+
+                if let Some(file) = files.first() {
+                    tiles.push((x, y, file.clone()));
+                }
+
+                if received < total_units {
+                    return;
+                }
+
+                self.save_tiled_frame(&unit.filename, &tiles);
+            }
+
+            self.record_frame(unit.index, string_to_extended(&unit.zoom), unit.maximum_iteration, &unit.filename);
+        });
+    }
+
+    // Renders all of the remaining keyframes with a separate renderer for each, a number at a time
+    pub fn render_parallel_keyframes(&mut self, count: usize, total_frames: usize) {
+        let mut keyframes = Vec::new();
+        let mut zoom = self.zoom;
+        let mut index = count;
+
+        while self.remaining_frames > 0 && zoom > self.end_zoom {
+            let output_index = self.get_output_index(index, total_frames);
+
+            let filename = resolve_filename_template(&self.filename_template, 
+                &self.name, 
+                output_index, 
+                &extended_to_string_short(zoom), 
+                self.maximum_iteration);
+
+            if !(self.resume && self.is_frame_complete(output_index, &filename)) {
+                let filename = if self.overwrite_protection {
+                    get_unused_filename(&filename)
+                } else {
+                    filename
+                };
+
+                keyframes.push((index + self.frame_offset, output_index, zoom, self.get_estimated_iterations(zoom), filename));
+            }
+
+            zoom.mantissa /= self.zoom_scale_factor;
+            zoom.reduce();
+
+            if let Some(eased_zoom) = self.get_eased_zoom(index + 1 + self.frame_offset) {
+                zoom = eased_zoom;
+            }
+
+            self.remaining_frames -= 1;
+            index += 1;
+        }
+
+        self.zoom = zoom;
+
+        let location = self.center_reference.get_location();
+
+        let real = location.real().to_string_radix(10, None);
+        let imag = location.imag().to_string_radix(10, None);
+
+        let cancelled = AtomicBool::new(false);
+
+        for chunk in keyframes.chunks(self.parallel_keyframes) {
+            chunk.par_iter().for_each(|(frame, _, zoom, maximum_iteration, filename)| {
+                let mut settings = self.settings.clone();
+
+                settings.set("rotate", self.get_frame_rotation(*frame).to_degrees()).unwrap();
+
+                let (palette_iteration_span, palette_offset) = self.get_frame_palette(*frame);
+                settings.set("palette_iteration_span", palette_iteration_span as f64).unwrap();
+                settings.set("palette_offset", palette_offset as f64).unwrap();
+
+                settings.set("real", real.clone()).unwrap();
+                settings.set("imag", imag.clone()).unwrap();
+                settings.set("zoom", extended_to_string_long(*zoom)).unwrap();
+                settings.set("iterations", *maximum_iteration as i64).unwrap();
+                settings.set("frames", 1i64).unwrap();
+                settings.set("frame_offset", 0i64).unwrap();
+                settings.set("filename_template", filename.clone()).unwrap();
+                settings.set("overwrite_protection", false).unwrap();
+                settings.set("resume", false).unwrap();
+                settings.set("parallel_keyframes", 0i64).unwrap();
+                settings.set("manifest", false).unwrap();
+                settings.set("history", false).unwrap();
+                settings.set("show_output", false).unwrap();
+                settings.set("threads", 0i64).unwrap();
+                settings.set("pin_threads", false).unwrap();
+
+                let mut renderer = FractalRenderer::new(settings);
+                renderer.stop_flag = self.stop_flag.clone();
+                renderer.render();
+
+                // The flag is cleared by the renderer which stopped, so it is set again for the other keyframes
+                if renderer.cancelled {
+                    cancelled.store(true, Ordering::SeqCst);
+                    self.stop_flag.store(true, Ordering::SeqCst);
+                    return;
+                }
+
+                if self.show_output {
+                    println!(" {:<15}| {} ms", extended_to_string_short(*zoom), renderer.render_time);
+                }
+            });
+
+            if cancelled.load(Ordering::SeqCst) {
+                self.stop_flag.store(false, Ordering::SeqCst);
+                self.cancelled = true;
+                return;
+            }
+
+            for (_, output_index, zoom, maximum_iteration, filename) in chunk {
+                self.record_frame(*output_index, *zoom, *maximum_iteration, filename);
+            }
+        }
+    }
+
+    // Progressive indices are ordered from a coarse grid to the full resolution, so that each stage can be previewed
+    pub fn generate_render_indices(image_width: usize, image_height: usize, remove_centre: bool, zoom_scale_factor: f64, progressive: bool, pixel_order: PixelOrder) -> Vec<usize> {
+        // let time = Instant::now();
+
+        let mut indices = Vec::with_capacity(image_width * image_height);
+
+        let temp = 0.5 - 0.5 / zoom_scale_factor;
+        let val1 = (image_width as f64 * temp).ceil() as usize;
+        let val2 = (image_height as f64 * temp).ceil() as usize;
+
+        if progressive {
+            // Could order each subsection with circular ordering
+            let values = [16, 8, 4, 2, 1];
+
+            for (n, value) in values.iter().enumerate() {
+                let mut stage = Vec::new();
+
+                for j in (0..image_height).step_by(*value) {
+                    for i in (0..image_width).step_by(*value) {
+                        if n == 0 || i & (values[n - 1] - 1) != 0 || j & (values[n - 1] - 1) != 0 {
+                            if !remove_centre || (i <= val1 || i >= image_width - val1 || j <= val2 || j >= image_height - val2) {
+                                stage.push(j * image_width + i);
+                            }
+
+                        }
+                    }
+                }
+
+                // The order only applies within each stage
+                FractalRenderer::order_indices(&mut stage, image_width, image_height, pixel_order);
+                indices.append(&mut stage);
+            }
+        } else {
+            for j in 0..image_height {
+                for i in 0..image_width {
+                    if !remove_centre || (i <= val1 || i >= image_width - val1 || j <= val2 || j >= image_height - val2) {
+                        indices.push(j * image_width + i);
+                    }
+                }
+            }
+
+            FractalRenderer::order_indices(&mut indices, image_width, image_height, pixel_order);
+        }
+
+        // println!("generate indices took {}ms", time.elapsed().as_millis());
+
+        indices
+    }
+
+    // Reorders the indices, which start in scanline order. The random order uses a fixed seed so that renders are repeatable.
+    pub fn order_indices(indices: &mut Vec<usize>, image_width: usize, image_height: usize, pixel_order: PixelOrder) {
+        match pixel_order {
+            PixelOrder::Scanline => {},
+            PixelOrder::Random => {
+                indices.shuffle(&mut StdRng::seed_from_u64(0));
+            },
+            PixelOrder::Spiral => {
+                // Rings of increasing distance from the centre, each ring in order of angle
+                let centre_x = 0.5 * (image_width - 1) as f64;
+                let centre_y = 0.5 * (image_height - 1) as f64;
+
+                indices.sort_by_cached_key(|&index| {
+                    let x = (index % image_width) as f64 - centre_x;
+                    let y = (index / image_width) as f64 - centre_y;
+
+                    let ring = x.abs().max(y.abs()) as i64;
+                    let angle = (y.atan2(x) * 1e6) as i64;
+
+                    (ring, angle)
+                });
+            },
+            PixelOrder::Hilbert => {
+                let size = max(image_width, image_height).next_power_of_two();
+
+                indices.sort_by_cached_key(|&index| {
+                    FractalRenderer::get_hilbert_distance(size, index % image_width, index / image_width)
+                });
+            },
+            PixelOrder::Tiled => {
+                let tiles_x = (image_width + TILE_SIZE - 1) / TILE_SIZE;
+
+                indices.sort_by_key(|&index| (FractalRenderer::get_tile(index, image_width, tiles_x), index));
+            }
+        }
+    }
+
+    // The image is moved from the reference by the image offset, which is in pixels before the rotation
+    pub fn get_image_delta_top_left(&self, delta_pixel: f64, cos_rotate: f64, sin_rotate: f64) -> ComplexFixed<f64> {
+        let delta_top_left = get_delta_top_left(delta_pixel, self.image_width, self.image_height, cos_rotate, sin_rotate);
+        let (offset_x, offset_y) = self.image_offset;
+
+        ComplexFixed::new(
+            delta_top_left.re + (offset_x * cos_rotate - offset_y * sin_rotate) * delta_pixel,
+            delta_top_left.im + (offset_x * sin_rotate + offset_y * cos_rotate) * delta_pixel)
+    }
+
+    // The row major index of the tile containing the pixel
+    pub fn get_tile(index: usize, image_width: usize, tiles_x: usize) -> usize {
+        (index / image_width / TILE_SIZE) * tiles_x + (index % image_width) / TILE_SIZE
+    }
+
+    // Iterates the pixels with each tile as a separate task. The regions which escape quickly finish their tiles early,
+    // and the idle threads steal the remaining tiles rather than waiting on a thread with a slow block of scanlines.
+    // The pixels must be ordered by tile, which the tiled pixel order does.
+    pub fn iterate_tiles(&self, pixel_data: &mut [PixelData], stop_flag: &Arc<AtomicBool>, delta_pixel_extended: FloatExtended, scale: usize) {
+        let tiles_x = (self.image_width + TILE_SIZE - 1) / TILE_SIZE;
+
+        let mut tiles = Vec::new();
+        let mut remaining = pixel_data;
+
+        while !remaining.is_empty() {
+            let tile = FractalRenderer::get_tile(remaining[0].index, self.image_width, tiles_x);
+
+            let length = remaining.iter()
+                .position(|pixel| FractalRenderer::get_tile(pixel.index, self.image_width, tiles_x) != tile)
+                .unwrap_or(remaining.len());
+
+            let (current, rest) = std::mem::take(&mut remaining).split_at_mut(length);
+
+            tiles.push(current);
+            remaining = rest;
+        }
+
+        tiles.into_par_iter().for_each(|tile| {
+            // The tiles which have not been started are skipped entirely when the render is stopped
+            if stop_flag.load(Ordering::SeqCst) {
+                return;
+            }
+
+            let chunk_size = tile.len();
+
+            let index = tile[0].index;
+
+            Perturbation::iterate(tile, &self.center_reference, &self.progress.iteration, stop_flag, self.data_export.clone(), delta_pixel_extended, scale, chunk_size, self.fractal_type, self.pixel_data_type, &self.series_approximation, true);
+
+            if !stop_flag.load(Ordering::SeqCst) {
+                let x = (index % self.image_width) / TILE_SIZE * TILE_SIZE;
+                let y = (index / self.image_width) / TILE_SIZE * TILE_SIZE;
+
+                self.send_region(x, y, min(TILE_SIZE, self.image_width - x), min(TILE_SIZE, self.image_height - y));
+            }
+        });
+    }
+
+    // The distance along a hilbert curve filling a square of the size, which must be a power of two
+    pub fn get_hilbert_distance(size: usize, x: usize, y: usize) -> usize {
+        let mut x = x;
+        let mut y = y;
+        let mut distance = 0;
+        let mut s = size / 2;
+
+        while s > 0 {
+            let rx = (x & s > 0) as usize;
+            let ry = (y & s > 0) as usize;
+
+            distance += s * s * ((3 * rx) ^ ry);
+
+            // Rotate the quadrant
+            if ry == 0 {
+                if rx == 1 {
+                    x = size - 1 - x;
+                    y = size - 1 - y;
+                }
+
+                std::mem::swap(&mut x, &mut y);
+            }
+
+            s /= 2;
+        }
+
+        distance
+    }
+
+    pub fn parse_pixel_order(settings: &Config) -> PixelOrder {
+        match settings.get_str("pixel_order").unwrap_or_else(|_| String::from("SCANLINE")).to_ascii_uppercase().as_ref() {
+            "RANDOM" => PixelOrder::Random,
+            "SPIRAL" => PixelOrder::Spiral,
+            "HILBERT" => PixelOrder::Hilbert,
+            "TILED" => PixelOrder::Tiled,
+            _ => PixelOrder::Scanline
+        }
+    }
+
+    // Returns the number of frames, the scale factor between frames and the zoom to stop at
+    // If an end zoom and more than one frame are given, the scale factor is chosen so that the frames span the range exactly
+    // Setting the frames to zero renders until the end zoom is reached
+    pub fn get_sequence_range(settings: &Config, initial_zoom: &str, end_zoom: Option<&str>, exponential_map: bool, image_width: usize, image_height: usize) -> (usize, f64, FloatExtended) {
+        let end_zoom = end_zoom.map(|zoom| parse_zoom(zoom, image_height).expect("end zoom not valid"));
+
+        let frames = match settings.get_int("frames").unwrap_or(1) as usize {
+            0 => usize::MAX,
+            frames => frames
+        };
+
+        // Exponential map keyframes need to join up exactly, so the scale is fixed by the image dimensions
+        let zoom_scale_factor = if exponential_map {
+            get_exponential_map_scale_factor(image_width, image_height)
+        } else {
+            match end_zoom {
+                Some(end_zoom) if frames > 1 && frames < usize::MAX => get_zoom_scale_between(parse_zoom(initial_zoom, image_height).expect("zoom not valid"), end_zoom, frames),
+                _ => settings.get_float("zoom_scale").unwrap_or(2.0)
+            }
+        };
+
+        // Allow for a small amount of rounding error so that the final frame is still rendered
+        let end_zoom = match end_zoom {
+            Some(end_zoom) => end_zoom * (1.0 - 1e-6),
+            None => FloatExtended::new(0.5, 0)
+        };
+
+        (frames, zoom_scale_factor, end_zoom)
+    }
+
+    // Additional resolutions are given in the form "1920x1080", the entries which are not valid are reported by the
+    // validation and skipped here
+    pub fn parse_additional_resolutions(settings: &Config) -> Vec<(usize, usize)> {
+        settings.get_array("additional_resolutions").unwrap_or_default().into_iter()
+            .filter_map(|value| value.into_str().ok())
+            .filter_map(|resolution| parse_resolution(&resolution))
+            .collect()
+    }
+
+    pub fn regenerate_from_settings(&mut self, mut settings: Config) {
+        FractalRenderer::apply_output_directory(&mut settings);
+        FractalRenderer::apply_region(&mut settings);
+        FractalRenderer::apply_view_fit(&mut settings);
+
+        let parameters = RenderParameters::from_config(&settings);
+
+        self.exponential_map = parameters.exponential_map;
+        self.supersampling = max(parameters.supersampling, 1);
+        self.crop_margin = FractalRenderer::get_crop_margin(&settings, self.exponential_map) * self.supersampling;
+        self.image_width = parameters.image_width * self.supersampling + 2 * self.crop_margin;
+        self.image_height = parameters.image_height * self.supersampling + 2 * self.crop_margin;
+
+        let (offset_x, offset_y) = parameters.get_image_offset();
+        self.image_offset = (offset_x * self.supersampling as f64, offset_y * self.supersampling as f64);
+        self.data_export.lock().supersampling = self.supersampling;
+        self.data_export.lock().reconstruction_filter = FractalRenderer::parse_reconstruction_filter(&settings);
+        self.rotate = parameters.rotate.to_radians();
+        self.maximum_iteration = parameters.iterations;
+        let initial_zoom = parameters.zoom.to_ascii_uppercase();
+        let center_real = parameters.real.clone();
+        let center_imag = parameters.imag.clone();
+        let approximation_order = parameters.approximation_order;
+        self.glitch_percentage = parameters.glitch_percentage;
+        self.frame_offset = parameters.frame_offset;
+
+        let (remaining_frames, zoom_scale_factor, end_zoom) = FractalRenderer::get_sequence_range(&settings, &initial_zoom, parameters.end_zoom.as_deref(), self.exponential_map, self.image_width, self.image_height);
+
+        self.remaining_frames = remaining_frames;
+        self.sequence_frames = remaining_frames;
+        self.sequence_start_zoom = string_to_extended(&initial_zoom);
+        self.zoom_scale_factor = zoom_scale_factor;
+        self.end_zoom = end_zoom;
+
+        self.data_export.lock().display_glitches = parameters.display_glitches;
+        self.auto_adjust_iterations = parameters.auto_adjust_iterations;
+        self.automatic_iterations = parameters.automatic_iterations;
+        self.automatic_iterations_threshold = parameters.automatic_iterations_threshold;
+        self.automatic_iterations_limit = parameters.automatic_iterations_limit;
+
+        let series_approximation_tiled = parameters.series_approximation_tiled;
+        // The series approximation is derived for the Mandelbrot set, so it is not used for Julia sets
+        let series_approximation_enabled = parameters.series_approximation_enabled && !FractalRenderer::is_julia(&parameters);
+
+        let probe_sampling = parameters.probe_sampling;
+
+        self.data_export.lock().palette_iteration_span = parameters.palette_iteration_span as f32;
+        self.data_export.lock().palette_offset = parameters.palette_offset as f32;
+        self.data_export.lock().distance_transition = parameters.distance_transition as f32;
+        self.data_export.lock().distance_color = parameters.distance_color;
+
+        self.data_export.lock().lighting = parameters.lighting;
+
+        let lighting_direction = parameters.lighting_direction as f32;
+        let lighting_azimuth = parameters.lighting_azimuth as f32;
+        let lighting_opacity = parameters.lighting_opacity as f32;
+        let lighting_ambient = parameters.lighting_ambient as f32;
+        let lighting_diffuse = parameters.lighting_diffuse as f32;
+        let lighting_specular = parameters.lighting_specular as f32;
+        let lighting_shininess = parameters.lighting_shininess;
+
+        self.data_export.lock().change_lighting(lighting_direction, lighting_azimuth, lighting_opacity, lighting_ambient, lighting_diffuse, lighting_specular, lighting_shininess);
+
+        let valid_iteration_probe_multiplier = parameters.valid_iteration_probe_multiplier as f32;
+        let glitch_tolerance = parameters.glitch_tolerance;
+        let data_storage_interval = parameters.data_storage_interval;
+        
+        let coloring_type = match parameters.coloring_type.to_ascii_uppercase().as_ref() {
+            "SMOOTH_ITERATION" | "SMOOTH" => ColoringType::SmoothIteration,
+            "STEP_ITERATION" | "STEP" => ColoringType::StepIteration,
+            "DISTANCE" => ColoringType::Distance,
+            "STRIPE" => ColoringType::Stripe,
+            "DISTANCE_STRIPE" => ColoringType::DistanceStripe,
+            _ => ColoringType::SmoothIteration
+        };
+
+        let pixel_data_type = match coloring_type {
+            ColoringType::SmoothIteration | ColoringType::StepIteration => DataType::Iteration,
+            ColoringType::Stripe => DataType::Stripe,
+            ColoringType::DistanceStripe => DataType::DistanceStripe,
+            _ => DataType::Distance
+        };
+
+        self.data_export.lock().stripe_scale = parameters.stripe_scale as f32;
+
+        self.data_export.lock().heightmap_type = match parameters.heightmap.to_ascii_uppercase().as_ref() {
+            "ITERATION" | "SMOOTH" => HeightmapType::Iteration,
+            "DISTANCE" => HeightmapType::Distance,
+            _ => HeightmapType::None
+        };
+
+        self.data_export.lock().mesh_type = match parameters.mesh.to_ascii_uppercase().as_ref() {
+            "OBJ" => MeshType::Obj,
+            "STL" => MeshType::Stl,
+            _ => MeshType::None
+        };
+
+        self.data_export.lock().mesh_scale = parameters.mesh_scale as f32;
+        self.data_export.lock().mesh_decimation = parameters.mesh_decimation;
+        self.data_export.lock().additional_resolutions = FractalRenderer::parse_additional_resolutions(&settings);
+
+        self.jitter = parameters.jitter;
+        self.jitter_factor = parameters.jitter_factor;
+        self.show_output = parameters.show_output;
+        self.log_json = parameters.log_format.eq_ignore_ascii_case("json");
+        self.name = parameters.name.clone();
+        self.filename_template = parameters.filename_template.clone();
+        self.overwrite_protection = parameters.overwrite_protection;
+        self.export_statistics = parameters.export_statistics;
+        self.resume = parameters.resume;
+        self.parallel_keyframes = parameters.parallel_keyframes;
+        self.parallel_keyframe_zoom = parameters.parallel_keyframe_zoom;
+        self.keyframe_iterations = FractalRenderer::parse_keyframe_iterations(&settings);
+        self.zoom_easing = Easing::from_settings(&settings, "zoom_easing", "zoom_curve");
+        self.rotate_easing = Easing::from_settings(&settings, "rotate_easing", "rotate_curve");
+        self.rotate_start = self.rotate;
+        self.rotate_end = parameters.end_rotate.map(|angle| angle.to_radians());
+        self.remove_centre = parameters.remove_centre && !self.exponential_map && self.zoom_easing == Easing::Linear && self.rotate_end.is_none()
+            && FractalRenderer::parse_recentre(&settings) == Recentre::None;
+        self.palette_easing = Easing::from_settings(&settings, "palette_easing", "palette_curve");
+        self.palette_start = (
+            parameters.palette_iteration_span as f32, 
+            parameters.palette_offset as f32);
+        self.palette_end = (
+            parameters.end_palette_iteration_span.map(|span| span as f32), 
+            parameters.end_palette_offset.map(|offset| offset as f32));
+        self.sequence_end_zoom = parameters.end_zoom.as_ref().map(|zoom| string_to_extended(&zoom.to_ascii_uppercase()));
+        self.initial_maximum_iteration = self.maximum_iteration;
+        self.data_export.lock().export_glitch_map = parameters.export_glitch_map;
+        self.data_export.lock().export_histogram = parameters.export_histogram;
+        self.data_export.lock().histogram_bins = parameters.histogram_bins;
+        self.data_export.lock().export_reference_orbit = parameters.export_reference_orbit;
+        self.data_export.lock().reference_orbit_overlay = parameters.reference_orbit_overlay;
+        self.data_export.lock().reference_orbit_points = parameters.reference_orbit_points;
+        self.data_export.lock().raw_channels = parameters.raw_channels.clone();
+        self.data_export.lock().glitch_colour = FractalRenderer::get_glitch_colour(&parameters);
+        self.data_export.lock().glitch_overlay = parameters.glitch_overlay;
+        self.progressive = parameters.progressive;
+        self.progressive_export = parameters.progressive_export;
+        self.pixel_order = FractalRenderer::parse_pixel_order(&settings);
+        self.data_export.lock().progressive = self.progressive;
+        self.adaptive_antialiasing_samples = parameters.adaptive_antialiasing_samples;
+        self.adaptive_antialiasing_threshold = parameters.adaptive_antialiasing_threshold;
+        self.samples_per_pixel = max(parameters.samples_per_pixel, 1);
+        self.jitter_seed = FractalRenderer::get_jitter_seed(&settings);
+        self.deterministic = parameters.deterministic;
+        self.memory_limit = parameters.memory_limit;
+        self.threads = parameters.threads;
+        self.thread_pool = FractalRenderer::build_thread_pool(self.threads, settings.get_bool("pin_threads").unwrap_or(false));
+        self.checkpoint_interval = parameters.checkpoint_interval;
+
+        let mut zoom = string_to_extended(&initial_zoom);
+        let precision = FractalRenderer::get_precision(zoom, self.image_width, self.image_height);
+
+        let center_location = ComplexArbitrary::with_val(
+            precision,
+            ComplexArbitrary::parse("(".to_owned() + &center_real + "," + &center_imag + ")").expect("provided location not valid"));
+        let auto_approximation = get_approximation_terms(approximation_order, parameters.approximation_aggressiveness, self.image_width, self.image_height);
+
+        let julia_seed = FractalRenderer::get_julia_seed(&parameters, precision);
+
+        self.center_reference = Reference::new(center_location.clone(), 
+            julia_seed.clone().unwrap_or(center_location), 
+            1, 
+            self.maximum_iteration, 
+            data_storage_interval,
+            glitch_tolerance,
+            zoom);
+
+        self.center_reference.julia = julia_seed.is_some();
+
+        self.series_approximation = SeriesApproximation::new_central(auto_approximation, 
+            self.maximum_iteration, 
+            FloatExtended::new(0.0, 0), 
+            probe_sampling,
+            series_approximation_tiled,
+            series_approximation_enabled,
+            valid_iteration_probe_multiplier,
+            parameters.approximation_maximum_skip,
+            data_storage_interval,
+            self.fractal_type);
+
+        let mut data_export = self.data_export.lock();
+
+        if self.image_width != data_export.image_width || self.image_height != data_export.image_height {
+            self.render_indices = FractalRenderer::generate_render_indices(self.image_width, self.image_height, self.remove_centre, self.zoom_scale_factor, data_export.export_type == ExportType::Gui || self.progressive, self.pixel_order);
+            data_export.centre_removed = self.remove_centre;
+        }
+
+        self.total_pixels = self.render_indices.len();
+
+        // Change the zoom level to the correct one for the frame offset
+        for _ in 0..self.frame_offset {
+            zoom.mantissa /= self.zoom_scale_factor;
+            zoom.reduce();
+        }
+
+        self.zoom = zoom;
+        self.initial_zoom = zoom;
+
+        self.progress.reset_all(self.maximum_iteration);
+
+        data_export.image_width = self.image_width;
+        data_export.image_height = self.image_height;
+        data_export.data_type = pixel_data_type;
+        data_export.coloring_type = coloring_type;
+
+        // The plugin is only loaded again if it has changed
+        if parameters.coloring_plugin != self.parameters.coloring_plugin {
+            data_export.coloring_plugin = ColoringPlugin::load_shared(&parameters.coloring_plugin);
+        }
+
+        data_export.clear_buffers();
+
+        drop(data_export);
+
+        self.camera_path = CameraPath::from_settings(&settings);
+        self.manifest = FractalRenderer::get_manifest(&settings, &self.name);
+        self.recentre = FractalRenderer::parse_recentre(&settings);
+        self.recentre_radius = parameters.recentre_radius;
+        self.pixel_chunk_size = parameters.pixel_chunk_size;
+        self.solid_guessing = parameters.solid_guessing;
+        self.solid_guessing_tile = parameters.solid_guessing_tile;
+        self.motion_blur_samples = parameters.motion_blur_samples;
+        self.motion_blur_shutter = parameters.motion_blur_shutter;
+        self.zoom_in = parameters.sequence_direction.to_ascii_uppercase() == "IN";
+        self.reverse_numbering = parameters.reverse_numbering;
+        self.settings = settings;
+        self.parameters = parameters;
+    }
+}
\ No newline at end of file
@@ -0,0 +1,67 @@
+use std::fs;
+
+// The cpus of each NUMA node, read from sysfs. When the topology is not available all of the cpus are on a single node.
+pub fn get_numa_nodes() -> Vec<Vec<usize>> {
+    let mut nodes = fs::read_dir("/sys/devices/system/node").ok()
+        .map(|entries| entries.filter_map(|entry| {
+            let entry = entry.ok()?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            let node = name.strip_prefix("node")?.parse::<usize>().ok()?;
+            let cpus = parse_cpu_list(&fs::read_to_string(entry.path().join("cpulist")).ok()?);
+
+            Some((node, cpus))
+        }).filter(|(_, cpus)| !cpus.is_empty()).collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    if nodes.is_empty() {
+        return vec![(0..num_cpus()).collect()];
+    }
+
+    nodes.sort_by_key(|(node, _)| *node);
+    nodes.into_iter().map(|(_, cpus)| cpus).collect()
+}
+
+// The cpu lists are of the form "0-3,8-11"
+pub fn parse_cpu_list(list: &str) -> Vec<usize> {
+    list.trim().split(',').filter(|range| !range.is_empty()).flat_map(|range| {
+        let mut bounds = range.split('-').filter_map(|bound| bound.trim().parse::<usize>().ok());
+
+        match (bounds.next(), bounds.next()) {
+            (Some(start), Some(end)) => (start..=end).collect::<Vec<_>>(),
+            (Some(cpu), None) => vec![cpu],
+            _ => Vec::new()
+        }
+    }).collect()
+}
+
+// The order the worker threads are pinned in. Each node is filled before the next, so that neighbouring threads
+// (which work on neighbouring pixel chunks) share a memory controller.
+pub fn get_pinned_cpus() -> Vec<usize> {
+    get_numa_nodes().into_iter().flatten().collect()
+}
+
+fn num_cpus() -> usize {
+    std::thread::available_parallelism().map(|count| count.get()).unwrap_or(1)
+}
+
+// Pins the calling thread to a single cpu. Nothing is allocated on a chosen node: the kernel places a page on the node
+// of the thread which first writes it, so the pixel chunks the pinned workers fill tend to be local to them, while the
+// reference orbit is a single allocation shared by every node.
+#[cfg(target_os = "linux")]
+pub fn pin_current_thread(cpu: usize) -> bool {
+    if cpu >= libc::CPU_SETSIZE as usize {
+        return false;
+    }
+
+    unsafe {
+        let mut set = std::mem::zeroed::<libc::cpu_set_t>();
+        libc::CPU_SET(cpu, &mut set);
+
+        libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) == 0
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn pin_current_thread(_cpu: usize) -> bool {
+    false
+}
@@ -0,0 +1,76 @@
+use serde_json::json;
+
+// What the location needs, found from the reference and series approximation without iterating any pixels
+#[derive(Clone, Default)]
+pub struct LocationAnalysis {
+    pub zoom: String,
+    pub precision: u32,
+    pub reference_iteration: usize,
+    pub reference_escaped: bool,
+    pub period: Option<usize>,
+    pub minibrot_zoom: Option<String>,
+    pub minibrot_distance: Option<f64>,
+    pub approximation_order: usize,
+    pub approximation_orders: Vec<(usize, usize, u128)>,
+    pub suggested_order: Option<usize>,
+    pub skipped_iterations: usize,
+    pub suggested_iterations: usize,
+}
+
+impl LocationAnalysis {
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "zoom": self.zoom,
+            "precision": self.precision,
+            "reference_iteration": self.reference_iteration,
+            "reference_escaped": self.reference_escaped,
+            "period": self.period,
+            "minibrot_zoom": self.minibrot_zoom,
+            "minibrot_distance": self.minibrot_distance,
+            "approximation_order": self.approximation_order,
+            "approximation_orders": self.approximation_orders.iter().map(|(order, skipped, time)| json!({
+                "order": order,
+                "skipped_iterations": skipped,
+                "time": *time as u64,
+            })).collect::<Vec<_>>(),
+            "suggested_order": self.suggested_order,
+            "skipped_iterations": self.skipped_iterations,
+            "suggested_iterations": self.suggested_iterations,
+        })
+    }
+
+    pub fn print(&self) {
+        println!(" {:<24}| {}", "Zoom", self.zoom);
+        println!(" {:<24}| {} bits", "Precision", self.precision);
+
+        println!(" {:<24}| {}{}", "Reference iterations", self.reference_iteration, if self.reference_escaped {
+            " (escaped)"
+        } else {
+            ""
+        });
+
+        match self.period {
+            Some(period) => println!(" {:<24}| {}", "Period", period),
+            None => println!(" {:<24}| none found", "Period")
+        }
+
+        // The distance is in units of half the image height, so a minibrot within 1 is in the image
+        match (&self.minibrot_zoom, self.minibrot_distance) {
+            (Some(zoom), Some(distance)) => println!(" {:<24}| fills the image at a zoom of {}, {:.3} half heights from the centre", "Minibrot", zoom, distance),
+            _ => println!(" {:<24}| none found", "Minibrot")
+        }
+
+        println!(" {:<24}| {}", "Approximation order", self.approximation_order);
+
+        for (order, skipped, time) in &self.approximation_orders {
+            println!(" {:<24}| {} skipped in {} ms", format!("  Order {}", order), skipped, time);
+        }
+
+        if let Some(order) = self.suggested_order {
+            println!(" {:<24}| {}", "Suggested order", order);
+        }
+
+        println!(" {:<24}| {}", "Skipped iterations", self.skipped_iterations);
+        println!(" {:<24}| {}", "Suggested iterations", self.suggested_iterations);
+    }
+}
@@ -0,0 +1,1302 @@
+// A pure Rust version of the arbitrary precision floats from rug, which is used when the core is built without the
+// mpfr feature. GMP and MPFR do not build for wasm32, so the browser renderer uses this instead. Only the operations
+// which the renderer needs are implemented, with the same names and semantics as rug so that the rest of the crate
+// does not change with the feature.
+//
+// A value is a binary float with a mantissa of exactly its precision in bits, and the operations round to nearest
+// with ties to even. The multiplication is schoolbook and the division is bit by bit, so it is much slower than MPFR
+// at deep zooms.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, ShlAssign, ShrAssign, Sub, SubAssign};
+
+// An unsigned integer as little endian 32 bit limbs, which never has a leading zero limb
+type Natural = Vec<u32>;
+
+fn trim(mut value: Natural) -> Natural {
+    while value.last() == Some(&0) {
+        value.pop();
+    }
+
+    value
+}
+
+fn natural_from_u64(value: u64) -> Natural {
+    trim(vec![value as u32, (value >> 32) as u32])
+}
+
+fn bit_length(value: &[u32]) -> u64 {
+    match value.last() {
+        Some(&limb) => 32 * value.len() as u64 - limb.leading_zeros() as u64,
+        None => 0
+    }
+}
+
+fn bit(value: &[u32], index: u64) -> bool {
+    let limb = (index / 32) as usize;
+
+    limb < value.len() && (value[limb] >> (index % 32)) & 1 == 1
+}
+
+// Whether any of the bits below the index are set
+fn any_below(value: &[u32], index: u64) -> bool {
+    let limb = (index / 32) as usize;
+
+    value.iter().take(limb).any(|&part| part != 0) || (limb < value.len() && value[limb] & ((1 << (index % 32)) - 1) != 0)
+}
+
+fn shift_left(value: &[u32], bits: u64) -> Natural {
+    if value.is_empty() {
+        return Vec::new();
+    }
+
+    let offset = (bits % 32) as u32;
+    let mut result = vec![0; (bits / 32) as usize];
+
+    if offset == 0 {
+        result.extend_from_slice(value);
+    } else {
+        let mut carry = 0;
+
+        for &limb in value {
+            result.push((limb << offset) | carry);
+            carry = limb >> (32 - offset);
+        }
+
+        result.push(carry);
+    }
+
+    trim(result)
+}
+
+fn shift_right(value: &[u32], bits: u64) -> Natural {
+    let limbs = (bits / 32) as usize;
+
+    if limbs >= value.len() {
+        return Vec::new();
+    }
+
+    let value = &value[limbs..];
+    let offset = (bits % 32) as u32;
+
+    if offset == 0 {
+        return value.to_vec();
+    }
+
+    let result = (0..value.len()).map(|index| {
+        let high = value.get(index + 1).map_or(0, |&limb| limb << (32 - offset));
+        (value[index] >> offset) | high
+    }).collect();
+
+    trim(result)
+}
+
+fn compare(a: &[u32], b: &[u32]) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add(a: &[u32], b: &[u32]) -> Natural {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+
+    let mut result = Vec::with_capacity(long.len() + 1);
+    let mut carry = 0;
+
+    for (index, &limb) in long.iter().enumerate() {
+        let sum = limb as u64 + *short.get(index).unwrap_or(&0) as u64 + carry;
+        result.push(sum as u32);
+        carry = sum >> 32;
+    }
+
+    if carry != 0 {
+        result.push(carry as u32);
+    }
+
+    result
+}
+
+// The difference of a and b, where a is not less than b
+fn subtract(a: &[u32], b: &[u32]) -> Natural {
+    let mut result = Vec::with_capacity(a.len());
+    let mut borrow = 0;
+
+    for (index, &limb) in a.iter().enumerate() {
+        let difference = limb as i64 - *b.get(index).unwrap_or(&0) as i64 - borrow;
+
+        if difference < 0 {
+            result.push((difference + (1 << 32)) as u32);
+            borrow = 1;
+        } else {
+            result.push(difference as u32);
+            borrow = 0;
+        }
+    }
+
+    trim(result)
+}
+
+fn multiply(a: &[u32], b: &[u32]) -> Natural {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+
+    let mut result = vec![0; a.len() + b.len()];
+
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry = 0;
+
+        for (j, &y) in b.iter().enumerate() {
+            let product = x as u64 * y as u64 + result[i + j] as u64 + carry;
+            result[i + j] = product as u32;
+            carry = product >> 32;
+        }
+
+        result[i + b.len()] = carry as u32;
+    }
+
+    trim(result)
+}
+
+fn divide_small(a: &[u32], b: u32) -> (Natural, u32) {
+    let mut quotient = vec![0; a.len()];
+    let mut remainder = 0;
+
+    for (index, &limb) in a.iter().enumerate().rev() {
+        let current = (remainder << 32) | limb as u64;
+        quotient[index] = (current / b as u64) as u32;
+        remainder = current % b as u64;
+    }
+
+    (trim(quotient), remainder as u32)
+}
+
+// The quotient and remainder of a non zero divisor, found one bit of the quotient at a time
+fn divide(a: &[u32], b: &[u32]) -> (Natural, Natural) {
+    if b.len() == 1 {
+        let (quotient, remainder) = divide_small(a, b[0]);
+        return (quotient, trim(vec![remainder]));
+    }
+
+    let a_length = bit_length(a);
+    let b_length = bit_length(b);
+
+    if a_length < b_length {
+        return (Vec::new(), a.to_vec());
+    }
+
+    let shift = a_length - b_length;
+
+    let mut quotient = vec![0; (shift / 32) as usize + 1];
+    let mut remainder = a.to_vec();
+    let mut divisor = shift_left(b, shift);
+
+    for index in (0..=shift).rev() {
+        if compare(&remainder, &divisor) != Ordering::Less {
+            remainder = subtract(&remainder, &divisor);
+            quotient[(index / 32) as usize] |= 1 << (index % 32);
+        }
+
+        divisor = shift_right(&divisor, 1);
+    }
+
+    (trim(quotient), remainder)
+}
+
+fn power(base: u32, mut exponent: u64) -> Natural {
+    let mut result = vec![1];
+    let mut square = trim(vec![base]);
+
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = multiply(&result, &square);
+        }
+
+        exponent >>= 1;
+
+        if exponent > 0 {
+            square = multiply(&square, &square);
+        }
+    }
+
+    result
+}
+
+fn is_power_of_two(value: &[u32]) -> bool {
+    match value.split_last() {
+        Some((&last, rest)) => last.is_power_of_two() && rest.iter().all(|&limb| limb == 0),
+        None => false
+    }
+}
+
+// Multiplies by a power of two without overflowing the intermediate values
+fn scale(mut value: f64, exponent: i64) -> f64 {
+    // Beyond this the result is zero or infinite anyway
+    let mut exponent = exponent.clamp(-2200, 2200) as i32;
+
+    while exponent > 1000 {
+        value *= 2f64.powi(1000);
+        exponent -= 1000;
+    }
+
+    while exponent < -1000 {
+        value *= 2f64.powi(-1000);
+        exponent += 1000;
+    }
+
+    value * 2f64.powi(exponent)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Kind {
+    Zero,
+    Normal,
+    Infinite,
+    Nan,
+}
+
+#[derive(Clone)]
+pub struct Float {
+    precision: u32,
+    kind: Kind,
+    negative: bool,
+    // A normal value is mantissa * 2^exponent, where the mantissa has exactly the precision in bits
+    mantissa: Natural,
+    exponent: i64,
+}
+
+// The values which a float can be created from, in the same way as rug's Float::with_val
+pub trait IntoFloat {
+    fn into_float(self, precision: u32) -> Float;
+}
+
+impl IntoFloat for f64 {
+    fn into_float(self, precision: u32) -> Float {
+        if self.is_nan() {
+            return Float::special(precision, Kind::Nan, false);
+        }
+
+        if self.is_infinite() {
+            return Float::special(precision, Kind::Infinite, self < 0.0);
+        }
+
+        if self == 0.0 {
+            return Float::special(precision, Kind::Zero, self.is_sign_negative());
+        }
+
+        let bits = self.to_bits();
+        let biased_exponent = ((bits >> 52) & 0x7ff) as i64;
+        let fraction = bits & ((1 << 52) - 1);
+
+        // Subnormal values have no implicit leading bit
+        let (mantissa, exponent) = if biased_exponent == 0 {
+            (fraction, -1074)
+        } else {
+            (fraction | (1 << 52), biased_exponent - 1075)
+        };
+
+        Float::from_parts(precision, self < 0.0, natural_from_u64(mantissa), exponent, false)
+    }
+}
+
+impl IntoFloat for f32 {
+    fn into_float(self, precision: u32) -> Float {
+        (self as f64).into_float(precision)
+    }
+}
+
+impl IntoFloat for i64 {
+    fn into_float(self, precision: u32) -> Float {
+        Float::from_parts(precision, self < 0, natural_from_u64(self.unsigned_abs()), 0, false)
+    }
+}
+
+impl IntoFloat for i32 {
+    fn into_float(self, precision: u32) -> Float {
+        (self as i64).into_float(precision)
+    }
+}
+
+impl IntoFloat for u64 {
+    fn into_float(self, precision: u32) -> Float {
+        Float::from_parts(precision, false, natural_from_u64(self), 0, false)
+    }
+}
+
+impl IntoFloat for u32 {
+    fn into_float(self, precision: u32) -> Float {
+        (self as u64).into_float(precision)
+    }
+}
+
+impl IntoFloat for Float {
+    fn into_float(self, precision: u32) -> Float {
+        self.round(precision)
+    }
+}
+
+impl IntoFloat for &Float {
+    fn into_float(self, precision: u32) -> Float {
+        self.clone().round(precision)
+    }
+}
+
+impl Float {
+    pub fn new(precision: u32) -> Self {
+        Float::special(precision, Kind::Zero, false)
+    }
+
+    pub fn with_val<T: IntoFloat>(precision: u32, value: T) -> Self {
+        value.into_float(precision)
+    }
+
+    fn special(precision: u32, kind: Kind, negative: bool) -> Self {
+        Float {
+            precision: precision.max(1),
+            kind,
+            negative,
+            mantissa: Vec::new(),
+            exponent: 0,
+        }
+    }
+
+    // Rounds mantissa * 2^exponent to the precision. The sticky flag is set when the mantissa was truncated from a
+    // larger exact value, so that a truncated value is not rounded as a tie.
+    fn from_parts(precision: u32, negative: bool, mantissa: Natural, exponent: i64, sticky: bool) -> Self {
+        let precision = precision.max(1);
+        let length = bit_length(&mantissa);
+
+        if length == 0 {
+            return Float::special(precision, Kind::Zero, negative);
+        }
+
+        let bits = precision as u64;
+
+        let (mantissa, exponent) = if length > bits {
+            let shift = length - bits;
+            let half = bit(&mantissa, shift - 1);
+            let rest = sticky || any_below(&mantissa, shift - 1);
+
+            let mut rounded = shift_right(&mantissa, shift);
+            let mut exponent = exponent + shift as i64;
+
+            if half && (rest || bit(&rounded, 0)) {
+                rounded = add(&rounded, &[1]);
+
+                // Rounding up can carry into a new bit
+                if bit_length(&rounded) > bits {
+                    rounded = shift_right(&rounded, 1);
+                    exponent += 1;
+                }
+            }
+
+            (rounded, exponent)
+        } else {
+            let shift = bits - length;
+            (shift_left(&mantissa, shift), exponent - shift as i64)
+        };
+
+        Float {
+            precision,
+            kind: Kind::Normal,
+            negative,
+            mantissa,
+            exponent,
+        }
+    }
+
+    fn round(self, precision: u32) -> Self {
+        match self.kind {
+            Kind::Normal => Float::from_parts(precision, self.negative, self.mantissa, self.exponent, false),
+            kind => Float::special(precision, kind, self.negative)
+        }
+    }
+
+    pub fn prec(&self) -> u32 {
+        self.precision
+    }
+
+    pub fn set_prec(&mut self, precision: u32) {
+        *self = self.clone().round(precision);
+    }
+
+    pub fn parse<S: AsRef<str>>(src: S) -> Result<ParseIncomplete, ParseFloatError> {
+        ParseIncomplete::new(src.as_ref(), 10)
+    }
+
+    pub fn parse_radix<S: AsRef<str>>(src: S, radix: i32) -> Result<ParseIncomplete, ParseFloatError> {
+        assert!((2..=36).contains(&radix), "radix {} is out of range", radix);
+        ParseIncomplete::new(src.as_ref(), radix as u32)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.kind == Kind::Zero
+    }
+
+    pub fn is_nan(&self) -> bool {
+        self.kind == Kind::Nan
+    }
+
+    pub fn is_infinite(&self) -> bool {
+        self.kind == Kind::Infinite
+    }
+
+    pub fn is_finite(&self) -> bool {
+        self.kind == Kind::Zero || self.kind == Kind::Normal
+    }
+
+    pub fn is_sign_negative(&self) -> bool {
+        self.negative
+    }
+
+    // The value as a mantissa in [0.5, 1) and an exponent, the mantissa being rounded to a double
+    pub fn to_f64_exp(&self) -> (f64, i32) {
+        match self.kind {
+            Kind::Normal => {
+                let length = bit_length(&self.mantissa);
+                let shift = length.saturating_sub(64);
+
+                let top = shift_right(&self.mantissa, shift);
+                let mut top = top[0] as u64 | (*top.get(1).unwrap_or(&0) as u64) << 32;
+
+                // The truncated bits are kept as the lowest bit, so that the conversion does not round twice
+                if any_below(&self.mantissa, shift) {
+                    top |= 1;
+                }
+
+                let mut mantissa = top as f64 / 2f64.powi((length - shift) as i32);
+                let mut exponent = self.exponent + length as i64;
+
+                if mantissa == 1.0 {
+                    mantissa = 0.5;
+                    exponent += 1;
+                }
+
+                let mantissa = if self.negative { -mantissa } else { mantissa };
+                (mantissa, exponent.clamp(i32::MIN as i64, i32::MAX as i64) as i32)
+            },
+            _ => (self.to_f64(), 0)
+        }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        match self.kind {
+            Kind::Zero => if self.negative { -0.0 } else { 0.0 },
+            Kind::Infinite => if self.negative { f64::NEG_INFINITY } else { f64::INFINITY },
+            Kind::Nan => f64::NAN,
+            Kind::Normal => {
+                let (mantissa, exponent) = self.to_f64_exp();
+                scale(mantissa, exponent as i64)
+            }
+        }
+    }
+
+    // Two to the power of the value. The integer part is exact, which is all that the renderer uses, and the
+    // fraction is calculated in double precision.
+    pub fn exp2(self) -> Self {
+        match self.kind {
+            Kind::Zero => Float::with_val(self.precision, 1),
+            Kind::Infinite if self.negative => Float::new(self.precision),
+            Kind::Infinite | Kind::Nan => self,
+            Kind::Normal => {
+                let value = self.to_f64();
+                let whole = value.floor();
+
+                let mut result = Float::with_val(self.precision, (value - whole).exp2());
+                result.shift(whole as i64);
+                result
+            }
+        }
+    }
+
+    pub fn square(self) -> Self {
+        let precision = self.precision;
+        multiply_floats(&self, &self, precision)
+    }
+
+    pub fn square_mut(&mut self) {
+        *self = multiply_floats(self, self, self.precision);
+    }
+
+    // Moves to the next representable value towards positive infinity
+    pub fn next_up(&mut self) {
+        match self.kind {
+            Kind::Normal if !self.negative => {
+                self.mantissa = add(&self.mantissa, &[1]);
+
+                if bit_length(&self.mantissa) > self.precision as u64 {
+                    self.mantissa = shift_right(&self.mantissa, 1);
+                    self.exponent += 1;
+                }
+            },
+            Kind::Normal => {
+                self.mantissa = subtract(&self.mantissa, &[1]);
+
+                // A power of two has a closer value below it, at the exponent below
+                if bit_length(&self.mantissa) < self.precision as u64 {
+                    self.mantissa = add(&shift_left(&self.mantissa, 1), &[1]);
+                    self.exponent -= 1;
+                }
+
+                if self.mantissa.is_empty() {
+                    self.kind = Kind::Zero;
+                }
+            },
+            Kind::Zero => {
+                self.kind = Kind::Normal;
+                self.negative = false;
+                self.mantissa = shift_left(&[1], self.precision as u64 - 1);
+                self.exponent = i32::MIN as i64;
+            },
+            Kind::Infinite | Kind::Nan => {}
+        }
+    }
+
+    fn shift(&mut self, bits: i64) {
+        if self.kind == Kind::Normal {
+            self.exponent += bits;
+        }
+    }
+
+    // The digits needed to recover the value are given when the number of digits is not
+    pub fn to_string_radix(&self, radix: i32, num_digits: Option<usize>) -> String {
+        assert!((2..=36).contains(&radix), "radix {} is out of range", radix);
+
+        match self.kind {
+            Kind::Zero => return String::from(if self.negative { "-0" } else { "0" }),
+            Kind::Infinite => return String::from(if self.negative { "-inf" } else { "inf" }),
+            Kind::Nan => return String::from("NaN"),
+            Kind::Normal => {}
+        }
+
+        let radix = radix as u32;
+        let digits = num_digits.unwrap_or_else(|| 1 + (self.precision as f64 / (radix as f64).log2()).ceil() as usize).max(1);
+
+        let maximum = power(radix, digits as u64);
+        let minimum = power(radix, digits as u64 - 1);
+
+        // An estimate of the position of the leading digit, which is corrected if the digits do not fit
+        let (mantissa, exponent) = self.to_f64_exp();
+        let mut leading = ((mantissa.abs().log2() + exponent as f64) / (radix as f64).log2()).floor() as i64;
+
+        let scaled = loop {
+            let scaled = self.get_scaled_integer(radix, digits as i64 - 1 - leading);
+
+            if compare(&scaled, &maximum) != Ordering::Less {
+                leading += 1;
+            } else if compare(&scaled, &minimum) == Ordering::Less {
+                leading -= 1;
+            } else {
+                break scaled;
+            }
+        };
+
+        let mut string = String::with_capacity(digits + 24);
+
+        if self.negative {
+            string.push('-');
+        }
+
+        let text = get_digits(scaled, radix, digits);
+
+        // The point is placed in the digits when the value is not too large or small, as rug does
+        if leading >= 0 && (leading as usize) < digits - 1 {
+            string.push_str(&text[..=leading as usize]);
+            string.push('.');
+            string.push_str(&text[leading as usize + 1..]);
+        } else {
+            string.push_str(&text[..1]);
+
+            if digits > 1 {
+                string.push('.');
+                string.push_str(&text[1..]);
+            }
+
+            string.push(if radix <= 10 { 'e' } else { '@' });
+            string.push_str(&leading.to_string());
+        }
+
+        string
+    }
+
+    // The magnitude multiplied by a power of the radix, rounded to the nearest integer
+    fn get_scaled_integer(&self, radix: u32, radix_exponent: i64) -> Natural {
+        let mut numerator = self.mantissa.clone();
+        let mut denominator = vec![1];
+
+        if radix_exponent >= 0 {
+            numerator = multiply(&numerator, &power(radix, radix_exponent as u64));
+        } else {
+            denominator = power(radix, radix_exponent.unsigned_abs());
+        }
+
+        if self.exponent >= 0 {
+            numerator = shift_left(&numerator, self.exponent as u64);
+        } else {
+            denominator = shift_left(&denominator, self.exponent.unsigned_abs());
+        }
+
+        // Dividing by a power of two is a shift, which is the usual case
+        if is_power_of_two(&denominator) {
+            let shift = bit_length(&denominator) - 1;
+            let quotient = shift_right(&numerator, shift);
+
+            return if shift > 0 && bit(&numerator, shift - 1) {
+                add(&quotient, &[1])
+            } else {
+                quotient
+            };
+        }
+
+        let (quotient, remainder) = divide(&numerator, &denominator);
+
+        if compare(&shift_left(&remainder, 1), &denominator) != Ordering::Less {
+            add(&quotient, &[1])
+        } else {
+            quotient
+        }
+    }
+}
+
+// The digits of an integer in a radix, padded with zeros to the number of digits
+fn get_digits(mut value: Natural, radix: u32, digits: usize) -> String {
+    let mut text = Vec::with_capacity(digits);
+
+    while !value.is_empty() {
+        let (quotient, remainder) = divide_small(&value, radix);
+        text.push(std::char::from_digit(remainder, radix).unwrap());
+        value = quotient;
+    }
+
+    text.resize(digits.max(text.len()), '0');
+    text.iter().rev().collect()
+}
+
+fn add_floats(a: &Float, b: &Float, subtract_b: bool, precision: u32) -> Float {
+    let b_negative = b.negative != subtract_b;
+
+    match (a.kind, b.kind) {
+        (Kind::Nan, _) | (_, Kind::Nan) => Float::special(precision, Kind::Nan, false),
+        (Kind::Infinite, Kind::Infinite) if a.negative != b_negative => Float::special(precision, Kind::Nan, false),
+        (Kind::Infinite, _) => Float::special(precision, Kind::Infinite, a.negative),
+        (_, Kind::Infinite) => Float::special(precision, Kind::Infinite, b_negative),
+        (Kind::Zero, Kind::Zero) => Float::special(precision, Kind::Zero, a.negative && b_negative),
+        (Kind::Zero, _) => Float::from_parts(precision, b_negative, b.mantissa.clone(), b.exponent, false),
+        (_, Kind::Zero) => Float::from_parts(precision, a.negative, a.mantissa.clone(), a.exponent, false),
+        _ => {
+            let a_top = a.exponent + bit_length(&a.mantissa) as i64;
+            let b_top = b.exponent + bit_length(&b.mantissa) as i64;
+
+            // A value which is entirely below the rounding of the other cannot change the result, and would otherwise
+            // be shifted by the whole difference in exponents
+            let guard = precision as i64 + 3;
+
+            if b_top < a_top - guard {
+                return Float::from_parts(precision, a.negative, a.mantissa.clone(), a.exponent, false);
+            } else if a_top < b_top - guard {
+                return Float::from_parts(precision, b_negative, b.mantissa.clone(), b.exponent, false);
+            }
+
+            let exponent = a.exponent.min(b.exponent);
+            let a_mantissa = shift_left(&a.mantissa, (a.exponent - exponent) as u64);
+            let b_mantissa = shift_left(&b.mantissa, (b.exponent - exponent) as u64);
+
+            if a.negative == b_negative {
+                return Float::from_parts(precision, a.negative, add(&a_mantissa, &b_mantissa), exponent, false);
+            }
+
+            match compare(&a_mantissa, &b_mantissa) {
+                Ordering::Greater => Float::from_parts(precision, a.negative, subtract(&a_mantissa, &b_mantissa), exponent, false),
+                Ordering::Less => Float::from_parts(precision, b_negative, subtract(&b_mantissa, &a_mantissa), exponent, false),
+                Ordering::Equal => Float::new(precision)
+            }
+        }
+    }
+}
+
+fn multiply_floats(a: &Float, b: &Float, precision: u32) -> Float {
+    let negative = a.negative != b.negative;
+
+    match (a.kind, b.kind) {
+        (Kind::Nan, _) | (_, Kind::Nan) => Float::special(precision, Kind::Nan, false),
+        (Kind::Infinite, Kind::Zero) | (Kind::Zero, Kind::Infinite) => Float::special(precision, Kind::Nan, false),
+        (Kind::Infinite, _) | (_, Kind::Infinite) => Float::special(precision, Kind::Infinite, negative),
+        (Kind::Zero, _) | (_, Kind::Zero) => Float::special(precision, Kind::Zero, negative),
+        _ => Float::from_parts(precision, negative, multiply(&a.mantissa, &b.mantissa), a.exponent + b.exponent, false)
+    }
+}
+
+fn divide_floats(a: &Float, b: &Float, precision: u32) -> Float {
+    let negative = a.negative != b.negative;
+
+    match (a.kind, b.kind) {
+        (Kind::Nan, _) | (_, Kind::Nan) => Float::special(precision, Kind::Nan, false),
+        (Kind::Infinite, Kind::Infinite) | (Kind::Zero, Kind::Zero) => Float::special(precision, Kind::Nan, false),
+        (Kind::Infinite, _) | (_, Kind::Zero) => Float::special(precision, Kind::Infinite, negative),
+        (Kind::Zero, _) | (_, Kind::Infinite) => Float::special(precision, Kind::Zero, negative),
+        _ => {
+            // The numerator is shifted so that the quotient has two bits more than the precision
+            let shift = (precision as i64 + 2 + bit_length(&b.mantissa) as i64 - bit_length(&a.mantissa) as i64).max(0) as u64;
+            let (quotient, remainder) = divide(&shift_left(&a.mantissa, shift), &b.mantissa);
+
+            Float::from_parts(precision, negative, quotient, a.exponent - shift as i64 - b.exponent, !remainder.is_empty())
+        }
+    }
+}
+
+// The exact product, which is only rounded once it has been added to something
+fn multiply_exact(a: &Float, b: &Float) -> Float {
+    multiply_floats(a, b, a.precision + b.precision)
+}
+
+impl PartialEq for Float {
+    fn eq(&self, other: &Float) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for Float {
+    fn partial_cmp(&self, other: &Float) -> Option<Ordering> {
+        let sign = |value: &Float| match value.kind {
+            Kind::Zero => 0,
+            _ if value.negative => -1,
+            _ => 1
+        };
+
+        if self.is_nan() || other.is_nan() {
+            return None;
+        }
+
+        match sign(self).cmp(&sign(other)) {
+            Ordering::Equal if sign(self) != 0 => {},
+            ordering => return Some(ordering)
+        }
+
+        let magnitude = match (self.kind, other.kind) {
+            (Kind::Infinite, Kind::Infinite) => Ordering::Equal,
+            (Kind::Infinite, _) => Ordering::Greater,
+            (_, Kind::Infinite) => Ordering::Less,
+            _ => {
+                let self_top = self.exponent + bit_length(&self.mantissa) as i64;
+                let other_top = other.exponent + bit_length(&other.mantissa) as i64;
+
+                self_top.cmp(&other_top).then_with(|| {
+                    let exponent = self.exponent.min(other.exponent);
+
+                    compare(&shift_left(&self.mantissa, (self.exponent - exponent) as u64),
+                        &shift_left(&other.mantissa, (other.exponent - exponent) as u64))
+                })
+            }
+        };
+
+        Some(if self.negative { magnitude.reverse() } else { magnitude })
+    }
+}
+
+impl fmt::Display for Float {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_string_radix(10, None))
+    }
+}
+
+impl fmt::Debug for Float {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_string_radix(10, None))
+    }
+}
+
+impl Neg for Float {
+    type Output = Float;
+
+    fn neg(mut self) -> Float {
+        if !self.is_nan() {
+            self.negative = !self.negative;
+        }
+
+        self
+    }
+}
+
+impl Neg for &Float {
+    type Output = Float;
+
+    fn neg(self) -> Float {
+        -self.clone()
+    }
+}
+
+// The owned value keeps its precision, as in rug, and a result of two references has the larger precision
+macro_rules! float_operator {
+    ($trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident, |$a:ident, $b:ident, $precision:ident| $body:expr) => {
+        impl $trait<&Float> for Float {
+            type Output = Float;
+
+            fn $method(self, other: &Float) -> Float {
+                let ($a, $b, $precision) = (&self, other, self.precision);
+                $body
+            }
+        }
+
+        impl $trait<Float> for Float {
+            type Output = Float;
+
+            fn $method(self, other: Float) -> Float {
+                self.$method(&other)
+            }
+        }
+
+        impl $trait<&Float> for &Float {
+            type Output = Float;
+
+            fn $method(self, other: &Float) -> Float {
+                let ($a, $b, $precision) = (self, other, self.precision.max(other.precision));
+                $body
+            }
+        }
+
+        impl $trait<f64> for Float {
+            type Output = Float;
+
+            fn $method(self, other: f64) -> Float {
+                let other = Float::with_val(64, other);
+                self.$method(&other)
+            }
+        }
+
+        impl $assign_trait<&Float> for Float {
+            fn $assign_method(&mut self, other: &Float) {
+                let ($a, $b, $precision) = (&*self, other, self.precision);
+                *self = $body;
+            }
+        }
+
+        impl $assign_trait<Float> for Float {
+            fn $assign_method(&mut self, other: Float) {
+                self.$assign_method(&other);
+            }
+        }
+
+        impl $assign_trait<f64> for Float {
+            fn $assign_method(&mut self, other: f64) {
+                self.$assign_method(&Float::with_val(64, other));
+            }
+        }
+    };
+}
+
+float_operator!(Add, add, AddAssign, add_assign, |a, b, precision| add_floats(a, b, false, precision));
+float_operator!(Sub, sub, SubAssign, sub_assign, |a, b, precision| add_floats(a, b, true, precision));
+float_operator!(Mul, mul, MulAssign, mul_assign, |a, b, precision| multiply_floats(a, b, precision));
+float_operator!(Div, div, DivAssign, div_assign, |a, b, precision| divide_floats(a, b, precision));
+
+impl ShlAssign<i32> for Float {
+    fn shl_assign(&mut self, bits: i32) {
+        self.shift(bits as i64);
+    }
+}
+
+impl ShrAssign<i32> for Float {
+    fn shr_assign(&mut self, bits: i32) {
+        self.shift(-(bits as i64));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseFloatError;
+
+impl fmt::Display for ParseFloatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("the string is not a valid float")
+    }
+}
+
+impl std::error::Error for ParseFloatError {}
+
+// A parsed value, which is only rounded once the precision is given with Float::with_val
+#[derive(Clone, Debug)]
+pub struct ParseIncomplete {
+    kind: Kind,
+    negative: bool,
+    // The value is digits * radix^exponent
+    digits: Natural,
+    radix: u32,
+    exponent: i64,
+}
+
+impl ParseIncomplete {
+    // The exponent is marked with @ in any radix, or with e in a radix up to 10
+    fn new(src: &str, radix: u32) -> Result<Self, ParseFloatError> {
+        let src = src.trim();
+
+        let (negative, src) = match src.as_bytes().first() {
+            Some(b'-') => (true, &src[1..]),
+            Some(b'+') => (false, &src[1..]),
+            _ => (false, src)
+        };
+
+        let special = |kind| Ok(ParseIncomplete {
+            kind,
+            negative,
+            digits: Vec::new(),
+            radix,
+            exponent: 0,
+        });
+
+        match src.to_ascii_lowercase().as_ref() {
+            "inf" | "infinity" | "@inf@" => return special(Kind::Infinite),
+            "nan" | "@nan@" => return special(Kind::Nan),
+            _ => {}
+        }
+
+        let (significand, exponent) = match src.find(|c: char| c == '@' || (radix <= 10 && (c == 'e' || c == 'E'))) {
+            Some(index) => (&src[..index], src[index + 1..].parse::<i64>().map_err(|_| ParseFloatError)?),
+            None => (src, 0)
+        };
+
+        let mut values = Vec::with_capacity(significand.len());
+        let mut fraction_digits = 0;
+        let mut point = false;
+
+        for character in significand.chars() {
+            if character == '.' && !point {
+                point = true;
+                continue;
+            }
+
+            values.push(character.to_digit(radix).ok_or(ParseFloatError)?);
+
+            if point {
+                fraction_digits += 1;
+            }
+        }
+
+        if values.is_empty() {
+            return Err(ParseFloatError);
+        }
+
+        // The digits are added in the largest groups which fit in a limb
+        let mut group_size = 1;
+
+        while (radix as u64).pow(group_size as u32 + 1) <= u32::MAX as u64 {
+            group_size += 1;
+        }
+
+        let mut digits = Vec::new();
+
+        for group in values.chunks(group_size) {
+            let value = group.iter().fold(0, |value, &digit| value * radix + digit);
+
+            digits = add(&multiply(&digits, &[radix.pow(group.len() as u32)]), &trim(vec![value]));
+        }
+
+        Ok(ParseIncomplete {
+            kind: Kind::Normal,
+            negative,
+            digits,
+            radix,
+            exponent: exponent.checked_sub(fraction_digits).ok_or(ParseFloatError)?,
+        })
+    }
+}
+
+impl IntoFloat for ParseIncomplete {
+    fn into_float(self, precision: u32) -> Float {
+        if self.kind != Kind::Normal {
+            return Float::special(precision, self.kind, self.negative);
+        }
+
+        // A radix which is a power of two only moves the exponent
+        if self.radix.is_power_of_two() {
+            let bits = self.radix.trailing_zeros() as i64;
+            return Float::from_parts(precision, self.negative, self.digits, self.exponent * bits, false);
+        }
+
+        if self.exponent >= 0 {
+            let value = multiply(&self.digits, &power(self.radix, self.exponent as u64));
+            return Float::from_parts(precision, self.negative, value, 0, false);
+        }
+
+        if self.digits.is_empty() {
+            return Float::special(precision, Kind::Zero, self.negative);
+        }
+
+        // The digits are shifted so that the quotient has two bits more than the precision
+        let denominator = power(self.radix, self.exponent.unsigned_abs());
+        let shift = (precision as i64 + 2 + bit_length(&denominator) as i64 - bit_length(&self.digits) as i64).max(0) as u64;
+        let (quotient, remainder) = divide(&shift_left(&self.digits, shift), &denominator);
+
+        Float::from_parts(precision, self.negative, quotient, -(shift as i64), !remainder.is_empty())
+    }
+}
+
+#[derive(Clone)]
+pub struct Complex {
+    real: Float,
+    imag: Float,
+}
+
+// The precision of a complex value is given for both parts, or as one for both
+pub trait IntoPrecision {
+    fn into_precision(self) -> (u32, u32);
+}
+
+impl IntoPrecision for u32 {
+    fn into_precision(self) -> (u32, u32) {
+        (self, self)
+    }
+}
+
+impl IntoPrecision for (u32, u32) {
+    fn into_precision(self) -> (u32, u32) {
+        self
+    }
+}
+
+pub trait IntoComplex {
+    fn into_complex(self, precision: (u32, u32)) -> Complex;
+}
+
+impl<T: IntoFloat, U: IntoFloat> IntoComplex for (T, U) {
+    fn into_complex(self, precision: (u32, u32)) -> Complex {
+        Complex {
+            real: self.0.into_float(precision.0),
+            imag: self.1.into_float(precision.1),
+        }
+    }
+}
+
+impl IntoComplex for Complex {
+    fn into_complex(self, precision: (u32, u32)) -> Complex {
+        (self.real, self.imag).into_complex(precision)
+    }
+}
+
+impl IntoComplex for &Complex {
+    fn into_complex(self, precision: (u32, u32)) -> Complex {
+        (&self.real, &self.imag).into_complex(precision)
+    }
+}
+
+impl IntoComplex for ParseComplexIncomplete {
+    fn into_complex(self, precision: (u32, u32)) -> Complex {
+        (self.real, self.imag).into_complex(precision)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ParseComplexIncomplete {
+    real: ParseIncomplete,
+    imag: ParseIncomplete,
+}
+
+impl Complex {
+    pub fn new<P: IntoPrecision>(precision: P) -> Self {
+        let (real, imag) = precision.into_precision();
+
+        Complex {
+            real: Float::new(real),
+            imag: Float::new(imag),
+        }
+    }
+
+    pub fn with_val<P: IntoPrecision, T: IntoComplex>(precision: P, value: T) -> Self {
+        value.into_complex(precision.into_precision())
+    }
+
+    // Either a real number, or the real and imaginary parts in brackets separated by a comma or by spaces
+    pub fn parse<S: AsRef<str>>(src: S) -> Result<ParseComplexIncomplete, ParseFloatError> {
+        let src = src.as_ref().trim();
+
+        match src.strip_prefix('(').and_then(|inner| inner.strip_suffix(')')) {
+            Some(inner) => {
+                let parts = if inner.contains(',') {
+                    inner.split(',').collect::<Vec<_>>()
+                } else {
+                    inner.split_whitespace().collect::<Vec<_>>()
+                };
+
+                if parts.len() != 2 {
+                    return Err(ParseFloatError);
+                }
+
+                Ok(ParseComplexIncomplete {
+                    real: ParseIncomplete::new(parts[0], 10)?,
+                    imag: ParseIncomplete::new(parts[1], 10)?,
+                })
+            },
+            None => Ok(ParseComplexIncomplete {
+                real: ParseIncomplete::new(src, 10)?,
+                imag: ParseIncomplete::new("0", 10)?,
+            })
+        }
+    }
+
+    pub fn prec(&self) -> (u32, u32) {
+        (self.real.precision, self.imag.precision)
+    }
+
+    pub fn set_prec<P: IntoPrecision>(&mut self, precision: P) {
+        let (real, imag) = precision.into_precision();
+
+        self.real.set_prec(real);
+        self.imag.set_prec(imag);
+    }
+
+    pub fn real(&self) -> &Float {
+        &self.real
+    }
+
+    pub fn imag(&self) -> &Float {
+        &self.imag
+    }
+
+    pub fn mut_real(&mut self) -> &mut Float {
+        &mut self.real
+    }
+
+    pub fn mut_imag(&mut self) -> &mut Float {
+        &mut self.imag
+    }
+
+    pub fn square(self) -> Self {
+        let precision = self.prec();
+        multiply_complex(&self, &self, precision)
+    }
+
+    pub fn square_mut(&mut self) {
+        *self = multiply_complex(self, self, self.prec());
+    }
+
+    // The squared magnitude, as the real part with a zero imaginary part
+    pub fn norm(self) -> Self {
+        let real = add_floats(&multiply_exact(&self.real, &self.real), &multiply_exact(&self.imag, &self.imag), false, self.real.precision);
+
+        Complex {
+            real,
+            imag: Float::new(self.imag.precision),
+        }
+    }
+}
+
+fn add_complex(a: &Complex, b: &Complex, subtract_b: bool, precision: (u32, u32)) -> Complex {
+    Complex {
+        real: add_floats(&a.real, &b.real, subtract_b, precision.0),
+        imag: add_floats(&a.imag, &b.imag, subtract_b, precision.1),
+    }
+}
+
+// The products are exact so that each part is only rounded once
+fn multiply_complex(a: &Complex, b: &Complex, precision: (u32, u32)) -> Complex {
+    Complex {
+        real: add_floats(&multiply_exact(&a.real, &b.real), &multiply_exact(&a.imag, &b.imag), true, precision.0),
+        imag: add_floats(&multiply_exact(&a.real, &b.imag), &multiply_exact(&a.imag, &b.real), false, precision.1),
+    }
+}
+
+fn divide_complex(a: &Complex, b: &Complex, precision: (u32, u32)) -> Complex {
+    let working = 2 * precision.0.max(precision.1) + 8;
+
+    let denominator = add_floats(&multiply_exact(&b.real, &b.real), &multiply_exact(&b.imag, &b.imag), false, working);
+    let real = add_floats(&multiply_exact(&a.real, &b.real), &multiply_exact(&a.imag, &b.imag), false, working);
+    let imag = add_floats(&multiply_exact(&a.imag, &b.real), &multiply_exact(&a.real, &b.imag), true, working);
+
+    Complex {
+        real: divide_floats(&real, &denominator, precision.0),
+        imag: divide_floats(&imag, &denominator, precision.1),
+    }
+}
+
+fn max_precision(a: &Complex, b: &Complex) -> (u32, u32) {
+    (a.real.precision.max(b.real.precision), a.imag.precision.max(b.imag.precision))
+}
+
+impl fmt::Display for Complex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({} {})", self.real, self.imag)
+    }
+}
+
+impl fmt::Debug for Complex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({:?} {:?})", self.real, self.imag)
+    }
+}
+
+impl PartialEq for Complex {
+    fn eq(&self, other: &Complex) -> bool {
+        self.real == other.real && self.imag == other.imag
+    }
+}
+
+impl Neg for Complex {
+    type Output = Complex;
+
+    fn neg(self) -> Complex {
+        Complex {
+            real: -self.real,
+            imag: -self.imag,
+        }
+    }
+}
+
+macro_rules! complex_operator {
+    ($trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident, |$a:ident, $b:ident, $precision:ident| $body:expr) => {
+        impl $trait<&Complex> for Complex {
+            type Output = Complex;
+
+            fn $method(self, other: &Complex) -> Complex {
+                let ($a, $b, $precision) = (&self, other, self.prec());
+                $body
+            }
+        }
+
+        impl $trait<Complex> for Complex {
+            type Output = Complex;
+
+            fn $method(self, other: Complex) -> Complex {
+                self.$method(&other)
+            }
+        }
+
+        impl $trait<&Complex> for &Complex {
+            type Output = Complex;
+
+            fn $method(self, other: &Complex) -> Complex {
+                let ($a, $b, $precision) = (self, other, max_precision(self, other));
+                $body
+            }
+        }
+
+        impl $trait<f64> for Complex {
+            type Output = Complex;
+
+            fn $method(self, other: f64) -> Complex {
+                let other = Complex::with_val(64, (other, 0.0));
+                self.$method(&other)
+            }
+        }
+
+        impl $assign_trait<&Complex> for Complex {
+            fn $assign_method(&mut self, other: &Complex) {
+                let ($a, $b, $precision) = (&*self, other, self.prec());
+                *self = $body;
+            }
+        }
+
+        impl $assign_trait<Complex> for Complex {
+            fn $assign_method(&mut self, other: Complex) {
+                self.$assign_method(&other);
+            }
+        }
+
+        impl $assign_trait<f64> for Complex {
+            fn $assign_method(&mut self, other: f64) {
+                self.$assign_method(&Complex::with_val(64, (other, 0.0)));
+            }
+        }
+    };
+}
+
+complex_operator!(Add, add, AddAssign, add_assign, |a, b, precision| add_complex(a, b, false, precision));
+complex_operator!(Sub, sub, SubAssign, sub_assign, |a, b, precision| add_complex(a, b, true, precision));
+complex_operator!(Mul, mul, MulAssign, mul_assign, |a, b, precision| multiply_complex(a, b, precision));
+complex_operator!(Div, div, DivAssign, div_assign, |a, b, precision| divide_complex(a, b, precision));
@@ -0,0 +1,259 @@
+use crate::renderer::FractalRenderer;
+use crate::util::{extended_to_string_short, merge_location_file, parse_extended};
+
+use config::{Config, File, FileFormat, Value};
+use serde_json::json;
+
+use std::collections::HashMap;
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::time::Instant;
+
+// The keys of a location which describe it rather than the render, such as those of a bookmark
+const DESCRIPTION_KEYS: [&str; 3] = ["name", "notes", "thumbnail"];
+
+// A single location of a batch, with the keys it overrides in the shared settings
+pub struct BatchLocation {
+    pub name: String,
+    pub values: HashMap<String, Value>,
+}
+
+pub struct BatchResult {
+    pub name: String,
+    pub zoom: String,
+    pub time: u128,
+    pub score: Option<f64>,
+    pub bland: bool,
+    pub error: Option<String>,
+}
+
+impl BatchResult {
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "name": self.name,
+            "zoom": self.zoom,
+            "time": self.time as u64,
+            "score": self.score,
+            "bland": self.bland,
+            "error": self.error,
+        })
+    }
+}
+
+// Renders many locations with the same image and colouring settings. The locations file is either TOML with an array
+// of [[locations]] tables, or has one location per line, given as "name real imag zoom [iterations]" (the name can be
+// left out) or as the path of a location file, which can also be from Kalles Fraktaler or Fractint.
+pub struct Batch {
+    pub settings: Config,
+    pub locations: Vec<BatchLocation>,
+    pub filename_template: String,
+    pub output: Option<String>,
+    pub minimum_score: f64,
+}
+
+impl Batch {
+    pub fn new(filename: &str, settings: Config) -> Self {
+        let contents = fs::read_to_string(filename).unwrap_or_else(|error| panic!("failed to read {}: {}", filename, error));
+
+        let locations = match Batch::parse_table(&contents) {
+            Some(locations) => locations,
+            None => Batch::parse_lines(&contents)
+        };
+
+        Batch {
+            filename_template: settings.get_str("batch_filename").unwrap_or_else(|_| String::from("output/{name}")),
+            output: settings.get_str("batch_output").ok(),
+            minimum_score: settings.get_float("batch_minimum_score").unwrap_or(0.0),
+            settings,
+            locations,
+        }
+    }
+
+    fn parse_table(contents: &str) -> Option<Vec<BatchLocation>> {
+        let mut table = Config::default();
+        table.merge(File::from_str(contents, FileFormat::Toml)).ok()?;
+
+        let locations = table.get_array("locations").ok()?.into_iter().enumerate().map(|(index, value)| {
+            let values = value.into_table().expect("each batch location must be a table");
+
+            let name = values.get("name")
+                .and_then(|name| name.clone().into_str().ok())
+                .unwrap_or_else(|| format!("location_{}", index));
+
+            BatchLocation {
+                name,
+                values
+            }
+        }).collect();
+
+        Some(locations)
+    }
+
+    // A malformed line is reported with its line number and skipped, so that one typo does not stop the whole batch
+    fn parse_lines(contents: &str) -> Vec<BatchLocation> {
+        contents.lines()
+            .enumerate()
+            .map(|(line_number, line)| (line_number + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .enumerate()
+            .filter_map(|(index, (line_number, line))| {
+                match Batch::parse_line(index, line) {
+                    Ok(location) => Some(location),
+                    Err(error) => {
+                        eprintln!("Skipping line {} of the batch: {}", line_number, error);
+                        None
+                    }
+                }
+            }).collect()
+    }
+
+    fn parse_line(index: usize, line: &str) -> Result<BatchLocation, String> {
+        let parts = line.split_whitespace().collect::<Vec<&str>>();
+
+        if parts.len() == 1 {
+            return Batch::parse_location_file(parts[0]);
+        }
+
+        // The name is only there if the first value is not a number
+        let (name, parts) = if parts[0].parse::<f64>().is_err() {
+            (parts[0].to_owned(), &parts[1..])
+        } else {
+            (format!("location_{}", index), &parts[..])
+        };
+
+        if parts.len() < 3 || parts.len() > 4 {
+            return Err(format!("\"{}\" is not of the form \"name real imag zoom [iterations]\"", line));
+        }
+
+        for value in &parts[..3] {
+            parse_extended(value)?;
+        }
+
+        // The centre and zoom are kept as strings, as they can have more digits than a float
+        let mut values = HashMap::new();
+        values.insert(String::from("real"), Value::from(parts[0]));
+        values.insert(String::from("imag"), Value::from(parts[1]));
+        values.insert(String::from("zoom"), Value::from(parts[2]));
+
+        if let Some(iterations) = parts.get(3) {
+            let iterations = iterations.parse::<i64>().map_err(|_| format!("iterations in \"{}\" must be an integer", line))?;
+            values.insert(String::from("iterations"), Value::from(iterations));
+        }
+
+        Ok(BatchLocation {
+            name,
+            values
+        })
+    }
+
+    // The location file readers panic on a missing or malformed file, so this is caught to skip only the one line
+    fn parse_location_file(filename: &str) -> Result<BatchLocation, String> {
+        let location = panic::catch_unwind(|| {
+            let mut location = Config::default();
+            merge_location_file(&mut location, filename);
+            location
+        }).map_err(|_| format!("failed to read the location file {}", filename))?;
+
+        let values = location.try_into().map_err(|error| format!("failed to read the location file {}: {}", filename, error))?;
+
+        Ok(BatchLocation {
+            name: Path::new(filename).file_stem().map_or_else(|| filename.to_owned(), |stem| stem.to_string_lossy().to_string()),
+            values
+        })
+    }
+
+    // The settings of a location, the whole location is a single frame unless it sets the frames itself
+    fn get_settings(&self, location: &BatchLocation) -> Config {
+        let mut settings = self.settings.clone();
+
+        settings.set("frames", 1i64).unwrap();
+        settings.set("filename_template", self.filename_template.clone()).unwrap();
+
+        for (key, value) in location.values.iter().filter(|(key, _)| !DESCRIPTION_KEYS.contains(&key.as_str())) {
+            settings.set(key, value.clone()).unwrap();
+        }
+
+        settings.set("name", location.name.clone()).unwrap();
+        settings
+    }
+
+    // A location which fails is reported and skipped, so that the rest of the batch is still rendered. The locations
+    // which score below the minimum interestingness are still saved, but are marked as bland in the report.
+    pub fn run(&self) -> Vec<BatchResult> {
+        println!(" {:<31}| {:<15}| {:<15}| {:<15}| {:<15}", "Location", "Zoom", "Time [ms]", "Score", "Result");
+
+        let results = self.locations.iter().enumerate().map(|(index, location)| {
+            let settings = self.get_settings(location);
+            let zoom = settings.get_str("zoom").unwrap_or_else(|_| String::from("1E0"));
+            let start_time = Instant::now();
+
+            // The settings are checked before rendering, and a panic while rendering only stops this location
+            let rendered = panic::catch_unwind(AssertUnwindSafe(|| {
+                FractalRenderer::try_new(settings).map(|mut renderer| {
+                    renderer.render();
+                    renderer.statistics.interestingness
+                })
+            }));
+
+            let (score, error) = match rendered {
+                Ok(Ok(score)) => (Some(score), None),
+                Ok(Err(validation)) => (None, Some(validation.errors.join(", "))),
+                Err(error) => (None, Some(error.downcast_ref::<String>().cloned()
+                    .or_else(|| error.downcast_ref::<&str>().map(|error| error.to_string()))
+                    .unwrap_or_else(|| String::from("the render panicked"))))
+            };
+
+            let result = BatchResult {
+                name: location.name.clone(),
+                zoom: parse_extended(&zoom).map(extended_to_string_short).unwrap_or(zoom),
+                time: start_time.elapsed().as_millis(),
+                bland: score.map_or(false, |score| score < self.minimum_score),
+                score,
+                error,
+            };
+
+            println!(" {:<31}| {:<15}| {:<15}| {:<15}| {:<15}",
+                result.name,
+                result.zoom,
+                result.time,
+                result.score.map(|score| format!("{:.3}", score)).unwrap_or_else(|| String::from("-")),
+                match (&result.error, result.bland) {
+                    (Some(_), _) => "failed",
+                    (None, true) => "bland",
+                    (None, false) => "rendered"
+                });
+
+            if let Some(error) = &result.error {
+                eprintln!("Location {} ({}) failed: {}", index, result.name, error);
+            }
+
+            result
+        }).collect::<Vec<_>>();
+
+        let failed = results.iter().filter(|result| result.error.is_some()).count();
+        let total_time = results.iter().map(|result| result.time).sum::<u128>();
+
+        let bland = results.iter().filter(|result| result.bland).count();
+
+        println!("Rendered {} of {} locations in {} ms.", results.len() - failed, results.len(), total_time);
+
+        if bland > 0 {
+            println!("{} locations scored below the minimum of {}.", bland, self.minimum_score);
+        }
+
+        if let Some(output) = &self.output {
+            let report = json!({
+                "version": env!("CARGO_PKG_VERSION"),
+                "rendered": results.len() - failed,
+                "failed": failed,
+                "bland": bland,
+                "locations": results.iter().map(|result| result.to_json()).collect::<Vec<_>>(),
+            });
+
+            fs::write(output, serde_json::to_string_pretty(&report).unwrap()).unwrap();
+        }
+
+        results
+    }
+}
@@ -0,0 +1,138 @@
+use crate::renderer::FractalRenderer;
+use crate::util::{extended_to_string_short, string_to_extended};
+use crate::util::presets::apply_preset;
+
+use config::{Config, File, FileFormat};
+use serde_json::json;
+
+use std::fs;
+
+// The benchmark renders a fixed set of the preset locations, which are compiled in so that every version renders exactly
+// the same set
+const BENCHMARK_LOCATIONS: [(&str, &str); 5] = [
+    ("shallow", "e14"),
+    ("mid", "e50"),
+    ("deep", "e227"),
+    ("very_deep", "e1086"),
+    ("glitch_heavy", "glitch_test"),
+];
+
+// The settings are copies kept in the crate, so that it builds when packaged on its own
+const BENCHMARK_SETTINGS: &str = include_str!("../../data/benchmarks/1024.toml");
+const DEFAULT_SETTINGS: &str = include_str!("../../data/default.toml");
+
+pub struct BenchmarkResult {
+    pub name: String,
+    pub zoom: String,
+    pub pixels: usize,
+    pub reference_time: u128,
+    pub approximation_time: u128,
+    pub iteration_time: u128,
+    pub correction_time: u128,
+    pub frame_time: u128,
+    pub reference_count: usize,
+}
+
+impl BenchmarkResult {
+    // Pixels per second over the iteration and correction stages, which do not depend on the location depth as much
+    pub fn throughput(&self) -> f64 {
+        1000.0 * self.pixels as f64 / (self.iteration_time + self.correction_time).max(1) as f64
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "name": self.name,
+            "zoom": self.zoom,
+            "pixels": self.pixels,
+            "timings": {
+                "reference": self.reference_time as u64,
+                "approximation": self.approximation_time as u64,
+                "iteration": self.iteration_time as u64,
+                "correction": self.correction_time as u64,
+                "frame": self.frame_time as u64,
+            },
+            "reference_count": self.reference_count,
+            "throughput": self.throughput(),
+        })
+    }
+}
+
+// Renders the built in locations at fixed settings, only the thread count and the output file are used from the given settings
+pub struct Benchmark {
+    pub output: Option<String>,
+}
+
+impl Benchmark {
+    pub fn new(settings: Config) -> Self {
+        Benchmark {
+            output: settings.get_str("benchmark_output").ok(),
+        }
+    }
+
+    pub fn run(&self) -> Vec<BenchmarkResult> {
+        println!(" {:<15}| {:<15}| {:<15}| {:<15}| {:<15}| {:<15}| {:<6}| {:<15}", "Location", "Zoom", "Reference [ms]", "Approx [ms]", "Iteration [ms]", "Correct [ms]", "Ref", "Throughput [px/s]");
+
+        let filename = std::env::temp_dir().join("rust_fractal_benchmark").to_string_lossy().to_string();
+
+        let results = BENCHMARK_LOCATIONS.iter().map(|(name, preset)| {
+            let mut settings = Config::default();
+
+            settings.merge(File::from_str(DEFAULT_SETTINGS, FileFormat::Toml)).unwrap();
+            settings.merge(File::from_str(BENCHMARK_SETTINGS, FileFormat::Toml)).unwrap();
+            apply_preset(&mut settings, preset);
+
+            settings.set("filename_template", filename.clone()).unwrap();
+            settings.set("show_output", false).unwrap();
+            settings.set("history", false).unwrap();
+
+            let mut renderer = FractalRenderer::new(settings);
+            renderer.render();
+
+            let statistics = &renderer.statistics;
+
+            let result = BenchmarkResult {
+                name: name.to_string(),
+                zoom: extended_to_string_short(string_to_extended(&statistics.zoom)),
+                pixels: renderer.total_pixels,
+                reference_time: statistics.reference_time,
+                approximation_time: statistics.approximation_time,
+                iteration_time: statistics.iteration_time,
+                correction_time: statistics.correction_time,
+                frame_time: statistics.frame_time,
+                reference_count: statistics.reference_count,
+            };
+
+            println!(" {:<15}| {:<15}| {:<15}| {:<15}| {:<15}| {:<15}| {:<6}| {:<15.0}",
+                result.name,
+                result.zoom,
+                result.reference_time,
+                result.approximation_time,
+                result.iteration_time,
+                result.correction_time,
+                result.reference_count,
+                result.throughput());
+
+            result
+        }).collect::<Vec<_>>();
+
+        for extension in ["", ".png"].iter() {
+            fs::remove_file(filename.clone() + extension).ok();
+        }
+
+        let total_time = results.iter().map(|result| result.frame_time).sum::<u128>();
+
+        println!("Rendered {} locations in {} ms.", results.len(), total_time);
+
+        if let Some(output) = &self.output {
+            let report = json!({
+                "version": env!("CARGO_PKG_VERSION"),
+                "threads": rayon::current_num_threads(),
+                "locations": results.iter().map(|result| result.to_json()).collect::<Vec<_>>(),
+            });
+
+            fs::write(output, serde_json::to_string_pretty(&report).unwrap()).unwrap();
+        }
+
+        results
+    }
+}
@@ -0,0 +1,166 @@
+use crate::renderer::FractalRenderer;
+use crate::util::RenderParameters;
+
+use config::{Config, File, FileFormat};
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// A named location with notes about it. The thumbnail is the path of an image rendered when the bookmark was added.
+pub struct Bookmark {
+    pub name: String,
+    pub real: String,
+    pub imag: String,
+    pub zoom: String,
+    pub iterations: usize,
+    pub rotate: f64,
+    pub notes: String,
+    pub thumbnail: Option<String>,
+}
+
+impl Bookmark {
+    pub fn from_parameters(name: &str, notes: &str, parameters: &RenderParameters) -> Self {
+        Bookmark {
+            name: name.to_owned(),
+            real: parameters.real.clone(),
+            imag: parameters.imag.clone(),
+            zoom: parameters.zoom.clone(),
+            iterations: parameters.iterations,
+            rotate: parameters.rotate,
+            notes: notes.to_owned(),
+            thumbnail: None,
+        }
+    }
+
+    // The strings are quoted as JSON, which has the same escapes as the basic strings of TOML
+    fn to_toml(&self) -> String {
+        let quote = |value: &str| serde_json::to_string(value).unwrap();
+
+        let mut contents = format!("[[locations]]\nname = {}\nreal = {}\nimag = {}\nzoom = {}\niterations = {}\nrotate = {}\nnotes = {}\n",
+            quote(&self.name),
+            quote(&self.real),
+            quote(&self.imag),
+            quote(&self.zoom),
+            self.iterations,
+            self.rotate,
+            quote(&self.notes));
+
+        if let Some(thumbnail) = &self.thumbnail {
+            contents += &format!("thumbnail = {}\n", quote(thumbnail));
+        }
+
+        contents
+    }
+}
+
+// The bookmarks are kept in a TOML file with an array of [[locations]] tables, which is also a locations file for the
+// batch renderer, so a collection of bookmarks can be rendered with the same image and colouring settings.
+pub struct Bookmarks {
+    pub filename: String,
+    pub bookmarks: Vec<Bookmark>,
+}
+
+impl Bookmarks {
+    // A file which does not exist yet is an empty collection, but one which cannot be read is an error, so that
+    // adding a bookmark does not overwrite the bookmarks which are already there
+    pub fn load(filename: &str) -> Result<Self, String> {
+        let mut bookmarks = Vec::new();
+
+        let contents = match fs::read_to_string(filename) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(error) => return Err(format!("failed to read {}: {}", filename, error))
+        };
+
+        let mut table = Config::default();
+        table.merge(File::from_str(&contents, FileFormat::Toml)).map_err(|error| format!("failed to read {}: {}", filename, error))?;
+
+        for (index, value) in table.get_array("locations").unwrap_or_default().into_iter().enumerate() {
+            let values = value.into_table().map_err(|_| format!("failed to read {}: bookmark {} is not a table", filename, index))?;
+            let get_str = |key: &str| values.get(key).and_then(|value| value.clone().into_str().ok());
+
+            bookmarks.push(Bookmark {
+                name: get_str("name").unwrap_or_else(|| format!("location_{}", index)),
+                real: get_str("real").unwrap_or_else(|| String::from("-0.75")),
+                imag: get_str("imag").unwrap_or_else(|| String::from("0.0")),
+                zoom: get_str("zoom").unwrap_or_else(|| String::from("1E0")),
+                iterations: values.get("iterations").and_then(|value| value.clone().into_int().ok()).unwrap_or(1000) as usize,
+                rotate: values.get("rotate").and_then(|value| value.clone().into_float().ok()).unwrap_or(0.0),
+                notes: get_str("notes").unwrap_or_default(),
+                thumbnail: get_str("thumbnail"),
+            });
+        }
+
+        Ok(Bookmarks {
+            filename: filename.to_owned(),
+            bookmarks,
+        })
+    }
+
+    pub fn save(&self) {
+        let contents = self.bookmarks.iter().map(|bookmark| bookmark.to_toml()).collect::<Vec<String>>().join("\n");
+
+        fs::write(&self.filename, contents).unwrap_or_else(|error| panic!("failed to write {}: {}", self.filename, error));
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Bookmark> {
+        self.bookmarks.iter().find(|bookmark| bookmark.name == name)
+    }
+
+    // A bookmark with the same name is replaced
+    pub fn add(&mut self, bookmark: Bookmark) {
+        match self.bookmarks.iter_mut().find(|existing| existing.name == bookmark.name) {
+            Some(existing) => *existing = bookmark,
+            None => self.bookmarks.push(bookmark)
+        }
+    }
+
+    // The thumbnails are kept in a folder next to the bookmarks file, named after the bookmark
+    pub fn get_thumbnail_filename(&self, name: &str) -> String {
+        let path = Path::new(&self.filename);
+        let stem = path.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_else(|| String::from("bookmarks"));
+        let safe_name = name.chars().map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' }).collect::<String>();
+
+        path.with_file_name(format!("{}_thumbnails", stem))
+            .join(format!("{}.png", safe_name))
+            .to_string_lossy()
+            .to_string()
+    }
+
+    // Renders the thumbnail with the settings at the given width, keeping the aspect of the settings
+    pub fn render_thumbnail(&self, bookmark: &mut Bookmark, settings: &Config, thumbnail_width: usize) {
+        let parameters = RenderParameters::from_config(settings);
+        let thumbnail_height = ((thumbnail_width as f64 * parameters.image_height as f64 / parameters.image_width as f64) as usize).max(2);
+
+        let mut settings = settings.clone();
+
+        settings.set("real", bookmark.real.clone()).unwrap();
+        settings.set("imag", bookmark.imag.clone()).unwrap();
+        settings.set("zoom", bookmark.zoom.clone()).unwrap();
+        settings.set("iterations", bookmark.iterations as i64).unwrap();
+        settings.set("rotate", bookmark.rotate).unwrap();
+        settings.set("image_width", thumbnail_width as i64).unwrap();
+        settings.set("image_height", thumbnail_height as i64).unwrap();
+        settings.set("frames", 1i64).unwrap();
+        settings.set("supersampling", 1i64).unwrap();
+        settings.set("crop_margin", 0i64).unwrap();
+        settings.set("view_fit", "height").unwrap();
+        settings.set("region", Vec::<f64>::new()).unwrap();
+        settings.set("show_output", false).unwrap();
+        settings.set("history", false).unwrap();
+
+        let mut renderer = FractalRenderer::new(settings);
+        let image = renderer.render_to_buffer(false);
+
+        let filename = self.get_thumbnail_filename(&bookmark.name);
+
+        if let Some(directory) = Path::new(&filename).parent() {
+            fs::create_dir_all(directory).unwrap();
+        }
+
+        image::save_buffer(&filename, &image.rgb, image.image_width as u32, image.image_height as u32, image::ColorType::Rgb8).unwrap();
+
+        bookmark.thumbnail = Some(filename);
+    }
+}
@@ -0,0 +1,114 @@
+use crate::util::{ComplexArbitrary, FloatExtended, linear_interpolation_between_zoom, parse_zoom};
+
+use config::Config;
+
+use std::cmp::max;
+use std::f64::consts::{LN_2, PI};
+
+pub struct Waypoint {
+    pub location: ComplexArbitrary,
+    pub zoom: FloatExtended,
+    pub rotate: f64,
+}
+
+// A path through a list of locations, each segment between two locations uses the same number of frames
+pub struct CameraPath {
+    pub waypoints: Vec<Waypoint>,
+}
+
+impl CameraPath {
+    // The path is given as an array of tables, each with the real, imag, zoom and optionally rotate keys. A path which
+    // cannot be read is reported by the validation and ignored here.
+    pub fn from_settings(settings: &Config) -> Option<CameraPath> {
+        CameraPath::parse(settings).ok().flatten()
+    }
+
+    pub fn parse(settings: &Config) -> Result<Option<CameraPath>, String> {
+        let values = match settings.get_array("path") {
+            Ok(values) => values,
+            Err(_) => return Ok(None)
+        };
+
+        let image_height = settings.get_int("image_height").unwrap_or(720) as usize;
+
+        let entries = values.into_iter().enumerate().map(|(index, value)| -> Result<_, String> {
+            let table = value.into_table().map_err(|_| format!("path entry {} must be a table with real, imag and zoom", index + 1))?;
+
+            let get_string = |key: &str| table.get(key)
+                .and_then(|value| value.clone().into_str().ok())
+                .ok_or_else(|| format!("path entry {} is missing {}", index + 1, key));
+
+            let real = get_string("real")?;
+            let imag = get_string("imag")?;
+            let zoom = parse_zoom(&get_string("zoom")?.to_ascii_uppercase(), image_height)
+                .map_err(|error| format!("path entry {}: {}", index + 1, error))?;
+
+            let rotate = match table.get("rotate") {
+                Some(rotate) => rotate.clone().into_float().map_err(|_| format!("path entry {} has a rotate which is not a number", index + 1))?,
+                None => 0.0
+            };
+
+            Ok((index, real, imag, zoom, rotate.to_radians()))
+        }).collect::<Result<Vec<_>, String>>()?;
+
+        if entries.len() < 2 {
+            return Ok(None);
+        }
+
+        // All of the locations use the precision required by the deepest zoom
+        let precision = entries.iter().map(|(_, _, _, zoom, _)| max(64, zoom.exponent + 64)).max().unwrap() as u32;
+
+        let waypoints = entries.into_iter().map(|(index, real, imag, zoom, rotate)| -> Result<Waypoint, String> {
+            let location = ComplexArbitrary::parse("(".to_owned() + &real + "," + &imag + ")")
+                .map_err(|_| format!("path entry {} has a location ({}, {}) which is not valid", index + 1, real, imag))?;
+
+            Ok(Waypoint {
+                location: ComplexArbitrary::with_val(precision, location),
+                zoom,
+                rotate
+            })
+        }).collect::<Result<Vec<Waypoint>, String>>()?;
+
+        Ok(Some(CameraPath {
+            waypoints
+        }))
+    }
+
+    // Returns the location, zoom and rotation at a position in [0, 1] along the path
+    // Each segment is eased so that the camera slows down at every location. The zoom is interpolated logarithmically,
+    // and the centre moves in proportion to the width of the view, so that the location being zoomed towards stays
+    // at the same place on the screen rather than arriving in the last few frames.
+    pub fn get_frame(&self, position: f64) -> (ComplexArbitrary, FloatExtended, f64) {
+        let segments = self.waypoints.len() - 1;
+        let scaled = position.clamp(0.0, 1.0) * segments as f64;
+
+        let index = (scaled.floor() as usize).min(segments - 1);
+        let factor = 0.5 - 0.5 * (PI * (scaled - index as f64)).cos();
+
+        let start = &self.waypoints[index];
+        let end = &self.waypoints[index + 1];
+
+        let location = start.location.clone() + (end.location.clone() - &start.location) * get_centre_factor(start.zoom, end.zoom, factor);
+        let zoom = linear_interpolation_between_zoom(start.zoom, end.zoom, factor);
+        let rotate = start.rotate + (end.rotate - start.rotate) * factor;
+
+        (location, zoom, rotate)
+    }
+}
+
+// The fraction of the distance between the centres which has been moved when the width of the view has changed by the
+// given fraction of the way in log space. The width is w0 * r^t, where r is the ratio of the widths, and the moved
+// fraction is (1 - r^t) / (1 - r). The exponentials are arranged so that they cannot overflow for a deep zoom.
+fn get_centre_factor(start_zoom: FloatExtended, end_zoom: FloatExtended, factor: f64) -> f64 {
+    let get_log = |zoom: FloatExtended| zoom.mantissa.ln() + zoom.exponent as f64 * LN_2;
+
+    let log_ratio = get_log(start_zoom) - get_log(end_zoom);
+
+    if log_ratio.abs() < 1e-9 {
+        factor
+    } else if log_ratio < 0.0 {
+        (1.0 - (factor * log_ratio).exp()) / (1.0 - log_ratio.exp())
+    } else {
+        (((factor - 1.0) * log_ratio).exp() - (-log_ratio).exp()) / (1.0 - (-log_ratio).exp())
+    }
+}
@@ -0,0 +1,121 @@
+use crate::util::data_export::DataExport;
+
+use serde_json::{json, Value};
+
+use std::fs;
+use std::path::Path;
+
+// The state of a partially rendered frame. The file is a single line JSON header followed by the
+// raw little endian pixel channels, so that the buffers of large images can be written quickly.
+pub struct RenderCheckpoint {
+    pub filename: String,
+    pub metadata: Vec<(String, String)>,
+    pub completed: Vec<bool>,
+}
+
+impl RenderCheckpoint {
+    // The metadata describes the reference and frame, a checkpoint is only used if it matches exactly
+    pub fn new(filename: &str, metadata: &[(String, String)], total_pixels: usize) -> Self {
+        RenderCheckpoint {
+            filename: filename.to_owned(),
+            metadata: metadata.to_vec(),
+            completed: vec![false; total_pixels],
+        }
+    }
+
+    // The file is written next to the final location and renamed, so that a power cut while
+    // saving leaves the previous checkpoint intact
+    pub fn save(&self, export: &DataExport) {
+        if let Some(parent) = Path::new(&self.filename).parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+
+        let header = json!({
+            "metadata": self.metadata.iter().map(|(key, value)| json!([key, value])).collect::<Vec<_>>(),
+            "image_width": export.image_width,
+            "image_height": export.image_height,
+            "completed_pixels": self.completed.iter().filter(|&&completed| completed).count(),
+        });
+
+        let mut contents = serde_json::to_string(&header).unwrap().into_bytes();
+        contents.push(b'\n');
+
+        contents.extend(self.completed.iter().map(|&completed| completed as u8));
+        contents.extend(export.glitched.iter().map(|&glitched| glitched as u8));
+        contents.extend_from_slice(&export.buffer);
+
+        for &value in export.iterations.iter().chain(export.correction_passes.iter()) {
+            contents.extend_from_slice(&value.to_le_bytes());
+        }
+
+        for channel in [&export.smooth, &export.stripe, &export.distance_x, &export.distance_y].iter() {
+            for &value in channel.iter() {
+                contents.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+
+        let temporary_filename = format!("{}.tmp", self.filename);
+
+        fs::write(&temporary_filename, contents).unwrap();
+        fs::rename(&temporary_filename, &self.filename).unwrap();
+    }
+
+    // Restores the pixel channels into the export if the checkpoint was written for the same frame
+    pub fn load(filename: &str, metadata: &[(String, String)], export: &mut DataExport) -> Option<Self> {
+        let contents = fs::read(filename).ok()?;
+        let header_length = contents.iter().position(|&byte| byte == b'\n')?;
+
+        let header = serde_json::from_slice::<Value>(&contents[..header_length]).ok()?;
+
+        let stored_metadata = header["metadata"].as_array()?.iter().filter_map(|pair| {
+            Some((pair[0].as_str()?.to_owned(), pair[1].as_str()?.to_owned()))
+        }).collect::<Vec<_>>();
+
+        if stored_metadata != metadata
+            || header["image_width"].as_u64()? as usize != export.image_width
+            || header["image_height"].as_u64()? as usize != export.image_height {
+            return None;
+        }
+
+        let total_pixels = export.image_width * export.image_height;
+        let mut data = &contents[(header_length + 1)..];
+
+        if data.len() != total_pixels * 29 {
+            return None;
+        }
+
+        let mut take = |length: usize| {
+            let (start, end) = data.split_at(length);
+            data = end;
+            start
+        };
+
+        let completed = take(total_pixels).iter().map(|&byte| byte != 0).collect::<Vec<bool>>();
+        export.glitched = take(total_pixels).iter().map(|&byte| byte != 0).collect();
+        export.buffer = take(3 * total_pixels).to_vec();
+
+        let mut read_u32 = |length: usize| take(4 * length).chunks_exact(4)
+            .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .collect::<Vec<u32>>();
+
+        export.iterations = read_u32(total_pixels);
+        export.correction_passes = read_u32(total_pixels);
+
+        let mut read_f32 = |length: usize| read_u32(length).into_iter().map(f32::from_bits).collect::<Vec<f32>>();
+
+        export.smooth = read_f32(total_pixels);
+        export.stripe = read_f32(total_pixels);
+        export.distance_x = read_f32(total_pixels);
+        export.distance_y = read_f32(total_pixels);
+
+        Some(RenderCheckpoint {
+            filename: filename.to_owned(),
+            metadata: metadata.to_vec(),
+            completed,
+        })
+    }
+
+    pub fn remove(&self) {
+        fs::remove_file(&self.filename).ok();
+    }
+}
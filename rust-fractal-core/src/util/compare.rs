@@ -0,0 +1,159 @@
+use crate::util::data_export::DataExport;
+
+use exr::prelude::simple_image::*;
+
+use std::fs;
+use std::path::Path;
+
+// The data of a single image, either the iterations from a raw export or the channels of a colour image
+pub struct ComparisonImage {
+    pub image_width: usize,
+    pub image_height: usize,
+    pub channels: usize,
+    pub values: Vec<f64>,
+    pub iterations: Vec<u32>,
+    pub raw: bool,
+}
+
+impl ComparisonImage {
+    // The images are given by the user, so a file which cannot be read is an error rather than a panic
+    pub fn read(filename: &str) -> Result<Self, String> {
+        if filename.ends_with(".exr") {
+            let raw_data = Image::read_from_file(filename, read_options::high()).map_err(|error| format!("failed to read {}: {}", filename, error))?;
+
+            let mut iterations = Vec::new();
+            let mut smooth = Vec::new();
+
+            for layer in &raw_data.layers {
+                for channel in &layer.channels {
+                    match (channel.name.to_string().as_str(), &channel.samples) {
+                        ("N", Samples::U32(u32_vec)) => iterations = u32_vec.clone(),
+                        ("NF", Samples::F32(f32_vec)) => smooth = f32_vec.iter().map(|&value| value as f64).collect(),
+                        ("NF", Samples::F16(f16_vec)) => smooth = f16_vec.iter().map(|value| value.to_f64()).collect(),
+                        _ => {}
+                    }
+                }
+            }
+
+            let dimensions = raw_data.attributes.display_window.size;
+
+            if iterations.len() != dimensions.x() * dimensions.y() {
+                return Err(format!("{} does not have an iteration channel for every pixel", filename));
+            }
+
+            smooth.resize(iterations.len(), 0.0);
+
+            Ok(ComparisonImage {
+                image_width: dimensions.x(),
+                image_height: dimensions.y(),
+                channels: 1,
+                values: iterations.iter().zip(smooth.iter()).map(|(&iteration, &smooth)| iteration as f64 + smooth).collect(),
+                iterations,
+                raw: true,
+            })
+        } else {
+            let image = image::open(filename).map_err(|error| format!("failed to read {}: {}", filename, error))?.to_rgb8();
+
+            Ok(ComparisonImage {
+                image_width: image.width() as usize,
+                image_height: image.height() as usize,
+                channels: 3,
+                values: image.into_raw().into_iter().map(|value| value as f64).collect(),
+                iterations: Vec::new(),
+                raw: false,
+            })
+        }
+    }
+}
+
+pub struct ComparisonResult {
+    pub total_pixels: usize,
+    pub mismatched_pixels: usize,
+    pub maximum_error: f64,
+    pub mean_error: f64,
+}
+
+// Compares two renders of the same location pixel by pixel. For raw exports a pixel is mismatched
+// if the integer iteration differs, for images if any of the channels differ.
+pub struct ImageComparison {
+    pub first: ComparisonImage,
+    pub second: ComparisonImage,
+    pub errors: Vec<f64>,
+    pub mismatched: Vec<bool>,
+}
+
+impl ImageComparison {
+    pub fn new(first_filename: &str, second_filename: &str) -> Result<Self, String> {
+        let first = ComparisonImage::read(first_filename)?;
+        let second = ComparisonImage::read(second_filename)?;
+
+        if first.raw != second.raw {
+            return Err(format!("{} and {} must both be raw exports or both be images to be compared", first_filename, second_filename));
+        }
+
+        if first.image_width != second.image_width || first.image_height != second.image_height {
+            return Err(format!("{} is {}x{} but {} is {}x{}, the images must have the same dimensions to be compared",
+                first_filename, first.image_width, first.image_height,
+                second_filename, second.image_width, second.image_height));
+        }
+
+        let channels = first.channels;
+
+        let (errors, mismatched) = first.values.chunks(channels).zip(second.values.chunks(channels)).enumerate().map(|(k, (a, b))| {
+            let error = a.iter().zip(b.iter()).map(|(a, b)| (a - b).abs()).fold(0.0, f64::max);
+
+            let mismatched = if first.raw {
+                first.iterations[k] != second.iterations[k]
+            } else {
+                error > 0.0
+            };
+
+            (error, mismatched)
+        }).unzip();
+
+        Ok(ImageComparison {
+            first,
+            second,
+            errors,
+            mismatched,
+        })
+    }
+
+    pub fn get_result(&self) -> ComparisonResult {
+        let total_pixels = self.errors.len();
+
+        ComparisonResult {
+            total_pixels,
+            mismatched_pixels: self.mismatched.iter().filter(|&&mismatched| mismatched).count(),
+            maximum_error: self.errors.iter().cloned().fold(0.0, f64::max),
+            mean_error: self.errors.iter().sum::<f64>() / total_pixels.max(1) as f64,
+        }
+    }
+
+    // The error is scaled so that the largest difference is white, pixels with a mismatched iteration are red
+    pub fn save_difference(&self, filename: &str) -> Result<(), String> {
+        if let Some(parent) = Path::new(filename).parent() {
+            fs::create_dir_all(parent).map_err(|error| format!("failed to create {}: {}", parent.display(), error))?;
+        }
+
+        let maximum_error = self.errors.iter().cloned().fold(0.0, f64::max);
+
+        let buffer = self.errors.iter().zip(self.mismatched.iter()).flat_map(|(&error, &mismatched)| {
+            let value = if maximum_error > 0.0 {
+                (255.0 * error / maximum_error) as u8
+            } else {
+                0
+            };
+
+            if mismatched && self.first.raw {
+                vec![255, 0, 0]
+            } else {
+                vec![value, value, value]
+            }
+        }).collect::<Vec<u8>>();
+
+        DataExport::write_colour_buffer(filename, &buffer, self.first.image_width, self.first.image_height, &[]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,1302 @@
+use crate::util::{PixelData, FloatExtended, ComplexFixed, FractalType, srgb_to_linear, linear_to_srgb};
+use crate::util::mesh_export::{HeightfieldMesh, MeshType};
+use crate::util::plugin::ColoringPlugin;
+use crate::util::histogram::IterationHistogram;
+use crate::util::reference_orbit::ReferenceOrbit;
+use crate::math::Reference;
+
+use std::{collections::HashMap, f64::consts::LN_2};
+use std::fs::{self, File};
+use std::path::Path;
+use std::io::BufWriter;
+use std::time::{SystemTime, UNIX_EPOCH};
+// use std::cmp::{min, max};
+use std::f32::consts::{FRAC_PI_4};
+use std::sync::Arc;
+
+use exr::{prelude::simple_image};
+use colorgrad::{Color, CustomGradient, Interpolation, BlendMode};
+use parking_lot::Mutex;
+
+// This is 1e16f32.ln().log2() + 1.0
+const ESCAPE_RADIUS_LN_LOG2_P1: f32 = 5.203254472696 + 1.0;
+// const ESCAPE_RADIUS_LN_LOG2_P1: f32 = 7.203254472699 + 1.0;
+// const ESCAPE_RADIUS_LN_LOG2_P1: f32 = 8.2032544726997 + 1.0;
+
+
+// const ESCAPE_RADIUS_LN_LOG3_P1: f32 = 3.282888062227 + 1.0;
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum ExportType {
+    Color,
+    Raw,
+    Both,
+    Gui
+}
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum ColoringType {
+    SmoothIteration,
+    StepIteration,
+    Distance,
+    DistanceStripe,
+    Stripe
+}
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum DataType { 
+    Iteration,
+    Distance,
+    Stripe,
+    DistanceStripe,
+    AtomDomain
+}
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum ReconstructionFilter {
+    Box,
+    Triangle,
+    Gaussian,
+    Lanczos
+}
+
+impl ReconstructionFilter {
+    // The filter radius is in output pixels
+    pub fn radius(&self) -> f32 {
+        match self {
+            ReconstructionFilter::Box => 0.5,
+            ReconstructionFilter::Triangle => 1.0,
+            ReconstructionFilter::Gaussian => 1.5,
+            ReconstructionFilter::Lanczos => 3.0
+        }
+    }
+
+    pub fn weight(&self, x: f32) -> f32 {
+        let x = x.abs();
+
+        if x > self.radius() {
+            return 0.0;
+        }
+
+        match self {
+            ReconstructionFilter::Box => 1.0,
+            ReconstructionFilter::Triangle => 1.0 - x,
+            ReconstructionFilter::Gaussian => (-2.0 * x * x).exp(),
+            ReconstructionFilter::Lanczos => {
+                if x < 1e-6 {
+                    1.0
+                } else {
+                    let pi_x = std::f32::consts::PI * x;
+                    3.0 * pi_x.sin() * (pi_x / 3.0).sin() / (pi_x * pi_x)
+                }
+            }
+        }
+    }
+}
+
+// Downsamples one axis of a linear image by an integer factor, the image is stored as rows of 3 channel pixels
+fn downsample_axis(input: &[f32], width: usize, height: usize, factor: usize, filter: ReconstructionFilter, horizontal: bool) -> Vec<f32> {
+    let (input_length, other_length) = if horizontal { (width, height) } else { (height, width) };
+    let output_length = input_length / factor;
+
+    // The same weights are used for every row or column
+    let weights = (0..output_length).map(|o| {
+        let centre = (o as f32 + 0.5) * factor as f32 - 0.5;
+        let radius = filter.radius() * factor as f32;
+
+        let start = (centre - radius).floor().max(0.0) as usize;
+        let end = ((centre + radius).ceil() as usize).min(input_length - 1);
+
+        let taps = (start..=end)
+            .map(|x| (x, filter.weight((x as f32 - centre) / factor as f32)))
+            .filter(|(_, weight)| *weight != 0.0)
+            .collect::<Vec<_>>();
+
+        let total = taps.iter().map(|(_, weight)| weight).sum::<f32>();
+
+        taps.into_iter().map(|(x, weight)| (x, weight / total)).collect::<Vec<_>>()
+    }).collect::<Vec<_>>();
+
+    let (output_width, output_height) = if horizontal { (output_length, height) } else { (width, output_length) };
+    let mut output = vec![0.0f32; output_width * output_height * 3];
+
+    for other in 0..other_length {
+        for (o, taps) in weights.iter().enumerate() {
+            let output_index = if horizontal { other * output_width + o } else { o * output_width + other };
+
+            for &(x, weight) in taps {
+                let input_index = if horizontal { other * width + x } else { x * width + other };
+
+                for channel in 0..3 {
+                    output[3 * output_index + channel] += weight * input[3 * input_index + channel];
+                }
+            }
+        }
+    }
+
+    output
+}
+
+// Downsamples the colour buffer by an integer factor in linear light
+pub fn downsample_buffer(buffer: &[u8], width: usize, height: usize, factor: usize, filter: ReconstructionFilter) -> Vec<u8> {
+    let linear = buffer.iter().map(|&value| srgb_to_linear(value)).collect::<Vec<f32>>();
+
+    let horizontal = downsample_axis(&linear, width, height, factor, filter, true);
+    let vertical = downsample_axis(&horizontal, width / factor, height, factor, filter, false);
+
+    vertical.into_iter().map(linear_to_srgb).collect()
+}
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum HeightmapType {
+    None,
+    Iteration,
+    Distance
+}
+
+// Replaces the placeholders in an output filename template, the frame index is zero padded
+// Supported placeholders are {name}, {index}, {zoom}, {iterations} and {timestamp}
+pub fn resolve_filename_template(template: &str, name: &str, index: usize, zoom: &str, iterations: usize) -> String {
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or(0);
+
+    template.replace("{name}", name)
+        .replace("{index}", &format!("{:08}", index))
+        .replace("{zoom}", zoom)
+        .replace("{iterations}", &iterations.to_string())
+        .replace("{timestamp}", &timestamp.to_string())
+}
+
+// The subfolder which keeps the files of a run apart from the others, named by the time it was started
+pub fn get_run_folder() -> String {
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or(0);
+
+    format!("run_{}", timestamp)
+}
+
+// Places the files of the template in the output directory, replacing the directory of the template. The directory is
+// created, and returned along with the new template.
+pub fn resolve_output_directory(template: &str, output_directory: &str) -> (String, String) {
+    let directory = Path::new(output_directory).to_path_buf();
+
+    fs::create_dir_all(&directory).unwrap_or_else(|error| panic!("failed to create the output directory {}: {}", directory.display(), error));
+
+    let file_name = Path::new(template).file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_else(|| template.to_owned());
+
+    (directory.to_string_lossy().to_string(), directory.join(file_name).to_string_lossy().to_string())
+}
+
+// How much detail the iterations of an image have, so that bland frames can be found without looking at them. The pixels
+// are put into bands of a quarter of a doubling of the iteration count, and the score is the entropy of the bands in
+// bits, multiplied by the fraction of neighbouring pixels in different bands and the fraction of pixels which escaped.
+// A flat or mostly interior image scores close to zero, and the most detailed images score around 5.
+pub fn get_interestingness(iterations: &[u32], image_width: usize, image_height: usize, maximum_iteration: usize) -> f64 {
+    let get_band = |iteration: u32| (4.0 * (iteration as f64 + 1.0).log2()) as usize;
+
+    let mut bands = vec![0usize; 160];
+    let mut escaped = 0;
+
+    for &iteration in iterations.iter().filter(|&&iteration| (iteration as usize) < maximum_iteration) {
+        bands[get_band(iteration).min(159)] += 1;
+        escaped += 1;
+    }
+
+    if escaped == 0 || iterations.len() < image_width * image_height {
+        return 0.0;
+    }
+
+    let entropy = bands.iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let probability = count as f64 / escaped as f64;
+            -probability * probability.log2()
+        })
+        .sum::<f64>();
+
+    let mut edges = 0;
+    let mut pairs = 0;
+
+    for j in 0..image_height {
+        for i in 0..image_width {
+            let k = j * image_width + i;
+            let band = get_band(iterations[k]);
+
+            if i + 1 < image_width {
+                pairs += 1;
+                edges += (band != get_band(iterations[k + 1])) as usize;
+            }
+
+            if j + 1 < image_height {
+                pairs += 1;
+                edges += (band != get_band(iterations[k + image_width])) as usize;
+            }
+        }
+    }
+
+    let edge_density = edges as f64 / pairs.max(1) as f64;
+
+    entropy * edge_density * escaped as f64 / iterations.len() as f64
+}
+
+// Checks if the output (with or without an extension added) already exists
+pub fn output_exists(filename: &str) -> bool {
+    Path::new(filename).exists() || ["png", "jpg", "jpeg", "exr"].iter().any(|extension| {
+        Path::new(&format!("{}.{}", filename, extension)).exists()
+    })
+}
+
+// Appends an increasing number to the filename until no existing output would be overwritten
+pub fn get_unused_filename(filename: &str) -> String {
+    if !output_exists(filename) {
+        return filename.to_owned();
+    }
+
+    let (name, extension) = match filename.split_terminator('.').last() {
+        Some(extension @ "jpg") | Some(extension @ "jpeg") | Some(extension @ "png") | Some(extension @ "exr") => {
+            (&filename[..(filename.len() - extension.len() - 1)], format!(".{}", extension))
+        }
+        _ => (filename, String::new())
+    };
+
+    let mut number = 1;
+
+    loop {
+        let candidate = format!("{}_{}{}", name, number, extension);
+
+        if !output_exists(&candidate) {
+            return candidate;
+        }
+
+        number += 1;
+    }
+}
+
+#[derive(Clone)]
+pub struct LightingParameters {
+    pub diffuse: [f32; 4],
+    pub specular: [f32; 4],
+    pub shininess: i32,
+    pub opacity: [f32; 2],
+    pub ambient: f32
+}
+
+impl LightingParameters {
+    pub fn new(direction: f32, azimuth: f32, opacity: f32, ambient: f32, diffuse: f32, specular: f32, shininess: i32) -> Self { 
+        let phi_half = FRAC_PI_4 + azimuth.to_radians() / 2.0;
+        let direction = direction.to_radians();
+        let azimuth = azimuth.to_radians();
+
+        LightingParameters {
+            diffuse: [direction.cos() * azimuth.cos(), direction.sin() * azimuth.cos(), azimuth.sin(), diffuse],
+            specular: [direction.cos() * phi_half.sin(), direction.sin() * phi_half.sin(), phi_half.cos(), specular],
+            shininess,
+            opacity: [opacity, (1.0 - opacity) / 2.0],
+            ambient
+        } 
+    }
+}
+
+// The channels which are saved in the raw export, at the rendered (supersampled) size
+#[derive(Clone)]
+pub struct RawChannels {
+    pub image_width: usize,
+    pub image_height: usize,
+    pub maximum_iteration: usize,
+    pub iterations: Vec<u32>,
+    pub smooth: Vec<f32>,
+    pub stripe: Vec<f32>,
+    pub distance_x: Vec<f32>,
+    pub distance_y: Vec<f32>,
+    pub glitched: Vec<bool>,
+}
+
+// A rendered frame in memory, the colour image is at the output size in RGB order
+#[derive(Clone)]
+pub struct RenderedImage {
+    pub image_width: usize,
+    pub image_height: usize,
+    pub rgb: Vec<u8>,
+    pub raw: Option<RawChannels>,
+}
+
+// A rectangle of the image which has been iterated, at the rendered (supersampled) size in row major order with the
+// colour in RGB order. The glitched pixels are sent again in a later region once they have been corrected.
+#[derive(Clone)]
+pub struct CompletedRegion {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+    pub rgb: Vec<u8>,
+    pub iterations: Vec<u32>,
+    pub smooth: Vec<f32>,
+    pub glitched: Vec<bool>,
+}
+
+#[derive(Clone)]
+pub struct DataExport {
+    pub image_width: usize,
+    pub image_height: usize,
+    pub buffer: Vec<u8>,
+    pub iterations: Vec<u32>,
+    pub smooth: Vec<f32>,
+    pub stripe: Vec<f32>,
+    pub distance_x: Vec<f32>,
+    pub distance_y: Vec<f32>,
+    pub glitched: Vec<bool>,
+    pub palette_buffer: Vec<Color>,
+    pub palette_interpolated_buffer: Vec<Color>,
+    pub palette_cyclic: bool,
+    pub display_glitches: bool,
+    pub glitch_colour: [u8; 3],
+    pub glitch_overlay: bool,
+    pub palette_iteration_span: f32,
+    pub palette_offset: f32,
+    pub distance_transition: f32,
+    pub centre_removed: bool,
+    pub data_type: DataType,
+    pub coloring_type: ColoringType,
+    pub maximum_iteration: usize,
+    pub fractal_type: FractalType,
+    pub export_type: ExportType,
+    pub lighting_parameters: LightingParameters,
+    pub lighting: bool,
+    pub distance_color: bool,
+    pub stripe_scale: f32,
+    pub heightmap_type: HeightmapType,
+    pub mesh_type: MeshType,
+    pub mesh_scale: f32,
+    pub mesh_decimation: usize,
+    pub additional_resolutions: Vec<(usize, usize)>,
+    pub correction_passes: Vec<u32>,
+    pub export_glitch_map: bool,
+    pub export_histogram: bool,
+    pub histogram_bins: usize,
+    pub export_reference_orbit: bool,
+    pub reference_orbit_overlay: bool,
+    pub reference_orbit_points: usize,
+    pub reference_orbit: Option<ReferenceOrbit>,
+    pub raw_channels: Vec<String>,
+    pub progressive: bool,
+    pub supersampling: usize,
+    pub reconstruction_filter: ReconstructionFilter,
+    pub coloring_plugin: Option<Arc<Mutex<ColoringPlugin>>>,
+}
+
+impl DataExport {
+    pub fn new(image_width: usize, 
+        image_height: usize, 
+        display_glitches: bool, 
+        palette_buffer: Vec<Color>, 
+        palette_interpolated_buffer: Vec<Color>, 
+        palette_cyclic: bool,
+        palette_iteration_span: f32, 
+        palette_offset: f32, 
+        distance_transition: f32, 
+        stripe_scale: f32,
+        distance_color: bool,
+        lighting: bool,
+        coloring_type: ColoringType,
+        data_type: DataType,
+        fractal_type: FractalType, 
+        export_type: ExportType) -> Self {
+
+        // TODO maybe make the distance estimate arrays empty until required
+        DataExport {
+            image_width,
+            image_height,
+            buffer: vec![0u8; image_width * image_height * 3],
+            iterations: vec![0u32; image_width * image_height],
+            smooth: vec![0.0f32; image_width * image_height],
+            stripe: vec![0.0f32; image_width * image_height],
+            distance_x: vec![0.0f32; image_width * image_height],
+            distance_y: vec![0.0f32; image_width * image_height],
+            glitched: vec![false; image_width * image_height],
+            palette_buffer,
+            palette_interpolated_buffer,
+            palette_cyclic,
+            display_glitches,
+            glitch_colour: [255, 0, 0],
+            glitch_overlay: false,
+            palette_iteration_span,
+            palette_offset,
+            distance_transition,
+            centre_removed: false,
+            data_type,
+            coloring_type,
+            maximum_iteration: 0,
+            fractal_type,
+            export_type,
+            lighting_parameters: LightingParameters::new(0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0),
+            lighting,
+            distance_color,
+            stripe_scale,
+            heightmap_type: HeightmapType::None,
+            mesh_type: MeshType::None,
+            mesh_scale: 0.1,
+            mesh_decimation: 1,
+            additional_resolutions: Vec::new(),
+            correction_passes: vec![0u32; image_width * image_height],
+            export_glitch_map: false,
+            export_histogram: false,
+            histogram_bins: 256,
+            export_reference_orbit: false,
+            reference_orbit_overlay: false,
+            reference_orbit_points: 4096,
+            reference_orbit: None,
+            raw_channels: vec![String::from("iterations"), String::from("smooth"), String::from("distance")],
+            progressive: false,
+            supersampling: 1,
+            reconstruction_filter: ReconstructionFilter::Lanczos,
+            coloring_plugin: None,
+        }
+    }
+
+    #[inline]
+    pub fn export_pixels(&mut self, pixel_data: &[PixelData], reference: &Reference, delta_pixel: FloatExtended, scale: usize) {
+        for pixel in pixel_data {
+            let new_scale = if self.export_type == ExportType::Gui || self.progressive {
+                scale
+            } else {
+                1
+            };
+
+            self.glitched[pixel.index] = pixel.glitched;
+
+            if pixel.glitched {
+                if self.display_glitches || self.glitch_overlay {
+                    self.set_with_scale(pixel.index, self.glitch_colour, new_scale);
+                };
+                
+                continue;
+            }
+
+            self.iterations[pixel.index] = pixel.iteration as u32;
+
+            if pixel.iteration >= self.maximum_iteration {
+                self.set_with_scale(pixel.index, self.get_interior_colour(pixel.index), new_scale);
+                continue;
+            }
+
+            // self.smooth[pixel.index] = match self.fractal_type {
+            //     FractalType::Mandelbrot2 => {
+            //         ESCAPE_RADIUS_LN_LOG2_P1 - (z_norm.ln() as f32).log2()
+            //     }
+            //     FractalType::Mandelbrot3 => {
+            //         ESCAPE_RADIUS_LN_LOG3_P1 - (z_norm.ln() as f32).log(3.0)
+            //     }
+            // };
+
+            self.smooth[pixel.index] = ESCAPE_RADIUS_LN_LOG2_P1 - (pixel.z_norm.ln() as f32).log2();
+
+            if self.data_type == DataType::Stripe || self.data_type == DataType::DistanceStripe {
+                let temp = pixel.stripe_storage.iter().map(|z| {0.5 * (z.arg() as f32 * self.stripe_scale).sin() + 0.5}).collect::<Vec<f32>>();
+
+                self.stripe[pixel.index] = (
+                    temp[(pixel.stripe_iteration + 2) % 4] 
+                    + temp[(pixel.stripe_iteration + 3) % 4] 
+                    + temp[pixel.stripe_iteration] * self.smooth[pixel.index] 
+                    + temp[(pixel.stripe_iteration + 1) % 4] * (1.0 - self.smooth[pixel.index])) / 3.0;
+            }
+
+            if self.data_type == DataType::Distance || self.data_type == DataType::DistanceStripe {
+                // This calculates the distance in terms of pixels
+                let temp1 = reference.reference_data_extended[pixel.iteration - reference.start_iteration] + pixel.delta_current;
+                let temp2 = temp1.norm();
+
+                let temp3 = 2.0f64.powi(temp1.exponent - temp2.exponent) / temp2.mantissa;
+
+                let norm_z_x = temp1.mantissa.re * temp3;
+                let norm_z_y = temp1.mantissa.im * temp3;
+
+                let jxa = FloatExtended::new(pixel.derivative_current.mantissa.re, pixel.derivative_current.exponent);
+                let jya = FloatExtended::new(pixel.derivative_current.mantissa.im, pixel.derivative_current.exponent);
+
+                let scaled_jxa = (jxa * delta_pixel).to_float();
+                let scaled_jya = (jya * delta_pixel).to_float();
+
+                let num = (temp2 * (temp2.mantissa.ln() + temp2.exponent as f64 * LN_2)).to_float();
+
+                let den_1 = norm_z_x * scaled_jxa + norm_z_y * scaled_jya;
+                let den_2 = norm_z_x * -1.0 * scaled_jya + norm_z_y * scaled_jxa;
+
+                let output = num / ComplexFixed::new(den_1, den_2);
+
+                self.distance_x[pixel.index] = output.re as f32;
+                self.distance_y[pixel.index] = output.im as f32;
+            };
+
+            self.colour_index(pixel.index, new_scale)
+        }
+    }
+
+    // The metadata is a list of key-value pairs which describe the render parameters
+    pub fn save(&mut self, filename: &str, approximation_order: usize, zoom: &str, metadata: &[(String, String)]) {
+        match self.export_type {
+            ExportType::Color => {
+                self.save_colour(filename, metadata);
+            },
+            ExportType::Raw => {
+                self.save_raw(filename, approximation_order, zoom, metadata);
+            },
+            ExportType::Both => {
+                self.save_colour(filename, metadata);
+                self.save_raw(filename, approximation_order, zoom, metadata);
+            }
+            _ => {},
+        }
+
+        if self.heightmap_type != HeightmapType::None && self.export_type != ExportType::Gui {
+            self.save_heightmap(filename);
+        }
+
+        if self.mesh_type != MeshType::None && self.export_type != ExportType::Gui {
+            self.save_mesh(filename);
+        }
+
+        if self.export_glitch_map && self.export_type != ExportType::Gui {
+            self.save_glitch_map(filename);
+        }
+
+        if self.export_histogram && self.export_type != ExportType::Gui {
+            self.get_iteration_histogram().save(filename);
+        }
+
+        if let (true, Some(reference_orbit)) = (self.export_reference_orbit && self.export_type != ExportType::Gui, &self.reference_orbit) {
+            reference_orbit.save(filename);
+        }
+    }
+
+    // The main image files which are written for a frame with the current export type
+    pub fn get_output_files(&self, filename: &str) -> Vec<String> {
+        let mut files = Vec::new();
+
+        if self.export_type == ExportType::Color || self.export_type == ExportType::Both {
+            match filename.split_terminator('.').last() {
+                Some("jpg") | Some("jpeg") | Some("png") => files.push(filename.to_owned()),
+                _ => files.push(filename.to_owned() + ".png")
+            }
+        }
+
+        if self.export_type == ExportType::Raw || self.export_type == ExportType::Both {
+            files.push(filename.to_owned() + ".exr");
+        }
+
+        files
+    }
+
+    // Saves the current colour buffer of a progressive render stage, where each pixel covers a block of the given size
+    pub fn save_preview(&self, filename: &str, scale: usize) {
+        DataExport::write_colour_buffer(&format!("{}_preview_{}.png", filename, scale), &self.buffer, self.image_width, self.image_height, &[]);
+    }
+
+    // The same images that are saved, for front-ends which use the results directly
+    pub fn get_image_buffer(&self, include_raw: bool) -> RenderedImage {
+        let (rgb, image_width, image_height) = self.get_output_buffer();
+
+        let raw = if include_raw {
+            Some(RawChannels {
+                image_width: self.image_width,
+                image_height: self.image_height,
+                maximum_iteration: self.maximum_iteration,
+                iterations: self.iterations.clone(),
+                smooth: self.smooth.clone(),
+                stripe: self.stripe.clone(),
+                distance_x: self.distance_x.clone(),
+                distance_y: self.distance_y.clone(),
+                glitched: self.glitched.clone(),
+            })
+        } else {
+            None
+        };
+
+        RenderedImage {
+            image_width,
+            image_height,
+            rgb,
+            raw,
+        }
+    }
+
+    pub fn get_region(&self, x: usize, y: usize, width: usize, height: usize) -> CompletedRegion {
+        let mut region = CompletedRegion {
+            x,
+            y,
+            width,
+            height,
+            rgb: Vec::with_capacity(3 * width * height),
+            iterations: Vec::with_capacity(width * height),
+            smooth: Vec::with_capacity(width * height),
+            glitched: Vec::with_capacity(width * height),
+        };
+
+        for j in y..(y + height) {
+            let start = j * self.image_width + x;
+            let end = start + width;
+
+            region.rgb.extend_from_slice(&self.buffer[(3 * start)..(3 * end)]);
+            region.iterations.extend_from_slice(&self.iterations[start..end]);
+            region.smooth.extend_from_slice(&self.smooth[start..end]);
+            region.glitched.extend_from_slice(&self.glitched[start..end]);
+        }
+
+        region
+    }
+
+    // The supersampled image is downsampled to the output size before it is saved
+    pub fn get_output_buffer(&self) -> (Vec<u8>, usize, usize) {
+        if self.supersampling > 1 {
+            (downsample_buffer(&self.buffer, self.image_width, self.image_height, self.supersampling, self.reconstruction_filter), 
+                self.image_width / self.supersampling, 
+                self.image_height / self.supersampling)
+        } else {
+            (self.buffer.clone(), self.image_width, self.image_height)
+        }
+    }
+
+    pub fn save_colour(&mut self, filename: &str, metadata: &[(String, String)]) {
+        // Extension is specified
+        let (name, extension) = match filename.split_terminator('.').last() {
+            Some(extension @ "jpg") | Some(extension @ "jpeg") | Some(extension @ "png") => {
+                (&filename[..(filename.len() - extension.len() - 1)], extension)
+            }
+            _ => (filename, "png")
+        };
+
+        let (mut buffer, image_width, image_height) = self.get_output_buffer();
+
+        if let (true, Some(reference_orbit)) = (self.reference_orbit_overlay, &self.reference_orbit) {
+            reference_orbit.draw_overlay(&mut buffer, image_width, image_height);
+        }
+
+        DataExport::write_colour_buffer(&format!("{}.{}", name, extension), &buffer, image_width, image_height, metadata);
+
+        // The additional resolutions are downscaled from the full resolution image with a lanczos filter
+        if !self.additional_resolutions.is_empty() {
+            let image = image::RgbImage::from_raw(image_width as u32, image_height as u32, buffer).unwrap();
+
+            for &(width, height) in &self.additional_resolutions {
+                let resized = image::imageops::resize(&image, width as u32, height as u32, image::imageops::FilterType::Lanczos3);
+
+                DataExport::write_colour_buffer(&format!("{}_{}x{}.{}", name, width, height, extension), &resized.into_raw(), width, height, metadata);
+            }
+        }
+    }
+
+    pub fn write_colour_buffer(filename: &str, buffer: &[u8], image_width: usize, image_height: usize, metadata: &[(String, String)]) {
+        if filename.ends_with(".png") {
+            // The image crate does not support writing text chunks, so the png is encoded directly
+            let writer = BufWriter::new(File::create(filename).unwrap());
+
+            let mut encoder = png::Encoder::new(writer, image_width as u32, image_height as u32);
+            encoder.set_color(png::ColorType::Rgb);
+            encoder.set_depth(png::BitDepth::Eight);
+
+            for (key, value) in metadata {
+                encoder.add_text_chunk(key.clone(), value.clone()).unwrap();
+            }
+
+            let mut writer = encoder.write_header().unwrap();
+            writer.write_image_data(buffer).unwrap();
+        } else {
+            image::save_buffer(
+                filename.to_owned(), 
+                buffer, 
+                image_width as u32, 
+                image_height as u32, 
+                image::ColorType::Rgb8).unwrap();
+        }
+    }
+
+    // Returns the height of each pixel normalised over the frame to the range [0, 1]
+    // Pixels inside the set are placed at the highest point
+    pub fn get_heightfield(&self, heightmap_type: HeightmapType) -> Vec<f32> {
+        let use_distance = heightmap_type == HeightmapType::Distance 
+            && (self.data_type == DataType::Distance || self.data_type == DataType::DistanceStripe);
+
+        let mut heights = (0..self.iterations.len()).map(|k| {
+            if self.iterations[k] >= self.maximum_iteration as u32 {
+                f32::NAN
+            } else if use_distance {
+                -(self.distance_x[k].powi(2) + self.distance_y[k].powi(2)).sqrt().ln()
+            } else {
+                self.iterations[k] as f32 + self.smooth[k]
+            }
+        }).collect::<Vec<f32>>();
+
+        let (minimum, maximum) = heights.iter()
+            .filter(|value| value.is_finite())
+            .fold((f32::MAX, f32::MIN), |(minimum, maximum), &value| (minimum.min(value), maximum.max(value)));
+
+        let range = if maximum > minimum {
+            maximum - minimum
+        } else {
+            1.0
+        };
+
+        for value in heights.iter_mut() {
+            *value = if value.is_finite() {
+                (*value - minimum) / range
+            } else {
+                1.0
+            };
+        }
+
+        heights
+    }
+
+    pub fn save_heightmap(&self, filename: &str) {
+        let heights = self.get_heightfield(self.heightmap_type);
+
+        let mut data = Vec::with_capacity(2 * heights.len());
+
+        // PNG stores 16 bit samples in big endian order
+        for value in heights {
+            data.extend_from_slice(&((value * 65535.0).round() as u16).to_be_bytes());
+        }
+
+        let writer = BufWriter::new(File::create(filename.to_owned() + "_heightmap.png").unwrap());
+
+        let mut encoder = png::Encoder::new(writer, self.image_width as u32, self.image_height as u32);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::Sixteen);
+
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(&data).unwrap();
+    }
+
+    // The mesh uses the iteration heightfield unless a distance heightmap has been selected
+    pub fn save_mesh(&self, filename: &str) {
+        let heights = self.get_heightfield(self.heightmap_type);
+
+        HeightfieldMesh::new(&heights, self.image_width, self.image_height, self.mesh_scale, self.mesh_decimation)
+            .save(filename, self.mesh_type);
+    }
+
+    // Pixels which were never glitched are black, otherwise brightness increases with the number of correction passes
+    // Pixels which are still glitched after correction are shown in red
+    pub fn save_glitch_map(&self, filename: &str) {
+        let maximum_passes = *self.correction_passes.iter().max().unwrap_or(&0);
+
+        let mut buffer = vec![0u8; self.image_width * self.image_height * 3];
+
+        for (k, &passes) in self.correction_passes.iter().enumerate() {
+            if self.glitched[k] {
+                buffer[3 * k] = 255;
+            } else if passes > 0 {
+                let value = (64.0 + 191.0 * passes as f32 / maximum_passes as f32) as u8;
+
+                buffer[3 * k] = value;
+                buffer[3 * k + 1] = value;
+                buffer[3 * k + 2] = value;
+            }
+        }
+
+        DataExport::write_colour_buffer(&(filename.to_owned() + "_glitches.png"), &buffer, self.image_width, self.image_height, &[]);
+    }
+
+    pub fn save_raw(&mut self, filename: &str, approximation_order: usize, zoom: &str, metadata: &[(String, String)]) {
+        let names = self.get_raw_channel_names();
+
+        if names.is_empty() {
+            return;
+        }
+
+        let channels = names.into_iter()
+            .map(|name| simple_image::Channel::non_color_data(simple_image::Text::from(name).unwrap(), self.get_raw_samples(name)))
+            .collect();
+
+        let mut layer = simple_image::Layer::new(simple_image::Text::from("fractal_data").unwrap(), (self.image_width, self.image_height), channels)
+            .with_compression(simple_image::Compression::PXR24)
+            .with_block_format(None, simple_image::attribute::LineOrder::Increasing);   
+
+        let mut attributes = HashMap::new();
+        attributes.insert(simple_image::Text::from("IterationsBias").unwrap(), exr::meta::attribute::AttributeValue::I32(0));
+        attributes.insert(simple_image::Text::from("Iterations").unwrap(), exr::meta::attribute::AttributeValue::I32(self.maximum_iteration as i32));
+        attributes.insert(simple_image::Text::from("Zoom").unwrap(), exr::meta::attribute::AttributeValue::Text(simple_image::Text::from(zoom).unwrap()));
+        attributes.insert(simple_image::Text::from("approximation_order").unwrap(), exr::meta::attribute::AttributeValue::I32(approximation_order as i32));
+
+        // The escape statistics, so that a collection of raw exports can be compared without reading every pixel
+        let histogram = self.get_iteration_histogram();
+        attributes.insert(simple_image::Text::from("EscapedFraction").unwrap(), exr::meta::attribute::AttributeValue::F32(histogram.get_escaped_fraction() as f32));
+        attributes.insert(simple_image::Text::from("MinimumIteration").unwrap(), exr::meta::attribute::AttributeValue::I32(histogram.minimum_iteration as i32));
+        attributes.insert(simple_image::Text::from("MeanIteration").unwrap(), exr::meta::attribute::AttributeValue::F32(histogram.mean_iteration as f32));
+        attributes.insert(simple_image::Text::from("MaximumEscapedIteration").unwrap(), exr::meta::attribute::AttributeValue::I32(histogram.maximum_iteration as i32));
+
+        for (key, value) in metadata {
+            attributes.insert(simple_image::Text::from(key.as_str()).unwrap(), exr::meta::attribute::AttributeValue::Text(simple_image::Text::from(value.as_str()).unwrap()));
+        }
+
+        layer.attributes = exr::meta::header::LayerAttributes::new(simple_image::Text::from("fractal_data").unwrap());
+        layer.attributes.custom = attributes;
+
+        let image = simple_image::Image::new_from_single_layer(layer);
+
+        image.write_to_file(filename.to_owned() + ".exr", simple_image::write_options::high()).unwrap();
+    }
+
+    // The names in the EXR of the channels of the raw export which are selected and have data for the colouring. The
+    // distance and stripe channels are only calculated for the colourings which use them.
+    pub fn get_raw_channel_names(&self) -> Vec<&'static str> {
+        let selected = |channel: &str| self.raw_channels.iter().any(|name| name.eq_ignore_ascii_case(channel));
+
+        let mut names = Vec::new();
+
+        if selected("iterations") {
+            names.push("N");
+        }
+
+        if selected("smooth") {
+            names.push("NF");
+        }
+
+        if selected("distance") && (self.data_type == DataType::Distance || self.data_type == DataType::DistanceStripe) {
+            names.extend_from_slice(&["DEX", "DEY"]);
+        }
+
+        if selected("stripe") && (self.data_type == DataType::Stripe || self.data_type == DataType::DistanceStripe) {
+            names.push("STRIPE");
+        }
+
+        if selected("glitch") || self.export_glitch_map {
+            names.push("GLITCH");
+        }
+
+        names
+    }
+
+    fn get_raw_samples(&self, name: &str) -> simple_image::Samples {
+        match name {
+            "N" => simple_image::Samples::U32(self.iterations.clone()),
+            "NF" => simple_image::Samples::F32(self.smooth.clone()),
+            "DEX" => simple_image::Samples::F32(self.distance_x.clone()),
+            "DEY" => simple_image::Samples::F32(self.distance_y.clone()),
+            "STRIPE" => simple_image::Samples::F32(self.stripe.clone()),
+            "GLITCH" => simple_image::Samples::U32(self.correction_passes.clone()),
+            _ => panic!("{} is not a raw channel", name)
+        }
+    }
+
+    // Each of the selected channels has four bytes per pixel. The EXR is written with PXR24 compression, which rounds
+    // the float channels to 24 bits before compressing them, so the file is smaller than this estimate.
+    pub fn get_raw_bytes_per_pixel(&self) -> f64 {
+        4.0 * self.get_raw_channel_names().len() as f64
+    }
+
+    // Restores the channels of a raw export of the same frame, returning the maximum iteration it was rendered with.
+    // Nothing is changed if the dimensions or the zoom of the export do not match.
+    pub fn load_raw(&mut self, filename: &str, zoom: &str) -> Option<usize> {
+        let raw_data = simple_image::Image::read_from_file(filename, simple_image::read_options::high()).ok()?;
+        let layer = raw_data.layers.first()?;
+
+        let stored_zoom = match layer.attributes.custom.get(&simple_image::Text::from("Zoom")?)? {
+            exr::meta::attribute::AttributeValue::Text(text) => text.to_string(),
+            _ => return None
+        };
+
+        let maximum_iteration = match layer.attributes.custom.get(&simple_image::Text::from("Iterations")?)? {
+            exr::meta::attribute::AttributeValue::I32(iterations) => *iterations as usize,
+            _ => return None
+        };
+
+        let dimensions = raw_data.attributes.display_window.size;
+
+        if stored_zoom != zoom || dimensions.x() != self.image_width || dimensions.y() != self.image_height {
+            return None;
+        }
+
+        self.clear_buffers();
+
+        for channel in &layer.channels {
+            match (channel.name.to_string().as_str(), &channel.samples) {
+                ("N", simple_image::Samples::U32(values)) => self.iterations = values.clone(),
+                ("NF", simple_image::Samples::F32(values)) => self.smooth = values.clone(),
+                ("NF", simple_image::Samples::F16(values)) => self.smooth = values.iter().map(|value| value.to_f32()).collect(),
+                ("DEX", simple_image::Samples::F32(values)) => self.distance_x = values.clone(),
+                ("DEY", simple_image::Samples::F32(values)) => self.distance_y = values.clone(),
+                ("GLITCH", simple_image::Samples::U32(values)) => self.correction_passes = values.clone(),
+                _ => {}
+            }
+        }
+
+        Some(maximum_iteration)
+    }
+
+    // The highest iteration of any pixel which escaped in the current frame
+    pub fn get_maximum_escaped_iteration(&self) -> usize {
+        self.iterations.iter()
+            .filter(|&&iteration| iteration < self.maximum_iteration as u32)
+            .max()
+            .map(|&iteration| iteration as usize)
+            .unwrap_or(0)
+    }
+
+    pub fn get_interestingness(&self) -> f64 {
+        get_interestingness(&self.iterations, self.image_width, self.image_height, self.maximum_iteration)
+    }
+
+    pub fn get_iteration_histogram(&self) -> IterationHistogram {
+        IterationHistogram::new(&self.iterations, self.maximum_iteration, self.histogram_bins)
+    }
+
+    pub fn clear_buffers(&mut self) {
+        self.buffer = vec![0u8; self.image_width * self.image_height * 3];
+        self.iterations = vec![0xFFFFFFFF; self.image_width * self.image_height];
+        self.smooth = vec![0.0f32; self.image_width * self.image_height];
+        self.stripe = vec![0.0f32; self.image_width * self.image_height];
+        self.distance_x = vec![0.0f32; self.image_width * self.image_height];
+        self.distance_y = vec![0.0f32; self.image_width * self.image_height];
+        self.glitched = vec![false; self.image_width * self.image_height];
+        self.correction_passes = vec![0u32; self.image_width * self.image_height];
+    }
+
+    pub fn regenerate(&mut self) {
+        for i in 0..self.iterations.len() {
+            if self.glitched[i] && (self.display_glitches || self.glitch_overlay) {
+                self.set_with_scale(i, self.glitch_colour, 1);
+                continue;
+            }
+
+            if self.iterations[i] >= self.maximum_iteration as u32 {
+                self.set_with_scale(i, self.get_interior_colour(i), 1);
+                continue;
+            }
+
+            self.colour_index(i, 1);
+        }
+    }
+
+    // pub fn interpolate_glitches(&mut self, pixel_data: &[PixelData]) {
+    //     if !self.display_glitches {
+    //         for pixel in pixel_data {
+    //             let k = pixel.image_y * self.image_width + pixel.image_x;
+
+    //             let k_up = (max(1, pixel.image_y) - 1) * self.image_width + pixel.image_x;
+    //             let k_down = (min(self.image_height - 2, pixel.image_y) + 1) * self.image_width + pixel.image_x;
+
+    //             let k_left = pixel.image_y * self.image_width + max(1, pixel.image_x) - 1;
+    //             let k_right = pixel.image_y * self.image_width + min(self.image_width - 2, pixel.image_x) + 1;
+
+    //             self.iterations[k] = (self.iterations[k_up] + self.iterations[k_down] + self.iterations[k_left] + self.iterations[k_right]) / 4;
+    //             self.smooth[k] = (self.smooth[k_up] + self.smooth[k_down] + self.smooth[k_left] + self.smooth[k_right]) / 4.0;
+
+    //             if self.data_type == DataType::Distance {
+    //                 self.distance_x[k] = (self.distance_x[k_up] + self.distance_x[k_down] + self.distance_x[k_left] + self.distance_x[k_right]) / 4.0;
+    //                 self.distance_y[k] = (self.distance_y[k_up] + self.distance_y[k_down] + self.distance_y[k_left] + self.distance_y[k_right]) / 4.0;
+    //             }
+
+    //             self.colour_index(k, 1);
+    //         }
+    //     }
+    // }
+
+    #[inline]
+    pub fn calculate_blinn_phong(&self, k: usize) -> f32 {
+        if self.lighting {
+            // Blinn-phong from GPU mandelbrot
+            let mut normal = ComplexFixed::new(self.distance_x[k], self.distance_y[k]);
+            normal /= normal.norm();
+            
+            // This is diffuse lighting
+            let light_diffuse = (normal.re * self.lighting_parameters.diffuse[0] + normal.im * self.lighting_parameters.diffuse[1] + self.lighting_parameters.diffuse[2]) / (1.0 + self.lighting_parameters.diffuse[2]);
+
+            // This is specular lighting
+            let light_specular = ((normal.re * self.lighting_parameters.specular[0] + normal.im * self.lighting_parameters.specular[1] + self.lighting_parameters.specular[2]) / (1.0 + self.lighting_parameters.specular[2])).powi(self.lighting_parameters.shininess);
+
+            // Ambient + diffuse + specular
+            let bright = self.lighting_parameters.ambient + self.lighting_parameters.diffuse[3] * light_diffuse + self.lighting_parameters.specular[3] * light_specular;
+
+            // Add intensity
+            bright * self.lighting_parameters.opacity[0] - self.lighting_parameters.opacity[1]
+        } else {
+            0.5
+        }
+    }
+
+    #[inline]
+    pub fn calculate_color(&self, value: f32) -> Color {
+        let pos1 = value.floor() as usize;
+        let pos2 = (pos1 + 1) % self.palette_interpolated_buffer.len();
+
+        self.palette_interpolated_buffer[pos1].interpolate_rgb(&self.palette_interpolated_buffer[pos2], value.fract() as f64)
+    }
+
+    #[inline]
+    pub fn calculate_iteration_palette_value(&self, k: usize) -> Color {
+        let mut floating_iteration = self.iterations[k] as f32 / self.palette_iteration_span;
+               
+        // TODO add as another option
+        if self.coloring_type != ColoringType::StepIteration {
+            floating_iteration += self.smooth[k] / self.palette_iteration_span
+        };
+        
+        self.calculate_color(self.palette_interpolated_buffer.len() as f32 * (floating_iteration + self.palette_offset).fract())
+    }
+
+    #[inline]
+    pub fn calculate_distance_palette_value(&self, distance: f32) -> Color {
+        self.calculate_color(self.palette_interpolated_buffer.len() as f32 * (distance + self.palette_offset).fract())
+    }
+
+    #[inline]
+    pub fn calculate_scaled_distance(&self, k: usize) -> f32 {
+        // Calculate distance estimate in terms of pixels
+        let length_pixel = (self.distance_x[k].powi(2) + self.distance_y[k].powi(2)).sqrt();
+
+        // Scale so transition will happen about 50 pixels
+        (length_pixel / self.distance_transition).max(0.0)
+    }
+
+    #[inline]
+    pub fn gamma_blend(color: Color, bright: f64) -> Color {
+        let mut rgb = [color.red(), color.green(), color.blue()];
+
+        if bright < 0.5 {
+            for part in rgb.iter_mut() {
+                *part *= 2.0 * bright
+            }
+        } else {
+            for part in rgb.iter_mut() {
+                *part = 1.0 - 2.0 * (1.0 - *part) * (1.0 - bright)
+            }
+        }
+
+        Color::from_rgb(rgb[0], rgb[1], rgb[2])
+    }
+
+    // The glitch overlay replaces the colouring with a neutral grey, so that only the glitches stand out. The pixels
+    // which were glitched and then corrected are tinted with the glitch colour, and the interior is a darker grey.
+    pub fn get_glitch_overlay_colour(&self, k: usize) -> [u8; 3] {
+        let background = if self.iterations[k] >= self.maximum_iteration as u32 { 80 } else { 128 };
+
+        if self.correction_passes[k] > 0 {
+            let [red, green, blue] = self.glitch_colour;
+            let tint = |component: u8| ((component as u16 + background) / 2) as u8;
+
+            [tint(red), tint(green), tint(blue)]
+        } else {
+            [background as u8; 3]
+        }
+    }
+
+    #[inline]
+    pub fn get_interior_colour(&self, k: usize) -> [u8; 3] {
+        if self.glitch_overlay {
+            self.get_glitch_overlay_colour(k)
+        } else {
+            [0, 0, 0]
+        }
+    }
+
+    #[inline]
+    pub fn colour_index(&mut self, k: usize, scale: usize) {
+        if self.glitch_overlay {
+            self.set_with_scale(k, self.get_glitch_overlay_colour(k), scale);
+            return;
+        }
+
+        if let Some(plugin) = &self.coloring_plugin {
+            let value = plugin.lock().colour(self.iterations[k], self.smooth[k], self.stripe[k], self.distance_x[k], self.distance_y[k], self.maximum_iteration, self.glitch_colour);
+
+            self.set_with_scale(k, value, scale);
+            return;
+        }
+
+        let color = match self.coloring_type {
+            ColoringType::Distance => {
+                let bright = self.calculate_blinn_phong(k) as f64;
+
+                let distance = self.calculate_scaled_distance(k);
+
+                // TODO, it could be possible to have some kind of continous distance estimate - which is based on the zoom level as well; so that keyframes can be added together
+                let color = if self.distance_color {
+                    self.calculate_distance_palette_value(distance)
+                } else {
+                    self.calculate_iteration_palette_value(k)
+                };
+
+                // Apply sigmoid function for non-linear transform
+                // let value = if length_scaled < 1.0 {
+                //     1.0 - 1.0 / (1.0 + (-10.0 * (length_scaled - 0.5)).exp())
+                // } else {
+                //     0.0
+                // };
+                
+                DataExport::gamma_blend(color, bright)
+            },
+            ColoringType::SmoothIteration | ColoringType::StepIteration => {
+                self.calculate_iteration_palette_value(k)
+            },
+            ColoringType::Stripe => {
+                let color = self.calculate_iteration_palette_value(k);
+                let bright = self.stripe[k] as f64;
+
+                DataExport::gamma_blend(color, bright)
+            },
+            ColoringType::DistanceStripe => {
+                let bright = self.calculate_blinn_phong(k);
+
+                let distance = self.calculate_scaled_distance(k);
+
+                let color = if self.distance_color {
+                    self.calculate_distance_palette_value(distance)
+                } else {
+                    self.calculate_iteration_palette_value(k)
+                };
+
+                // Apply sigmoid function for non-linear transform
+                let value = if distance < 1.0 {
+                    1.0 - 1.0 / (1.0 + (-10.0 * (distance - 0.5)).exp())
+                } else {
+                    0.0
+                };
+
+                let temp = if self.stripe[k] < 0.5 {
+                    2.0 * self.stripe[k] * bright
+                } else {
+                    1.0 - 2.0 * (1.0 - bright) * (1.0 - self.stripe[k])
+                };
+
+                let bright = (temp * (1.0 - value) + bright * value) as f64;
+
+                DataExport::gamma_blend(color, bright)
+            }
+        };
+
+        let (r, g, b, _) = color.rgba_u8();
+
+        self.set_with_scale(k, [r, g, b], scale)
+    }
+
+    #[inline]
+    pub fn change_palette(&mut self, palette: Option<Vec<(u8, u8, u8)>>, palette_iteration_span: f32, palette_offset: f32, distance_transition: f32, distance_color: bool, cyclic: bool, lighting: bool) {
+        let mut new_palette = false;
+        
+        if let Some(palette) = palette {
+            self.palette_buffer = palette.iter().map(|value| {
+                Color::from_rgb_u8(value.0, value.1, value.2)
+            }).collect::<Vec<Color>>();
+
+            if self.palette_buffer[0] != *self.palette_buffer.last().unwrap() {
+                self.palette_buffer.push(self.palette_buffer[0].clone());
+            };
+
+            new_palette = true;
+        };
+
+        if new_palette || (cyclic != self.palette_cyclic) {
+            let mut number_colors = self.palette_buffer.len();
+
+            if !cyclic {
+                number_colors -= 1;
+            }
+
+            let palette_generator = CustomGradient::new()
+                .colors(&self.palette_buffer[0..number_colors])
+                .interpolation(Interpolation::CatmullRom)
+                .mode(BlendMode::Oklab)
+                .build().unwrap();
+
+            self.palette_interpolated_buffer = palette_generator.colors(number_colors * 64);
+        };
+
+        self.palette_iteration_span = palette_iteration_span;
+        self.palette_offset = palette_offset;
+        self.palette_cyclic = cyclic;
+        self.lighting = lighting;
+        self.distance_transition = distance_transition;
+        self.distance_color = distance_color;
+    }
+
+    #[inline]
+    pub fn change_lighting(&mut self, direction: f32, azimuth: f32, opacity: f32, ambient: f32, diffuse: f32, specular: f32, shininess: i32) {
+        self.lighting_parameters = LightingParameters::new(direction, azimuth, opacity, ambient, diffuse, specular, shininess);
+    }
+
+    #[inline]
+    pub fn set_with_scale(&mut self, index: usize, value: [u8; 3], scale: usize) {
+        if scale > 1 {
+            let image_x = index % self.image_width;
+            let image_y = index / self.image_width;
+
+            let horizontal = if image_x + scale < self.image_width {
+                scale
+            } else {
+                self.image_width - image_x
+            };
+
+            let vertical = if image_y + scale < self.image_height {
+                scale
+            } else {
+                self.image_height - image_y
+            };
+
+            if self.data_type == DataType::DistanceStripe {
+                for i in image_x..(image_x + horizontal) {
+                    for j in image_y..(image_y + vertical) {
+                        let scale_index = j * self.image_width + i;
+
+                        self.iterations[scale_index] = self.iterations[index];
+                        self.smooth[scale_index] = self.smooth[index];
+                        self.glitched[scale_index] = self.glitched[index];
+                        self.distance_x[scale_index] = self.distance_x[index];
+                        self.distance_y[scale_index] = self.distance_y[index];
+                        self.stripe[scale_index] = self.stripe[index];
+    
+                        self.buffer[3 * (scale_index)] = value[0];
+                        self.buffer[3 * (scale_index) + 1] = value[1];
+                        self.buffer[3 * (scale_index) + 2] = value[2];
+                    }
+                }
+            } else if self.data_type == DataType::Distance {
+                for i in image_x..(image_x + horizontal) {
+                    for j in image_y..(image_y + vertical) {
+                        let scale_index = j * self.image_width + i;
+
+                        self.iterations[scale_index] = self.iterations[index];
+                        self.smooth[scale_index] = self.smooth[index];
+                        self.glitched[scale_index] = self.glitched[index];
+                        self.distance_x[scale_index] = self.distance_x[index];
+                        self.distance_y[scale_index] = self.distance_y[index];
+    
+                        self.buffer[3 * (scale_index)] = value[0];
+                        self.buffer[3 * (scale_index) + 1] = value[1];
+                        self.buffer[3 * (scale_index) + 2] = value[2];
+                    }
+                }
+            } else if self.data_type == DataType::Stripe {
+                for i in image_x..(image_x + horizontal) {
+                    for j in image_y..(image_y + vertical) {
+                        let scale_index = j * self.image_width + i;
+
+                        self.iterations[scale_index] = self.iterations[index];
+                        self.smooth[scale_index] = self.smooth[index];
+                        self.glitched[scale_index] = self.glitched[index];
+                        self.stripe[scale_index] = self.stripe[index];
+    
+                        self.buffer[3 * (scale_index)] = value[0];
+                        self.buffer[3 * (scale_index) + 1] = value[1];
+                        self.buffer[3 * (scale_index) + 2] = value[2];
+                    }
+                }
+            } else {
+                for i in image_x..(image_x + horizontal) {
+                    for j in image_y..(image_y + vertical) {
+                        let scale_index = j * self.image_width + i;
+
+                        self.iterations[scale_index] = self.iterations[index];
+                        self.smooth[scale_index] = self.smooth[index];
+                        self.glitched[scale_index] = self.glitched[index];
+    
+                        self.buffer[3 * (scale_index)] = value[0];
+                        self.buffer[3 * (scale_index) + 1] = value[1];
+                        self.buffer[3 * (scale_index) + 2] = value[2];
+                    }
+                }
+            }
+        } else {
+            self.buffer[3 * index] = value[0];
+            self.buffer[3 * index + 1] = value[1];
+            self.buffer[3 * index + 2] = value[2];
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,284 @@
+use crate::renderer::FractalRenderer;
+use crate::util::ReferenceCache;
+
+use config::{Config, File, FileFormat};
+use serde_json::{json, Value};
+
+use std::collections::VecDeque;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::{Arc, mpsc};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+
+// A result file may not be larger than this, so that a faulty worker cannot fill the disk of the coordinator
+const MAXIMUM_FILE_LENGTH: u64 = 1 << 32;
+
+// A single frame of a sequence, or a tile of a single frame, with the settings which render only that part. Each
+// worker calculates the reference and series approximation of its frames itself. The tiles of a frame share the
+// reference at its centre, which is sent to the workers from the reference cache of the coordinator as the files
+// in reference_files, so that they only iterate their pixels.
+#[derive(Clone)]
+pub struct WorkUnit {
+    pub index: usize,
+    pub zoom: String,
+    pub maximum_iteration: usize,
+    pub filename: String,
+    pub files: Vec<String>,
+    pub settings: Value,
+    pub tile: Option<(usize, usize)>,
+    pub reference_files: Vec<String>,
+}
+
+// The messages are single lines of JSON. The files of a result, and the reference files of a work unit, follow
+// the message as raw bytes, in the order and with the lengths given in the message.
+fn send_message(stream: &mut TcpStream, message: &Value) -> std::io::Result<()> {
+    stream.write_all(serde_json::to_string(message).unwrap().as_bytes())?;
+    stream.write_all(b"\n")
+}
+
+fn receive_message(reader: &mut BufReader<TcpStream>) -> Option<Value> {
+    let mut line = String::new();
+
+    match reader.read_line(&mut line) {
+        Ok(length) if length > 0 => serde_json::from_str(&line).ok(),
+        _ => None
+    }
+}
+
+// Hands out the work units to the workers which connect, and calls the handler with the files of each completed unit.
+// A unit is given to another worker if the connection to its worker is lost before the result arrives.
+pub fn run_coordinator<F: FnMut(&WorkUnit, &[String])>(address: &str, units: Vec<WorkUnit>, mut handle_result: F) {
+    let total_units = units.len();
+
+    if total_units == 0 {
+        return;
+    }
+
+    let listener = TcpListener::bind(address).unwrap();
+    let queue = Arc::new(Mutex::new(units.into_iter().collect::<VecDeque<_>>()));
+    let completed = Arc::new(AtomicUsize::new(0));
+    let (tx, rx) = mpsc::channel::<(WorkUnit, Vec<String>)>();
+
+    {
+        let queue = queue.clone();
+        let completed = completed.clone();
+
+        thread::spawn(move || {
+            for stream in listener.incoming().filter_map(|stream| stream.ok()) {
+                let queue = queue.clone();
+                let completed = completed.clone();
+                let tx = tx.clone();
+
+                thread::spawn(move || serve_worker(stream, queue, completed, total_units, tx));
+            }
+        });
+    }
+
+    for (unit, files) in rx.iter() {
+        handle_result(&unit, &files);
+
+        if completed.fetch_add(1, Ordering::SeqCst) + 1 == total_units {
+            break;
+        }
+    }
+}
+
+fn serve_worker(mut stream: TcpStream, queue: Arc<Mutex<VecDeque<WorkUnit>>>, completed: Arc<AtomicUsize>, total_units: usize, tx: mpsc::Sender<(WorkUnit, Vec<String>)>) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+    while let Some(message) = receive_message(&mut reader) {
+        if message["type"] != "ready" {
+            break;
+        }
+
+        let unit = loop {
+            if let Some(unit) = queue.lock().pop_front() {
+                break Some(unit);
+            }
+
+            // The units which are still being rendered could be returned to the queue by a failed worker
+            if completed.load(Ordering::SeqCst) >= total_units {
+                break None;
+            }
+
+            thread::sleep(Duration::from_secs(1));
+        };
+
+        let unit = match unit {
+            Some(unit) => unit,
+            None => {
+                send_message(&mut stream, &json!({"type": "done"})).ok();
+                return;
+            }
+        };
+
+        let files = send_work(&mut stream, &unit).ok()
+            .and_then(|_| receive_files(&mut reader, &unit.files));
+
+        match files {
+            Some(files) => tx.send((unit, files)).unwrap(),
+            None => {
+                queue.lock().push_back(unit);
+                return;
+            }
+        }
+    }
+}
+
+// The reference files which cannot be read are left out, and the worker calculates the reference itself
+fn send_work(stream: &mut TcpStream, unit: &WorkUnit) -> io::Result<()> {
+    let reference_files = unit.reference_files.iter()
+        .filter_map(|path| {
+            let length = fs::metadata(path).ok()?.len();
+            let extension = Path::new(path).extension()?.to_string_lossy().to_string();
+
+            Some((path, extension, length))
+        })
+        .collect::<Vec<_>>();
+
+    let message = json!({
+        "type": "work",
+        "index": unit.index,
+        "filename": unit.filename,
+        "settings": unit.settings,
+        "reference_files": reference_files.iter().map(|(_, extension, length)| json!({"extension": extension, "length": length})).collect::<Vec<_>>(),
+    });
+
+    send_message(stream, &message)?;
+
+    // The lengths have been sent, so a file which changed since is an error rather than a stream out of step
+    for (path, _, length) in reference_files {
+        if io::copy(&mut fs::File::open(path)?.take(length), stream)? != length {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, format!("{} is shorter than was sent", path)));
+        }
+    }
+
+    Ok(())
+}
+
+// The files are written to the same paths that the worker rendered them to. Only the outputs expected for the
+// unit are accepted, so a worker cannot write anywhere else on the coordinator.
+fn receive_files(reader: &mut BufReader<TcpStream>, expected: &[String]) -> Option<Vec<String>> {
+    let message = receive_message(reader)?;
+
+    if message["type"] != "result" {
+        return None;
+    }
+
+    message["files"].as_array()?.iter().map(|file| {
+        let path = expected.iter().find(|path| Some(path.as_str()) == file["path"].as_str())?.clone();
+        let length = file["length"].as_u64()?;
+
+        if length > MAXIMUM_FILE_LENGTH {
+            return None;
+        }
+
+        if let Some(parent) = Path::new(&path).parent() {
+            fs::create_dir_all(parent).ok()?;
+        }
+
+        // The contents are copied to the file as they arrive rather than held in memory
+        let mut output = fs::File::create(&path).ok()?;
+
+        if io::copy(&mut reader.by_ref().take(length), &mut output).ok()? != length {
+            fs::remove_file(&path).ok();
+            return None;
+        }
+
+        Some(path)
+    }).collect()
+}
+
+// The reference files are moved into the reference cache of the worker, where the renderer of the unit finds them
+fn receive_reference_files(reader: &mut BufReader<TcpStream>, message: &Value, cache: &ReferenceCache) -> Result<(), String> {
+    let disconnected = |error: io::Error| format!("lost the connection to the coordinator: {}", error);
+
+    let files = match message["reference_files"].as_array() {
+        Some(files) => files,
+        None => return Ok(())
+    };
+
+    fs::create_dir_all(&cache.directory).map_err(|error| format!("could not create the directory of {}: {}", cache.directory, error))?;
+
+    for (index, file) in files.iter().enumerate() {
+        let length = file["length"].as_u64().ok_or("the reference file has no length")?;
+        let extension = file["extension"].as_str().unwrap_or("");
+
+        if length > MAXIMUM_FILE_LENGTH {
+            return Err(format!("the reference file is {} bytes, which is larger than allowed", length));
+        }
+
+        // The file is received into the cache directory, so that importing it is only a rename
+        let temporary = format!("{}/received_{}.tmp", cache.directory, index);
+        let mut output = fs::File::create(&temporary).map_err(|error| format!("could not create {}: {}", temporary, error))?;
+
+        if io::copy(&mut reader.by_ref().take(length), &mut output).map_err(disconnected)? != length {
+            fs::remove_file(&temporary).ok();
+            return Err(String::from("lost the connection to the coordinator while receiving the reference"));
+        }
+
+        if !cache.import(&temporary, extension) {
+            fs::remove_file(&temporary).ok();
+        }
+    }
+
+    Ok(())
+}
+
+// Renders the work units from a coordinator until it has no more. The reference is calculated by each worker, unless
+// the coordinator sends it with the unit.
+pub fn run_worker(address: &str) -> Result<(), String> {
+    let disconnected = |error: io::Error| format!("lost the connection to the coordinator: {}", error);
+
+    let mut stream = TcpStream::connect(address)
+        .map_err(|error| format!("could not connect to the coordinator at {}: {}", address, error))?;
+    let mut reader = BufReader::new(stream.try_clone().map_err(disconnected)?);
+
+    loop {
+        send_message(&mut stream, &json!({"type": "ready"})).map_err(disconnected)?;
+
+        let message = match receive_message(&mut reader) {
+            Some(message) if message["type"] == "work" => message,
+            _ => return Ok(())
+        };
+
+        let filename = message["filename"].as_str().ok_or("the work unit has no filename")?.to_owned();
+
+        let mut settings = Config::default();
+        settings.merge(File::from_str(&message["settings"].to_string(), FileFormat::Json))
+            .map_err(|error| format!("the settings of work unit {} are not valid: {}", message["index"], error))?;
+
+        let cache = ReferenceCache::new(&settings.get_str("reference_cache_directory").unwrap_or_else(|_| String::from("output/cache")));
+        receive_reference_files(&mut reader, &message, &cache)?;
+
+        if let Some(parent) = Path::new(&filename).parent() {
+            fs::create_dir_all(parent).map_err(|error| format!("could not create the directory of {}: {}", filename, error))?;
+        }
+
+        let mut renderer = FractalRenderer::new(settings);
+        renderer.render();
+
+        let files = renderer.data_export.lock().get_output_files(&filename).into_iter()
+            .filter_map(|path| fs::read(&path).ok().map(|contents| (path, contents)))
+            .collect::<Vec<_>>();
+
+        println!(" {:<15}| {} ms", message["index"], renderer.render_time);
+
+        let result = json!({
+            "type": "result",
+            "files": files.iter().map(|(path, contents)| json!({"path": path, "length": contents.len()})).collect::<Vec<_>>(),
+        });
+
+        send_message(&mut stream, &result).map_err(disconnected)?;
+
+        for (_, contents) in &files {
+            stream.write_all(contents).map_err(disconnected)?;
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use config::Config;
+
+use std::f64::consts::PI;
+
+// Maps the progress through a sequence in [0, 1] to the progress of an animated value
+#[derive(PartialEq, Clone)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+    // Values at evenly spaced points through the sequence, linearly interpolated between
+    Curve(Vec<f64>)
+}
+
+impl Easing {
+    pub fn apply(&self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => 1.0 - (0.5 * PI * t).cos(),
+            Easing::EaseOut => (0.5 * PI * t).sin(),
+            Easing::EaseInOut => 0.5 - 0.5 * (PI * t).cos(),
+            Easing::Curve(values) => {
+                match values.len() {
+                    0 => t,
+                    1 => values[0],
+                    length => {
+                        let position = t * (length - 1) as f64;
+                        let index = (position.floor() as usize).min(length - 2);
+                        let fraction = position - index as f64;
+
+                        values[index] * (1.0 - fraction) + values[index + 1] * fraction
+                    }
+                }
+            }
+        }
+    }
+
+    // A curve given by the curve key takes precedence over the named easing
+    pub fn from_settings(settings: &Config, easing_key: &str, curve_key: &str) -> Easing {
+        if let Ok(values) = settings.get_array(curve_key) {
+            return Easing::Curve(values.into_iter().map(|value| value.into_float().unwrap()).collect());
+        }
+
+        match settings.get_str(easing_key).unwrap_or_else(|_| String::from("LINEAR")).to_ascii_uppercase().as_ref() {
+            "EASE_IN" => Easing::EaseIn,
+            "EASE_OUT" => Easing::EaseOut,
+            "EASE_IN_OUT" => Easing::EaseInOut,
+            _ => Easing::Linear
+        }
+    }
+}
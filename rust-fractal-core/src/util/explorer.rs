@@ -0,0 +1,235 @@
+use crate::renderer::FractalRenderer;
+use crate::util::{ComplexArbitrary, FloatExtended, RenderParameters, extended_to_string_long, extended_to_string_short, pixel_to_complex, string_to_extended};
+use crate::util::data_export::{RawChannels, get_interestingness};
+
+use config::Config;
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use std::fs;
+
+// The scan is split into a grid of this many tiles on each side, and the view zooms into one of them at each step
+const GRID_SIZE: usize = 4;
+
+pub struct ExploredLocation {
+    pub real: String,
+    pub imag: String,
+    pub zoom: String,
+    pub iterations: usize,
+    pub score: f64,
+    pub filename: String,
+}
+
+// Descends from the location towards the target zoom by repeatedly rendering a small scan of the view and zooming into
+// one of its tiles. The tile is chosen at random, weighted towards the tiles with many different and high iteration
+// counts, and the tiles which are mostly interior are never chosen. Each candidate is saved as a location file with a
+// thumbnail, unless the thumbnail scores below the minimum interestingness.
+pub struct Explorer {
+    pub settings: Config,
+    pub target_zoom: FloatExtended,
+    pub candidates: usize,
+    pub scan_width: usize,
+    pub thumbnail_width: usize,
+    pub minimum_score: f64,
+    rng: StdRng,
+}
+
+impl Explorer {
+    pub fn new(settings: Config) -> Self {
+        let rng = match settings.get_int("explore_seed") {
+            Ok(seed) => StdRng::seed_from_u64(seed as u64),
+            Err(_) => StdRng::from_entropy()
+        };
+
+        Explorer {
+            target_zoom: string_to_extended(&settings.get_str("explore_zoom").unwrap_or_else(|_| String::from("1E50")).to_ascii_uppercase()),
+            candidates: settings.get_int("explore_candidates").unwrap_or(8).max(1) as usize,
+            scan_width: settings.get_int("explore_scan_width").unwrap_or(96).max(16) as usize,
+            thumbnail_width: settings.get_int("explore_thumbnail_width").unwrap_or(320).max(16) as usize,
+            minimum_score: settings.get_float("explore_minimum_score").unwrap_or(0.0),
+            settings,
+            rng,
+        }
+    }
+
+    pub fn run(&mut self) -> Vec<ExploredLocation> {
+        fs::create_dir_all("output").unwrap();
+
+        let mut locations = Vec::new();
+
+        for index in 0..self.candidates {
+            match self.explore() {
+                Some(mut location) => {
+                    location.filename = format!("output/explore_{:03}", index);
+
+                    let saved = self.save(&mut location);
+
+                    println!(" {:<6}| {:<15}| {:<15}| {:<15.3}| {}",
+                        index,
+                        extended_to_string_short(string_to_extended(&location.zoom)),
+                        location.iterations,
+                        location.score,
+                        if saved { &location.filename } else { "discarded as bland" });
+
+                    if saved {
+                        locations.push(location);
+                    }
+                },
+                None => println!(" {:<6}| stopped in the interior before reaching the target zoom", index)
+            }
+        }
+
+        // The most interesting candidates are listed first
+        locations.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
+
+        if !locations.is_empty() {
+            println!("Ranked by score: {}", locations.iter().map(|location| location.filename.as_str()).collect::<Vec<&str>>().join(", "));
+        }
+
+        locations
+    }
+
+    fn explore(&mut self) -> Option<ExploredLocation> {
+        let parameters = RenderParameters::from_config(&self.settings);
+
+        let aspect = parameters.image_width as f64 / parameters.image_height as f64;
+        let scan_height = ((self.scan_width as f64 / aspect) as usize).max(GRID_SIZE);
+
+        let precision = FractalRenderer::get_precision(self.target_zoom, self.scan_width, scan_height);
+
+        let mut centre = ComplexArbitrary::with_val(precision,
+            ComplexArbitrary::parse(format!("({},{})", parameters.real, parameters.imag)).expect("provided location not valid"));
+        let mut zoom = string_to_extended(&parameters.zoom.to_ascii_uppercase());
+        let mut iterations = parameters.iterations;
+
+        while zoom * GRID_SIZE as f64 <= self.target_zoom {
+            let raw = self.scan(&centre, zoom, iterations, scan_height);
+
+            // The iteration limit is raised once the escaped pixels come close to it
+            let maximum_escaped = raw.iterations.iter().filter(|&&iteration| (iteration as usize) < raw.maximum_iteration).max().copied().unwrap_or(0) as usize;
+
+            if 2 * maximum_escaped > iterations {
+                iterations *= 2;
+            }
+
+            let weights = Explorer::get_tile_weights(&raw);
+            let total = weights.iter().sum::<f64>();
+
+            if total <= 0.0 {
+                return None;
+            }
+
+            let mut choice = self.rng.gen::<f64>() * total;
+            let tile = weights.iter().position(|&weight| {
+                choice -= weight;
+                choice <= 0.0
+            }).unwrap_or(weights.len() - 1);
+
+            let tile_x = (tile % GRID_SIZE) as f64 + 0.5;
+            let tile_y = (tile / GRID_SIZE) as f64 + 0.5;
+
+            centre = pixel_to_complex(&centre,
+                zoom,
+                raw.image_width,
+                raw.image_height,
+                parameters.rotate.to_radians(),
+                tile_x * raw.image_width as f64 / GRID_SIZE as f64 - 0.5,
+                tile_y * raw.image_height as f64 / GRID_SIZE as f64 - 0.5);
+
+            zoom *= GRID_SIZE as f64;
+        }
+
+        Some(ExploredLocation {
+            real: centre.real().to_string_radix(10, None),
+            imag: centre.imag().to_string_radix(10, None),
+            zoom: extended_to_string_long(zoom),
+            iterations,
+            score: 0.0,
+            filename: String::new(),
+        })
+    }
+
+    fn get_settings(&self, centre: &ComplexArbitrary, zoom: FloatExtended, iterations: usize, image_width: usize, image_height: usize) -> Config {
+        let mut settings = self.settings.clone();
+
+        settings.set("real", centre.real().to_string_radix(10, None)).unwrap();
+        settings.set("imag", centre.imag().to_string_radix(10, None)).unwrap();
+        settings.set("zoom", extended_to_string_long(zoom)).unwrap();
+        settings.set("iterations", iterations as i64).unwrap();
+        settings.set("image_width", image_width as i64).unwrap();
+        settings.set("image_height", image_height as i64).unwrap();
+        settings.set("frames", 1i64).unwrap();
+        settings.set("supersampling", 1i64).unwrap();
+        settings.set("crop_margin", 0i64).unwrap();
+        settings.set("recentre", "none").unwrap();
+        settings.set("show_output", false).unwrap();
+
+        settings
+    }
+
+    fn scan(&self, centre: &ComplexArbitrary, zoom: FloatExtended, iterations: usize, scan_height: usize) -> RawChannels {
+        let settings = self.get_settings(centre, zoom, iterations, self.scan_width, scan_height);
+
+        let mut renderer = FractalRenderer::new(settings);
+        renderer.render_to_buffer(true).raw.unwrap()
+    }
+
+    // The interestingness of each tile, scaled by how deep the iterations are. The tiles which are mostly interior are
+    // not chosen.
+    fn get_tile_weights(raw: &RawChannels) -> Vec<f64> {
+        (0..(GRID_SIZE * GRID_SIZE)).map(|tile| {
+            let start_x = (tile % GRID_SIZE) * raw.image_width / GRID_SIZE;
+            let end_x = (tile % GRID_SIZE + 1) * raw.image_width / GRID_SIZE;
+            let start_y = (tile / GRID_SIZE) * raw.image_height / GRID_SIZE;
+            let end_y = (tile / GRID_SIZE + 1) * raw.image_height / GRID_SIZE;
+
+            // The glitched pixels are counted as interior
+            let iterations = (start_y..end_y)
+                .flat_map(|j| (start_x..end_x).map(move |i| j * raw.image_width + i))
+                .map(|k| if raw.glitched[k] { raw.maximum_iteration as u32 } else { raw.iterations[k] })
+                .collect::<Vec<u32>>();
+
+            let escaped = iterations.iter()
+                .filter(|&&iteration| (iteration as usize) < raw.maximum_iteration)
+                .map(|&iteration| (iteration as f64 + 1.0).ln())
+                .collect::<Vec<f64>>();
+
+            if escaped.is_empty() || 2 * escaped.len() < iterations.len() {
+                return 0.0;
+            }
+
+            let depth = escaped.iter().sum::<f64>() / escaped.len() as f64;
+            let score = get_interestingness(&iterations, end_x - start_x, end_y - start_y, raw.maximum_iteration);
+
+            // A little is added so that a flat tile can still be chosen when there is nothing better
+            (score + 0.1) * depth * depth
+        }).collect()
+    }
+
+    // Renders the thumbnail and scores it, only saving the thumbnail and location if the score is high enough
+    fn save(&self, location: &mut ExploredLocation) -> bool {
+        let parameters = RenderParameters::from_config(&self.settings);
+        let thumbnail_height = ((self.thumbnail_width as f64 * parameters.image_height as f64 / parameters.image_width as f64) as usize).max(2);
+
+        let precision = FractalRenderer::get_precision(self.target_zoom, self.thumbnail_width, thumbnail_height);
+        let centre = ComplexArbitrary::with_val(precision,
+            ComplexArbitrary::parse(format!("({},{})", location.real, location.imag)).unwrap());
+
+        let settings = self.get_settings(&centre, string_to_extended(&location.zoom), location.iterations, self.thumbnail_width, thumbnail_height);
+
+        let mut renderer = FractalRenderer::new(settings);
+        let image = renderer.render_to_buffer(false);
+
+        location.score = renderer.statistics.interestingness;
+
+        if location.score < self.minimum_score {
+            return false;
+        }
+
+        image::save_buffer(format!("{}.png", location.filename), &image.rgb, image.image_width as u32, image.image_height as u32, image::ColorType::Rgb8).unwrap();
+
+        let contents = format!("real = \"{}\"\nimag = \"{}\"\nzoom = \"{}\"\niterations = {}\n", location.real, location.imag, location.zoom, location.iterations);
+        fs::write(format!("{}.toml", location.filename), contents).unwrap();
+
+        true
+    }
+}
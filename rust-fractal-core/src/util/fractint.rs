@@ -0,0 +1,125 @@
+use config::Config;
+
+use std::fs;
+use std::path::Path;
+
+// Fractint parameter files hold entries of the form "name { key=value ... }", with comments after semicolons. The
+// entry named by fractint_entry is read, or the first one in the file. Only the mandel and julia types can be rendered.
+pub fn apply_fractint(settings: &mut Config, contents: &str, directory: &Path) {
+    let entries = get_entries(contents);
+    let name = settings.get_str("fractint_entry").ok();
+
+    let (_, body) = match &name {
+        Some(name) => entries.iter()
+            .find(|(entry, _)| entry.eq_ignore_ascii_case(name))
+            .unwrap_or_else(|| panic!("the parameter file does not have an entry named {}", name)),
+        None => entries.first().expect("the parameter file does not have any entries")
+    };
+
+    let mut found_location = false;
+
+    for (key, value) in body.split_whitespace().filter_map(|token| token.split_once('=')) {
+        let values = value.split('/').collect::<Vec<&str>>();
+
+        match key.to_ascii_lowercase().as_str() {
+            "type" => {
+                if !["mandel", "julia"].contains(&value.to_ascii_lowercase().as_str()) {
+                    panic!("the fractal type {} is not supported, only mandel and julia can be rendered", value);
+                }
+            },
+            // The magnification is relative to a view two units tall, the zoom to a view four units tall
+            "center-mag" if values.len() >= 3 => {
+                let magnification = values[2].parse::<f64>().expect("magnification not valid");
+
+                settings.set("real", values[0]).unwrap();
+                settings.set("imag", values[1]).unwrap();
+                settings.set("zoom", format!("{:E}", 2.0 * magnification)).unwrap();
+
+                if let Some(rotate) = values.get(4).and_then(|rotate| rotate.parse::<f64>().ok()) {
+                    settings.set("rotate", rotate).unwrap();
+                }
+
+                found_location = true;
+            },
+            "corners" if values.len() >= 4 => {
+                let corners = values.iter()
+                    .map(|corner| corner.parse::<f64>().expect("corner not valid"))
+                    .collect::<Vec<f64>>();
+
+                settings.set("real", ((corners[0] + corners[1]) / 2.0).to_string()).unwrap();
+                settings.set("imag", ((corners[2] + corners[3]) / 2.0).to_string()).unwrap();
+                settings.set("zoom", format!("{:E}", 4.0 / (corners[3] - corners[2]).abs())).unwrap();
+
+                found_location = true;
+            },
+            "maxiter" => {
+                if let Ok(iterations) = value.parse::<i64>() {
+                    settings.set("iterations", iterations).unwrap();
+                }
+            },
+            // The seed is only used by the julia type, the mandel type has the perturbation of the start point here
+            "params" if values.len() >= 2 && body.to_ascii_lowercase().contains("type=julia") => {
+                settings.set("julia_real", values[0]).unwrap();
+                settings.set("julia_imag", values[1]).unwrap();
+            },
+            "map" => {
+                let filename = directory.join(value);
+                let filename = if filename.exists() { filename } else { Path::new(value).to_path_buf() };
+
+                let palette = read_map(&filename);
+
+                // Fractint steps through one colour of the map for each iteration
+                settings.set("palette_iteration_span", (palette.len() / 3) as f64).unwrap();
+                settings.set("palette", palette).unwrap();
+            },
+            _ => {}
+        }
+    }
+
+    if !found_location {
+        panic!("the parameter entry does not have a center-mag or corners");
+    }
+}
+
+pub fn read_fractint(settings: &mut Config, filename: &str) {
+    let contents = fs::read_to_string(filename).unwrap_or_else(|error| panic!("failed to read {}: {}", filename, error));
+    let directory = Path::new(filename).parent().unwrap_or_else(|| Path::new(""));
+
+    apply_fractint(settings, &contents, directory);
+}
+
+// Whether a location file should be read as a Fractint parameter file rather than with the config crate
+pub fn is_fractint(filename: &str) -> bool {
+    filename.to_ascii_lowercase().ends_with(".par")
+}
+
+fn get_entries(contents: &str) -> Vec<(String, String)> {
+    let contents = contents.lines()
+        .map(|line| line.split(';').next().unwrap())
+        .collect::<Vec<&str>>()
+        .join("\n");
+
+    let mut entries = Vec::new();
+    let mut remaining = contents.as_str();
+
+    while let Some(start) = remaining.find('{') {
+        let name = remaining[..start].split_whitespace().last().unwrap_or("").to_owned();
+        let end = remaining[start..].find('}').map(|end| start + end).unwrap_or_else(|| remaining.len());
+
+        entries.push((name, remaining[(start + 1)..end].to_owned()));
+        remaining = &remaining[(end + 1).min(remaining.len())..];
+    }
+
+    entries
+}
+
+// Map files have a colour on each line as "red green blue", optionally followed by a comment
+fn read_map(filename: &Path) -> Vec<i64> {
+    let contents = fs::read_to_string(filename).unwrap_or_else(|error| panic!("failed to read {}: {}", filename.display(), error));
+
+    contents.lines()
+        .map(|line| line.split_whitespace().take(3).filter_map(|value| value.parse::<i64>().ok()).collect::<Vec<i64>>())
+        .filter(|colour| colour.len() == 3)
+        .flatten()
+        .collect()
+}
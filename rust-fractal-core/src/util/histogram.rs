@@ -0,0 +1,102 @@
+use serde_json::{json, Map, Value};
+
+use std::fs;
+
+// The percentiles of the escape iterations which are reported, useful for spreading a palette over the image
+const PERCENTILES: [usize; 5] = [1, 10, 50, 90, 99];
+
+// The escape time distribution of a frame. The pixels inside the set are counted in the total but not in the
+// histogram, and the pixels which were never iterated (in a cancelled frame) are left out altogether, so the
+// iteration statistics are of the escaped pixels only.
+#[derive(Clone, Default)]
+pub struct IterationHistogram {
+    pub total_pixels: usize,
+    pub escaped_pixels: usize,
+    pub minimum_iteration: u32,
+    pub mean_iteration: f64,
+    pub maximum_iteration: u32,
+    pub percentiles: Vec<(usize, u32)>,
+    pub bin_start: u32,
+    pub bin_width: usize,
+    pub counts: Vec<u64>,
+}
+
+impl IterationHistogram {
+    pub fn new(iterations: &[u32], maximum_iteration: usize, bins: usize) -> Self {
+        let total_pixels = iterations.iter().filter(|&&iteration| iteration != u32::MAX).count();
+
+        let mut escaped = iterations.iter()
+            .copied()
+            .filter(|&iteration| (iteration as usize) < maximum_iteration)
+            .collect::<Vec<u32>>();
+
+        if escaped.is_empty() {
+            return IterationHistogram {
+                total_pixels,
+                ..IterationHistogram::default()
+            };
+        }
+
+        escaped.sort_unstable();
+
+        let minimum_iteration = escaped[0];
+        let maximum_iteration = escaped[escaped.len() - 1];
+        let mean_iteration = escaped.iter().map(|&iteration| iteration as f64).sum::<f64>() / escaped.len() as f64;
+
+        let percentiles = PERCENTILES.iter()
+            .map(|&percentile| (percentile, escaped[(percentile * (escaped.len() - 1) + 50) / 100]))
+            .collect();
+
+        // The bins cover the range of the escaped pixels, each with the same whole number of iterations
+        let range = (maximum_iteration - minimum_iteration) as usize + 1;
+        let bin_width = ((range + bins.max(1) - 1) / bins.max(1)).max(1);
+        let mut counts = vec![0u64; (range + bin_width - 1) / bin_width];
+
+        for &iteration in &escaped {
+            counts[(iteration - minimum_iteration) as usize / bin_width] += 1;
+        }
+
+        IterationHistogram {
+            total_pixels,
+            escaped_pixels: escaped.len(),
+            minimum_iteration,
+            mean_iteration,
+            maximum_iteration,
+            percentiles,
+            bin_start: minimum_iteration,
+            bin_width,
+            counts,
+        }
+    }
+
+    pub fn get_escaped_fraction(&self) -> f64 {
+        if self.total_pixels == 0 {
+            0.0
+        } else {
+            self.escaped_pixels as f64 / self.total_pixels as f64
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        let percentiles = self.percentiles.iter()
+            .map(|(percentile, iteration)| (percentile.to_string(), json!(iteration)))
+            .collect::<Map<String, Value>>();
+
+        json!({
+            "total_pixels": self.total_pixels,
+            "escaped_pixels": self.escaped_pixels,
+            "escaped_fraction": self.get_escaped_fraction(),
+            "minimum_iteration": self.minimum_iteration,
+            "mean_iteration": self.mean_iteration,
+            "maximum_iteration": self.maximum_iteration,
+            "percentiles": percentiles,
+            "bin_start": self.bin_start,
+            "bin_width": self.bin_width,
+            "counts": self.counts,
+        })
+    }
+
+    pub fn save(&self, filename: &str) {
+        fs::write(filename.to_owned() + "_histogram.json", serde_json::to_string_pretty(&self.to_json()).unwrap()).unwrap();
+    }
+}
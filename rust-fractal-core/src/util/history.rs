@@ -0,0 +1,99 @@
+use crate::util::RenderParameters;
+
+use serde_json::{json, Value};
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// A frame which was saved, with the parameters needed to render it again. The statistics are only known when the frame
+// was rendered by the renderer which records it, and not for the frames rendered by parallel keyframes or workers.
+pub struct HistoryEntry {
+    pub time: u64,
+    pub output: String,
+    pub files: Vec<String>,
+    pub parameters: RenderParameters,
+    pub statistics: Option<Value>,
+}
+
+impl HistoryEntry {
+    pub fn new(output: &str, files: &[String], parameters: RenderParameters, statistics: Option<Value>) -> Self {
+        HistoryEntry {
+            time: SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or(0),
+            output: output.to_owned(),
+            files: files.to_vec(),
+            parameters,
+            statistics,
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "time": self.time,
+            "output": self.output,
+            "files": self.files,
+            "parameters": serde_json::from_str::<Value>(&self.parameters.to_json()).unwrap(),
+            "statistics": self.statistics,
+        })
+    }
+
+    pub fn from_json(value: &Value) -> Option<Self> {
+        Some(HistoryEntry {
+            time: value["time"].as_u64()?,
+            output: value["output"].as_str()?.to_owned(),
+            files: value["files"].as_array()
+                .map(|files| files.iter().filter_map(|file| file.as_str().map(|file| file.to_owned())).collect())
+                .unwrap_or_default(),
+            parameters: serde_json::from_value(value["parameters"].clone()).ok()?,
+            statistics: Some(value["statistics"].clone()).filter(|statistics| !statistics.is_null()),
+        })
+    }
+
+    // The time in UTC, as there is no time zone information without another dependency
+    pub fn get_date(&self) -> String {
+        let days = (self.time / 86400) as i64;
+        let seconds = self.time % 86400;
+
+        // The civil date from the days since the epoch, counted in eras of 400 years from the first of March
+        let shifted = days + 719468;
+        let era = shifted.div_euclid(146097);
+        let day_of_era = shifted.rem_euclid(146097);
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month + 2) / 5 + 1;
+        let month = if month < 10 { month + 3 } else { month - 9 };
+        let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+        format!("{:04}-{:02}-{:02} {:02}:{:02} UTC", year, month, day, seconds / 3600, (seconds / 60) % 60)
+    }
+}
+
+// Each entry is a line of JSON, so the file is only ever appended to and a partly written line does not affect the
+// entries before it
+pub fn append_history(filename: &str, entry: &HistoryEntry) {
+    if let Some(directory) = Path::new(filename).parent() {
+        fs::create_dir_all(directory).ok();
+    }
+
+    let result = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(filename)
+        .and_then(|mut file| writeln!(file, "{}", entry.to_json()));
+
+    if let Err(error) = result {
+        eprintln!("Warning: failed to write to the history {}: {}", filename, error);
+    }
+}
+
+// The lines which cannot be read are skipped
+pub fn read_history(filename: &str) -> Vec<HistoryEntry> {
+    fs::read_to_string(filename)
+        .map(|contents| contents.lines()
+            .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+            .filter_map(|value| HistoryEntry::from_json(&value))
+            .collect())
+        .unwrap_or_default()
+}
@@ -0,0 +1,242 @@
+use exr::prelude::simple_image::*;
+use rayon::prelude::*;
+use config::Config;
+
+use colorgrad::Color;
+
+use std::fs;
+use std::time::Instant;
+
+use crate::util::get_palette;
+
+// The value of a pixel inside the set, and of a pixel which was left glitched or was never iterated
+const INTERIOR: f32 = -1.0;
+const MISSING: f32 = f32::NAN;
+
+// The iteration data of a single EXR keyframe, combined into a single smooth iteration value
+pub struct Keyframe {
+    pub image_width: usize,
+    pub image_height: usize,
+    pub values: Vec<f32>,
+}
+
+impl Keyframe {
+    pub fn read(filename: &str) -> Self {
+        let raw_data = Image::read_from_file(filename, read_options::high()).unwrap();
+
+        let mut iterations = Vec::new();
+        let mut smooth = Vec::new();
+        let mut maximum_iteration = u32::MAX;
+
+        // The channels are found by name, as the glitch channel has the same type as the iterations
+        for layer in &raw_data.layers {
+            if let Some(exr::meta::attribute::AttributeValue::I32(iterations)) = Text::from("Iterations").and_then(|key| layer.attributes.custom.get(&key)) {
+                maximum_iteration = *iterations as u32;
+            }
+
+            for channel in &layer.channels {
+                match (channel.name.to_string().as_str(), &channel.samples) {
+                    ("N", Samples::U32(u32_vec)) => iterations = u32_vec.clone(),
+                    ("NF", Samples::F16(f16_vec)) => smooth = f16_vec.iter().map(|value| value.to_f32()).collect(),
+                    ("NF", Samples::F32(f32_vec)) => smooth = f32_vec.clone(),
+                    _ => {}
+                };
+            }
+        }
+
+        let dimensions = raw_data.attributes.display_window.size;
+
+        // The iterations of the pixels which were not resolved are left at their initial value of u32::MAX, while the
+        // pixels inside the set reach the maximum iteration
+        let values = iterations.iter().zip(smooth.iter()).map(|(&iteration, &smooth)| {
+            if iteration == u32::MAX {
+                MISSING
+            } else if iteration >= maximum_iteration {
+                INTERIOR
+            } else {
+                iteration as f32 + smooth
+            }
+        }).collect();
+
+        Keyframe {
+            image_width: dimensions.x(),
+            image_height: dimensions.y(),
+            values
+        }
+    }
+
+    // Bilinear sample of the escaped pixels, the interior is only returned if none of the surrounding pixels escaped,
+    // and a missing value if none of them has data
+    pub fn sample(&self, x: f32, y: f32) -> f32 {
+        let x0 = (x.floor().max(0.0) as usize).min(self.image_width - 1);
+        let y0 = (y.floor().max(0.0) as usize).min(self.image_height - 1);
+        let x1 = (x0 + 1).min(self.image_width - 1);
+        let y1 = (y0 + 1).min(self.image_height - 1);
+
+        let fx = (x - x0 as f32).clamp(0.0, 1.0);
+        let fy = (y - y0 as f32).clamp(0.0, 1.0);
+
+        let samples = [
+            (self.values[y0 * self.image_width + x0], (1.0 - fx) * (1.0 - fy)),
+            (self.values[y0 * self.image_width + x1], fx * (1.0 - fy)),
+            (self.values[y1 * self.image_width + x0], (1.0 - fx) * fy),
+            (self.values[y1 * self.image_width + x1], fx * fy)];
+
+        let (total, weight) = samples.iter()
+            .filter(|(value, _)| *value >= 0.0)
+            .fold((0.0, 0.0), |(total, weight), (value, sample_weight)| (total + value * sample_weight, weight + sample_weight));
+
+        if weight > 0.0 {
+            total / weight
+        } else if samples.iter().any(|(value, _)| *value == INTERIOR) {
+            INTERIOR
+        } else {
+            MISSING
+        }
+    }
+}
+
+// Generates the video frames between keyframes by scaling the iteration data of the two keyframes surrounding each frame
+// The deeper keyframe is used wherever it covers the frame, as it contains the most detail
+pub struct KeyframeInterpolator {
+    palette_buffer: Vec<Color>,
+    files: Vec<String>,
+    palette_iteration_span: f32,
+    palette_offset: f32,
+    zoom_scale_factor: f32,
+    frames_per_keyframe: usize,
+    crop_margin: usize,
+}
+
+impl KeyframeInterpolator {
+    pub fn new(settings: Config) -> Self {
+        let (_, palette_buffer) = get_palette(&settings);
+
+        let palette_iteration_span = settings.get_float("palette_iteration_span").unwrap_or(100.0) as f32;
+        // The offset is the same as for the colouring of the raw exports
+        let palette_offset = settings.get_float("iteration_offset").unwrap_or(0.0) as f32;
+        let zoom_scale_factor = settings.get_float("zoom_scale").unwrap_or(2.0) as f32;
+        let frames_per_keyframe = settings.get_int("interpolation_frames").unwrap_or(60) as usize;
+        let crop_margin = settings.get_int("crop_margin").unwrap_or(0) as usize;
+
+        let paths = fs::read_dir("./output/").unwrap();
+        let mut exr_files = Vec::new();
+
+        for path in paths {
+            let name = path.unwrap()
+                .path()
+                .to_str()
+                .unwrap()
+                .to_string();
+
+            if name.ends_with(".exr") {
+                exr_files.push(name)
+            }
+        };
+
+        // The keyframes are numbered from the deepest
+        exr_files.sort();
+
+        KeyframeInterpolator {
+            palette_buffer,
+            files: exr_files,
+            palette_iteration_span,
+            palette_offset,
+            zoom_scale_factor,
+            frames_per_keyframe,
+            crop_margin
+        }
+    }
+
+    pub fn colour_value(&self, value: f32) -> [u8; 3] {
+        if value.is_nan() || value < 0.0 {
+            return [0, 0, 0];
+        }
+
+        let temp = self.palette_buffer.len() as f32 * (value / self.palette_iteration_span + self.palette_offset).fract();
+
+        let pos1 = temp.floor() as usize;
+        let pos2 = (pos1 + 1) % self.palette_buffer.len();
+
+        let (r, g, b, _) = self.palette_buffer[pos1].interpolate_rgb(&self.palette_buffer[pos2], temp.fract() as f64).rgba_u8();
+
+        [r, g, b]
+    }
+
+    pub fn interpolate(&self) {
+        let interpolation_time = Instant::now();
+
+        fs::create_dir_all("./output/frames/").unwrap();
+
+        // The frames are written in zoom in order, starting from the shallowest keyframe
+        for (segment, pair) in self.files.windows(2).rev().enumerate() {
+            let deep = Keyframe::read(&pair[0]);
+            let shallow = Keyframe::read(&pair[1]);
+
+            // The keyframes may have a margin around the output frame, which is used at the edges
+            let image_width = shallow.image_width - 2 * self.crop_margin;
+            let image_height = shallow.image_height - 2 * self.crop_margin;
+
+            let centre_x = 0.5 * (shallow.image_width - 1) as f32;
+            let centre_y = 0.5 * (shallow.image_height - 1) as f32;
+
+            (0..self.frames_per_keyframe).into_par_iter().for_each(|frame| {
+                // The magnification of this frame relative to the shallow keyframe
+                let ratio = self.zoom_scale_factor.powf(frame as f32 / self.frames_per_keyframe as f32);
+
+                let mut rgb_buffer = vec![0u8; image_width * image_height * 3];
+
+                for j in 0..image_height {
+                    for i in 0..image_width {
+                        let offset_x = (i + self.crop_margin) as f32 - centre_x;
+                        let offset_y = (j + self.crop_margin) as f32 - centre_y;
+
+                        let deep_x = centre_x + offset_x * self.zoom_scale_factor / ratio;
+                        let deep_y = centre_y + offset_y * self.zoom_scale_factor / ratio;
+
+                        let deep_value = if deep_x >= 0.0 && deep_x <= (deep.image_width - 1) as f32 && deep_y >= 0.0 && deep_y <= (deep.image_height - 1) as f32 {
+                            deep.sample(deep_x, deep_y)
+                        } else {
+                            MISSING
+                        };
+
+                        // The shallow keyframe is also used where the deep keyframe has no data
+                        let value = if deep_value.is_nan() {
+                            shallow.sample(centre_x + offset_x / ratio, centre_y + offset_y / ratio)
+                        } else {
+                            deep_value
+                        };
+
+                        let k = j * image_width + i;
+                        rgb_buffer[3 * k..3 * k + 3].copy_from_slice(&self.colour_value(value));
+                    }
+                }
+
+                let filename = format!("./output/frames/{:08}.png", segment * self.frames_per_keyframe + frame);
+
+                image::save_buffer(filename, &rgb_buffer, image_width as u32, image_height as u32, image::ColorType::Rgb8).unwrap();
+            });
+        }
+
+        // The final frame is the deepest keyframe
+        if let Some(first) = self.files.first() {
+            let deepest = Keyframe::read(first);
+
+            let image_width = deepest.image_width - 2 * self.crop_margin;
+            let image_height = deepest.image_height - 2 * self.crop_margin;
+
+            let rgb_buffer = (0..image_height).flat_map(|j| (0..image_width).map(move |i| (i, j)))
+                .flat_map(|(i, j)| {
+                    let k = (j + self.crop_margin) * deepest.image_width + i + self.crop_margin;
+                    self.colour_value(deepest.values[k]).to_vec()
+                })
+                .collect::<Vec<u8>>();
+
+            let filename = format!("./output/frames/{:08}.png", (self.files.len() - 1) * self.frames_per_keyframe);
+
+            image::save_buffer(filename, &rgb_buffer, image_width as u32, image_height as u32, image::ColorType::Rgb8).unwrap();
+        }
+
+        println!("Interpolating {} keyframes took {} ms.", self.files.len(), interpolation_time.elapsed().as_millis());
+    }
+}
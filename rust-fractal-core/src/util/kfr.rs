@@ -0,0 +1,105 @@
+use crate::util::{RenderParameters, extended_to_string_long, parse_extended};
+
+use config::Config;
+
+use std::fs;
+
+// Kalles Fraktaler locations are lines of "Key: Value". The centre, zoom, iterations and rotation are read directly, as
+// both renderers use a zoom of one for a view four units tall. The colouring is only approximate: Kalles Fraktaler
+// spreads its key colours over a table of 1024 entries, which is stepped through once every IterDiv iterations.
+pub fn apply_kfr(settings: &mut Config, contents: &str) {
+    let mut found_location = false;
+
+    for line in contents.lines() {
+        let (key, value) = match line.split_once(':') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => continue
+        };
+
+        match key {
+            "Re" | "Im" | "Zoom" => {
+                let key = match key {
+                    "Re" => "real",
+                    "Im" => "imag",
+                    _ => "zoom"
+                };
+
+                // The centre and zoom are kept as strings, as they can have more digits than a float
+                settings.set(key, value).unwrap();
+                found_location = true;
+            },
+            "Iterations" => {
+                if let Ok(iterations) = value.parse::<i64>() {
+                    settings.set("iterations", iterations).unwrap();
+                }
+            },
+            "Rotate" => {
+                if let Ok(rotate) = value.parse::<f64>() {
+                    settings.set("rotate", rotate).unwrap();
+                }
+            },
+            "IterDiv" => {
+                if let Ok(divisor) = value.parse::<f64>() {
+                    settings.set("palette_iteration_span", 1024.0 * divisor.max(1e-6)).unwrap();
+                }
+            },
+            "ColorOffset" => {
+                if let Ok(offset) = value.parse::<f64>() {
+                    settings.set("palette_offset", (offset / 1024.0).fract()).unwrap();
+                }
+            },
+            "Colors" => {
+                let components = value.split(',')
+                    .filter_map(|component| component.trim().parse::<i64>().ok())
+                    .collect::<Vec<i64>>();
+
+                if components.len() >= 6 {
+                    let length = components.len() - components.len() % 3;
+                    settings.set("palette", components[..length].to_vec()).unwrap();
+                }
+            },
+            _ => {}
+        }
+    }
+
+    if !found_location {
+        panic!("the location does not have any of the Re, Im or Zoom of a Kalles Fraktaler location");
+    }
+}
+
+// The inverse of the conversion when reading, with the zoom written in the form Kalles Fraktaler expects. Julia sets
+// and the other colouring settings have no equivalent, so only the location and palette are written.
+pub fn get_kfr(parameters: &RenderParameters) -> String {
+    let zoom = parse_extended(&parameters.zoom).map(extended_to_string_long).unwrap_or_else(|_| parameters.zoom.clone());
+
+    let mut kfr = format!("Re: {}\r\nIm: {}\r\nZoom: {}\r\nIterations: {}\r\nIterDiv: {}\r\nColorOffset: {}\r\nRotate: {}\r\nSmooth: 1\r\n",
+        parameters.real,
+        parameters.imag,
+        zoom,
+        parameters.iterations,
+        parameters.palette_iteration_span / 1024.0,
+        (parameters.palette_offset.rem_euclid(1.0) * 1024.0).round() as i64 % 1024,
+        parameters.rotate);
+
+    if let Some(palette) = &parameters.palette {
+        let colors = palette.iter().map(|component| format!("{},", component)).collect::<String>();
+        kfr += &format!("Colors: {}\r\n", colors);
+    }
+
+    kfr
+}
+
+pub fn save_kfr(parameters: &RenderParameters, filename: &str) {
+    fs::write(filename, get_kfr(parameters)).unwrap_or_else(|error| panic!("failed to write {}: {}", filename, error));
+}
+
+pub fn read_kfr(settings: &mut Config, filename: &str) {
+    let contents = fs::read_to_string(filename).unwrap_or_else(|error| panic!("failed to read {}: {}", filename, error));
+
+    apply_kfr(settings, &contents);
+}
+
+// Whether a location file should be read as a Kalles Fraktaler location rather than with the config crate
+pub fn is_kfr(filename: &str) -> bool {
+    filename.to_ascii_lowercase().ends_with(".kfr")
+}
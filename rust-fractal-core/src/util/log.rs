@@ -0,0 +1,46 @@
+use serde_json::{json, Value};
+
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// The log formats which can be selected, the text format is the usual output for a terminal
+pub const LOG_FORMATS: [&str; 2] = ["text", "json"];
+
+// Structured events are lines of JSON on standard output, each with the time in seconds since the epoch, a level and
+// the name of the event, followed by the fields of the event
+pub fn log_event(level: &str, event: &str, fields: Value) {
+    let time = SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs_f64()).unwrap_or(0.0);
+
+    let mut line = json!({
+        "time": time,
+        "level": level,
+        "event": event,
+    });
+
+    if let (Value::Object(line), Value::Object(fields)) = (&mut line, fields) {
+        line.extend(fields);
+    }
+
+    let stdout = std::io::stdout();
+    let mut stdout = stdout.lock();
+
+    writeln!(stdout, "{}", line).ok();
+    stdout.flush().ok();
+}
+
+// Warnings are printed to standard error in the text format, so they are not mixed into piped output
+pub fn log_warning(json: bool, message: &str) {
+    if json {
+        log_event("warning", "warning", json!({"message": message}));
+    } else {
+        eprintln!("Warning: {}", message);
+    }
+}
+
+pub fn log_error(json: bool, message: &str) {
+    if json {
+        log_event("error", "error", json!({"message": message}));
+    } else {
+        eprintln!("Error: {}", message);
+    }
+}
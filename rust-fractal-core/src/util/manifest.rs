@@ -0,0 +1,131 @@
+use serde_json::{json, Value};
+
+use std::fs;
+use std::path::Path;
+
+pub struct ManifestFile {
+    pub path: String,
+    pub checksum: String,
+}
+
+pub struct ManifestEntry {
+    pub index: usize,
+    pub zoom: String,
+    pub maximum_iteration: usize,
+    pub filename: String,
+    pub files: Vec<ManifestFile>,
+}
+
+// An index of every frame written for a sequence, this is rewritten after each frame so that it
+// is valid for partial sequences as well
+pub struct SequenceManifest {
+    pub filename: String,
+    pub name: String,
+    pub entries: Vec<ManifestEntry>,
+}
+
+impl SequenceManifest {
+    // Existing entries are kept so that a resumed sequence adds to the same manifest
+    pub fn new(filename: &str, name: &str) -> Self {
+        let mut manifest = SequenceManifest {
+            filename: filename.to_owned(),
+            name: name.to_owned(),
+            entries: Vec::new(),
+        };
+
+        if let Some(frames) = fs::read_to_string(filename).ok()
+            .and_then(|contents| serde_json::from_str::<Value>(&contents).ok())
+            .and_then(|value| value["frames"].as_array().cloned()) {
+            manifest.entries = frames.iter().filter_map(ManifestEntry::from_json).collect();
+        }
+
+        manifest
+    }
+
+    // Adds the frame with the checksums of the files as they are currently on disk
+    pub fn add_frame(&mut self, index: usize, zoom: &str, maximum_iteration: usize, filename: &str, files: &[String]) {
+        let files = files.iter().filter_map(|path| {
+            get_file_checksum(path).map(|checksum| ManifestFile {
+                path: path.clone(),
+                checksum
+            })
+        }).collect();
+
+        self.entries.retain(|entry| entry.index != index);
+        self.entries.push(ManifestEntry {
+            index,
+            zoom: zoom.to_owned(),
+            maximum_iteration,
+            filename: filename.to_owned(),
+            files
+        });
+
+        self.entries.sort_by_key(|entry| entry.index);
+    }
+
+    // A frame is complete if all of its files are present with matching checksums
+    pub fn is_complete(&self, index: usize) -> bool {
+        match self.entries.iter().find(|entry| entry.index == index) {
+            Some(entry) => !entry.files.is_empty() && entry.files.iter().all(|file| {
+                get_file_checksum(&file.path).map_or(false, |checksum| checksum == file.checksum)
+            }),
+            None => false
+        }
+    }
+
+    pub fn save(&self) {
+        if let Some(parent) = Path::new(&self.filename).parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+
+        let manifest = json!({
+            "name": self.name,
+            "frames": self.entries.iter().map(|entry| entry.to_json()).collect::<Vec<_>>(),
+        });
+
+        fs::write(&self.filename, serde_json::to_string_pretty(&manifest).unwrap()).unwrap();
+    }
+}
+
+impl ManifestEntry {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "index": self.index,
+            "zoom": self.zoom,
+            "maximum_iteration": self.maximum_iteration,
+            "filename": self.filename,
+            "files": self.files.iter().map(|file| json!({
+                "path": file.path,
+                "checksum": file.checksum,
+            })).collect::<Vec<_>>(),
+        })
+    }
+
+    pub fn from_json(value: &Value) -> Option<Self> {
+        let files = value["files"].as_array()?.iter().filter_map(|file| {
+            Some(ManifestFile {
+                path: file["path"].as_str()?.to_owned(),
+                checksum: file["checksum"].as_str()?.to_owned(),
+            })
+        }).collect();
+
+        Some(ManifestEntry {
+            index: value["index"].as_u64()? as usize,
+            zoom: value["zoom"].as_str()?.to_owned(),
+            maximum_iteration: value["maximum_iteration"].as_u64()? as usize,
+            filename: value["filename"].as_str()?.to_owned(),
+            files
+        })
+    }
+}
+
+// 64 bit FNV-1a hash of the file contents, this is only used to detect incomplete or modified files
+pub fn get_file_checksum(filename: &str) -> Option<String> {
+    let contents = fs::read(filename).ok()?;
+
+    let hash = contents.iter().fold(0xcbf29ce484222325u64, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+
+    Some(format!("{:016x}", hash))
+}
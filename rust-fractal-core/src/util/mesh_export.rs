@@ -0,0 +1,111 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum MeshType {
+    None,
+    Obj,
+    Stl
+}
+
+// A triangulated surface generated from a normalised heightfield
+pub struct HeightfieldMesh {
+    pub vertices: Vec<[f32; 3]>,
+    pub triangles: Vec<[usize; 3]>,
+}
+
+impl HeightfieldMesh {
+    // Samples every decimation pixels of the heightfield, the horizontal extent is scaled so that the image height is 1
+    pub fn new(heights: &[f32], image_width: usize, image_height: usize, vertical_scale: f32, decimation: usize) -> Self {
+        let decimation = decimation.max(1);
+
+        let columns = (0..image_width).step_by(decimation).collect::<Vec<usize>>();
+        let rows = (0..image_height).step_by(decimation).collect::<Vec<usize>>();
+
+        let scale = 1.0 / (image_height - 1).max(1) as f32;
+
+        let mut vertices = Vec::with_capacity(columns.len() * rows.len());
+
+        for &j in &rows {
+            for &i in &columns {
+                // Flip the vertical axis so that the mesh is not mirrored compared to the image
+                vertices.push([
+                    i as f32 * scale,
+                    (image_height - 1 - j) as f32 * scale,
+                    heights[j * image_width + i] * vertical_scale]);
+            }
+        }
+
+        let mut triangles = Vec::with_capacity(2 * columns.len() * rows.len());
+
+        for j in 0..rows.len().saturating_sub(1) {
+            for i in 0..columns.len().saturating_sub(1) {
+                let top_left = j * columns.len() + i;
+                let top_right = top_left + 1;
+                let bottom_left = top_left + columns.len();
+                let bottom_right = bottom_left + 1;
+
+                triangles.push([top_left, bottom_left, top_right]);
+                triangles.push([top_right, bottom_left, bottom_right]);
+            }
+        }
+
+        HeightfieldMesh {
+            vertices,
+            triangles
+        }
+    }
+
+    pub fn save(&self, filename: &str, mesh_type: MeshType) {
+        match mesh_type {
+            MeshType::Obj => self.save_obj(&(filename.to_owned() + ".obj")),
+            MeshType::Stl => self.save_stl(&(filename.to_owned() + ".stl")),
+            MeshType::None => {}
+        }
+    }
+
+    pub fn save_obj(&self, filename: &str) {
+        let mut writer = BufWriter::new(File::create(filename).unwrap());
+
+        for vertex in &self.vertices {
+            writeln!(writer, "v {} {} {}", vertex[0], vertex[1], vertex[2]).unwrap();
+        }
+
+        // OBJ indices start from 1
+        for triangle in &self.triangles {
+            writeln!(writer, "f {} {} {}", triangle[0] + 1, triangle[1] + 1, triangle[2] + 1).unwrap();
+        }
+    }
+
+    // Binary STL with an empty header
+    pub fn save_stl(&self, filename: &str) {
+        let mut writer = BufWriter::new(File::create(filename).unwrap());
+
+        writer.write_all(&[0u8; 80]).unwrap();
+        writer.write_all(&(self.triangles.len() as u32).to_le_bytes()).unwrap();
+
+        for triangle in &self.triangles {
+            let a = self.vertices[triangle[0]];
+            let b = self.vertices[triangle[1]];
+            let c = self.vertices[triangle[2]];
+
+            let u = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+            let v = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+
+            let mut normal = [u[1] * v[2] - u[2] * v[1], u[2] * v[0] - u[0] * v[2], u[0] * v[1] - u[1] * v[0]];
+            let length = (normal[0] * normal[0] + normal[1] * normal[1] + normal[2] * normal[2]).sqrt();
+
+            if length > 0.0 {
+                for component in normal.iter_mut() {
+                    *component /= length;
+                }
+            }
+
+            for value in normal.iter().chain(a.iter()).chain(b.iter()).chain(c.iter()) {
+                writer.write_all(&value.to_le_bytes()).unwrap();
+            }
+
+            writer.write_all(&0u16.to_le_bytes()).unwrap();
+        }
+    }
+}
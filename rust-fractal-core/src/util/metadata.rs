@@ -0,0 +1,88 @@
+use exr::prelude::simple_image;
+use config::{Config, Value};
+
+use std::fs::File;
+
+// Reads the render parameters which were embedded in a previously exported PNG or EXR image
+// The image dimensions are also returned so that the render can be reproduced at the same resolution
+pub fn read_metadata(filename: &str) -> Vec<(String, String)> {
+    let mut metadata = Vec::new();
+
+    if filename.ends_with(".exr") {
+        let raw_data = simple_image::Image::read_from_file(filename, simple_image::read_options::high()).unwrap();
+        let dimensions = raw_data.attributes.display_window.size;
+
+        metadata.push((String::from("image_width"), dimensions.x().to_string()));
+        metadata.push((String::from("image_height"), dimensions.y().to_string()));
+
+        for layer in &raw_data.layers {
+            for (key, value) in &layer.attributes.custom {
+                if let exr::meta::attribute::AttributeValue::Text(text) = value {
+                    metadata.push((key.to_string(), text.to_string()));
+                }
+            }
+        }
+    } else {
+        let decoder = png::Decoder::new(File::open(filename).unwrap());
+        let reader = decoder.read_info().unwrap();
+        let info = reader.info();
+
+        metadata.push((String::from("image_width"), info.width.to_string()));
+        metadata.push((String::from("image_height"), info.height.to_string()));
+
+        for chunk in &info.uncompressed_latin1_text {
+            metadata.push((chunk.keyword.clone(), chunk.text.clone()));
+        }
+    }
+
+    let get_value = |name: &str, default: usize| metadata.iter()
+        .find(|(key, _)| key == name)
+        .and_then(|(_, value)| value.parse::<usize>().ok())
+        .unwrap_or(default);
+
+    // The margin is added to the image size when rendering, so the stored size is without it
+    // The EXR is saved at the supersampled size, while the colour output is downsampled
+    let crop_margin = get_value("crop_margin", 0);
+    let scale = if filename.ends_with(".exr") {
+        get_value("supersampling", 1).max(1)
+    } else {
+        1
+    };
+
+    for (key, value) in metadata.iter_mut() {
+        if key == "image_width" || key == "image_height" {
+            *value = (value.parse::<usize>().unwrap() / scale - 2 * crop_margin).to_string();
+        }
+    }
+
+    metadata
+}
+
+// Overwrites the settings with the embedded values, the palette is stored as a comma separated list
+pub fn apply_metadata(settings: &mut Config, metadata: &[(String, String)]) {
+    for (key, value) in metadata {
+        // The EXR export also contains some capitalised attributes for other programs
+        if key.chars().any(|c| c.is_ascii_uppercase()) {
+            continue;
+        }
+
+        // The crop window is derived from the margin, and the full parameters are read separately
+        if key == "crop_window" || key == "parameters" {
+            continue;
+        }
+
+        // A palette which cannot be read is left as text, so that it is reported by the validation
+        if key == "palette" {
+            let palette = value.split(',')
+                .map(|component| component.trim().parse::<i64>().map(Value::from))
+                .collect::<Result<Vec<Value>, _>>();
+
+            match palette {
+                Ok(palette) => settings.set(key, palette).unwrap(),
+                Err(_) => settings.set(key, value.clone()).unwrap()
+            };
+        } else {
+            settings.set(key, value.clone()).unwrap();
+        }
+    }
+}
@@ -0,0 +1,498 @@
+use std::f64::consts::{LOG2_10, LOG10_2, PI};
+
+pub mod data_export;
+pub mod float_extended;
+pub mod complex_extended;
+pub mod recolour_exr;
+pub mod progress;
+pub mod metadata;
+pub mod mesh_export;
+pub mod statistics;
+pub mod easing;
+pub mod camera_path;
+pub mod interpolate_keyframes;
+pub mod manifest;
+pub mod checkpoint;
+pub mod benchmark;
+pub mod compare;
+pub mod affinity;
+pub mod distributed;
+pub mod reference_cache;
+pub mod parameters;
+pub mod server;
+pub mod plugin;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod script;
+pub mod terminal_preview;
+pub mod validation;
+pub mod batch;
+pub mod kfr;
+pub mod fractint;
+pub mod analysis;
+pub mod explorer;
+pub mod palettes;
+pub mod history;
+pub mod bookmarks;
+pub mod presets;
+pub mod queue;
+pub mod log;
+pub mod histogram;
+pub mod reference_orbit;
+pub mod arbitrary;
+
+use colorgrad::{CustomGradient, Interpolation, Color, BlendMode};
+use config::{Config, File};
+pub use complex_extended::ComplexExtended;
+pub use float_extended::FloatExtended;
+pub use recolour_exr::RecolourExr;
+pub use progress::{ProgressCounters, ProgressReport, RenderStage};
+pub use statistics::{RenderStatistics, MemoryEstimate};
+pub use easing::Easing;
+pub use camera_path::CameraPath;
+pub use interpolate_keyframes::KeyframeInterpolator;
+pub use manifest::SequenceManifest;
+pub use checkpoint::RenderCheckpoint;
+pub use benchmark::Benchmark;
+pub use compare::ImageComparison;
+pub use reference_cache::ReferenceCache;
+pub use parameters::RenderParameters;
+pub use plugin::ColoringPlugin;
+pub use terminal_preview::TerminalPreview;
+pub use validation::Validation;
+pub use batch::Batch;
+pub use analysis::LocationAnalysis;
+pub use explorer::Explorer;
+
+pub type ComplexFixed<T> = num_complex::Complex<T>;
+
+// Without the mpfr feature, the arbitrary precision values use the pure Rust implementation which builds for wasm32
+#[cfg(feature = "mpfr")]
+pub type ComplexArbitrary = rug::Complex;
+#[cfg(feature = "mpfr")]
+pub type FloatArbitrary = rug::Float;
+
+#[cfg(not(feature = "mpfr"))]
+pub type ComplexArbitrary = arbitrary::Complex;
+#[cfg(not(feature = "mpfr"))]
+pub type FloatArbitrary = arbitrary::Float;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::os::raw::{c_double, c_int};
+
+#[cfg(not(target_arch = "wasm32"))]
+extern "C" {
+    fn frexp(x: c_double, exp: *mut c_int) -> c_double;
+    fn ldexp(x: c_double, exp: c_int) -> c_double;
+}
+
+pub trait FloatExp: Sized {
+    fn frexp(self) -> (Self, i32);
+    fn ldexp(self, exp: i32) -> Self;
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FloatExp for f64 {
+    fn frexp(self) -> (Self, i32) {
+        let mut exp: c_int = 0;
+        let res = unsafe { frexp(self, &mut exp) };
+        (res, exp)
+    }
+
+    fn ldexp(self, exp: i32) -> Self {
+        unsafe { ldexp(self, exp) }
+    }
+}
+
+// There is no C library to link on wasm32, so the exponent is read from the bits
+#[cfg(target_arch = "wasm32")]
+impl FloatExp for f64 {
+    fn frexp(self) -> (Self, i32) {
+        if self == 0.0 || !self.is_finite() {
+            return (self, 0);
+        }
+
+        // Subnormal values are scaled up so that they have the implicit leading bit
+        let (value, offset) = if self.abs() < f64::MIN_POSITIVE {
+            (self * 2f64.powi(54), -54)
+        } else {
+            (self, 0)
+        };
+
+        let bits = value.to_bits();
+        let exponent = ((bits >> 52) & 0x7ff) as i32 - 1022;
+
+        (f64::from_bits((bits & !(0x7ff << 52)) | (1022 << 52)), exponent + offset)
+    }
+
+    fn ldexp(self, exp: i32) -> Self {
+        // As in scalbn from musl, the large powers are applied in steps which keep the value normal, so that it is
+        // only rounded by the last multiplication
+        let mut value = self;
+        let mut exp = exp;
+
+        if exp > 1023 {
+            value *= 2f64.powi(1023);
+            exp -= 1023;
+
+            if exp > 1023 {
+                value *= 2f64.powi(1023);
+                exp = (exp - 1023).min(1023);
+            }
+        } else if exp < -1022 {
+            value *= 2f64.powi(-1022 + 53);
+            exp += 1022 - 53;
+
+            if exp < -1022 {
+                value *= 2f64.powi(-1022 + 53);
+                exp = (exp + 1022 - 53).max(-1022);
+            }
+        }
+
+        value * f64::from_bits(((0x3ff + exp) as u64) << 52)
+    }
+}
+
+#[inline]
+pub fn to_fixed(value: &ComplexArbitrary) -> ComplexFixed<f64> {
+    let re = value.real().to_f64();
+    let im = value.imag().to_f64();
+
+    ComplexFixed::new(re, im)
+}
+
+pub fn to_extended(value: &ComplexArbitrary) -> ComplexExtended {
+    let (mut re, p1) = value.real().to_f64_exp();
+    let (mut im, p2) = value.imag().to_f64_exp();
+
+    let exponent = if p1 < p2 {
+        re *= 2f64.powi(p1 - p2);
+        p2
+    } else {
+        im *= 2f64.powi(p2 - p1);
+        p1
+    };
+
+    ComplexExtended::new2(re, im, exponent)
+}
+
+pub fn string_to_extended(string: &str) -> FloatExtended {
+    parse_extended(string).unwrap_or_else(|error| panic!("{}", error))
+}
+
+// Numbers are accepted as "1.5E100", "1.5e+100", "1.5*10^100", "10^100" or written out in full. The decimal exponent
+// is moved to the binary exponent directly, so values far outside the range of an f64 are not rounded to infinity.
+pub fn parse_extended(string: &str) -> Result<FloatExtended, String> {
+    let trimmed = string.chars().filter(|character| !character.is_whitespace() && *character != '_').collect::<String>();
+
+    let power = trimmed.find("10^").map(|index| (&trimmed[..index], &trimmed[(index + 3)..]));
+
+    let (mantissa, exponent) = match power {
+        Some(("", exponent)) => ("1", exponent),
+        Some((mantissa, exponent)) if mantissa.ends_with(&['*', 'x', 'X', '\u{d7}'][..]) => (&mantissa[..(mantissa.len() - mantissa.chars().last().unwrap().len_utf8())], exponent),
+        _ => match trimmed.find(&['E', 'e'][..]) {
+            Some(index) => (&trimmed[..index], &trimmed[(index + 1)..]),
+            None => (&trimmed[..], "")
+        }
+    };
+
+    let invalid = || format!("\"{}\" is not a valid number", string);
+
+    let exponent = if exponent.is_empty() {
+        0
+    } else {
+        exponent.parse::<i64>().map_err(|_| invalid())?
+    };
+
+    let (negative, mantissa) = match mantissa.strip_prefix('-') {
+        Some(mantissa) => (true, mantissa),
+        None => (false, mantissa.strip_prefix('+').unwrap_or(mantissa))
+    };
+
+    let mut parts = mantissa.splitn(2, '.');
+    let integer = parts.next().unwrap_or("");
+    let fraction = parts.next().unwrap_or("");
+
+    if (integer.is_empty() && fraction.is_empty()) || !integer.chars().chain(fraction.chars()).all(|character| character.is_ascii_digit()) {
+        return Err(invalid());
+    }
+
+    // The mantissa is reduced to the leading digits, so that long integers do not overflow
+    let digits = integer.to_owned() + fraction;
+    let leading_zeros = digits.len() - digits.trim_start_matches('0').len();
+    let significant = &digits[leading_zeros..];
+
+    if significant.is_empty() {
+        return Ok(FloatExtended::new(0.0, 0));
+    }
+
+    let first = ("0.".to_owned() + &significant[..significant.len().min(17)]).parse::<f64>().unwrap();
+    let first = if negative { -first } else { first };
+
+    let decimal_exponent = exponent.checked_add(integer.len() as i64 - leading_zeros as i64).ok_or_else(invalid)?;
+    let second = decimal_exponent as f64 * LOG2_10;
+
+    // The binary exponent is stored in an i32
+    if second.abs() >= i32::MAX as f64 {
+        return Err(format!("\"{}\" is too large or too small to be represented", string));
+    }
+
+    Ok(FloatExtended::new(first * 2.0f64.powf(second - second.floor()), second.floor() as i32))
+}
+
+// A zoom can also be given as the distance between neighbouring pixels with a "px" suffix, for example "2.5E-300px",
+// which is converted using the image height in the same way as the renderer spaces the pixels
+pub fn parse_zoom(string: &str, image_height: usize) -> Result<FloatExtended, String> {
+    let trimmed = string.trim();
+
+    let zoom = match trimmed.strip_suffix("px").or_else(|| trimmed.strip_suffix("PX")) {
+        Some(pixel_size) => {
+            let pixel_size = parse_extended(pixel_size)?;
+
+            if pixel_size.mantissa <= 0.0 {
+                return Err(format!("pixel size \"{}\" must be positive", string));
+            }
+
+            4.0 / (pixel_size * (image_height.max(2) - 1) as f64)
+        },
+        None => parse_extended(trimmed)?
+    };
+
+    if zoom.mantissa <= 0.0 {
+        return Err(format!("zoom \"{}\" must be positive", string));
+    }
+
+    Ok(zoom)
+}
+
+// A resolution is given in the form "1920x1080", with both sides at least one pixel
+pub fn parse_resolution(string: &str) -> Option<(usize, usize)> {
+    let (width, height) = string.to_ascii_lowercase().split_once('x')
+        .map(|(width, height)| (width.trim().parse::<usize>(), height.trim().parse::<usize>()))?;
+
+    match (width, height) {
+        (Ok(width), Ok(height)) if width > 0 && height > 0 => Some((width, height)),
+        _ => None
+    }
+}
+
+pub fn extended_to_string_short(value: FloatExtended) -> String {
+    let first = value.mantissa;
+    let second = value.exponent as f64 * LOG10_2;
+
+    if second < 0.0 {
+        format!("{:.2}E{}", first * 10.0f64.powf(1.0 + second.fract()), second.floor() as i32)
+    } else {
+        format!("{:.2}E{}", first * 10.0f64.powf(second.fract()), second.floor() as i32)
+    }
+}
+
+pub fn extended_to_string_long(value: FloatExtended) -> String {
+    let first = value.mantissa;
+    let second = value.exponent as f64 * LOG10_2;
+
+    if second < 0.0 {
+        format!("{}E{}", first * 10.0f64.powf(1.0 + second.fract()), second.floor() as i32)
+    } else {
+        format!("{}E{}", first * 10.0f64.powf(second.fract()), second.floor() as i32)
+    }
+}
+
+pub fn linear_interpolation_between_zoom(zoom1: FloatExtended, zoom2: FloatExtended, factor: f64) -> FloatExtended {
+    let temp = (1.0 - factor) * (zoom1.mantissa.log2() + zoom1.exponent as f64) + factor * (zoom2.mantissa.log2() + zoom2.exponent as f64);
+
+    FloatExtended::new(2.0f64.powf(temp - temp.floor()), temp.floor() as i32)
+}
+
+// The scale factor between frames so that the sequence from start to end zoom has the given number of frames
+pub fn get_zoom_scale_between(start_zoom: FloatExtended, end_zoom: FloatExtended, frames: usize) -> f64 {
+    let start = start_zoom.mantissa.log2() + start_zoom.exponent as f64;
+    let end = end_zoom.mantissa.log2() + end_zoom.exponent as f64;
+
+    2.0f64.powf((start - end) / (frames - 1) as f64)
+}
+
+// The pixels are square, so the centre is half of the pixel spacings across and down from the top left pixel. This
+// holds for any aspect, including portrait images and banners only a few pixels tall.
+pub fn get_delta_top_left(delta_pixel: f64, image_width: usize, image_height: usize, cos_rotate: f64, sin_rotate: f64) -> ComplexFixed<f64> {
+    let temp_real = -0.5 * (image_width - 1) as f64 * delta_pixel;
+    let temp_imag = -0.5 * (image_height - 1) as f64 * delta_pixel;
+
+    ComplexFixed::new(
+        temp_real * cos_rotate - temp_imag * sin_rotate, 
+        temp_real * sin_rotate + temp_imag * cos_rotate)
+}
+
+// The location of a pixel in a view, with the pixel coordinates measured from the top left of the image as rendered.
+// The result has the precision of the centre, which should be enough for the zoom of the view.
+pub fn pixel_to_complex(centre: &ComplexArbitrary, zoom: FloatExtended, image_width: usize, image_height: usize, rotate: f64, x: f64, y: f64) -> ComplexArbitrary {
+    let delta_pixel = 4.0 / ((image_height.max(2) - 1) as f64 * zoom.mantissa);
+
+    let cos_rotate = rotate.cos();
+    let sin_rotate = rotate.sin();
+
+    let delta_top_left = get_delta_top_left(delta_pixel, image_width, image_height, cos_rotate, sin_rotate);
+
+    let precision = centre.prec().0;
+
+    let mut delta_real = FloatArbitrary::with_val(precision, delta_top_left.re + (x * cos_rotate - y * sin_rotate) * delta_pixel);
+    let mut delta_imag = FloatArbitrary::with_val(precision, delta_top_left.im + (x * sin_rotate + y * cos_rotate) * delta_pixel);
+
+    delta_real >>= zoom.exponent;
+    delta_imag >>= zoom.exponent;
+
+    let mut location = centre.clone();
+
+    *location.mut_real() += delta_real;
+    *location.mut_imag() += delta_imag;
+
+    location
+}
+
+// The inverse of pixel_to_complex, the pixel coordinates of a location in a view. The difference from the centre is
+// taken at the precision of the location, so only the offset within the view needs to fit in a double.
+pub fn complex_to_pixel(centre: &ComplexArbitrary, zoom: FloatExtended, image_width: usize, image_height: usize, rotate: f64, location: &ComplexArbitrary) -> (f64, f64) {
+    let delta_pixel = 4.0 / ((image_height.max(2) - 1) as f64 * zoom.mantissa);
+
+    let cos_rotate = rotate.cos();
+    let sin_rotate = rotate.sin();
+
+    let delta_top_left = get_delta_top_left(delta_pixel, image_width, image_height, cos_rotate, sin_rotate);
+
+    let mut delta = location.clone() - centre;
+
+    *delta.mut_real() <<= zoom.exponent;
+    *delta.mut_imag() <<= zoom.exponent;
+
+    let delta_real = delta.real().to_f64() - delta_top_left.re;
+    let delta_imag = delta.imag().to_f64() - delta_top_left.im;
+
+    ((delta_real * cos_rotate + delta_imag * sin_rotate) / delta_pixel,
+        (delta_imag * cos_rotate - delta_real * sin_rotate) / delta_pixel)
+}
+
+// In an exponential map each column is an angle and each row is a logarithmic radius, the first row being the outermost
+// A keyframe covers a zoom factor of exp(2 * pi * image_height / image_width)
+pub fn get_exponential_map_delta(i: f64, j: f64, image_width: usize, outer_radius: f64, rotate: f64) -> ComplexFixed<f64> {
+    let angle = 2.0 * PI * (i + 0.5) / image_width as f64 + rotate;
+    let radius = outer_radius * (-2.0 * PI * (j + 0.5) / image_width as f64).exp();
+
+    ComplexFixed::new(radius * angle.cos(), radius * angle.sin())
+}
+
+pub fn get_exponential_map_scale_factor(image_width: usize, image_height: usize) -> f64 {
+    (2.0 * PI * image_height as f64 / image_width as f64).exp()
+}
+
+#[inline]
+pub fn srgb_to_linear(value: u8) -> f32 {
+    let value = value as f32 / 255.0;
+
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+#[inline]
+pub fn linear_to_srgb(value: f32) -> u8 {
+    let value = if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    };
+
+    (value * 255.0).round().clamp(0.0, 255.0) as u8
+}
+
+// The automatic order grows with the number of pixels, as more pixels share the time spent on the approximation. The
+// aggressiveness scales the automatic order, so that a location can trade time on the approximation for iteration time.
+pub fn get_approximation_terms(approximation_order: usize, aggressiveness: f64, image_width: usize, image_height: usize) -> usize {
+    if approximation_order == 0 {
+        let auto = (((image_width * image_height) as f64).log(1e6).powf(6.619) * 16.0f64 * aggressiveness) as usize;
+        auto.clamp(3, 64)
+    } else {
+        approximation_order
+    }
+}
+
+// Location files from Kalles Fraktaler and Fractint are converted, any other format is read by the config crate
+pub fn merge_location_file(settings: &mut Config, filename: &str) {
+    if kfr::is_kfr(filename) {
+        kfr::read_kfr(settings, filename);
+    } else if fractint::is_fractint(filename) {
+        fractint::read_fractint(settings, filename);
+    } else {
+        settings.merge(File::with_name(filename).required(true)).unwrap();
+    }
+}
+
+// Returns the palette colours and the interpolated palette, using the default palette if none is specified
+pub fn get_palette(settings: &Config) -> (Vec<Color>, Vec<Color>) {
+    // The colours given in the settings are used before a palette chosen by name
+    let colour_values = match settings.get_array("palette") {
+        Ok(values) => Some(values.into_iter().map(|value| value.into_int().unwrap()).collect::<Vec<i64>>()),
+        Err(_) => settings.get_str("palette_name").ok().and_then(|name| palettes::get_named_palette(&name))
+    };
+
+    if let Some(colour_values) = colour_values {
+        let mut colors = colour_values.chunks_exact(3).map(|value| {
+            Color::from_rgb_u8(value[0] as u8, value[1] as u8, value[2] as u8)
+        }).collect::<Vec<Color>>();
+
+        if colors[0] != *colors.last().unwrap() {
+            colors.push(colors[0].clone());
+        };
+
+        let mut number_colors = colors.len();
+
+        if settings.get_bool("palette_cyclic").unwrap_or(true) {
+            number_colors -= 1;
+        }
+
+        let palette_generator = CustomGradient::new()
+            .colors(&colors[0..number_colors])
+            .interpolation(Interpolation::CatmullRom)
+            .mode(BlendMode::Oklab)
+            .build().unwrap();
+
+        (colors, palette_generator.colors(number_colors * 64))
+    } else {
+        generate_default_palette()
+    }
+}
+
+pub fn generate_default_palette() -> (Vec<Color>, Vec<Color>) {
+    let palette_generator = CustomGradient::new()
+        .colors(
+            &[Color::from_rgb_u8(0, 2, 0),
+            Color::from_rgb_u8(0, 7, 100), 
+            Color::from_rgb_u8(32, 107, 203), 
+            Color::from_rgb_u8(237, 255, 255),
+            Color::from_rgb_u8(255, 170, 0),
+            Color::from_rgb_u8(0, 2, 0)])
+            .domain(&[0.0, 0.1425, 0.3025, 0.5625, 0.785, 1.0])
+            .interpolation(Interpolation::CatmullRom).mode(BlendMode::Oklab)
+            .build().unwrap();
+
+    (palette_generator.colors(6), palette_generator.colors(6 * 64))
+}
+
+#[derive(Clone)]
+pub struct PixelData {
+    pub index: usize,
+    pub iteration: usize,
+    pub delta_reference: ComplexExtended,
+    pub delta_current: ComplexExtended,
+    pub derivative_current: ComplexExtended,
+    pub glitched: bool,
+    pub z_norm: f64,
+    pub stripe_storage: [ComplexFixed<f64>; 4],
+    pub stripe_iteration: usize,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum FractalType {
+    Mandelbrot2,
+    Mandelbrot3
+}
\ No newline at end of file
@@ -0,0 +1,55 @@
+// The palettes which can be chosen with palette_name instead of listing the colours. Each is given as the colours of a
+// palette setting, so it is interpolated and repeated in the same way. The classic palette is the default gradient,
+// which has its colours at uneven positions and so is generated separately.
+pub const PALETTE_NAMES: [&str; 6] = ["classic", "grayscale", "fire", "ocean", "viridis", "rainbow"];
+
+pub fn get_named_palette(name: &str) -> Option<Vec<i64>> {
+    let colours: &[i64] = match name.to_ascii_lowercase().as_str() {
+        "grayscale" | "greyscale" => &[
+            0, 0, 0,
+            255, 255, 255],
+        "fire" => &[
+            0, 0, 0,
+            128, 0, 0,
+            255, 64, 0,
+            255, 192, 0,
+            255, 255, 192,
+            255, 160, 0,
+            160, 32, 0],
+        "ocean" => &[
+            0, 8, 32,
+            0, 48, 96,
+            0, 112, 160,
+            64, 192, 208,
+            224, 248, 255,
+            32, 144, 176,
+            0, 64, 112],
+        // Sampled from the viridis colour map and run back again, so that the palette repeats without a jump
+        "viridis" => &[
+            68, 1, 84,
+            59, 82, 139,
+            33, 145, 140,
+            94, 201, 98,
+            253, 231, 37,
+            94, 201, 98,
+            33, 145, 140,
+            59, 82, 139],
+        "rainbow" => &[
+            255, 0, 0,
+            255, 160, 0,
+            255, 255, 0,
+            0, 200, 0,
+            0, 160, 255,
+            64, 0, 192,
+            192, 0, 160],
+        _ => return None
+    };
+
+    Some(colours.to_vec())
+}
+
+pub fn is_palette_name(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+
+    name == "greyscale" || PALETTE_NAMES.contains(&name.as_str())
+}
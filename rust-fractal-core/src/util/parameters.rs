@@ -0,0 +1,445 @@
+use crate::util::{FloatExtended, extended_to_string_long, parse_zoom};
+
+use config::{Config, Environment, File, FileFormat};
+use schemars::{JsonSchema, schema_for};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use std::f64::consts::LOG10_2;
+use std::fs;
+
+// Every setting which describes a render, with the defaults used when a key is not given. The settings files are read
+// into this so that the renderer does not look up keys individually, and so that a parameter set can be saved, diffed
+// and embedded in the outputs. The keys which are not set have the same names as in the settings files.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct RenderParameters {
+    pub real: String,
+    pub imag: String,
+    pub zoom: String,
+    pub iterations: usize,
+    pub rotate: f64,
+    pub julia_real: Option<String>,
+    pub julia_imag: Option<String>,
+
+    pub image_width: usize,
+    pub image_height: usize,
+    pub supersampling: usize,
+    pub supersampling_filter: String,
+    pub crop_margin: Option<usize>,
+    pub exponential_map: bool,
+    pub remove_centre: bool,
+    pub view_fit: String,
+    pub region: Vec<f64>,
+    pub region_fraction: bool,
+    pub image_offset: Vec<f64>,
+
+    pub approximation_order: usize,
+    pub approximation_aggressiveness: f64,
+    pub approximation_maximum_skip: f64,
+    pub glitch_percentage: f64,
+    pub glitch_tolerance: f64,
+    pub probe_sampling: usize,
+    pub data_storage_interval: usize,
+    pub valid_iteration_probe_multiplier: f64,
+    pub series_approximation_tiled: bool,
+    pub series_approximation_enabled: bool,
+    pub auto_adjust_iterations: bool,
+    pub automatic_iterations: bool,
+    pub automatic_iterations_threshold: f64,
+    pub automatic_iterations_limit: usize,
+
+    pub frames: usize,
+    pub frame_offset: usize,
+    pub zoom_scale: f64,
+    pub end_zoom: Option<String>,
+    pub end_rotate: Option<f64>,
+    pub end_palette_iteration_span: Option<f64>,
+    pub end_palette_offset: Option<f64>,
+    pub sequence_direction: String,
+    pub reverse_numbering: bool,
+    pub parallel_keyframes: usize,
+    pub parallel_keyframe_zoom: f64,
+    pub keyframe_iterations: String,
+    pub motion_blur_samples: usize,
+    pub motion_blur_shutter: f64,
+    pub recentre: String,
+    pub recentre_radius: f64,
+
+    pub coloring_type: String,
+    pub coloring_plugin: Option<String>,
+    pub palette: Option<Vec<i64>>,
+    pub palette_name: Option<String>,
+    pub palette_iteration_span: f64,
+    pub palette_offset: f64,
+    pub palette_cyclic: bool,
+    pub display_glitches: bool,
+    pub glitch_colour: Vec<i64>,
+    pub glitch_overlay: bool,
+    pub distance_color: bool,
+    pub distance_transition: f64,
+    pub stripe_scale: f64,
+    pub lighting: bool,
+    pub lighting_direction: f64,
+    pub lighting_azimuth: f64,
+    pub lighting_opacity: f64,
+    pub lighting_ambient: f64,
+    pub lighting_diffuse: f64,
+    pub lighting_specular: f64,
+    pub lighting_shininess: i32,
+
+    pub jitter: bool,
+    pub jitter_factor: f64,
+    pub jitter_seed: Option<u64>,
+    pub samples_per_pixel: usize,
+    pub adaptive_antialiasing_samples: usize,
+    pub adaptive_antialiasing_threshold: f64,
+    pub deterministic: bool,
+
+    pub pixel_chunk_size: usize,
+    pub pixel_order: String,
+    pub solid_guessing: bool,
+    pub solid_guessing_tile: usize,
+    pub progressive: bool,
+    pub progressive_export: bool,
+    pub memory_limit: usize,
+    pub threads: usize,
+
+    pub name: String,
+    pub export: String,
+    pub filename_template: String,
+    pub output_dir: Option<String>,
+    pub output_subfolder: bool,
+    pub heightmap: String,
+    pub mesh: String,
+    pub mesh_scale: f64,
+    pub mesh_decimation: usize,
+    pub overwrite_protection: bool,
+    pub export_statistics: bool,
+    pub history: bool,
+    pub history_file: String,
+    pub export_glitch_map: bool,
+    pub export_histogram: bool,
+    pub histogram_bins: usize,
+    pub export_reference_orbit: bool,
+    pub reference_orbit_overlay: bool,
+    pub reference_orbit_points: usize,
+    pub raw_channels: Vec<String>,
+    pub resume: bool,
+    pub checkpoint_interval: usize,
+    pub continue_from: Option<String>,
+    pub reference_cache: bool,
+    pub reference_cache_directory: String,
+    pub show_output: bool,
+    pub log_format: String,
+}
+
+impl Default for RenderParameters {
+    fn default() -> Self {
+        RenderParameters {
+            real: String::from("-0.75"),
+            imag: String::from("0.0"),
+            zoom: String::from("1E0"),
+            iterations: 1000,
+            rotate: 0.0,
+            julia_real: None,
+            julia_imag: None,
+
+            image_width: 1000,
+            image_height: 1000,
+            supersampling: 1,
+            supersampling_filter: String::from("lanczos"),
+            crop_margin: None,
+            exponential_map: false,
+            remove_centre: false,
+            view_fit: String::from("height"),
+            region: Vec::new(),
+            region_fraction: false,
+            image_offset: Vec::new(),
+
+            approximation_order: 0,
+            approximation_aggressiveness: 1.0,
+            approximation_maximum_skip: 1.0,
+            glitch_percentage: 0.001,
+            glitch_tolerance: 1.4e-6,
+            probe_sampling: 3,
+            data_storage_interval: 10,
+            valid_iteration_probe_multiplier: 0.02,
+            series_approximation_tiled: true,
+            series_approximation_enabled: true,
+            auto_adjust_iterations: true,
+            automatic_iterations: false,
+            automatic_iterations_threshold: 0.001,
+            automatic_iterations_limit: 100_000_000,
+
+            frames: 1,
+            frame_offset: 0,
+            zoom_scale: 2.0,
+            end_zoom: None,
+            end_rotate: None,
+            end_palette_iteration_span: None,
+            end_palette_offset: None,
+            sequence_direction: String::from("out"),
+            reverse_numbering: false,
+            parallel_keyframes: 0,
+            parallel_keyframe_zoom: 1e10,
+            keyframe_iterations: String::from("constant"),
+            motion_blur_samples: 1,
+            motion_blur_shutter: 0.5,
+            recentre: String::from("none"),
+            recentre_radius: 0.1,
+
+            coloring_type: String::from("smooth_iteration"),
+            coloring_plugin: None,
+            palette: None,
+            palette_name: None,
+            palette_iteration_span: 100.0,
+            palette_offset: 0.0,
+            palette_cyclic: true,
+            display_glitches: false,
+            glitch_colour: vec![255, 0, 0],
+            glitch_overlay: false,
+            distance_color: false,
+            distance_transition: 0.0,
+            stripe_scale: 1.0,
+            lighting: true,
+            lighting_direction: 30.0,
+            lighting_azimuth: 35.0,
+            lighting_opacity: 0.75,
+            lighting_ambient: 0.4,
+            lighting_diffuse: 0.5,
+            lighting_specular: 0.5,
+            lighting_shininess: 20,
+
+            jitter: false,
+            jitter_factor: 0.2,
+            jitter_seed: None,
+            samples_per_pixel: 1,
+            adaptive_antialiasing_samples: 0,
+            adaptive_antialiasing_threshold: 24.0,
+            deterministic: false,
+
+            pixel_chunk_size: 0,
+            pixel_order: String::from("scanline"),
+            solid_guessing: false,
+            solid_guessing_tile: 64,
+            progressive: false,
+            progressive_export: false,
+            memory_limit: 0,
+            threads: 0,
+
+            name: String::from("render"),
+            export: String::from("colour"),
+            filename_template: String::from("output/{index}_{zoom}"),
+            output_dir: None,
+            output_subfolder: false,
+            heightmap: String::from("none"),
+            mesh: String::from("none"),
+            mesh_scale: 0.1,
+            mesh_decimation: 1,
+            overwrite_protection: false,
+            export_statistics: false,
+            history: false,
+            history_file: String::from("output/history.jsonl"),
+            export_glitch_map: false,
+            export_histogram: false,
+            histogram_bins: 256,
+            export_reference_orbit: false,
+            reference_orbit_overlay: false,
+            reference_orbit_points: 4096,
+            raw_channels: vec![String::from("iterations"), String::from("smooth"), String::from("distance")],
+            resume: false,
+            checkpoint_interval: 0,
+            continue_from: None,
+            reference_cache: false,
+            reference_cache_directory: String::from("output/cache"),
+            show_output: true,
+            log_format: String::from("text"),
+        }
+    }
+}
+
+impl RenderParameters {
+    // The keys which are not parameters, such as the camera path and easing curves, are ignored
+    pub fn from_config(settings: &Config) -> Self {
+        let mut settings = settings.clone();
+        let automatic_iterations = RenderParameters::replace_automatic_iterations(&mut settings);
+
+        let mut parameters: RenderParameters = settings.try_into().unwrap_or_else(|error| panic!("settings are not valid: {}", error));
+
+        // Zooms given as a pixel size or fitting another side of the image are converted here, so that the rest of
+        // the renderer only sees plain zooms fitting the height
+        let image_height = parameters.image_height;
+        let fit_scale = parameters.get_view_fit_scale();
+
+        let resolve = |zoom: &str| if zoom.trim().to_ascii_lowercase().ends_with("px") {
+            extended_to_string_long(parse_zoom(zoom, image_height).unwrap_or_else(|error| panic!("settings are not valid: {}", error)))
+        } else if fit_scale != 1.0 {
+            parse_zoom(zoom, image_height).map(|zoom| extended_to_string_long(zoom * fit_scale)).unwrap_or_else(|_| zoom.to_owned())
+        } else {
+            zoom.to_owned()
+        };
+
+        parameters.zoom = resolve(&parameters.zoom);
+        parameters.end_zoom = parameters.end_zoom.as_ref().map(|zoom| resolve(zoom));
+        parameters.view_fit = String::from("height");
+
+        if automatic_iterations {
+            parameters.automatic_iterations = true;
+            parameters.iterations = parse_zoom(&parameters.zoom, image_height)
+                .map(RenderParameters::get_automatic_iterations)
+                .unwrap_or(1000);
+        }
+
+        parameters
+    }
+
+    // A zoom of one is a view four units across the side of the image chosen by the fit, which is the height unless
+    // set otherwise. This is the factor which converts the zoom to the zoom fitting the height. The exponential map
+    // always covers the same radius, so it has no fit.
+    pub fn get_view_fit_scale(&self) -> f64 {
+        let width = (self.image_width.max(2) - 1) as f64;
+        let height = (self.image_height.max(2) - 1) as f64;
+
+        let span = match self.view_fit.to_ascii_lowercase().as_str() {
+            "width" => width,
+            "diagonal" => width.hypot(height),
+            "shorter" => width.min(height),
+            "longer" => width.max(height),
+            _ => height
+        };
+
+        if self.exponential_map {
+            1.0
+        } else {
+            span / height
+        }
+    }
+
+    // The view is moved by the offset, given as x and y in pixels of the output image before the rotation, without
+    // moving the centre. The work units of a frame split between workers share the reference at the centre this way.
+    pub fn get_image_offset(&self) -> (f64, f64) {
+        match self.image_offset.as_slice() {
+            [x, y] => (*x, *y),
+            _ => (0.0, 0.0)
+        }
+    }
+
+    // The region is given as x, y, width and height from the top left of the image, in pixels or as fractions of the
+    // image size. It is rounded to whole pixels and clipped to the image, so that the crop lines up with the pixels of
+    // the full render.
+    pub fn get_region(&self) -> Option<(usize, usize, usize, usize)> {
+        if self.region.len() != 4 {
+            return None;
+        }
+
+        let (scale_x, scale_y) = if self.region_fraction {
+            (self.image_width as f64, self.image_height as f64)
+        } else {
+            (1.0, 1.0)
+        };
+
+        let x = (self.region[0] * scale_x).round().max(0.0) as usize;
+        let y = (self.region[1] * scale_y).round().max(0.0) as usize;
+
+        if x >= self.image_width || y >= self.image_height {
+            return None;
+        }
+
+        let width = ((self.region[2] * scale_x).round().max(0.0) as usize).min(self.image_width - x);
+        let height = ((self.region[3] * scale_y).round().max(0.0) as usize).min(self.image_height - y);
+
+        if width == 0 || height == 0 {
+            None
+        } else {
+            Some((x, y, width, height))
+        }
+    }
+
+    // The iterations can be "auto", which starts from an estimate and raises it while rendering. The value is replaced
+    // by a count so that the settings can be read as parameters, and whether it was automatic is returned.
+    pub fn replace_automatic_iterations(settings: &mut Config) -> bool {
+        let automatic_iterations = matches!(settings.get_str("iterations"), Ok(iterations) if iterations.trim().eq_ignore_ascii_case("auto"));
+
+        if automatic_iterations {
+            settings.set("iterations", 1000i64).unwrap();
+        }
+
+        automatic_iterations
+    }
+
+    // The starting estimate of the automatic iterations, which grows with the number of decimal digits of the zoom.
+    // It only needs to be close, as the frame is continued with more iterations if too many pixels reach it.
+    pub fn get_automatic_iterations(zoom: FloatExtended) -> usize {
+        let digits = (zoom.mantissa.log2() + zoom.exponent as f64) * LOG10_2;
+
+        (1000.0 + 2000.0 * digits.max(0.0)) as usize
+    }
+
+    // The environment variables override the settings files. The keys are in upper case after the prefix, for
+    // example RUST_FRACTAL_ITERATIONS=100000 or RUST_FRACTAL_IMAGE_WIDTH=1920.
+    pub fn apply_environment(settings: &mut Config) {
+        settings.merge(Environment::with_prefix("RUST_FRACTAL")).expect("environment overrides are not valid");
+    }
+
+    // A JSON schema of the parameters with their types and defaults, which the TOML and JSON settings files follow
+    pub fn get_schema() -> String {
+        serde_json::to_string_pretty(&schema_for!(RenderParameters)).unwrap()
+    }
+
+    // The settings which the parameters were read from can have additional keys, these are kept
+    pub fn to_config(&self, settings: &Config) -> Config {
+        let mut settings = settings.clone();
+
+        settings.merge(File::from_str(&self.to_json(), FileFormat::Json)).unwrap();
+        settings
+    }
+
+    // The unset optional values are left out, as the settings files have no way to represent them
+    pub fn to_json(&self) -> String {
+        let value = match serde_json::to_value(self).unwrap() {
+            Value::Object(map) => Value::Object(map.into_iter().filter(|(_, value)| !value.is_null()).collect()),
+            value => value
+        };
+
+        serde_json::to_string(&value).unwrap()
+    }
+
+    pub fn save(&self, filename: &str) {
+        let value = serde_json::from_str::<Value>(&self.to_json()).unwrap();
+
+        fs::write(filename, serde_json::to_string_pretty(&value).unwrap()).unwrap();
+    }
+
+    // Any format the settings files can be written in is accepted
+    pub fn load(filename: &str) -> Self {
+        let mut settings = Config::default();
+        settings.merge(File::with_name(filename)).unwrap();
+
+        RenderParameters::from_config(&settings)
+    }
+
+    // The parameters embedded in an exported image, if it has them
+    pub fn from_metadata(metadata: &[(String, String)]) -> Option<Self> {
+        metadata.iter()
+            .find(|(key, _)| key == "parameters")
+            .and_then(|(_, value)| serde_json::from_str(value).ok())
+    }
+
+    // The keys which differ between the parameter sets, with the values of each
+    pub fn diff(&self, other: &RenderParameters) -> Vec<(String, Value, Value)> {
+        let first = serde_json::to_value(self).unwrap();
+        let second = serde_json::to_value(other).unwrap();
+
+        match (first, second) {
+            (Value::Object(first), Value::Object(second)) => first.into_iter()
+                .filter(|(key, value)| second[key] != *value)
+                .map(|(key, value)| {
+                    let other = second[&key].clone();
+                    (key, value, other)
+                })
+                .collect(),
+            _ => Vec::new()
+        }
+    }
+}
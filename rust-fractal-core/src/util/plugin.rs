@@ -0,0 +1,91 @@
+#[cfg(not(target_arch = "wasm32"))]
+use wasmtime::{Engine, Instance, Module, Store, TypedFunc};
+
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+// A colouring function loaded from a WASM module at runtime. The module must not import anything, and exports
+//
+//     colour(iterations: i32, smooth: f32, stripe: f32, distance_x: f32, distance_y: f32, maximum_iteration: i32) -> i32
+//
+// which returns the colour of the pixel as 0xRRGGBB. It is called for the pixels which escaped, the pixels inside the
+// set are black and the glitched pixels are coloured as usual. The smooth value is the fractional iteration added to
+// the iterations. The stripe and distance channels are only calculated when the coloring type needs them, so
+// `coloring_type = "distance_stripe"` gives all of them. The module is called once per pixel, in any order, so it
+// should not keep state between calls. The pixels on which the module traps are given the fallback colour, and
+// the first trap of a frame is kept to be reported.
+//
+// Only colourings can be plugins for now, formula plugins are not implemented. A formula plugin would have to give the
+// step of the reference orbit in arbitrary precision as well as the step of the perturbed delta, with the reference
+// orbit imported from the host, and the series approximation and glitch detection would be turned off for it.
+//
+// Wasmtime does not build for wasm32, so in the browser the plugins cannot be loaded.
+pub struct ColoringPlugin {
+    #[cfg(not(target_arch = "wasm32"))]
+    store: Store<()>,
+    #[cfg(not(target_arch = "wasm32"))]
+    colour: TypedFunc<(i32, f32, f32, f32, f32, i32), i32>,
+    trap: Option<String>,
+}
+
+impl ColoringPlugin {
+    #[cfg(target_arch = "wasm32")]
+    pub fn load(filename: &str) -> Result<Self, String> {
+        Err(format!("could not load the coloring plugin {}: plugins are not supported on wasm32", filename))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(filename: &str) -> Result<Self, String> {
+        let engine = Engine::default();
+
+        let module = Module::from_file(&engine, filename)
+            .map_err(|error| format!("could not load the coloring plugin {}: {}", filename, error))?;
+
+        let mut store = Store::new(&engine, ());
+
+        let instance = Instance::new(&mut store, &module, &[])
+            .map_err(|error| format!("could not instantiate the coloring plugin {}: {}", filename, error))?;
+
+        let colour = instance.get_typed_func::<(i32, f32, f32, f32, f32, i32), i32, _>(&mut store, "colour")
+            .map_err(|error| format!("the coloring plugin {} does not export a valid colour function: {}", filename, error))?;
+
+        Ok(ColoringPlugin {
+            store,
+            colour,
+            trap: None,
+        })
+    }
+
+    // The plugin is shared by the copies of the data export
+    pub fn load_shared(filename: &Option<String>) -> Option<Arc<Mutex<ColoringPlugin>>> {
+        filename.as_ref().map(|filename| {
+            Arc::new(Mutex::new(ColoringPlugin::load(filename).unwrap_or_else(|error| panic!("{}", error))))
+        })
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn colour(&mut self, _iterations: u32, _smooth: f32, _stripe: f32, _distance_x: f32, _distance_y: f32, _maximum_iteration: usize, fallback: [u8; 3]) -> [u8; 3] {
+        fallback
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[inline]
+    pub fn colour(&mut self, iterations: u32, smooth: f32, stripe: f32, distance_x: f32, distance_y: f32, maximum_iteration: usize, fallback: [u8; 3]) -> [u8; 3] {
+        match self.colour.call(&mut self.store, (iterations as i32, smooth, stripe, distance_x, distance_y, maximum_iteration as i32)) {
+            Ok(value) => [(value >> 16) as u8, (value >> 8) as u8, value as u8],
+            Err(trap) => {
+                if self.trap.is_none() {
+                    self.trap = Some(trap.to_string());
+                }
+
+                fallback
+            }
+        }
+    }
+
+    // The first trap since this was last called
+    pub fn take_trap(&mut self) -> Option<String> {
+        self.trap.take()
+    }
+}
@@ -0,0 +1,85 @@
+use config::{Config, File, FileFormat};
+
+pub struct PresetLocation {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub contents: &'static str,
+}
+
+// The locations are compiled in, so that they can be rendered without the locations folder and so that the benchmark
+// renders exactly the same set in every version. They are ordered from the shallowest to the deepest.
+pub const PRESET_LOCATIONS: [PresetLocation; 12] = [
+    PresetLocation {
+        name: "whole_set",
+        description: "the whole Mandelbrot set",
+        contents: "real = \"-0.75\"\nimag = \"0.0\"\nzoom = \"1E0\"\niterations = 1000\n",
+    },
+    PresetLocation {
+        name: "seahorse_valley",
+        description: "the valley between the main cardioid and the period 2 bulb",
+        contents: "real = \"-0.7453\"\nimag = \"0.1127\"\nzoom = \"4E2\"\niterations = 2000\n",
+    },
+    PresetLocation {
+        name: "spiral",
+        description: "a shallow spiral",
+        contents: include_str!("../../data/locations/spiral.toml"),
+    },
+    PresetLocation {
+        name: "e14",
+        description: "a shallow location within the range of double precision",
+        contents: include_str!("../../data/benchmarks/1e14.toml"),
+    },
+    PresetLocation {
+        name: "e50",
+        description: "a location past the range of double precision",
+        contents: include_str!("../../data/benchmarks/1e50.toml"),
+    },
+    PresetLocation {
+        name: "glitch_test",
+        description: "a location with many glitches to correct",
+        contents: include_str!("../../data/locations/glitch_test_2.toml"),
+    },
+    PresetLocation {
+        name: "tick_tock",
+        description: "a pattern of repeated features",
+        contents: include_str!("../../data/locations/tick_tock.toml"),
+    },
+    PresetLocation {
+        name: "flake",
+        description: "an embedded Julia set near the tip of the needle",
+        contents: include_str!("../../data/locations/flake.toml"),
+    },
+    PresetLocation {
+        name: "dragon",
+        description: "a deep location near the period 2 bulb",
+        contents: include_str!("../../data/locations/dragon.toml"),
+    },
+    PresetLocation {
+        name: "e227",
+        description: "a deep location with a high iteration count",
+        contents: include_str!("../../data/benchmarks/5e227.toml"),
+    },
+    PresetLocation {
+        name: "e1086",
+        description: "a very deep location which needs the extended exponent",
+        contents: include_str!("../../data/benchmarks/1e1086.toml"),
+    },
+    PresetLocation {
+        name: "deep_minibrot",
+        description: "a minibrot past a zoom of 1E2000",
+        contents: include_str!("../../data/locations/wfs_mb.toml"),
+    },
+];
+
+pub fn get_preset(name: &str) -> Option<&'static PresetLocation> {
+    PRESET_LOCATIONS.iter().find(|preset| preset.name.eq_ignore_ascii_case(name))
+}
+
+// The preset is merged in the same way as a location file, so the keys it does not set are kept
+pub fn apply_preset(settings: &mut Config, name: &str) {
+    let preset = get_preset(name).unwrap_or_else(|| panic!("there is no preset location named {}, it should be one of {}",
+        name,
+        PRESET_LOCATIONS.iter().map(|preset| preset.name).collect::<Vec<&str>>().join(", ")));
+
+    settings.merge(File::from_str(preset.contents, FileFormat::Toml)).unwrap();
+}
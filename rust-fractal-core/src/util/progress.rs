@@ -0,0 +1,207 @@
+use serde_json::json;
+
+use std::sync::{Arc, mpsc, atomic::{AtomicUsize, Ordering}};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum RenderStage {
+    Reference,
+    SeriesApproximation,
+    Iteration,
+    Correction,
+    Saving
+}
+
+impl RenderStage {
+    fn from_usize(value: usize) -> Self {
+        match value {
+            0 => RenderStage::Reference,
+            1 => RenderStage::SeriesApproximation,
+            2 => RenderStage::Iteration,
+            3 => RenderStage::Correction,
+            _ => RenderStage::Saving
+        }
+    }
+}
+
+// A snapshot of the progress of the current frame. The fraction and ETA are for the current stage only,
+// as the length of the later stages is not known until they start.
+#[derive(Clone, Debug)]
+pub struct ProgressReport {
+    pub zoom: String,
+    pub stage: RenderStage,
+    pub stage_fraction: f64,
+    pub reference_iteration: usize,
+    pub reference_maximum: usize,
+    pub pixels_iterated: usize,
+    pub total_pixels: usize,
+    pub glitched_remaining: usize,
+    pub elapsed: Duration,
+    pub stage_elapsed: Duration,
+    pub stage_eta: Option<Duration>,
+}
+
+impl ProgressReport {
+    // Used for the line delimited progress events, durations are in milliseconds
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "event": "progress",
+            "zoom": self.zoom,
+            "stage": format!("{:?}", self.stage).to_ascii_lowercase(),
+            "stage_fraction": self.stage_fraction,
+            "reference_iteration": self.reference_iteration,
+            "reference_maximum": self.reference_maximum,
+            "pixels_iterated": self.pixels_iterated,
+            "total_pixels": self.total_pixels,
+            "glitched_remaining": self.glitched_remaining,
+            "elapsed": self.elapsed.as_millis() as u64,
+            "stage_elapsed": self.stage_elapsed.as_millis() as u64,
+            "stage_eta": self.stage_eta.map(|eta| eta.as_millis() as u64),
+        })
+    }
+}
+
+// The counters are shared, so a clone can be given to a thread which reports on the frame
+#[derive(Clone)]
+pub struct ProgressCounters {
+    pub reference: Arc<AtomicUsize>,
+    pub reference_maximum: Arc<AtomicUsize>,
+    pub reference_count: Arc<AtomicUsize>,
+    pub series_approximation: Arc<AtomicUsize>,
+    pub min_series_approximation: Arc<AtomicUsize>,
+    pub max_series_approximation: Arc<AtomicUsize>,
+    pub series_validation: Arc<AtomicUsize>,
+    pub iteration: Arc<AtomicUsize>,
+    pub glitched_maximum: Arc<AtomicUsize>,
+    pub stage: Arc<AtomicUsize>,
+}
+
+impl ProgressCounters {
+    pub fn new(maximum_iteration: usize) -> ProgressCounters {
+        ProgressCounters {
+            reference: Arc::new(AtomicUsize::new(1)),
+            reference_maximum: Arc::new(AtomicUsize::new(maximum_iteration - 1)),
+            reference_count: Arc::new(AtomicUsize::new(1)),
+            series_approximation: Arc::new(AtomicUsize::new(0)),
+            min_series_approximation: Arc::new(AtomicUsize::new(1)),
+            max_series_approximation: Arc::new(AtomicUsize::new(1)),
+            series_validation: Arc::new(AtomicUsize::new(0)),
+            iteration: Arc::new(AtomicUsize::new(0)),
+            glitched_maximum: Arc::new(AtomicUsize::new(0)),
+            stage: Arc::new(AtomicUsize::new(0))
+        }
+    }
+
+    // TODO just set these to zero rather than reset
+    // Reset without the series approximation changed
+    pub fn reset(&mut self) {
+        self.min_series_approximation.store(1, Ordering::SeqCst);
+        self.max_series_approximation.store(1, Ordering::SeqCst);
+        self.series_validation.store(0, Ordering::SeqCst);
+        self.iteration.store(0, Ordering::SeqCst);
+        self.glitched_maximum.store(0, Ordering::SeqCst);
+        self.reference_count.store(1, Ordering::SeqCst);
+        self.stage.store(RenderStage::Reference as usize, Ordering::SeqCst);
+    }
+
+    // TODO just set these to zero rather than reset
+    // Reset with the series approximation changed
+    pub fn reset_series_approximation(&mut self) {
+        self.series_approximation.store(0, Ordering::SeqCst);
+    }
+
+    pub fn reset_all(&mut self, maximum_iteration: usize) {
+        self.min_series_approximation.store(1, Ordering::SeqCst);
+        self.max_series_approximation.store(1, Ordering::SeqCst);
+        self.series_validation.store(0, Ordering::SeqCst);
+        self.iteration.store(0, Ordering::SeqCst);
+        self.glitched_maximum.store(0, Ordering::SeqCst);
+        self.series_approximation.store(0, Ordering::SeqCst);
+        self.reference.store(1, Ordering::SeqCst);
+        self.reference_maximum.store(maximum_iteration - 1, Ordering::SeqCst);
+        self.reference_count.store(1, Ordering::SeqCst);
+        self.stage.store(RenderStage::Reference as usize, Ordering::SeqCst);
+    }
+
+    pub fn get_report(&self, zoom: &str, total_pixels: usize, elapsed: Duration, stage_elapsed: Duration) -> ProgressReport {
+        let stage = RenderStage::from_usize(self.stage.load(Ordering::SeqCst));
+
+        let reference_iteration = self.reference.load(Ordering::Relaxed);
+        let reference_maximum = self.reference_maximum.load(Ordering::Relaxed);
+        let pixels_iterated = self.iteration.load(Ordering::Relaxed);
+        let glitched_maximum = self.glitched_maximum.load(Ordering::Relaxed);
+
+        // The glitched pixels are counted again as they are corrected
+        let glitched_remaining = if stage == RenderStage::Correction {
+            total_pixels.saturating_sub(pixels_iterated)
+        } else {
+            glitched_maximum
+        };
+
+        let stage_fraction = match stage {
+            RenderStage::Reference => reference_iteration as f64 / reference_maximum as f64,
+            RenderStage::SeriesApproximation => self.series_approximation.load(Ordering::Relaxed) as f64 / reference_maximum as f64,
+            RenderStage::Iteration => pixels_iterated as f64 / total_pixels as f64,
+            RenderStage::Correction => 1.0 - glitched_remaining as f64 / glitched_maximum as f64,
+            RenderStage::Saving => 0.0
+        };
+
+        let stage_fraction = if stage_fraction.is_finite() {
+            stage_fraction.max(0.0).min(1.0)
+        } else {
+            0.0
+        };
+
+        // The remaining time assumes that the rest of the stage progresses at the same rate
+        let stage_eta = if stage_fraction > 0.0 && stage != RenderStage::Saving {
+            Some(stage_elapsed.mul_f64((1.0 - stage_fraction) / stage_fraction))
+        } else {
+            None
+        };
+
+        ProgressReport {
+            zoom: zoom.to_owned(),
+            stage,
+            stage_fraction,
+            reference_iteration,
+            reference_maximum,
+            pixels_iterated,
+            total_pixels,
+            glitched_remaining,
+            elapsed,
+            stage_elapsed,
+            stage_eta,
+        }
+    }
+
+    // Sends a report at every interval until the returned sender is dropped
+    pub fn spawn_reporter(&self, zoom: String, total_pixels: usize, interval: Duration, sender: mpsc::Sender<ProgressReport>) -> mpsc::Sender<()> {
+        let counters = self.clone();
+        let (tx, rx) = mpsc::channel::<()>();
+
+        thread::spawn(move || {
+            let frame_time = Instant::now();
+            let mut stage_time = Instant::now();
+            let mut stage = counters.stage.load(Ordering::SeqCst);
+
+            while let Err(mpsc::TryRecvError::Empty) = rx.try_recv() {
+                let current_stage = counters.stage.load(Ordering::SeqCst);
+
+                if current_stage != stage {
+                    stage = current_stage;
+                    stage_time = Instant::now();
+                }
+
+                // The receiver has gone away, so there is no need to keep reporting
+                if sender.send(counters.get_report(&zoom, total_pixels, frame_time.elapsed(), stage_time.elapsed())).is_err() {
+                    break;
+                }
+
+                thread::sleep(interval);
+            }
+        });
+
+        tx
+    }
+}
\ No newline at end of file
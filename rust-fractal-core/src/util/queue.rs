@@ -0,0 +1,361 @@
+use crate::renderer::FractalRenderer;
+
+use config::{Config, File, FileFormat};
+use serde_json::{json, Value};
+
+use std::fs::{self, OpenOptions};
+use std::io::ErrorKind;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+// How often the worker looks for new jobs, and for the current job being cancelled
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+// The queue is only locked while it is read, changed and written, so an older lock was left by a process which was killed
+const STALE_LOCK_AGE: Duration = Duration::from_secs(30);
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum QueuedJobStatus {
+    Queued,
+    Rendering,
+    Complete,
+    Cancelled,
+    Failed
+}
+
+impl QueuedJobStatus {
+    fn parse(status: &str) -> Option<Self> {
+        match status {
+            "queued" => Some(QueuedJobStatus::Queued),
+            "rendering" => Some(QueuedJobStatus::Rendering),
+            "complete" => Some(QueuedJobStatus::Complete),
+            "cancelled" => Some(QueuedJobStatus::Cancelled),
+            "failed" => Some(QueuedJobStatus::Failed),
+            _ => None
+        }
+    }
+
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            QueuedJobStatus::Queued => "queued",
+            QueuedJobStatus::Rendering => "rendering",
+            QueuedJobStatus::Complete => "complete",
+            QueuedJobStatus::Cancelled => "cancelled",
+            QueuedJobStatus::Failed => "failed"
+        }
+    }
+}
+
+// The settings are stored whole, so that a job renders the same even if the files it was submitted from change
+pub struct QueuedJob {
+    pub id: usize,
+    pub name: String,
+    pub priority: i64,
+    pub status: QueuedJobStatus,
+    pub submitted: u64,
+    pub settings: Value,
+    pub error: Option<String>,
+}
+
+impl QueuedJob {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "id": self.id,
+            "name": self.name,
+            "priority": self.priority,
+            "status": self.status.to_str(),
+            "submitted": self.submitted,
+            "settings": self.settings,
+            "error": self.error,
+        })
+    }
+
+    pub fn from_json(value: &Value) -> Option<Self> {
+        Some(QueuedJob {
+            id: value["id"].as_u64()? as usize,
+            name: value["name"].as_str().unwrap_or("").to_owned(),
+            priority: value["priority"].as_i64().unwrap_or(0),
+            status: QueuedJobStatus::parse(value["status"].as_str()?)?,
+            submitted: value["submitted"].as_u64().unwrap_or(0),
+            settings: value["settings"].clone(),
+            error: value["error"].as_str().map(|error| error.to_owned()),
+        })
+    }
+
+    pub fn get_settings(&self) -> Config {
+        let mut settings = Config::default();
+        settings.merge(File::from_str(&self.settings.to_string(), FileFormat::Json)).unwrap();
+
+        settings
+    }
+}
+
+// Only one process changes the queue at a time. The lock is a file created next to the queue, which is removed when the
+// lock is dropped.
+pub struct QueueLock {
+    filename: String,
+}
+
+impl QueueLock {
+    pub fn acquire(queue_filename: &str) -> Self {
+        let filename = format!("{}.lock", queue_filename);
+
+        if let Some(directory) = Path::new(&filename).parent() {
+            fs::create_dir_all(directory).ok();
+        }
+
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(&filename) {
+                Ok(_) => return QueueLock { filename },
+                Err(error) if error.kind() == ErrorKind::AlreadyExists => {
+                    let stale = fs::metadata(&filename).and_then(|metadata| metadata.modified()).ok()
+                        .and_then(|modified| modified.elapsed().ok())
+                        .map_or(false, |age| age > STALE_LOCK_AGE);
+
+                    if stale {
+                        fs::remove_file(&filename).ok();
+                    } else {
+                        thread::sleep(Duration::from_millis(50));
+                    }
+                },
+                Err(error) => panic!("failed to lock {}: {}", queue_filename, error)
+            }
+        }
+    }
+}
+
+impl Drop for QueueLock {
+    fn drop(&mut self) {
+        fs::remove_file(&self.filename).ok();
+    }
+}
+
+// The queue is a JSON file which is read and written again for every change, under a QueueLock, so that jobs can be submitted, reordered
+// and cancelled from other processes while the worker is running. The jobs with the highest priority are rendered
+// first, and those with the same priority in the order they were submitted.
+pub struct JobQueue {
+    pub filename: String,
+    pub jobs: Vec<QueuedJob>,
+}
+
+impl JobQueue {
+    // A file which does not exist yet is an empty queue, but one which cannot be read is an error, so that saving the
+    // queue does not lose the jobs which are already there
+    pub fn load(filename: &str) -> Result<Self, String> {
+        let contents = match fs::read_to_string(filename) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(JobQueue {
+                filename: filename.to_owned(),
+                jobs: Vec::new(),
+            }),
+            Err(error) => return Err(format!("failed to read {}: {}", filename, error))
+        };
+
+        let value = serde_json::from_str::<Value>(&contents).map_err(|error| format!("failed to read {}: {}", filename, error))?;
+
+        let jobs = value["jobs"].as_array()
+            .ok_or_else(|| format!("failed to read {}: there is no array of jobs", filename))?
+            .iter()
+            .enumerate()
+            .map(|(index, job)| QueuedJob::from_json(job).ok_or_else(|| format!("failed to read {}: job {} is not valid", filename, index)))
+            .collect::<Result<Vec<QueuedJob>, String>>()?;
+
+        Ok(JobQueue {
+            filename: filename.to_owned(),
+            jobs,
+        })
+    }
+
+    // Written to a temporary file first, so that a reader never sees a partly written queue
+    pub fn save(&self) {
+        if let Some(directory) = Path::new(&self.filename).parent() {
+            fs::create_dir_all(directory).ok();
+        }
+
+        let contents = serde_json::to_string_pretty(&json!({
+            "jobs": self.jobs.iter().map(|job| job.to_json()).collect::<Vec<Value>>(),
+        })).unwrap();
+
+        let temporary = format!("{}.tmp", self.filename);
+
+        fs::write(&temporary, contents)
+            .and_then(|_| fs::rename(&temporary, &self.filename))
+            .unwrap_or_else(|error| panic!("failed to write {}: {}", self.filename, error));
+    }
+
+    pub fn submit(&mut self, settings: &Config, name: &str, priority: i64) -> usize {
+        let id = self.jobs.iter().map(|job| job.id + 1).max().unwrap_or(0);
+
+        self.jobs.push(QueuedJob {
+            id,
+            name: name.to_owned(),
+            priority,
+            status: QueuedJobStatus::Queued,
+            submitted: SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or(0),
+            settings: settings.clone().try_into::<Value>().unwrap(),
+            error: None,
+        });
+
+        id
+    }
+
+    pub fn get_mut(&mut self, id: usize) -> Option<&mut QueuedJob> {
+        self.jobs.iter_mut().find(|job| job.id == id)
+    }
+
+    // Only the jobs which have not finished can be reordered or cancelled
+    pub fn set_priority(&mut self, id: usize, priority: i64) -> bool {
+        match self.get_mut(id) {
+            Some(job) if job.status == QueuedJobStatus::Queued => {
+                job.priority = priority;
+                true
+            },
+            _ => false
+        }
+    }
+
+    pub fn cancel(&mut self, id: usize) -> bool {
+        match self.get_mut(id) {
+            Some(job) if job.status == QueuedJobStatus::Queued || job.status == QueuedJobStatus::Rendering => {
+                job.status = QueuedJobStatus::Cancelled;
+                true
+            },
+            _ => false
+        }
+    }
+
+    // The queued jobs in the order they will be rendered
+    pub fn get_order(&self) -> Vec<usize> {
+        let mut queued = self.jobs.iter()
+            .filter(|job| job.status == QueuedJobStatus::Queued)
+            .map(|job| (job.priority, job.id))
+            .collect::<Vec<(i64, usize)>>();
+
+        queued.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        queued.into_iter().map(|(_, id)| id).collect()
+    }
+}
+
+// Renders the jobs of the queue one at a time until the stop flag is set, waiting for more once it is empty. A job left
+// rendering by a worker which was stopped is queued again, as is the current job when the worker is stopped. The
+// worker stops with an error if the queue cannot be read, rather than saving over it.
+pub fn run_queue(filename: &str, run_folder: &str, stop_flag: Arc<AtomicBool>) -> Result<(), String> {
+    {
+        let _lock = QueueLock::acquire(filename);
+        let mut queue = JobQueue::load(filename)?;
+
+        for job in queue.jobs.iter_mut().filter(|job| job.status == QueuedJobStatus::Rendering) {
+            job.status = QueuedJobStatus::Queued;
+        }
+
+        queue.save();
+    }
+
+    println!("Waiting for jobs in {}", filename);
+
+    while !stop_flag.load(Ordering::SeqCst) {
+        let lock = QueueLock::acquire(filename);
+        let mut queue = JobQueue::load(filename)?;
+
+        let id = match queue.get_order().first() {
+            Some(&id) => id,
+            None => {
+                drop(lock);
+                thread::sleep(POLL_INTERVAL);
+                continue;
+            }
+        };
+
+        let job = queue.get_mut(id).unwrap();
+        job.status = QueuedJobStatus::Rendering;
+
+        let mut settings = job.get_settings();
+        settings.set("show_output", false).unwrap();
+
+        // The jobs of a run share its folder
+        FractalRenderer::apply_run_folder(&mut settings, run_folder);
+
+        println!("Rendering job {} ({})", id, job.name);
+        queue.save();
+        drop(lock);
+
+        let job_stop_flag = Arc::new(AtomicBool::new(false));
+        let finished = Arc::new(AtomicBool::new(false));
+
+        // The queue is polled while rendering, so that cancelling the job from another process stops it
+        let watcher = {
+            let filename = filename.to_owned();
+            let stop_flag = stop_flag.clone();
+            let job_stop_flag = job_stop_flag.clone();
+            let finished = finished.clone();
+
+            thread::spawn(move || {
+                let mut last_poll = Instant::now();
+
+                while !finished.load(Ordering::SeqCst) {
+                    if last_poll.elapsed() >= POLL_INTERVAL {
+                        last_poll = Instant::now();
+
+                        let cancelled = JobQueue::load(&filename)
+                            .map(|queue| queue.jobs.iter().any(|job| job.id == id && job.status == QueuedJobStatus::Cancelled))
+                            .unwrap_or(false);
+
+                        if cancelled {
+                            job_stop_flag.store(true, Ordering::SeqCst);
+                        }
+                    }
+
+                    if stop_flag.load(Ordering::SeqCst) {
+                        job_stop_flag.store(true, Ordering::SeqCst);
+                    }
+
+                    thread::sleep(Duration::from_millis(100));
+                }
+            })
+        };
+
+        // Invalid settings panic in the renderer, which fails the job rather than stopping the worker
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let mut renderer = FractalRenderer::new(settings);
+            renderer.stop_flag = job_stop_flag.clone();
+            renderer.render();
+
+            if renderer.cancelled {
+                renderer.save_partial_output();
+            }
+
+            renderer.cancelled
+        }));
+
+        finished.store(true, Ordering::SeqCst);
+        watcher.join().ok();
+
+        // The queue is read again, as it can have changed while the job was rendering
+        let _lock = QueueLock::acquire(filename);
+        let mut queue = JobQueue::load(filename)?;
+
+        if let Some(job) = queue.get_mut(id) {
+            let (status, error) = match result {
+                Ok(false) => (QueuedJobStatus::Complete, None),
+                Ok(true) if stop_flag.load(Ordering::SeqCst) => (QueuedJobStatus::Queued, None),
+                Ok(true) => (QueuedJobStatus::Cancelled, None),
+                Err(error) => (QueuedJobStatus::Failed, Some(error.downcast_ref::<String>().cloned()
+                    .or_else(|| error.downcast_ref::<&str>().map(|error| error.to_string()))
+                    .unwrap_or_else(|| String::from("the render panicked"))))
+            };
+
+            println!("Job {} {}", id, status.to_str());
+
+            job.status = status;
+            job.error = error;
+        }
+
+        queue.save();
+    }
+
+    Ok(())
+}
@@ -2,12 +2,12 @@ use exr::prelude::simple_image::*;
 use rayon::prelude::*;
 use config::Config;
 
-use colorgrad::{Color, CustomGradient, Interpolation, BlendMode};
+use colorgrad::Color;
 
 use std::fs;
 use std::time::Instant;
 
-use crate::util::generate_default_palette;
+use crate::util::get_palette;
 
 pub struct RecolourExr {
     palette_buffer: Vec<Color>,
@@ -18,33 +18,7 @@ pub struct RecolourExr {
 
 impl RecolourExr {
     pub fn new(settings: Config) -> Self {
-        let (_, palette_buffer) = if let Ok(colour_values) = settings.get_array("palette") {
-            let mut colors = colour_values.chunks_exact(3).map(|value| {
-                Color::from_rgb_u8(value[0].clone().into_int().unwrap() as u8, 
-                    value[1].clone().into_int().unwrap() as u8, 
-                    value[2].clone().into_int().unwrap() as u8)
-            }).collect::<Vec<Color>>();
-
-            if colors[0] != *colors.last().unwrap() {
-                colors.push(colors[0].clone());
-            };
-
-            let mut number_colors = colors.len();
-
-            if settings.get_bool("palette_cyclic").unwrap_or(true) {
-                number_colors -= 1;
-            }
-
-            let palette_generator = CustomGradient::new()
-                .colors(&colors[0..number_colors])
-                .interpolation(Interpolation::CatmullRom)
-                .mode(BlendMode::Oklab)
-                .build().unwrap();
-
-            (colors, palette_generator.colors(number_colors * 64))
-        } else {
-            generate_default_palette()
-        };
+        let (_, palette_buffer) = get_palette(&settings);
 
         let palette_iteration_span = settings.get_float("palette_iteration_span").unwrap_or(10.0) as f32;
         let palette_offset = settings.get_float("iteration_offset").unwrap_or(0.0) as f32;
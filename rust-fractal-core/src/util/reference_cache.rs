@@ -0,0 +1,255 @@
+use crate::math::{Reference, ReferenceIteration, SeriesApproximation};
+use crate::util::{ComplexArbitrary, ComplexExtended, ComplexFixed, FloatArbitrary, FractalType};
+
+use serde_json::{json, Value};
+
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::{BufRead, BufReader};
+
+// Stores the central reference orbits and series approximation coefficients on disk, so that renders of the same
+// location at another resolution or with another palette can skip the arbitrary precision stages. The files are
+// a single line JSON header followed by the raw little endian orbit data, like the checkpoints.
+pub struct ReferenceCache {
+    pub directory: String,
+}
+
+impl ReferenceCache {
+    pub fn new(directory: &str) -> Self {
+        ReferenceCache {
+            directory: directory.to_owned(),
+        }
+    }
+
+    // The orbit only depends on the centre, its precision and the iteration limit. The key is stored in the file
+    // as well as hashed into its name, so a hash collision cannot load the wrong orbit.
+    fn get_key(reference: &Reference, fractal_type: FractalType) -> String {
+        let fractal_type = match fractal_type {
+            FractalType::Mandelbrot2 => "mandelbrot2",
+            FractalType::Mandelbrot3 => "mandelbrot3"
+        };
+
+        format!("{}_{}_{}_{}_{}_{}_{}",
+            fractal_type,
+            reference.c.prec().0,
+            reference.c.real().to_string_radix(16, None),
+            reference.c.imag().to_string_radix(16, None),
+            reference.start_iteration,
+            reference.maximum_iteration,
+            reference.data_storage_interval)
+    }
+
+    fn get_filename(&self, key: &str, extension: &str) -> String {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+
+        format!("{}/{:016x}.{}", self.directory, hasher.finish(), extension)
+    }
+
+    fn write(&self, filename: &str, header: &Value, data: &[u8]) {
+        fs::create_dir_all(&self.directory).unwrap();
+
+        let mut contents = serde_json::to_string(header).unwrap().into_bytes();
+        contents.push(b'\n');
+        contents.extend_from_slice(data);
+
+        let temporary_filename = format!("{}.tmp", filename);
+
+        fs::write(&temporary_filename, contents).unwrap();
+        fs::rename(&temporary_filename, filename).unwrap();
+    }
+
+    fn read(filename: &str, key: &str) -> Option<(Value, Vec<u8>)> {
+        let contents = fs::read(filename).ok()?;
+        let header_length = contents.iter().position(|&byte| byte == b'\n')?;
+
+        let header = serde_json::from_slice::<Value>(&contents[..header_length]).ok()?;
+
+        if header["key"].as_str()? != key {
+            return None;
+        }
+
+        Some((header, contents[(header_length + 1)..].to_vec()))
+    }
+
+    pub fn save_reference(&self, reference: &Reference, fractal_type: FractalType) {
+        let key = ReferenceCache::get_key(reference, fractal_type);
+
+        let to_strings = |value: &ComplexArbitrary| json!([value.real().to_string_radix(16, None), value.imag().to_string_radix(16, None)]);
+
+        let header = json!({
+            "key": key,
+            "current_iteration": reference.current_iteration,
+            "z": to_strings(&reference.z),
+            "reference_data": reference.reference_data.len(),
+            "extended_iterations": reference.extended_iterations,
+            "high_precision_data": reference.high_precision_data.iter().map(to_strings).collect::<Vec<_>>(),
+        });
+
+        let mut data = Vec::with_capacity(36 * reference.reference_data.len());
+
+        for iteration in &reference.reference_data {
+            data.extend_from_slice(&iteration.z.re.to_le_bytes());
+            data.extend_from_slice(&iteration.z.im.to_le_bytes());
+        }
+
+        for value in &reference.reference_data_extended {
+            data.extend_from_slice(&value.mantissa.re.to_le_bytes());
+            data.extend_from_slice(&value.mantissa.im.to_le_bytes());
+            data.extend_from_slice(&value.exponent.to_le_bytes());
+        }
+
+        self.write(&self.get_filename(&key, "reference"), &header, &data);
+    }
+
+    // Restores a cached orbit into the reference, which must have been created with the same centre and limits.
+    // The glitch tolerances are calculated again so that the cache does not depend on the tolerance setting.
+    pub fn load_reference(&self, reference: &mut Reference, fractal_type: FractalType) -> bool {
+        let key = ReferenceCache::get_key(reference, fractal_type);
+
+        let (header, data) = match ReferenceCache::read(&self.get_filename(&key, "reference"), &key) {
+            Some(contents) => contents,
+            None => return false
+        };
+
+        let precision = reference.c.prec();
+
+        let from_strings = |value: &Value| -> Option<ComplexArbitrary> {
+            let real = FloatArbitrary::with_val(precision.0, FloatArbitrary::parse_radix(value[0].as_str()?, 16).ok()?);
+            let imag = FloatArbitrary::with_val(precision.1, FloatArbitrary::parse_radix(value[1].as_str()?, 16).ok()?);
+
+            Some(ComplexArbitrary::with_val(precision, (real, imag)))
+        };
+
+        let length = match header["reference_data"].as_u64() {
+            Some(length) => length as usize,
+            None => return false
+        };
+
+        if data.len() != 36 * length {
+            return false;
+        }
+
+        let z = from_strings(&header["z"]);
+        let high_precision_data = header["high_precision_data"].as_array()
+            .and_then(|values| values.iter().map(from_strings).collect::<Option<Vec<_>>>());
+        let extended_iterations = header["extended_iterations"].as_array()
+            .and_then(|values| values.iter().map(|value| value.as_u64().map(|value| value as usize)).collect::<Option<Vec<_>>>());
+
+        let (z, high_precision_data, extended_iterations, current_iteration) = match (z, high_precision_data, extended_iterations, header["current_iteration"].as_u64()) {
+            (Some(z), Some(high_precision_data), Some(extended_iterations), Some(current_iteration)) => (z, high_precision_data, extended_iterations, current_iteration as usize),
+            _ => return false
+        };
+
+        let read_f64 = |bytes: &[u8]| f64::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7]]);
+
+        let glitch_tolerance = reference.glitch_tolerance;
+        let (fixed, extended) = data.split_at(16 * length);
+
+        reference.reference_data = fixed.chunks_exact(16).map(|bytes| {
+            let z = ComplexFixed::new(read_f64(&bytes[0..8]), read_f64(&bytes[8..16]));
+
+            ReferenceIteration {
+                z,
+                tolerance: glitch_tolerance * z.norm_sqr(),
+            }
+        }).collect();
+
+        reference.reference_data_extended = extended.chunks_exact(20).map(|bytes| {
+            ComplexExtended::new(
+                ComplexFixed::new(read_f64(&bytes[0..8]), read_f64(&bytes[8..16])),
+                i32::from_le_bytes([bytes[16], bytes[17], bytes[18], bytes[19]]))
+        }).collect();
+
+        reference.z = z;
+        reference.current_iteration = current_iteration;
+        reference.extended_iterations = extended_iterations;
+        reference.high_precision_data = high_precision_data;
+
+        true
+    }
+
+    pub fn get_reference_filename(&self, reference: &Reference, fractal_type: FractalType) -> String {
+        self.get_filename(&ReferenceCache::get_key(reference, fractal_type), "reference")
+    }
+
+    pub fn get_approximation_filename(&self, reference: &Reference, series_approximation: &SeriesApproximation) -> String {
+        self.get_filename(&ReferenceCache::get_approximation_key(reference, series_approximation), "approximation")
+    }
+
+    // Moves a file of another cache, such as one sent by the coordinator of a distributed render, into this cache.
+    // It is named from the key in its header rather than the name it had, as the hash of the key is not stable
+    // between builds.
+    pub fn import(&self, filename: &str, extension: &str) -> bool {
+        if extension != "reference" && extension != "approximation" {
+            return false;
+        }
+
+        let mut header = String::new();
+
+        let key = fs::File::open(filename).ok()
+            .and_then(|file| BufReader::new(file).read_line(&mut header).ok())
+            .and_then(|_| serde_json::from_str::<Value>(&header).ok())
+            .and_then(|header| header["key"].as_str().map(|key| key.to_owned()));
+
+        match key {
+            Some(key) => fs::create_dir_all(&self.directory).and_then(|_| fs::rename(filename, self.get_filename(&key, extension))).is_ok(),
+            None => false
+        }
+    }
+
+    // The coefficients also depend on the approximation order and the number of iterations they were generated to
+    fn get_approximation_key(reference: &Reference, series_approximation: &SeriesApproximation) -> String {
+        format!("{}_{}_{}", ReferenceCache::get_key(reference, series_approximation.fractal_type), series_approximation.order, series_approximation.maximum_iteration)
+    }
+
+    pub fn save_approximation(&self, reference: &Reference, series_approximation: &SeriesApproximation) {
+        let key = ReferenceCache::get_approximation_key(reference, series_approximation);
+
+        let header = json!({
+            "key": key,
+            "coefficients": series_approximation.coefficients.len(),
+        });
+
+        let mut data = Vec::new();
+
+        for value in series_approximation.coefficients.iter().flatten() {
+            data.extend_from_slice(&value.mantissa.re.to_le_bytes());
+            data.extend_from_slice(&value.mantissa.im.to_le_bytes());
+            data.extend_from_slice(&value.exponent.to_le_bytes());
+        }
+
+        self.write(&self.get_filename(&key, "approximation"), &header, &data);
+    }
+
+    pub fn load_approximation(&self, reference: &Reference, series_approximation: &mut SeriesApproximation) -> bool {
+        let key = ReferenceCache::get_approximation_key(reference, series_approximation);
+
+        let (header, data) = match ReferenceCache::read(&self.get_filename(&key, "approximation"), &key) {
+            Some(contents) => contents,
+            None => return false
+        };
+
+        let terms = series_approximation.order + 1;
+
+        match header["coefficients"].as_u64() {
+            Some(length) if data.len() == 20 * terms * length as usize => {},
+            _ => return false
+        }
+
+        let read_f64 = |bytes: &[u8]| f64::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7]]);
+
+        series_approximation.coefficients = data.chunks_exact(20 * terms).map(|coefficients| {
+            coefficients.chunks_exact(20).map(|bytes| {
+                ComplexExtended::new(
+                    ComplexFixed::new(read_f64(&bytes[0..8]), read_f64(&bytes[8..16])),
+                    i32::from_le_bytes([bytes[16], bytes[17], bytes[18], bytes[19]]))
+            }).collect()
+        }).collect();
+
+        series_approximation.generated_order = series_approximation.order;
+
+        true
+    }
+}
@@ -0,0 +1,272 @@
+use crate::math::Reference;
+use crate::util::ComplexFixed;
+use crate::util::data_export::DataExport;
+
+use serde_json::{json, Value};
+
+use std::cmp::Ordering;
+use std::f64::consts::LOG10_2;
+use std::fs;
+
+// The number of closest approaches to zero which are reported
+const CLOSEST_APPROACHES: usize = 10;
+
+// The height of the plot saved next to the image, which has a panel for the orbit and a panel for its magnitude
+const PLOT_SIZE: usize = 512;
+
+const BACKGROUND_COLOUR: [u8; 3] = [16, 16, 16];
+const AXIS_COLOUR: [u8; 3] = [72, 72, 72];
+const APPROACH_COLOUR: [u8; 3] = [255, 48, 48];
+
+// The orbit is coloured from blue at the first iteration to yellow at the last
+const START_COLOUR: [f64; 3] = [48.0, 96.0, 255.0];
+const END_COLOUR: [f64; 3] = [255.0, 208.0, 48.0];
+
+#[derive(Clone)]
+pub struct OrbitPoint {
+    pub iteration: usize,
+    pub z: ComplexFixed<f64>,
+    // The magnitude is taken from the extended values, so it is still known when z is too small for a double
+    pub log_magnitude: f64,
+}
+
+impl OrbitPoint {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "iteration": self.iteration,
+            "real": self.z.re,
+            "imag": self.z.im,
+            "log_magnitude": self.log_magnitude,
+        })
+    }
+}
+
+// The orbit of a reference decimated to a limited number of points. The orbit is split into equal runs of iterations,
+// and both the first point of each run and the point of the run which is closest to zero are kept, so that the passes
+// near zero which cause glitches are never lost to the decimation.
+#[derive(Clone)]
+pub struct ReferenceOrbit {
+    pub iterations: usize,
+    pub escaped: bool,
+    pub points: Vec<OrbitPoint>,
+    pub closest_approaches: Vec<OrbitPoint>,
+}
+
+impl ReferenceOrbit {
+    pub fn new(reference: &Reference, maximum_points: usize) -> Self {
+        let length = reference.reference_data.len().min(reference.reference_data_extended.len());
+
+        let get_log_magnitude = |index: usize| {
+            let magnitude = reference.reference_data_extended[index].norm();
+
+            if magnitude.mantissa == 0.0 {
+                f64::NEG_INFINITY
+            } else {
+                magnitude.mantissa.log10() + magnitude.exponent as f64 * LOG10_2
+            }
+        };
+
+        let get_point = |index: usize| OrbitPoint {
+            iteration: reference.start_iteration + index,
+            z: reference.reference_data[index].z,
+            log_magnitude: get_log_magnitude(index),
+        };
+
+        let compare = |a: &f64, b: &f64| a.partial_cmp(b).unwrap_or(Ordering::Equal);
+
+        let runs = (maximum_points / 2).max(1);
+        let run_length = ((length + runs - 1) / runs).max(1);
+
+        let mut points = Vec::with_capacity(2 * runs + 1);
+
+        for start in (0..length).step_by(run_length) {
+            points.push(get_point(start));
+
+            let end = (start + run_length).min(length);
+
+            if let Some(closest) = (start + 1..end).min_by(|&a, &b| compare(&get_log_magnitude(a), &get_log_magnitude(b))) {
+                points.push(get_point(closest));
+            }
+        }
+
+        if length > 1 && points.last().map(|point| point.iteration) != Some(reference.start_iteration + length - 1) {
+            points.push(get_point(length - 1));
+        }
+
+        // The closest approaches are the deepest local minima of the magnitude over the whole orbit
+        let mut closest_approaches: Vec<(f64, usize)> = Vec::new();
+        let mut previous = f64::INFINITY;
+
+        for index in 0..length {
+            let current = get_log_magnitude(index);
+            let next = if index + 1 < length { get_log_magnitude(index + 1) } else { f64::INFINITY };
+
+            if current < previous && current <= next {
+                closest_approaches.push((current, index));
+
+                if closest_approaches.len() > 2 * CLOSEST_APPROACHES {
+                    closest_approaches.sort_by(|a, b| compare(&a.0, &b.0));
+                    closest_approaches.truncate(CLOSEST_APPROACHES);
+                }
+            }
+
+            previous = current;
+        }
+
+        closest_approaches.sort_by(|a, b| compare(&a.0, &b.0));
+        closest_approaches.truncate(CLOSEST_APPROACHES);
+
+        ReferenceOrbit {
+            iterations: reference.current_iteration,
+            escaped: reference.reference_data.last().map(|data| data.z.norm_sqr() >= 1e256).unwrap_or(false),
+            points,
+            closest_approaches: closest_approaches.into_iter().map(|(_, index)| get_point(index)).collect(),
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "iterations": self.iterations,
+            "escaped": self.escaped,
+            "closest_approaches": self.closest_approaches.iter().map(|point| point.to_json()).collect::<Vec<Value>>(),
+            "points": self.points.iter().map(|point| point.to_json()).collect::<Vec<Value>>(),
+        })
+    }
+
+    // The points are saved as JSON and plotted to an image next to the output
+    pub fn save(&self, filename: &str) {
+        fs::write(filename.to_owned() + "_orbit.json", serde_json::to_string(&self.to_json()).unwrap()).unwrap();
+
+        let buffer = self.get_plot(PLOT_SIZE);
+
+        DataExport::write_colour_buffer(&(filename.to_owned() + "_orbit.png"), &buffer, 2 * PLOT_SIZE, PLOT_SIZE, &[]);
+    }
+
+    // An RGB image twice as wide as it is high. The left panel is the orbit around zero, fitted to the points which
+    // have not escaped, and the right panel is the log of the magnitude of each iteration, where the passes near zero
+    // show as dips. The closest approaches are marked in red in both panels.
+    pub fn get_plot(&self, size: usize) -> Vec<u8> {
+        let width = 2 * size;
+        let mut buffer = BACKGROUND_COLOUR.repeat(width * size);
+
+        if self.points.is_empty() || size < 2 {
+            return buffer;
+        }
+
+        let scale = (size - 1) as f64;
+
+        let extent = self.points.iter()
+            .filter(|point| point.log_magnitude <= LOG10_2)
+            .map(|point| point.z.re.abs().max(point.z.im.abs()))
+            .fold(0.0, f64::max);
+
+        let extent = if extent > 0.0 { 1.1 * extent } else { 2.0 };
+
+        let orbit_position = |point: &OrbitPoint| {
+            ((0.5 + 0.5 * point.z.re / extent).max(0.0).min(1.0) * scale,
+                (0.5 - 0.5 * point.z.im / extent).max(0.0).min(1.0) * scale)
+        };
+
+        // The magnitudes are shown up to 100, past which the orbit has escaped
+        let finite = self.points.iter().map(|point| point.log_magnitude).filter(|value| value.is_finite());
+        let top = finite.clone().fold(f64::NEG_INFINITY, f64::max).min(2.0);
+        let bottom = finite.fold(f64::INFINITY, f64::min);
+        let (top, bottom) = if bottom < top { (top, bottom - 0.05 * (top - bottom)) } else { (bottom + 1.0, bottom - 1.0) };
+
+        let first_iteration = self.points[0].iteration as f64;
+        let iteration_span = (self.points[self.points.len() - 1].iteration as f64 - first_iteration).max(1.0);
+
+        let magnitude_y = |log_magnitude: f64| ((top - log_magnitude) / (top - bottom)).max(0.0).min(1.0) * scale;
+
+        let magnitude_position = |point: &OrbitPoint| {
+            (size as f64 + (point.iteration as f64 - first_iteration) / iteration_span * scale, magnitude_y(point.log_magnitude))
+        };
+
+        let get_colour = |point: &OrbitPoint| {
+            let fraction = point.iteration as f64 / self.iterations.max(1) as f64;
+            let mut colour = [0u8; 3];
+
+            for (i, value) in colour.iter_mut().enumerate() {
+                *value = (START_COLOUR[i] + fraction * (END_COLOUR[i] - START_COLOUR[i])) as u8;
+            }
+
+            colour
+        };
+
+        // The axes through zero, the line of a magnitude of one and the border between the panels
+        draw_line(&mut buffer, width, size, (0.5 * scale, 0.0), (0.5 * scale, scale), AXIS_COLOUR);
+        draw_line(&mut buffer, width, size, (0.0, 0.5 * scale), (scale, 0.5 * scale), AXIS_COLOUR);
+        draw_line(&mut buffer, width, size, (size as f64, 0.0), (size as f64, scale), AXIS_COLOUR);
+
+        if top > 0.0 && bottom < 0.0 {
+            draw_line(&mut buffer, width, size, (size as f64, magnitude_y(0.0)), (size as f64 + scale, magnitude_y(0.0)), AXIS_COLOUR);
+        }
+
+        for pair in self.points.windows(2) {
+            let colour = get_colour(&pair[1]);
+
+            draw_line(&mut buffer, width, size, orbit_position(&pair[0]), orbit_position(&pair[1]), colour);
+            draw_line(&mut buffer, width, size, magnitude_position(&pair[0]), magnitude_position(&pair[1]), colour);
+        }
+
+        for point in &self.closest_approaches {
+            draw_marker(&mut buffer, width, size, orbit_position(point), APPROACH_COLOUR);
+            draw_marker(&mut buffer, width, size, magnitude_position(point), APPROACH_COLOUR);
+        }
+
+        buffer
+    }
+
+    // The plot is blended into the bottom left corner of the image, at a quarter of its height
+    pub fn draw_overlay(&self, buffer: &mut [u8], image_width: usize, image_height: usize) {
+        let margin = image_height / 32;
+        let size = (image_height / 4).min(image_width.saturating_sub(2 * margin) / 2);
+
+        if size < 16 {
+            return;
+        }
+
+        let plot = self.get_plot(size);
+        let offset_y = image_height - margin - size;
+
+        for y in 0..size {
+            for x in 0..(2 * size) {
+                let i = 3 * ((offset_y + y) * image_width + margin + x);
+                let j = 3 * (y * 2 * size + x);
+
+                for channel in 0..3 {
+                    buffer[i + channel] = (0.2 * buffer[i + channel] as f32 + 0.8 * plot[j + channel] as f32) as u8;
+                }
+            }
+        }
+    }
+}
+
+fn draw_line(buffer: &mut [u8], width: usize, height: usize, start: (f64, f64), end: (f64, f64), colour: [u8; 3]) {
+    let steps = (end.0 - start.0).abs().max((end.1 - start.1).abs()).ceil().max(1.0) as usize;
+
+    for step in 0..=steps {
+        let fraction = step as f64 / steps as f64;
+
+        set_pixel(buffer, width, height, start.0 + fraction * (end.0 - start.0), start.1 + fraction * (end.1 - start.1), colour);
+    }
+}
+
+fn draw_marker(buffer: &mut [u8], width: usize, height: usize, position: (f64, f64), colour: [u8; 3]) {
+    for dy in -2..=2 {
+        for dx in -2..=2 {
+            set_pixel(buffer, width, height, position.0 + dx as f64, position.1 + dy as f64, colour);
+        }
+    }
+}
+
+fn set_pixel(buffer: &mut [u8], width: usize, height: usize, x: f64, y: f64, colour: [u8; 3]) {
+    let (x, y) = (x.round(), y.round());
+
+    if x < 0.0 || y < 0.0 || x >= width as f64 || y >= height as f64 {
+        return;
+    }
+
+    let i = 3 * (y as usize * width + x as usize);
+    buffer[i..(i + 3)].copy_from_slice(&colour);
+}
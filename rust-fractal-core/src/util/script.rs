@@ -0,0 +1,95 @@
+use crate::renderer::FractalRenderer;
+use crate::util::{RenderParameters, extended_to_string_long, string_to_extended};
+
+use config::{Config, File, FileFormat};
+use rhai::{Dynamic, Engine, EvalAltResult, ImmutableString, Map};
+use serde_json::Value;
+
+use std::path::PathBuf;
+
+// Runs a Rhai script which automates renders. The parameter sets are maps with the keys of the settings files, and
+// any keys which a map does not have are taken from the settings the script was started with:
+//
+//     parameters()              the starting settings as a map
+//     render(parameters)        renders the frame or sequence and writes the outputs, returning a map of the results
+//     find_nucleus(parameters)  the parameters moved to the nearest nucleus within the re-centring radius, if any
+//     zoom_by(zoom, factor)     the zoom string multiplied by the factor
+//
+// For example, to zoom in on the nucleus nearest to a location and render each step:
+//
+//     let location = parameters();
+//
+//     for step in 0..10 {
+//         location = find_nucleus(location);
+//         let result = render(location);
+//         print(`rendered ${location.zoom} in ${result.render_time} ms`);
+//         location.zoom = zoom_by(location.zoom, 16.0);
+//     }
+pub fn run_script(filename: &str, settings: Config) -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    {
+        let settings = settings.clone();
+
+        engine.register_result_fn("parameters", move || {
+            rhai::serde::to_dynamic(&RenderParameters::from_config(&settings))
+        });
+    }
+
+    {
+        let settings = settings.clone();
+
+        engine.register_result_fn("render", move |parameters: Map| {
+            let mut renderer = FractalRenderer::new(get_settings(&settings, &parameters)?);
+            renderer.render();
+
+            let mut result = Map::new();
+
+            result.insert("cancelled".into(), Dynamic::from(renderer.cancelled));
+            result.insert("render_time".into(), Dynamic::from(renderer.render_time as i64));
+            result.insert("maximum_iteration".into(), Dynamic::from(renderer.maximum_iteration as i64));
+            result.insert("maximum_escaped_iteration".into(), Dynamic::from(renderer.data_export.lock().get_maximum_escaped_iteration() as i64));
+            result.insert("reference_count".into(), Dynamic::from(renderer.statistics.reference_count as i64));
+
+            Ok(result)
+        });
+    }
+
+    {
+        let settings = settings.clone();
+
+        engine.register_result_fn("find_nucleus", move |parameters: Map| {
+            let mut renderer = FractalRenderer::new(get_settings(&settings, &parameters)?);
+            renderer.recentre_on_nucleus();
+
+            let location = renderer.center_reference.get_location();
+            let mut parameters = parameters;
+
+            parameters.insert("real".into(), Dynamic::from(location.real().to_string_radix(10, None)));
+            parameters.insert("imag".into(), Dynamic::from(location.imag().to_string_radix(10, None)));
+
+            Ok(parameters)
+        });
+    }
+
+    engine.register_fn("zoom_by", |zoom: ImmutableString, factor: f64| {
+        extended_to_string_long(string_to_extended(&zoom) * factor)
+    });
+
+    engine.run_file(PathBuf::from(filename))
+}
+
+// The keys of the map which are not set are left out, as the settings files have no way to represent them
+fn get_settings(settings: &Config, parameters: &Map) -> Result<Config, Box<EvalAltResult>> {
+    let value = match serde_json::to_value(parameters).map_err(|error| format!("invalid parameters: {}", error))? {
+        Value::Object(map) => Value::Object(map.into_iter().filter(|(_, value)| !value.is_null()).collect()),
+        value => value
+    };
+
+    let mut settings = settings.clone();
+
+    settings.merge(File::from_str(&value.to_string(), FileFormat::Json))
+        .map_err(|error| format!("invalid parameters: {}", error))?;
+
+    Ok(settings)
+}
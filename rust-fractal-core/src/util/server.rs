@@ -0,0 +1,776 @@
+use crate::renderer::FractalRenderer;
+use crate::builder::FractalRendererBuilder;
+use crate::util::data_export::CompletedRegion;
+use crate::util::{ComplexArbitrary, FloatArbitrary, FloatExtended, Validation, string_to_extended};
+
+use config::{Config, File, FileFormat};
+use serde_json::{json, Value};
+
+use std::collections::BTreeMap;
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, mpsc};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use tungstenite::{Message, WebSocket};
+use tungstenite::handshake::derive_accept_key;
+use tungstenite::protocol::Role;
+
+// The parameters of a job are small, anything larger than this is rejected without being read
+const MAXIMUM_BODY_LENGTH: usize = 1 << 20;
+
+// The request line and headers together, which are read before the length of the body is known
+const MAXIMUM_HEAD_LENGTH: u64 = 1 << 14;
+
+// A client which stops sending part way through a request does not hold its thread for longer than this
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+// The posted parameters can only change the location and view of the render, the rest are those of the server
+const JOB_KEYS: [&str; 12] = [
+    "real", "imag", "zoom", "iterations", "rotate", "image_width", "image_height", "palette_iteration_span",
+    "palette_offset", "coloring_type", "julia_real", "julia_imag",
+];
+
+const MAXIMUM_IMAGE_SIZE: i64 = 8192;
+const MAXIMUM_ITERATIONS: i64 = 100_000_000;
+
+// The finished jobs keep their images in memory, so only the most recent are kept
+const MAXIMUM_FINISHED_JOBS: usize = 64;
+
+// The tile coordinates at deeper levels cannot be represented exactly by the map clients
+const MAXIMUM_TILE_LEVEL: u32 = 52;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum JobStatus {
+    Queued,
+    Rendering,
+    Complete,
+    Cancelled,
+    Failed
+}
+
+// The events sent to the clients which are watching a job
+#[derive(Clone)]
+enum JobEvent {
+    Progress(Value),
+    Region(Arc<CompletedRegion>),
+    Finished(Value),
+}
+
+struct Job {
+    status: JobStatus,
+    settings: Config,
+    stop_flag: Arc<AtomicBool>,
+    progress: Option<Value>,
+    error: Option<String>,
+    image: Option<Vec<u8>>,
+    subscribers: Vec<mpsc::Sender<JobEvent>>,
+}
+
+impl Job {
+    fn to_json(&self, id: usize) -> Value {
+        json!({
+            "id": id,
+            "status": format!("{:?}", self.status).to_ascii_lowercase(),
+            "progress": self.progress,
+            "error": self.error,
+        })
+    }
+
+    // The clients which have disconnected are removed
+    fn publish(&mut self, event: JobEvent) {
+        self.subscribers.retain(|subscriber| subscriber.send(event.clone()).is_ok());
+    }
+
+    fn is_finished(&self) -> bool {
+        self.status != JobStatus::Queued && self.status != JobStatus::Rendering
+    }
+}
+
+struct Request {
+    method: String,
+    path: String,
+    headers: BTreeMap<String, String>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    fn segments(&self) -> Vec<&str> {
+        self.path.split('?').next().unwrap_or("")
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect()
+    }
+
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).map(|value| value.as_str())
+    }
+}
+
+type Jobs = Arc<Mutex<BTreeMap<usize, Job>>>;
+
+struct Response {
+    code: u16,
+    content_type: &'static str,
+    body: Vec<u8>,
+}
+
+impl Response {
+    fn json(code: u16, value: Value) -> Self {
+        Response {
+            code,
+            content_type: "application/json",
+            body: serde_json::to_vec(&value).unwrap(),
+        }
+    }
+
+    fn error(code: u16, message: &str) -> Self {
+        Response::json(code, json!({"error": message}))
+    }
+
+    fn write(&self, stream: &mut TcpStream) -> std::io::Result<()> {
+        let reason = match self.code {
+            200 => "OK",
+            202 => "Accepted",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            408 => "Request Timeout",
+            409 => "Conflict",
+            413 => "Payload Too Large",
+            431 => "Request Header Fields Too Large",
+            _ => "Internal Server Error"
+        };
+
+        write!(stream, "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            self.code,
+            reason,
+            self.content_type,
+            self.body.len())?;
+
+        stream.write_all(&self.body)
+    }
+}
+
+// Accepts render jobs over HTTP. The parameters posted to /jobs are merged over the settings the server was started
+// with, and the jobs are rendered one at a time in the order they were posted, each using every render thread. Only
+// the location and view can be posted, and the oldest finished jobs are removed once there are too many.
+//
+//     POST   /jobs             parameters as JSON, returns the id of the job
+//     GET    /jobs             the status of every job
+//     GET    /jobs/{id}        the status and latest progress report of the job
+//     GET    /jobs/{id}/image  the PNG once the job is complete
+//     DELETE /jobs/{id}        cancels the job if it has not finished, and removes it
+//     GET    /jobs/{id}/events a websocket of the progress reports and the completed regions of the image
+pub fn run_server(address: &str, settings: Config) {
+    let listener = TcpListener::bind(address).unwrap();
+    let jobs: Jobs = Arc::new(Mutex::new(BTreeMap::new()));
+    let (tx, rx) = mpsc::channel::<usize>();
+
+    {
+        let jobs = jobs.clone();
+        thread::spawn(move || render_jobs(jobs, rx));
+    }
+
+    println!("listening on {}", address);
+
+    let next_id = Arc::new(Mutex::new(1));
+
+    for stream in listener.incoming().filter_map(|stream| stream.ok()) {
+        let jobs = jobs.clone();
+        let settings = settings.clone();
+        let next_id = next_id.clone();
+        let tx = tx.clone();
+
+        thread::spawn(move || {
+            let mut stream = stream;
+
+            let response = match read_request(&mut stream) {
+                Ok(request) if request.header("upgrade").map_or(false, |upgrade| upgrade.eq_ignore_ascii_case("websocket")) => {
+                    match stream_events(stream.try_clone().unwrap(), &request, &jobs) {
+                        Some(response) => response,
+                        None => return
+                    }
+                },
+                Ok(request) => handle_request(&request, &jobs, &settings, &next_id, &tx),
+                Err(response) => response
+            };
+
+            response.write(&mut stream).ok();
+        });
+    }
+}
+
+// Reads a line of the request line and headers, counting it against the length which remains for them
+fn read_head_line(reader: &mut BufReader<TcpStream>, remaining: &mut u64) -> Result<String, Response> {
+    let mut line = String::new();
+
+    let length = reader.by_ref().take(*remaining).read_line(&mut line).map_err(|error| match error.kind() {
+        ErrorKind::WouldBlock | ErrorKind::TimedOut => Response::error(408, "timed out reading the request"),
+        _ => Response::error(400, "could not read the request")
+    })?;
+
+    *remaining -= length as u64;
+
+    if *remaining == 0 {
+        return Err(Response::error(431, "the request headers are too large"));
+    }
+
+    Ok(line)
+}
+
+// The header names are lowercase
+fn read_request(stream: &mut TcpStream) -> Result<Request, Response> {
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT)).map_err(|_| Response::error(400, "could not read the request"))?;
+
+    let mut reader = BufReader::new(stream.try_clone().map_err(|_| Response::error(400, "could not read the request"))?);
+    let mut remaining = MAXIMUM_HEAD_LENGTH;
+
+    let line = read_head_line(&mut reader, &mut remaining)?;
+
+    let mut parts = line.split_whitespace();
+
+    let (method, path) = match (parts.next(), parts.next()) {
+        (Some(method), Some(path)) => (method.to_ascii_uppercase(), path.to_owned()),
+        _ => return Err(Response::error(400, "malformed request line"))
+    };
+
+    let mut headers = BTreeMap::new();
+
+    loop {
+        let header = read_head_line(&mut reader, &mut remaining)?;
+
+        if header.trim().is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_owned());
+        }
+    }
+
+    let content_length = match headers.get("content-length") {
+        Some(value) => value.parse::<usize>().map_err(|_| Response::error(400, "invalid content length"))?,
+        None => 0
+    };
+
+    if content_length > MAXIMUM_BODY_LENGTH {
+        return Err(Response::error(413, "the parameters are too large"));
+    }
+
+    let mut body = vec![0u8; content_length];
+    reader.read_exact(&mut body).map_err(|_| Response::error(400, "the body is shorter than its content length"))?;
+
+    Ok(Request {
+        method,
+        path,
+        headers,
+        body,
+    })
+}
+
+fn handle_request(request: &Request, jobs: &Jobs, settings: &Config, next_id: &Mutex<usize>, tx: &mpsc::Sender<usize>) -> Response {
+    let segments = request.segments();
+    let id = segments.get(1).and_then(|id| id.parse::<usize>().ok());
+
+    match (request.method.as_str(), segments.as_slice()) {
+        ("POST", ["jobs"]) => {
+            let parameters = match std::str::from_utf8(&request.body).ok().and_then(|body| serde_json::from_str::<Value>(body).ok()) {
+                Some(parameters) if parameters.is_object() => parameters,
+                _ => return Response::error(400, "the parameters must be a JSON object")
+            };
+
+            if let Some(key) = parameters.as_object().unwrap().keys().find(|key| !JOB_KEYS.contains(&key.as_str())) {
+                return Response::error(400, &format!("the parameter {} cannot be set for a job", key));
+            }
+
+            let mut settings = settings.clone();
+
+            if settings.merge(File::from_str(&parameters.to_string(), FileFormat::Json)).is_err() {
+                return Response::error(400, "the parameters could not be read as settings");
+            }
+
+            settings.set("show_output", false).unwrap();
+
+            let validation = Validation::check(&settings);
+
+            if !validation.is_valid() {
+                return Response::error(400, &validation.errors.join(", "));
+            }
+
+            for &(key, maximum) in &[("image_width", MAXIMUM_IMAGE_SIZE), ("image_height", MAXIMUM_IMAGE_SIZE), ("iterations", MAXIMUM_ITERATIONS)] {
+                if settings.get_int(key).map_or(false, |value| value > maximum) {
+                    return Response::error(400, &format!("{} must be at most {}", key, maximum));
+                }
+            }
+
+            let id = {
+                let mut next_id = next_id.lock();
+                *next_id += 1;
+                *next_id - 1
+            };
+
+            let mut jobs = jobs.lock();
+
+            jobs.insert(id, Job {
+                status: JobStatus::Queued,
+                settings,
+                stop_flag: Arc::new(AtomicBool::new(false)),
+                progress: None,
+                error: None,
+                image: None,
+                subscribers: Vec::new(),
+            });
+
+            // The ids increase, so the first finished jobs are the oldest
+            let finished = jobs.iter().filter(|(_, job)| job.is_finished()).map(|(id, _)| *id).collect::<Vec<_>>();
+
+            for id in finished.iter().take(finished.len().saturating_sub(MAXIMUM_FINISHED_JOBS)) {
+                jobs.remove(id);
+            }
+
+            drop(jobs);
+
+            tx.send(id).unwrap();
+
+            Response::json(202, json!({"id": id}))
+        },
+        ("GET", ["jobs"]) => {
+            let jobs = jobs.lock();
+            Response::json(200, Value::Array(jobs.iter().map(|(id, job)| job.to_json(*id)).collect()))
+        },
+        ("GET", ["jobs", _]) => match id.and_then(|id| jobs.lock().get(&id).map(|job| job.to_json(id))) {
+            Some(status) => Response::json(200, status),
+            None => Response::error(404, "no job with this id")
+        },
+        ("GET", ["jobs", _, "image"]) => match id.and_then(|id| jobs.lock().get(&id).map(|job| (job.status, job.image.clone()))) {
+            Some((_, Some(image))) => Response {
+                code: 200,
+                content_type: "image/png",
+                body: image,
+            },
+            Some((status, None)) => Response::error(409, &format!("the job is {}", format!("{:?}", status).to_ascii_lowercase())),
+            None => Response::error(404, "no job with this id")
+        },
+        ("DELETE", ["jobs", _]) => match id.and_then(|id| jobs.lock().remove(&id)) {
+            Some(job) => {
+                job.stop_flag.store(true, Ordering::SeqCst);
+                Response::json(200, json!({"id": id, "removed": true}))
+            },
+            None => Response::error(404, "no job with this id")
+        },
+        ("GET", ["jobs", _, "events"]) => Response::error(400, "the events are only sent over a websocket"),
+        (_, ["jobs"]) | (_, ["jobs", _]) | (_, ["jobs", _, "image"]) | (_, ["jobs", _, "events"]) => Response::error(405, "method not allowed"),
+        _ => Response::error(404, "not found")
+    }
+}
+
+fn render_jobs(jobs: Jobs, rx: mpsc::Receiver<usize>) {
+    for id in rx {
+        // The job can have been deleted while it was queued
+        let (settings, stop_flag) = match jobs.lock().get_mut(&id) {
+            Some(job) => {
+                job.status = JobStatus::Rendering;
+                (job.settings.clone(), job.stop_flag.clone())
+            },
+            None => continue
+        };
+
+        let progress_jobs = jobs.clone();
+        let region_jobs = jobs.clone();
+        let mut forwarders = Vec::new();
+
+        // Invalid settings panic in the renderer, which fails the job rather than stopping the server
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let mut renderer = FractalRenderer::new(settings);
+            renderer.stop_flag = stop_flag;
+
+            let receiver = renderer.get_progress_receiver();
+
+            forwarders.push(thread::spawn(move || {
+                for report in receiver {
+                    if let Some(job) = progress_jobs.lock().get_mut(&id) {
+                        job.progress = Some(report.to_json());
+                        job.publish(JobEvent::Progress(report.to_json()));
+                    }
+                }
+            }));
+
+            // With progressive rendering the coarse passes of each tile are sent before the full resolution one
+            let receiver = renderer.get_region_receiver();
+
+            forwarders.push(thread::spawn(move || {
+                for region in receiver {
+                    if let Some(job) = region_jobs.lock().get_mut(&id) {
+                        job.publish(JobEvent::Region(Arc::new(region)));
+                    }
+                }
+            }));
+
+            let image = renderer.render_to_buffer(false);
+
+            if renderer.cancelled {
+                None
+            } else {
+                Some(encode_png(&image.rgb, image.image_width, image.image_height, &renderer.get_output_metadata()))
+            }
+        }));
+
+        // The channels are closed once the renderer is dropped, so every event is published before the job finishes
+        for forwarder in forwarders {
+            forwarder.join().ok();
+        }
+
+        if let Some(job) = jobs.lock().get_mut(&id) {
+            match result {
+                Ok(Some(image)) => {
+                    job.status = JobStatus::Complete;
+                    job.image = Some(image);
+                },
+                Ok(None) => job.status = JobStatus::Cancelled,
+                Err(error) => {
+                    job.status = JobStatus::Failed;
+                    job.error = Some(error.downcast_ref::<String>().cloned()
+                        .or_else(|| error.downcast_ref::<&str>().map(|message| message.to_string()))
+                        .unwrap_or_else(|| String::from("the render failed")));
+                }
+            }
+
+            let status = job.to_json(id);
+            job.publish(JobEvent::Finished(status));
+            job.subscribers.clear();
+        }
+    }
+}
+
+// Upgrades the connection to a websocket and sends the events of the job until it finishes. The progress reports are
+// text messages, and each region is a text message with its position and size followed by a binary message with its
+// pixels in RGB order, at the rendered size. The regions completed before the client connected are not sent again.
+fn stream_events(mut stream: TcpStream, request: &Request, jobs: &Jobs) -> Option<Response> {
+    let segments = request.segments();
+
+    let id = match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["jobs", id, "events"]) => id.parse::<usize>().ok(),
+        _ => return Some(Response::error(404, "not found"))
+    };
+
+    let key = match request.header("sec-websocket-key") {
+        Some(key) => key.to_owned(),
+        None => return Some(Response::error(400, "missing websocket key"))
+    };
+
+    let (tx, rx) = mpsc::channel();
+
+    {
+        let mut jobs = jobs.lock();
+
+        let job = match id.and_then(|id| jobs.get_mut(&id)) {
+            Some(job) => job,
+            None => return Some(Response::error(404, "no job with this id"))
+        };
+
+        if let Some(progress) = &job.progress {
+            tx.send(JobEvent::Progress(progress.clone())).unwrap();
+        }
+
+        if job.is_finished() {
+            tx.send(JobEvent::Finished(job.to_json(id.unwrap()))).unwrap();
+        } else {
+            job.subscribers.push(tx);
+        }
+    }
+
+    write!(stream, "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        derive_accept_key(key.as_bytes())).ok()?;
+
+    let mut websocket = WebSocket::from_raw_socket(stream, Role::Server, None);
+
+    // The channel is closed without a finished event if the job is deleted
+    for event in rx {
+        let sent = match event {
+            JobEvent::Progress(progress) => websocket.write_message(Message::Text(progress.to_string())),
+            JobEvent::Region(region) => {
+                let header = json!({
+                    "event": "region",
+                    "x": region.x,
+                    "y": region.y,
+                    "width": region.width,
+                    "height": region.height,
+                });
+
+                websocket.write_message(Message::Text(header.to_string()))
+                    .and_then(|_| websocket.write_message(Message::Binary(region.rgb.clone())))
+            },
+            JobEvent::Finished(status) => {
+                let mut status = status;
+                status["event"] = json!("finished");
+
+                websocket.write_message(Message::Text(status.to_string())).ok();
+                break;
+            }
+        };
+
+        if sent.is_err() {
+            return None;
+        }
+    }
+
+    websocket.close(None).ok();
+    websocket.write_pending().ok();
+
+    None
+}
+
+fn encode_png(buffer: &[u8], image_width: usize, image_height: usize, metadata: &[(String, String)]) -> Vec<u8> {
+    let mut output = Vec::new();
+
+    {
+        let mut encoder = png::Encoder::new(&mut output, image_width as u32, image_height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        for (key, value) in metadata {
+            encoder.add_text_chunk(key.clone(), value.clone()).unwrap();
+        }
+
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(buffer).unwrap();
+    }
+
+    output
+}
+
+// The renderers of the most recently requested blocks of tiles are kept, with the request count they were last used at
+struct TileRenderer {
+    settings: Config,
+    real: String,
+    imag: String,
+    zoom: FloatExtended,
+    tile_size: usize,
+    block_size: u64,
+    maximum_renderers: usize,
+    renderers: BTreeMap<(u32, u64, u64), (usize, FractalRenderer)>,
+    requests: usize,
+}
+
+impl TileRenderer {
+    fn new(settings: Config) -> Result<Self, String> {
+        let mut settings = settings;
+
+        // The tiles must line up exactly, so the settings which change the geometry of the image are fixed
+        for &(key, value) in &[("rotate", 0i64), ("crop_margin", 0), ("supersampling", 1), ("frames", 1)] {
+            settings.set(key, value).unwrap();
+        }
+
+        settings.set("exponential_map", false).unwrap();
+        settings.set("remove_centre", false).unwrap();
+        settings.set("jitter", false).unwrap();
+        settings.set("show_output", false).unwrap();
+
+        // The pixel spacing of a renderer needs at least two rows
+        let tile_size = settings.get_int("tile_size").unwrap_or(256).max(2) as usize;
+
+        settings.set("image_width", tile_size as i64).unwrap();
+        settings.set("image_height", tile_size as i64).unwrap();
+
+        let validation = Validation::check(&settings);
+
+        if !validation.is_valid() {
+            return Err(validation.errors.join(", "));
+        }
+
+        let real = settings.get_str("real").unwrap_or_else(|_| String::from("-0.75"));
+        let imag = settings.get_str("imag").unwrap_or_else(|_| String::from("0.0"));
+
+        if ComplexArbitrary::parse("(".to_owned() + &real + "," + &imag + ")").is_err() {
+            return Err(String::from("the location of the settings is not valid"));
+        }
+
+        Ok(TileRenderer {
+            real,
+            imag,
+            zoom: string_to_extended(&settings.get_str("zoom").unwrap_or_else(|_| String::from("1E0"))),
+            tile_size,
+            block_size: settings.get_int("tile_reference_block").unwrap_or(4).max(1) as u64,
+            maximum_renderers: settings.get_int("tile_renderers").unwrap_or(16).max(1) as usize,
+            renderers: BTreeMap::new(),
+            requests: 0,
+            settings,
+        })
+    }
+
+    // The cached tiles of other settings are kept apart, so changing the location or palette does not serve old tiles
+    fn get_cache_key(&self) -> String {
+        let mut hasher = DefaultHasher::new();
+        self.settings.clone().try_into::<Value>().map(|value| value.to_string()).unwrap_or_default().hash(&mut hasher);
+
+        format!("{:016x}", hasher.finish())
+    }
+
+    // The tiles of a level are grouped into square blocks, with the reference at the centre of each block
+    fn render_tile(&mut self, level: u32, x: u64, y: u64) -> Result<Vec<u8>, String> {
+        let tiles = 1u64 << level;
+        let block_size = self.block_size.min(tiles);
+        let block = (level, x / block_size, y / block_size);
+
+        self.requests += 1;
+
+        if !self.renderers.contains_key(&block) {
+            if self.renderers.len() >= self.maximum_renderers {
+                let oldest = self.renderers.iter().min_by_key(|(_, (last_used, _))| *last_used).map(|(key, _)| *key).unwrap();
+                self.renderers.remove(&oldest);
+            }
+
+            let renderer = self.get_block_renderer(level, block.1 * block_size, block.2 * block_size, block_size)?;
+            self.renderers.insert(block, (0, renderer));
+        }
+
+        let (last_used, renderer) = self.renderers.get_mut(&block).unwrap();
+        *last_used = self.requests;
+
+        // The offset of the centre of the tile from the centre of its block, in pixels
+        let offset_x = ((x % block_size) as f64 + 0.5 - 0.5 * block_size as f64) * self.tile_size as f64;
+        let offset_y = ((y % block_size) as f64 + 0.5 - 0.5 * block_size as f64) * self.tile_size as f64;
+
+        // A renderer which panicked is not used again
+        match panic::catch_unwind(AssertUnwindSafe(|| renderer.render_offset_to_buffer(offset_x, offset_y))) {
+            Ok(image) => Ok(encode_png(&image.rgb, image.image_width, image.image_height, &[])),
+            Err(_) => {
+                self.renderers.remove(&block);
+                Err(String::from("the tile could not be rendered"))
+            }
+        }
+    }
+
+    fn get_block_renderer(&self, level: u32, first_x: u64, first_y: u64, block_size: u64) -> Result<FractalRenderer, String> {
+        let zoom = FloatExtended::new(self.zoom.mantissa, self.zoom.exponent + level as i32);
+
+        let mut renderer = panic::catch_unwind(AssertUnwindSafe(|| FractalRendererBuilder::from_settings(self.settings.clone())
+            .zoom_extended(zoom)
+            .build()))
+            .map_err(|_| String::from("the renderer could not be created"))?;
+
+        // The pixels of a tile are spaced over its height less one, as the renderer spaces them, and the tiles are
+        // spaced by their whole size, so that the last pixel of a tile and the first of the next are one pixel apart
+        let delta_pixel = 4.0 / ((self.tile_size - 1) as f64 * renderer.zoom.mantissa);
+        let precision = FractalRenderer::get_precision(renderer.zoom, block_size as usize * self.tile_size, block_size as usize * self.tile_size);
+
+        // The centre of the block from the centre of the level, in pixels. The offset is multiplied at the precision
+        // of the location, as at deep levels it has more bits than a double.
+        let tiles = 1u64 << level;
+        let centre_x = (first_x as f64 + 0.5 * block_size as f64 - 0.5 * tiles as f64) * self.tile_size as f64;
+        let centre_y = (first_y as f64 + 0.5 * block_size as f64 - 0.5 * tiles as f64) * self.tile_size as f64;
+
+        let mut delta_real = FloatArbitrary::with_val(precision, centre_x) * delta_pixel;
+        let mut delta_imag = FloatArbitrary::with_val(precision, centre_y) * delta_pixel;
+
+        delta_real >>= renderer.zoom.exponent;
+        delta_imag >>= renderer.zoom.exponent;
+
+        let mut location = ComplexArbitrary::with_val(
+            precision,
+            ComplexArbitrary::parse("(".to_owned() + &self.real + "," + &self.imag + ")").map_err(|_| String::from("the location of the settings is not valid"))?);
+
+        *location.mut_real() += delta_real;
+        *location.mut_imag() += delta_imag;
+
+        renderer.set_location(location);
+        Ok(renderer)
+    }
+}
+
+// Renders the tiles of a slippy map on demand. The single tile at level 0 shows the location of the settings, and
+// each level doubles the zoom with four tiles covering each tile of the level above. The rendered tiles are cached as
+// files in a folder for the settings, and the tiles of a block at the same level share a reference so that only the
+// first of them calculates it.
+//
+//     GET /tiles/{z}/{x}/{y}.png
+pub fn run_tile_server(address: &str, settings: Config) -> Result<(), String> {
+    let listener = TcpListener::bind(address).map_err(|error| format!("could not listen on {}: {}", address, error))?;
+
+    let cache_enabled = settings.get_bool("tile_cache").unwrap_or(true);
+    let cache_directory = settings.get_str("tile_cache_directory").unwrap_or_else(|_| String::from("output/tiles"));
+
+    let renderer = TileRenderer::new(settings)?;
+
+    let cache_directory = if cache_enabled {
+        Some(format!("{}/{}", cache_directory, renderer.get_cache_key()))
+    } else {
+        None
+    };
+
+    // The renders use every thread, so the tiles are rendered one at a time
+    let renderer = Arc::new(Mutex::new(renderer));
+
+    println!("serving tiles on {}", address);
+
+    for stream in listener.incoming().filter_map(|stream| stream.ok()) {
+        let renderer = renderer.clone();
+        let cache_directory = cache_directory.clone();
+
+        thread::spawn(move || {
+            let mut stream = stream;
+
+            let response = match read_request(&mut stream) {
+                Ok(request) => handle_tile_request(&request, &renderer, &cache_directory),
+                Err(response) => response
+            };
+
+            response.write(&mut stream).ok();
+        });
+    }
+
+    Ok(())
+}
+
+fn handle_tile_request(request: &Request, renderer: &Mutex<TileRenderer>, cache_directory: &Option<String>) -> Response {
+    let segments = request.segments();
+
+    let (level, x, y) = match segments.as_slice() {
+        ["tiles", level, x, y] => match (level.parse::<u32>(), x.parse::<u64>(), y.trim_end_matches(".png").parse::<u64>()) {
+            (Ok(level), Ok(x), Ok(y)) if level <= MAXIMUM_TILE_LEVEL && x < (1 << level) && y < (1 << level) => (level, x, y),
+            _ => return Response::error(404, "no tile at these coordinates")
+        },
+        _ => return Response::error(404, "not found")
+    };
+
+    if request.method != "GET" {
+        return Response::error(405, "method not allowed");
+    }
+
+    let filename = cache_directory.as_ref().map(|directory| format!("{}/{}/{}/{}.png", directory, level, x, y));
+
+    let image = match filename.as_ref().and_then(|filename| fs::read(filename).ok()) {
+        Some(image) => image,
+        None => {
+            let image = match renderer.lock().render_tile(level, x, y) {
+                Ok(image) => image,
+                Err(error) => return Response::error(500, &error)
+            };
+
+            if let Some(filename) = &filename {
+                if let Some(parent) = Path::new(filename).parent() {
+                    fs::create_dir_all(parent).ok();
+                }
+
+                fs::write(filename, &image).ok();
+            }
+
+            image
+        }
+    };
+
+    Response {
+        code: 200,
+        content_type: "image/png",
+        body: image,
+    }
+}
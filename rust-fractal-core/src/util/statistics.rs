@@ -0,0 +1,94 @@
+use serde_json::json;
+
+use std::fs;
+
+// Timings are in milliseconds
+#[derive(Clone, Default)]
+pub struct RenderStatistics {
+    pub zoom: String,
+    pub image_width: usize,
+    pub image_height: usize,
+    pub reference_time: u128,
+    pub approximation_time: u128,
+    pub iteration_time: u128,
+    pub correction_time: u128,
+    pub saving_time: u128,
+    pub frame_time: u128,
+    pub skipped_iterations: usize,
+    pub maximum_skipped_iterations: usize,
+    pub approximation_order: usize,
+    pub maximum_iteration: usize,
+    pub reference_count: usize,
+    pub reference_iteration: usize,
+    pub glitched_pixels_per_pass: Vec<usize>,
+    pub precision: u32,
+    pub peak_memory: Option<usize>,
+    pub cancelled: bool,
+    pub completed_pixels: usize,
+    pub interestingness: f64,
+}
+
+impl RenderStatistics {
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "zoom": self.zoom,
+            "image_width": self.image_width,
+            "image_height": self.image_height,
+            "timings": {
+                "reference": self.reference_time as u64,
+                "approximation": self.approximation_time as u64,
+                "iteration": self.iteration_time as u64,
+                "correction": self.correction_time as u64,
+                "saving": self.saving_time as u64,
+                "frame": self.frame_time as u64,
+            },
+            "skipped_iterations": self.skipped_iterations,
+            "maximum_skipped_iterations": self.maximum_skipped_iterations,
+            "approximation_order": self.approximation_order,
+            "maximum_iteration": self.maximum_iteration,
+            "reference_count": self.reference_count,
+            "reference_iteration": self.reference_iteration,
+            "glitched_pixels_per_pass": self.glitched_pixels_per_pass,
+            "precision": self.precision,
+            "peak_memory": self.peak_memory,
+            "cancelled": self.cancelled,
+            "completed_pixels": self.completed_pixels,
+            "interestingness": self.interestingness,
+        })
+    }
+
+    pub fn save(&self, filename: &str) {
+        fs::write(filename.to_owned() + ".json", serde_json::to_string_pretty(&self.to_json()).unwrap()).unwrap();
+    }
+}
+
+// Returns the peak resident memory of the process in bytes, this is only available on linux
+pub fn get_peak_memory() -> Option<usize> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+
+    status.lines()
+        .find(|line| line.starts_with("VmHWM:"))
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|value| value.parse::<usize>().ok())
+        .map(|kilobytes| kilobytes * 1024)
+}
+
+// The estimated peak memory of a frame in bytes, split by where it is used
+#[derive(Clone, Copy, Default)]
+pub struct MemoryEstimate {
+    pub pixel_state: usize,
+    pub reference: usize,
+    pub series_approximation: usize,
+    pub export: usize,
+}
+
+impl MemoryEstimate {
+    // The memory which does not depend on the pixel chunk size
+    pub fn fixed(&self) -> usize {
+        self.reference + self.series_approximation + self.export
+    }
+
+    pub fn total(&self) -> usize {
+        self.fixed() + self.pixel_state
+    }
+}
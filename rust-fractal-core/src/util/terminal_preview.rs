@@ -0,0 +1,168 @@
+use crate::renderer::FractalRenderer;
+use crate::util::RenderParameters;
+
+use config::Config;
+
+use std::io::Write;
+
+// Renders a small version of the first frame and prints it to the terminal, either with truecolor half block
+// characters, which show two pixels in each character, or as a sixel image for the terminals which support them
+pub struct TerminalPreview {
+    settings: Config,
+    sixel: bool,
+}
+
+impl TerminalPreview {
+    pub fn new(settings: Config, sixel: bool) -> Self {
+        TerminalPreview {
+            settings,
+            sixel,
+        }
+    }
+
+    pub fn show(&self) {
+        let parameters = RenderParameters::from_config(&self.settings);
+        let aspect = parameters.image_width as f64 / parameters.image_height as f64;
+
+        // The characters are about twice as tall as they are wide, so each half of a character is a square pixel
+        let (image_width, image_height) = if self.sixel {
+            let width = self.settings.get_int("preview_width").unwrap_or(480) as usize;
+            (width, ((width as f64 / aspect) as usize).max(2))
+        } else {
+            let columns = self.settings.get_int("preview_columns").ok()
+                .map(|columns| columns as usize)
+                .or_else(|| std::env::var("COLUMNS").ok().and_then(|columns| columns.parse::<usize>().ok()))
+                .unwrap_or(80);
+
+            (columns, ((columns as f64 / aspect) as usize).max(2))
+        };
+
+        let mut settings = self.settings.clone();
+
+        settings.set("image_width", image_width as i64).unwrap();
+        settings.set("image_height", image_height as i64).unwrap();
+        settings.set("frames", 1i64).unwrap();
+        settings.set("supersampling", 1i64).unwrap();
+        settings.set("crop_margin", 0i64).unwrap();
+        settings.set("show_output", false).unwrap();
+
+        let mut renderer = FractalRenderer::new(settings);
+        let image = renderer.render_to_buffer(false);
+
+        let output = if self.sixel {
+            encode_sixel(&image.rgb, image.image_width, image.image_height)
+        } else {
+            encode_half_blocks(&image.rgb, image.image_width, image.image_height)
+        };
+
+        let stdout = std::io::stdout();
+        let mut stdout = stdout.lock();
+
+        stdout.write_all(output.as_bytes()).unwrap();
+        stdout.flush().unwrap();
+
+        println!("{} by {} preview of {} rendered in {} ms", image.image_width, image.image_height, parameters.zoom, renderer.render_time);
+    }
+}
+
+// The upper pixel of each character is the foreground and the lower pixel the background
+pub fn encode_half_blocks(rgb: &[u8], image_width: usize, image_height: usize) -> String {
+    let mut output = String::new();
+
+    for j in (0..image_height).step_by(2) {
+        for i in 0..image_width {
+            let upper = 3 * (j * image_width + i);
+            output += &format!("\x1b[38;2;{};{};{}m", rgb[upper], rgb[upper + 1], rgb[upper + 2]);
+
+            // An odd row at the bottom is left on the terminal background
+            if j + 1 < image_height {
+                let lower = 3 * ((j + 1) * image_width + i);
+                output += &format!("\x1b[48;2;{};{};{}m", rgb[lower], rgb[lower + 1], rgb[lower + 2]);
+            }
+
+            output.push('\u{2580}');
+        }
+
+        output += "\x1b[0m\n";
+    }
+
+    output
+}
+
+// The colours are quantised to a 6x6x6 cube, which every sixel terminal can show. Each band of six rows is drawn once
+// for each colour which it contains, with runs of the same sixel compressed.
+pub fn encode_sixel(rgb: &[u8], image_width: usize, image_height: usize) -> String {
+    let indices = rgb.chunks_exact(3)
+        .map(|pixel| {
+            let level = |value: u8| (value as usize * 5 + 127) / 255;
+            36 * level(pixel[0]) + 6 * level(pixel[1]) + level(pixel[2])
+        })
+        .collect::<Vec<usize>>();
+
+    let mut output = format!("\x1bPq\"1;1;{};{}", image_width, image_height);
+
+    for index in 0..216 {
+        let percentage = |level: usize| level * 20;
+        output += &format!("#{};2;{};{};{}", index, percentage(index / 36), percentage((index / 6) % 6), percentage(index % 6));
+    }
+
+    for band in (0..image_height).step_by(6) {
+        let rows = (image_height - band).min(6);
+
+        let mut used = [false; 216];
+
+        for j in band..(band + rows) {
+            for i in 0..image_width {
+                used[indices[j * image_width + i]] = true;
+            }
+        }
+
+        for colour in (0..216).filter(|&colour| used[colour]) {
+            output += &format!("#{}", colour);
+
+            let mut previous = None;
+            let mut count = 0;
+
+            for i in 0..image_width {
+                let bits = (0..rows)
+                    .filter(|&row| indices[(band + row) * image_width + i] == colour)
+                    .fold(0u8, |bits, row| bits | (1 << row));
+
+                let character = (63 + bits) as char;
+
+                if previous == Some(character) {
+                    count += 1;
+                } else {
+                    if let Some(previous) = previous {
+                        push_sixel_run(&mut output, previous, count);
+                    }
+
+                    previous = Some(character);
+                    count = 1;
+                }
+            }
+
+            if let Some(previous) = previous {
+                push_sixel_run(&mut output, previous, count);
+            }
+
+            // Returns to the start of the band for the next colour
+            output.push('$');
+        }
+
+        output.push('-');
+    }
+
+    output += "\x1b\\";
+    output
+}
+
+fn push_sixel_run(output: &mut String, character: char, count: usize) {
+    if count > 3 {
+        *output += &format!("!{}{}", count, character);
+    } else {
+        for _ in 0..count {
+            output.push(character);
+        }
+    }
+}
@@ -0,0 +1,272 @@
+use crate::util::{CameraPath, RenderParameters, FloatArbitrary, parse_resolution, parse_zoom};
+use crate::util::palettes::{PALETTE_NAMES, is_palette_name};
+use crate::util::log::LOG_FORMATS;
+
+use config::{Config, ConfigError};
+use serde_json::Value;
+
+use std::f64::consts::LOG10_2;
+
+// The keys which are read from the settings directly rather than through the parameters
+const ADDITIONAL_KEYS: [&str; 41] = [
+    "additional_resolutions", "batch_filename", "batch_output", "benchmark_output", "calibration_iterations",
+    "calibration_scale", "calibration_size", "compare_output", "interpolation_frames", "iteration_offset", "manifest",
+    "manifest_filename", "path", "pin_threads", "tile_cache", "tile_cache_directory",
+    "tile_reference_block", "tile_renderers", "tile_size", "preview_columns", "preview_width", "zoom_easing",
+    "zoom_curve", "rotate_easing", "rotate_curve", "palette_easing", "palette_curve", "fractal_type", "watch_width",
+    "fractint_entry", "explore_zoom", "explore_candidates", "explore_seed", "explore_scan_width",
+    "explore_thumbnail_width", "batch_minimum_score", "explore_minimum_score", "bookmarks_file",
+    "bookmark_thumbnail_width", "queue_file", "distributed_tile_size",
+];
+
+// The channels which can be selected for the raw export
+const RAW_CHANNELS: [&str; 5] = ["iterations", "smooth", "distance", "stripe", "glitch"];
+
+// The sides of the image which the view can be fitted to
+const VIEW_FITS: [&str; 5] = ["height", "width", "diagonal", "shorter", "longer"];
+
+// The problems found in the settings before rendering. The errors would stop the render or make it panic, and the
+// warnings are for settings which are ignored or which will not give the image that was intended.
+#[derive(Default, Debug)]
+pub struct Validation {
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+impl Validation {
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    // All of the problems are checked, so that they can be fixed at once
+    pub fn check(settings: &Config) -> Self {
+        let mut validation = Validation::default();
+
+        validation.check_keys(settings);
+
+        let mut settings = settings.clone();
+        RenderParameters::replace_automatic_iterations(&mut settings);
+
+        let parameters = match settings.try_into::<RenderParameters>() {
+            Ok(parameters) => parameters,
+            Err(error) => {
+                validation.errors.push(format!("{}, check that the value has the right type", error));
+                return validation;
+            }
+        };
+
+        for (key, zoom) in [("zoom", Some(&parameters.zoom)), ("end_zoom", parameters.end_zoom.as_ref())].iter() {
+            if let Some(zoom) = zoom {
+                if get_zoom_exponent(zoom, parameters.image_height).is_none() {
+                    validation.errors.push(format!("{} \"{}\" is not valid, it should be a positive number such as \"1.5E100\" or \"1.5*10^100\", or a pixel size such as \"2E-100px\"", key, zoom));
+                }
+            }
+        }
+
+        for (key, value) in [("real", &parameters.real), ("imag", &parameters.imag)].iter() {
+            if FloatArbitrary::parse(value).is_err() {
+                validation.errors.push(format!("{} \"{}\" is not a valid number", key, value));
+            }
+        }
+
+        if let (Some(julia_real), Some(julia_imag)) = (&parameters.julia_real, &parameters.julia_imag) {
+            if FloatArbitrary::parse(julia_real).is_err() || FloatArbitrary::parse(julia_imag).is_err() {
+                validation.errors.push(format!("the julia seed ({}, {}) is not a valid number", julia_real, julia_imag));
+            }
+        } else if parameters.julia_real.is_some() || parameters.julia_imag.is_some() {
+            validation.warnings.push(String::from("only one of julia_real and julia_imag is set, both are needed to render a Julia set"));
+        }
+
+        // The pixel spacing is divided by the height less one
+        if parameters.image_width == 0 {
+            validation.errors.push(String::from("image_width must be at least 1"));
+        }
+
+        if parameters.image_height < 2 {
+            validation.errors.push(String::from("image_height must be at least 2"));
+        }
+
+        if parameters.iterations == 0 {
+            validation.errors.push(String::from("iterations must be at least 1"));
+        }
+
+        if parameters.glitch_colour.len() != 3 || parameters.glitch_colour.iter().any(|&component| !(0..=255).contains(&component)) {
+            validation.errors.push(format!("glitch_colour is {:?}, it must be three values from 0 to 255", parameters.glitch_colour));
+        }
+
+        for channel in parameters.raw_channels.iter().filter(|channel| !RAW_CHANNELS.contains(&channel.to_ascii_lowercase().as_str())) {
+            validation.errors.push(format!("raw channel \"{}\" is not known, it should be one of {}", channel, RAW_CHANNELS.join(", ")));
+        }
+
+        if ["RAW", "EXR", "BOTH"].contains(&parameters.export.to_ascii_uppercase().as_str()) && parameters.raw_channels.is_empty() && !parameters.export_glitch_map {
+            validation.warnings.push(String::from("no raw_channels are selected, so the raw export will not be written"));
+        }
+
+        if parameters.resume && parameters.output_dir.is_some() && parameters.output_subfolder {
+            validation.warnings.push(String::from("output_subfolder creates a new folder for each run, so resume will not find the frames of previous runs"));
+        }
+
+        if !VIEW_FITS.contains(&parameters.view_fit.to_ascii_lowercase().as_str()) {
+            validation.errors.push(format!("view_fit \"{}\" is not known, it should be one of {}", parameters.view_fit, VIEW_FITS.join(", ")));
+        }
+
+        if parameters.histogram_bins == 0 {
+            validation.errors.push(String::from("histogram_bins must be at least 1"));
+        }
+
+        if parameters.reference_orbit_points < 2 {
+            validation.errors.push(String::from("reference_orbit_points must be at least 2"));
+        }
+
+        if parameters.reference_orbit_overlay && ["RAW", "EXR"].contains(&parameters.export.to_ascii_uppercase().as_str()) {
+            validation.warnings.push(String::from("reference_orbit_overlay is only drawn on the colour image, which is not saved with the raw export"));
+        }
+
+        if !LOG_FORMATS.contains(&parameters.log_format.to_ascii_lowercase().as_str()) {
+            validation.errors.push(format!("log_format \"{}\" is not known, it should be one of {}", parameters.log_format, LOG_FORMATS.join(", ")));
+        }
+
+        if let Some(name) = &parameters.palette_name {
+            if !is_palette_name(name) {
+                validation.errors.push(format!("palette_name \"{}\" is not known, it should be one of {}", name, PALETTE_NAMES.join(", ")));
+            } else if parameters.palette.is_some() {
+                validation.warnings.push(format!("palette_name \"{}\" is ignored as the colours of the palette are also given", name));
+            }
+        }
+
+        if !parameters.region.is_empty() {
+            if parameters.region.len() != 4 {
+                validation.errors.push(format!("region has {} values, it should be the x, y, width and height", parameters.region.len()));
+            } else if parameters.get_region().is_none() {
+                validation.errors.push(String::from("region does not cover any pixels of the image"));
+            } else if parameters.exponential_map {
+                validation.warnings.push(String::from("region is ignored for the exponential map"));
+            } else if parameters.frames > 1 {
+                validation.warnings.push(String::from("region is applied to the centre of the sequence, so the frames after the first are not crops of the full frames"));
+            }
+        }
+
+        if !parameters.image_offset.is_empty() && parameters.image_offset.len() != 2 {
+            validation.errors.push(format!("image_offset has {} values, it should be the x and y", parameters.image_offset.len()));
+        }
+
+        match settings.get_array("additional_resolutions") {
+            Ok(resolutions) => {
+                for resolution in resolutions.into_iter().map(|resolution| resolution.into_str().unwrap_or_default()) {
+                    if parse_resolution(&resolution).is_none() {
+                        validation.errors.push(format!("additional resolution \"{}\" is not valid, it should be of the form \"1920x1080\"", resolution));
+                    }
+                }
+            },
+            Err(ConfigError::NotFound(_)) => {},
+            Err(_) => validation.errors.push(String::from("additional_resolutions must be a list such as [\"1920x1080\"]"))
+        }
+
+        if let Err(error) = CameraPath::parse(&settings) {
+            validation.errors.push(error);
+        }
+
+        if parameters.frames == 0 {
+            validation.errors.push(String::from("frames must be at least 1"));
+        }
+
+        if parameters.frames > 1 && parameters.zoom_scale <= 1.0 && parameters.end_zoom.is_none() {
+            validation.errors.push(format!("zoom_scale is {}, it must be more than 1 for a sequence of frames", parameters.zoom_scale));
+        }
+
+        if validation.is_valid() {
+            validation.check_centre_precision(&parameters);
+        }
+
+        validation
+    }
+
+    // The unknown keys are most likely typos, which would otherwise be silently ignored
+    fn check_keys(&mut self, settings: &Config) {
+        let mut known = match serde_json::to_value(RenderParameters::default()).unwrap() {
+            Value::Object(map) => map.into_iter().map(|(key, _)| key).collect::<Vec<String>>(),
+            _ => Vec::new()
+        };
+
+        known.extend(ADDITIONAL_KEYS.iter().map(|key| key.to_string()));
+
+        let keys = match settings.clone().try_into::<Value>() {
+            Ok(Value::Object(map)) => map.into_iter().map(|(key, _)| key).collect::<Vec<String>>(),
+            _ => Vec::new()
+        };
+
+        for key in keys.iter().filter(|key| !known.contains(key)) {
+            let closest = known.iter()
+                .map(|known_key| (get_edit_distance(key, known_key), known_key))
+                .min();
+
+            match closest {
+                Some((distance, known_key)) if distance <= 2 => {
+                    self.warnings.push(format!("unknown key \"{}\" is ignored, did you mean \"{}\"?", key, known_key));
+                },
+                _ => {
+                    self.warnings.push(format!("unknown key \"{}\" is ignored", key));
+                }
+            }
+        }
+    }
+
+    // A centre with fewer decimal places than the pixel spacing is rounded to a location which was not intended
+    fn check_centre_precision(&mut self, parameters: &RenderParameters) {
+        let zoom_exponent = get_zoom_exponent(&parameters.zoom, parameters.image_height).unwrap();
+        let needed = (zoom_exponent + (parameters.image_height as f64 / 4.0).log10()).ceil().max(0.0) as usize;
+
+        for (key, value) in [("real", &parameters.real), ("imag", &parameters.imag)].iter() {
+            let decimal_places = get_decimal_places(value);
+
+            // Values with only a few digits, such as a centre of -0.75, are taken to be exact
+            if decimal_places + 1 < needed && get_significant_digits(value) >= 6 {
+                self.warnings.push(format!("{} has {} decimal places but a zoom of {} needs about {}, so the centre is rounded and may not be at the intended location",
+                    key,
+                    decimal_places,
+                    parameters.zoom,
+                    needed));
+            }
+        }
+    }
+}
+
+// The decimal exponent of the zoom, if it can be read by the renderer
+fn get_zoom_exponent(zoom: &str, image_height: usize) -> Option<f64> {
+    parse_zoom(zoom, image_height).ok().map(|zoom| (zoom.mantissa.log2() + zoom.exponent as f64) * LOG10_2)
+}
+
+fn get_decimal_places(value: &str) -> usize {
+    let parts = value.split(&['E', 'e'][..]).collect::<Vec<&str>>();
+    let exponent = parts.get(1).and_then(|exponent| exponent.parse::<i64>().ok()).unwrap_or(0);
+
+    let fraction = parts[0].split('.').nth(1).unwrap_or("").len() as i64;
+
+    (fraction - exponent).max(0) as usize
+}
+
+fn get_significant_digits(value: &str) -> usize {
+    let mantissa = value.split(&['E', 'e'][..]).next().unwrap_or("");
+    let digits = mantissa.chars().filter(|character| character.is_ascii_digit()).collect::<String>();
+
+    digits.trim_start_matches('0').trim_end_matches('0').len()
+}
+
+fn get_edit_distance(first: &str, second: &str) -> usize {
+    let second = second.chars().collect::<Vec<char>>();
+    let mut previous = (0..=second.len()).collect::<Vec<usize>>();
+
+    for (i, first_character) in first.chars().enumerate() {
+        let mut current = vec![i + 1; second.len() + 1];
+
+        for (j, second_character) in second.iter().enumerate() {
+            let substitution = previous[j] + (first_character != *second_character) as usize;
+
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+
+        previous = current;
+    }
+
+    previous[second.len()]
+}
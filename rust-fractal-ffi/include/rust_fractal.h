@@ -0,0 +1,81 @@
+/*
+ * C interface to the rust-fractal renderer. Link with the rust_fractal library built from the rust-fractal-ffi crate.
+ *
+ * The settings have the same keys as the options and location files. The functions which can fail return one of the
+ * RF_ codes, and the message of the last error is read with rf_renderer_get_error.
+ */
+
+#ifndef RUST_FRACTAL_H
+#define RUST_FRACTAL_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define RF_OK 0
+#define RF_CANCELLED 1
+#define RF_INVALID_ARGUMENT -1
+#define RF_RENDER_FAILED -2
+
+/* The stages of a frame, in the order they run */
+#define RF_STAGE_REFERENCE 0
+#define RF_STAGE_SERIES_APPROXIMATION 1
+#define RF_STAGE_ITERATION 2
+#define RF_STAGE_CORRECTION 3
+#define RF_STAGE_SAVING 4
+
+typedef struct RfRenderer RfRenderer;
+
+/* The times are in milliseconds, and the stage ETA is negative when it is not known yet. The zoom is only valid
+ * during the callback. */
+typedef struct RfProgress {
+    const char *zoom;
+    int stage;
+    double stage_fraction;
+    size_t pixels_iterated;
+    size_t total_pixels;
+    size_t glitched_remaining;
+    double elapsed;
+    double stage_eta;
+} RfProgress;
+
+/* Called from a thread of the renderer while a render is running, never after rf_renderer_render has returned */
+typedef void (*RfProgressCallback)(const RfProgress *progress, void *user_data);
+
+RfRenderer *rf_renderer_create(void);
+void rf_renderer_destroy(RfRenderer *renderer);
+
+/* The centre and zoom are set as strings, so that no digits are lost */
+int rf_renderer_set_string(const RfRenderer *renderer, const char *key, const char *value);
+int rf_renderer_set_int(const RfRenderer *renderer, const char *key, int64_t value);
+int rf_renderer_set_float(const RfRenderer *renderer, const char *key, double value);
+int rf_renderer_set_bool(const RfRenderer *renderer, const char *key, bool value);
+
+/* Merges a JSON object of settings, or an options or location file in TOML */
+int rf_renderer_set_json(const RfRenderer *renderer, const char *json);
+int rf_renderer_merge_file(const RfRenderer *renderer, const char *filename);
+
+/* A null callback removes the current one */
+void rf_renderer_set_progress_callback(const RfRenderer *renderer, RfProgressCallback callback, void *user_data);
+
+/* Renders the first frame into memory without writing any files, blocking until it is complete. Returns RF_OK, or
+ * RF_CANCELLED with the partial frame if rf_renderer_cancel was called from another thread. */
+int rf_renderer_render(const RfRenderer *renderer);
+void rf_renderer_cancel(const RfRenderer *renderer);
+
+/* The last rendered image in RGB order, owned by the renderer and valid until the next render or until it is
+ * destroyed. Null if nothing has been rendered. */
+const uint8_t *rf_renderer_get_image(const RfRenderer *renderer, size_t *width, size_t *height);
+
+/* The message of the last error, valid until the next error. Null if there has not been an error. */
+const char *rf_renderer_get_error(const RfRenderer *renderer);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
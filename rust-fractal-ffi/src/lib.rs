@@ -0,0 +1,369 @@
+//! C bindings to the rust-fractal renderer, built as a shared and a static library so that it can be embedded in C
+//! and C++ applications and plugins. The declarations are in `include/rust_fractal.h`.
+//!
+//! ```c
+//! RfRenderer *renderer = rf_renderer_create();
+//!
+//! rf_renderer_set_string(renderer, "real", "-0.75");
+//! rf_renderer_set_string(renderer, "imag", "0.1");
+//! rf_renderer_set_string(renderer, "zoom", "1E10");
+//! rf_renderer_set_int(renderer, "iterations", 10000);
+//!
+//! if (rf_renderer_render(renderer) == RF_OK) {
+//!     size_t width, height;
+//!     const uint8_t *rgb = rf_renderer_get_image(renderer, &width, &height);
+//! }
+//!
+//! rf_renderer_destroy(renderer);
+//! ```
+//!
+//! The settings have the same keys as the options and location files, and any which are not set take their default
+//! values. A panic never unwinds into the caller, the render returns an error code instead and the message is read
+//! with `rf_renderer_get_error`.
+
+use rust_fractal_core::{Config, FractalRenderer, ProgressReport, RenderedImage};
+
+use config::{File, FileFormat};
+
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int, c_void};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+pub const RF_OK: c_int = 0;
+pub const RF_CANCELLED: c_int = 1;
+pub const RF_INVALID_ARGUMENT: c_int = -1;
+pub const RF_RENDER_FAILED: c_int = -2;
+
+/// A snapshot of the progress of the frame, as sent to the progress callback. The stages are numbered in the order
+/// they run, from the reference (0) to saving (4), and the times are in milliseconds. The stage ETA is negative when
+/// it is not known yet. The zoom is only valid during the callback.
+#[repr(C)]
+pub struct RfProgress {
+    pub zoom: *const c_char,
+    pub stage: c_int,
+    pub stage_fraction: f64,
+    pub pixels_iterated: usize,
+    pub total_pixels: usize,
+    pub glitched_remaining: usize,
+    pub elapsed: f64,
+    pub stage_eta: f64,
+}
+
+pub type RfProgressCallback = extern "C" fn(progress: *const RfProgress, user_data: *mut c_void);
+
+// The user data is only passed back to the callback, which the caller has to make safe to call from another thread
+#[derive(Clone, Copy)]
+struct ProgressCallback {
+    callback: RfProgressCallback,
+    user_data: *mut c_void,
+}
+
+unsafe impl Send for ProgressCallback {}
+
+impl ProgressCallback {
+    fn call(&self, report: &ProgressReport) {
+        let zoom = CString::new(report.zoom.clone()).unwrap_or_default();
+
+        let progress = RfProgress {
+            zoom: zoom.as_ptr(),
+            stage: report.stage as c_int,
+            stage_fraction: report.stage_fraction,
+            pixels_iterated: report.pixels_iterated,
+            total_pixels: report.total_pixels,
+            glitched_remaining: report.glitched_remaining,
+            elapsed: report.elapsed.as_millis() as f64,
+            stage_eta: report.stage_eta.map(|eta| eta.as_millis() as f64).unwrap_or(-1.0),
+        };
+
+        (self.callback)(&progress, self.user_data);
+    }
+}
+
+/// A handle holding the settings and the last rendered image. The state is behind locks so that a render can be
+/// cancelled from another thread while it is running.
+pub struct RfRenderer {
+    settings: Mutex<Config>,
+    progress: Mutex<Option<ProgressCallback>>,
+    image: Mutex<Option<RenderedImage>>,
+    error: Mutex<Option<CString>>,
+    stop_flag: Arc<AtomicBool>,
+}
+
+impl RfRenderer {
+    fn set_error(&self, message: &str) {
+        *self.error.lock().unwrap() = CString::new(message.replace('\0', "")).ok();
+    }
+
+    fn set<T: Into<config::Value>>(&self, key: *const c_char, value: T) -> c_int {
+        let key = match unsafe { get_str(key) } {
+            Some(key) => key,
+            None => {
+                self.set_error("the key must be a valid UTF-8 string");
+                return RF_INVALID_ARGUMENT;
+            }
+        };
+
+        match self.settings.lock().unwrap().set(key, value) {
+            Ok(_) => RF_OK,
+            Err(error) => {
+                self.set_error(&format!("failed to set {}: {}", key, error));
+                RF_INVALID_ARGUMENT
+            }
+        }
+    }
+
+    fn merge<T: 'static + config::Source + Send + Sync>(&self, source: T) -> c_int {
+        match self.settings.lock().unwrap().merge(source) {
+            Ok(_) => RF_OK,
+            Err(error) => {
+                self.set_error(&error.to_string());
+                RF_INVALID_ARGUMENT
+            }
+        }
+    }
+}
+
+unsafe fn get_str<'a>(value: *const c_char) -> Option<&'a str> {
+    if value.is_null() {
+        None
+    } else {
+        CStr::from_ptr(value).to_str().ok()
+    }
+}
+
+/// Creates a renderer with the default settings, which is freed with `rf_renderer_destroy`.
+#[no_mangle]
+pub extern "C" fn rf_renderer_create() -> *mut RfRenderer {
+    Box::into_raw(Box::new(RfRenderer {
+        settings: Mutex::new(Config::default()),
+        progress: Mutex::new(None),
+        image: Mutex::new(None),
+        error: Mutex::new(None),
+        stop_flag: Arc::new(AtomicBool::new(false)),
+    }))
+}
+
+/// # Safety
+/// The renderer must have come from `rf_renderer_create` and must not be used again, or be null.
+#[no_mangle]
+pub unsafe extern "C" fn rf_renderer_destroy(renderer: *mut RfRenderer) {
+    if !renderer.is_null() {
+        drop(Box::from_raw(renderer));
+    }
+}
+
+/// Sets a string setting. The centre and zoom are strings, so that no digits are lost.
+///
+/// # Safety
+/// The renderer must be valid or null, and the key and value null terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn rf_renderer_set_string(renderer: *const RfRenderer, key: *const c_char, value: *const c_char) -> c_int {
+    match (renderer.as_ref(), get_str(value)) {
+        (Some(renderer), Some(value)) => renderer.set(key, value),
+        _ => RF_INVALID_ARGUMENT
+    }
+}
+
+/// # Safety
+/// The renderer must be valid or null, and the key a null terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn rf_renderer_set_int(renderer: *const RfRenderer, key: *const c_char, value: i64) -> c_int {
+    renderer.as_ref().map(|renderer| renderer.set(key, value)).unwrap_or(RF_INVALID_ARGUMENT)
+}
+
+/// # Safety
+/// The renderer must be valid or null, and the key a null terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn rf_renderer_set_float(renderer: *const RfRenderer, key: *const c_char, value: f64) -> c_int {
+    renderer.as_ref().map(|renderer| renderer.set(key, value)).unwrap_or(RF_INVALID_ARGUMENT)
+}
+
+/// # Safety
+/// The renderer must be valid or null, and the key a null terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn rf_renderer_set_bool(renderer: *const RfRenderer, key: *const c_char, value: bool) -> c_int {
+    renderer.as_ref().map(|renderer| renderer.set(key, value)).unwrap_or(RF_INVALID_ARGUMENT)
+}
+
+/// Merges a JSON object of settings, for setting many at once or the arrays such as the palette.
+///
+/// # Safety
+/// The renderer must be valid or null, and the JSON a null terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn rf_renderer_set_json(renderer: *const RfRenderer, json: *const c_char) -> c_int {
+    let renderer = match renderer.as_ref() {
+        Some(renderer) => renderer,
+        None => return RF_INVALID_ARGUMENT
+    };
+
+    match get_str(json) {
+        Some(json) if matches!(serde_json::from_str(json), Ok(serde_json::Value::Object(_))) => renderer.merge(File::from_str(json, FileFormat::Json)),
+        _ => {
+            renderer.set_error("the settings must be a JSON object");
+            RF_INVALID_ARGUMENT
+        }
+    }
+}
+
+/// Merges an options or location file, in TOML as the command line renderer reads them.
+///
+/// # Safety
+/// The renderer must be valid or null, and the filename a null terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn rf_renderer_merge_file(renderer: *const RfRenderer, filename: *const c_char) -> c_int {
+    let renderer = match renderer.as_ref() {
+        Some(renderer) => renderer,
+        None => return RF_INVALID_ARGUMENT
+    };
+
+    let filename = match get_str(filename) {
+        Some(filename) => filename,
+        None => return RF_INVALID_ARGUMENT
+    };
+
+    match std::fs::read_to_string(filename) {
+        Ok(contents) => renderer.merge(File::from_str(&contents, FileFormat::Toml)),
+        Err(error) => {
+            renderer.set_error(&format!("failed to read {}: {}", filename, error));
+            RF_INVALID_ARGUMENT
+        }
+    }
+}
+
+/// Sets the function which is sent the progress of each render, or removes it when null. The callback is called
+/// from another thread while the render is running, but never after `rf_renderer_render` has returned.
+///
+/// # Safety
+/// The renderer must be valid or null, and the callback must be safe to call with the user data from any thread.
+#[no_mangle]
+pub unsafe extern "C" fn rf_renderer_set_progress_callback(renderer: *const RfRenderer, callback: Option<RfProgressCallback>, user_data: *mut c_void) {
+    if let Some(renderer) = renderer.as_ref() {
+        *renderer.progress.lock().unwrap() = callback.map(|callback| ProgressCallback {
+            callback,
+            user_data,
+        });
+    }
+}
+
+/// Renders the first frame of the settings into memory without writing any files, blocking until it is complete.
+/// Returns `RF_OK`, or `RF_CANCELLED` if the render was cancelled, in which case the image is the partial frame.
+///
+/// # Safety
+/// The renderer must be valid or null.
+#[no_mangle]
+pub unsafe extern "C" fn rf_renderer_render(renderer: *const RfRenderer) -> c_int {
+    let renderer = match renderer.as_ref() {
+        Some(renderer) => renderer,
+        None => return RF_INVALID_ARGUMENT
+    };
+
+    let mut settings = renderer.settings.lock().unwrap().clone();
+    settings.set("show_output", false).unwrap();
+
+    let progress = *renderer.progress.lock().unwrap();
+    let stop_flag = renderer.stop_flag.clone();
+    stop_flag.store(false, Ordering::SeqCst);
+
+    let mut reporter = None;
+
+    // Unwinding into C is undefined, so a panic from invalid settings or the render is returned as an error
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut fractal_renderer = FractalRenderer::new(settings);
+        fractal_renderer.stop_flag = stop_flag;
+
+        if let Some(progress) = progress {
+            let receiver = fractal_renderer.get_progress_receiver();
+
+            reporter = Some(thread::spawn(move || {
+                for report in receiver {
+                    progress.call(&report);
+                }
+            }));
+        }
+
+        let image = fractal_renderer.render_to_buffer(false);
+
+        (image, fractal_renderer.cancelled)
+    }));
+
+    // The reports stop once the renderer has been dropped
+    if let Some(reporter) = reporter {
+        reporter.join().ok();
+    }
+
+    match result {
+        Ok((image, cancelled)) => {
+            *renderer.image.lock().unwrap() = Some(image);
+
+            if cancelled {
+                RF_CANCELLED
+            } else {
+                RF_OK
+            }
+        },
+        Err(error) => {
+            let message = error.downcast_ref::<String>().cloned()
+                .or_else(|| error.downcast_ref::<&str>().map(|error| error.to_string()))
+                .unwrap_or_else(|| String::from("the render panicked"));
+
+            renderer.set_error(&message);
+            RF_RENDER_FAILED
+        }
+    }
+}
+
+/// Stops the running render as soon as possible. This can be called from any thread.
+///
+/// # Safety
+/// The renderer must be valid or null.
+#[no_mangle]
+pub unsafe extern "C" fn rf_renderer_cancel(renderer: *const RfRenderer) {
+    if let Some(renderer) = renderer.as_ref() {
+        renderer.stop_flag.store(true, Ordering::SeqCst);
+    }
+}
+
+/// The last rendered image in RGB order, at the output size which is written to the width and height. The pixels
+/// are owned by the renderer and are valid until the next render or until it is destroyed. Null if nothing has been
+/// rendered.
+///
+/// # Safety
+/// The renderer must be valid or null, and the width and height valid pointers or null.
+#[no_mangle]
+pub unsafe extern "C" fn rf_renderer_get_image(renderer: *const RfRenderer, width: *mut usize, height: *mut usize) -> *const u8 {
+    let renderer = match renderer.as_ref() {
+        Some(renderer) => renderer,
+        None => return ptr::null()
+    };
+
+    match &*renderer.image.lock().unwrap() {
+        Some(image) => {
+            if !width.is_null() {
+                *width = image.image_width;
+            }
+
+            if !height.is_null() {
+                *height = image.image_height;
+            }
+
+            image.rgb.as_ptr()
+        },
+        None => ptr::null()
+    }
+}
+
+/// The message of the last error, valid until the next error or until the renderer is destroyed. Null if there has
+/// not been an error.
+///
+/// # Safety
+/// The renderer must be valid or null.
+#[no_mangle]
+pub unsafe extern "C" fn rf_renderer_get_error(renderer: *const RfRenderer) -> *const c_char {
+    renderer.as_ref()
+        .and_then(|renderer| renderer.error.lock().unwrap().as_ref().map(|error| error.as_ptr()))
+        .unwrap_or(ptr::null())
+}
@@ -0,0 +1,734 @@
+use rust_fractal_core::{ComplexArbitrary, FloatExtended, FractalRenderer, FractalRendererBuilder, CompletedRegion, RenderedImage};
+use rust_fractal_core::util::{RenderParameters, extended_to_string_long, extended_to_string_short, pixel_to_complex, string_to_extended};
+use rust_fractal_core::util::kfr::get_kfr;
+use rust_fractal_core::util::palettes::{PALETTE_NAMES, get_named_palette};
+
+use eframe::{egui, epi};
+use eframe::egui::epaint::Mesh;
+
+use std::sync::{Arc, mpsc};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::fs;
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Each request is numbered, so that the updates of a render which has been replaced are ignored
+struct RenderRequest {
+    generation: usize,
+    builder: FractalRendererBuilder,
+    stop_flag: Arc<AtomicBool>,
+}
+
+enum RenderUpdate {
+    Region(usize, CompletedRegion),
+    Finished(usize, RenderedImage, u128),
+}
+
+// Renders the requests one at a time in the background. A render is stopped when a newer request is made, and only
+// the latest of the requests waiting is rendered.
+fn render_worker(requests: mpsc::Receiver<RenderRequest>, updates: mpsc::Sender<RenderUpdate>) {
+    while let Ok(mut request) = requests.recv() {
+        while let Ok(newer) = requests.try_recv() {
+            request = newer;
+        }
+
+        let generation = request.generation;
+
+        let mut renderer = request.builder.build();
+        renderer.stop_flag = request.stop_flag;
+
+        let regions = renderer.get_region_receiver();
+        let region_updates = updates.clone();
+
+        let forwarder = thread::spawn(move || {
+            for region in regions {
+                if region_updates.send(RenderUpdate::Region(generation, region)).is_err() {
+                    return;
+                }
+            }
+        });
+
+        let image = renderer.render_to_buffer(false);
+        let cancelled = renderer.cancelled;
+        let render_time = renderer.render_time;
+
+        // The region channel is closed once the renderer is dropped
+        drop(renderer);
+        forwarder.join().ok();
+
+        if !cancelled && updates.send(RenderUpdate::Finished(generation, image, render_time)).is_err() {
+            return;
+        }
+    }
+}
+
+#[derive(Clone)]
+struct View {
+    centre: ComplexArbitrary,
+    zoom: FloatExtended,
+    julia_seed: Option<ComplexArbitrary>,
+}
+
+impl View {
+    fn is_same(&self, other: &View) -> bool {
+        self.centre == other.centre 
+            && self.zoom.mantissa == other.zoom.mantissa 
+            && self.zoom.exponent == other.zoom.exponent 
+            && self.julia_seed == other.julia_seed
+    }
+}
+
+// The size of the Julia set preview shown while picking a seed
+const PREVIEW_WIDTH: usize = 240;
+const PREVIEW_HEIGHT: usize = 180;
+
+struct Explorer {
+    centre: ComplexArbitrary,
+    zoom: FloatExtended,
+    julia_seed: Option<ComplexArbitrary>,
+    mandelbrot_view: Option<(ComplexArbitrary, FloatExtended)>,
+    picking_seed: bool,
+    history: Vec<View>,
+    history_index: usize,
+    status: Option<String>,
+    iterations: usize,
+    palette_name: String,
+    palette_iteration_span: f64,
+    palette_offset: f64,
+    approximation_order: usize,
+    image_width: usize,
+    image_height: usize,
+    pixels: Vec<u8>,
+    texture: Option<egui::TextureId>,
+    texture_changed: bool,
+    requests: mpsc::Sender<RenderRequest>,
+    updates: mpsc::Receiver<RenderUpdate>,
+    generation: usize,
+    stop_flag: Arc<AtomicBool>,
+    rendering: bool,
+    render_time: Option<u128>,
+    drag_offset: egui::Vec2,
+    selection: Option<(egui::Pos2, egui::Pos2)>,
+    preview_seed: Option<ComplexArbitrary>,
+    preview_pixels: Option<RenderedImage>,
+    preview_texture: Option<egui::TextureId>,
+    preview_requests: mpsc::Sender<RenderRequest>,
+    preview_updates: mpsc::Receiver<RenderUpdate>,
+    preview_generation: usize,
+    preview_stop_flag: Arc<AtomicBool>,
+}
+
+impl Explorer {
+    fn new() -> Self {
+        let (request_sender, request_receiver) = mpsc::channel();
+        let (update_sender, update_receiver) = mpsc::channel();
+
+        thread::spawn(move || render_worker(request_receiver, update_sender));
+
+        // The previews have their own worker, so that they do not stop the render of the view
+        let (preview_request_sender, preview_request_receiver) = mpsc::channel();
+        let (preview_update_sender, preview_update_receiver) = mpsc::channel();
+
+        thread::spawn(move || render_worker(preview_request_receiver, preview_update_sender));
+
+        Explorer {
+            centre: ComplexArbitrary::with_val(64, (-0.75, 0.0)),
+            zoom: string_to_extended("1E0"),
+            julia_seed: None,
+            mandelbrot_view: None,
+            picking_seed: false,
+            history: Vec::new(),
+            history_index: 0,
+            status: None,
+            iterations: 1000,
+            palette_name: String::from("classic"),
+            palette_iteration_span: 100.0,
+            palette_offset: 0.0,
+            approximation_order: 0,
+            image_width: 0,
+            image_height: 0,
+            pixels: Vec::new(),
+            texture: None,
+            texture_changed: false,
+            requests: request_sender,
+            updates: update_receiver,
+            generation: 0,
+            stop_flag: Arc::new(AtomicBool::new(false)),
+            rendering: false,
+            render_time: None,
+            drag_offset: egui::Vec2::ZERO,
+            selection: None,
+            preview_seed: None,
+            preview_pixels: None,
+            preview_texture: None,
+            preview_requests: preview_request_sender,
+            preview_updates: preview_update_receiver,
+            preview_generation: 0,
+            preview_stop_flag: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    fn reset_view(&mut self) {
+        // A Julia set is centred on the origin
+        self.centre = match self.julia_seed {
+            Some(_) => ComplexArbitrary::with_val(64, (0.0, 0.0)),
+            None => ComplexArbitrary::with_val(64, (-0.75, 0.0))
+        };
+
+        self.zoom = string_to_extended("1E0");
+    }
+
+    // The pixel coordinates of the centre, as placed by the renderer half of the pixel spacings from the top left
+    fn get_centre_pixel(&self) -> (f64, f64) {
+        (0.5 * (self.image_width.max(1) - 1) as f64, 0.5 * (self.image_height.max(1) - 1) as f64)
+    }
+
+    // The location under the offset in pixels from the centre of the view
+    fn get_location(&self, offset_x: f64, offset_y: f64) -> ComplexArbitrary {
+        let (centre_x, centre_y) = self.get_centre_pixel();
+
+        pixel_to_complex(&self.centre, self.zoom, self.image_width, self.image_height, 0.0, centre_x + offset_x, centre_y + offset_y)
+    }
+
+    // Switches to the Julia set of the seed, the Mandelbrot view is kept so that it can be returned to
+    fn show_julia(&mut self, seed: ComplexArbitrary) {
+        if self.julia_seed.is_none() {
+            self.mandelbrot_view = Some((self.centre.clone(), self.zoom));
+        }
+
+        self.julia_seed = Some(seed);
+        self.picking_seed = false;
+        self.preview_seed = None;
+        self.preview_pixels = None;
+        self.reset_view();
+        self.request_render();
+    }
+
+    fn show_mandelbrot(&mut self) {
+        self.julia_seed = None;
+
+        match self.mandelbrot_view.take() {
+            Some((centre, zoom)) => {
+                self.centre = centre;
+                self.zoom = zoom;
+            },
+            None => self.reset_view()
+        }
+
+        self.request_render();
+    }
+
+    fn get_view(&self) -> View {
+        View {
+            centre: self.centre.clone(),
+            zoom: self.zoom,
+            julia_seed: self.julia_seed.clone(),
+        }
+    }
+
+    // The views are added to the history once they have been rendered, so that the views passed through while
+    // scrolling or dragging are left out. Going to a new view drops the views after the current one.
+    fn record_view(&mut self) {
+        let view = self.get_view();
+
+        if self.history.get(self.history_index).map_or(false, |current| current.is_same(&view)) {
+            return;
+        }
+
+        if !self.history.is_empty() {
+            self.history.truncate(self.history_index + 1);
+        }
+
+        self.history.push(view);
+        self.history_index = self.history.len() - 1;
+    }
+
+    fn go_to_history(&mut self, index: usize) {
+        if let Some(view) = self.history.get(index).cloned() {
+            self.history_index = index;
+            self.centre = view.centre;
+            self.zoom = view.zoom;
+            self.julia_seed = view.julia_seed;
+            self.request_render();
+        }
+    }
+
+    fn go_back(&mut self) {
+        if self.history_index > 0 {
+            self.go_to_history(self.history_index - 1);
+        }
+    }
+
+    fn go_forward(&mut self) {
+        self.go_to_history(self.history_index + 1);
+    }
+
+    // Writes the view as a location file, which can be given to the renderer with the other settings, and as a
+    // Kalles Fraktaler parameter file
+    fn save_view(&mut self) {
+        let time = SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or(0);
+        let filename = format!("output/view_{}", time);
+
+        let real = self.centre.real().to_string_radix(10, None);
+        let imag = self.centre.imag().to_string_radix(10, None);
+        let zoom = extended_to_string_long(self.zoom);
+
+        let mut location = format!("real = \"{}\"\nimag = \"{}\"\nzoom = \"{}\"\niterations = {}\npalette_name = \"{}\"\npalette_iteration_span = {}\npalette_offset = {}\n", 
+            real, 
+            imag, 
+            zoom, 
+            self.iterations, 
+            self.palette_name, 
+            self.palette_iteration_span, 
+            self.palette_offset);
+
+        if let Some(seed) = &self.julia_seed {
+            location += &format!("julia_real = \"{}\"\njulia_imag = \"{}\"\n", seed.real().to_string_radix(10, None), seed.imag().to_string_radix(10, None));
+        }
+
+        let kfr = get_kfr(&RenderParameters {
+            real,
+            imag,
+            zoom,
+            iterations: self.iterations,
+            palette: get_named_palette(&self.palette_name),
+            palette_iteration_span: self.palette_iteration_span,
+            palette_offset: self.palette_offset,
+            ..RenderParameters::default()
+        });
+
+        let result = fs::create_dir_all("output")
+            .and_then(|_| fs::write(format!("{}.toml", filename), location))
+            .and_then(|_| fs::write(format!("{}.kfr", filename), kfr));
+
+        self.status = Some(match result {
+            Ok(_) => format!("Saved {}.toml and {}.kfr", filename, filename),
+            Err(error) => format!("Could not save the view: {}", error)
+        });
+    }
+
+    fn get_builder(&self, image_width: usize, image_height: usize, centre: &ComplexArbitrary, zoom: FloatExtended, julia_seed: Option<&ComplexArbitrary>) -> FractalRendererBuilder {
+        let builder = FractalRendererBuilder::new()
+            .image_size(image_width, image_height)
+            .centre_arbitrary(centre)
+            .zoom_extended(zoom)
+            .iterations(self.iterations)
+            .palette_name(&self.palette_name)
+            .palette_iteration_span(self.palette_iteration_span)
+            .palette_offset(self.palette_offset)
+            .approximation_order(self.approximation_order)
+            .setting("progressive", true)
+            .setting("supersampling", 1i64)
+            .setting("auto_adjust_iterations", false);
+
+        match julia_seed {
+            Some(seed) => builder.julia_seed(seed),
+            None => builder
+        }
+    }
+
+    // Renders a small preview of the Julia set for the seed, unless it is the seed already shown
+    fn request_preview(&mut self, seed: ComplexArbitrary) {
+        if self.preview_seed.as_ref() == Some(&seed) {
+            return;
+        }
+
+        self.preview_stop_flag.store(true, Ordering::SeqCst);
+        self.preview_stop_flag = Arc::new(AtomicBool::new(false));
+        self.preview_generation += 1;
+
+        let builder = self.get_builder(PREVIEW_WIDTH, PREVIEW_HEIGHT, &ComplexArbitrary::with_val(64, (0.0, 0.0)), string_to_extended("1E0"), Some(&seed));
+
+        self.preview_requests.send(RenderRequest {
+            generation: self.preview_generation,
+            builder,
+            stop_flag: self.preview_stop_flag.clone(),
+        }).ok();
+
+        self.preview_seed = Some(seed);
+    }
+
+    // Moves the centre to the offset in pixels from the current centre, and multiplies the zoom by the factor. The
+    // current image is scaled to the new view, so that it is shown until the new render has been drawn over it.
+    fn change_view(&mut self, offset_x: f64, offset_y: f64, factor: f64) {
+        let precision = FractalRenderer::get_precision(self.zoom * factor.max(1.0), self.image_width, self.image_height);
+
+        if precision > self.centre.prec().0 {
+            self.centre.set_prec(precision);
+        }
+
+        let (centre_x, centre_y) = self.get_centre_pixel();
+
+        self.centre = self.get_location(offset_x, offset_y);
+        self.zoom = self.zoom * factor;
+
+        self.scale_pixels(centre_x, centre_y, offset_x, offset_y, factor);
+    }
+
+    fn scale_pixels(&mut self, centre_x: f64, centre_y: f64, offset_x: f64, offset_y: f64, factor: f64) {
+        if self.pixels.is_empty() {
+            return;
+        }
+
+        let mut pixels = vec![0u8; self.pixels.len()];
+
+        for j in 0..self.image_height {
+            for i in 0..self.image_width {
+                let x = (centre_x + offset_x + (i as f64 - centre_x) / factor).round();
+                let y = (centre_y + offset_y + (j as f64 - centre_y) / factor).round();
+
+                // The parts of the view outside of the previous image are left blank
+                if x < 0.0 || y < 0.0 || x >= self.image_width as f64 || y >= self.image_height as f64 {
+                    continue;
+                }
+
+                let source = 4 * (y as usize * self.image_width + x as usize);
+                let target = 4 * (j * self.image_width + i);
+
+                pixels[target..(target + 4)].copy_from_slice(&self.pixels[source..(source + 4)]);
+            }
+        }
+
+        self.pixels = pixels;
+        self.texture_changed = true;
+    }
+
+    // The point under the cursor, given as an offset from the centre in pixels, stays in place
+    fn zoom_at(&mut self, factor: f64, offset_x: f64, offset_y: f64) {
+        self.change_view(offset_x * (1.0 - 1.0 / factor), offset_y * (1.0 - 1.0 / factor), factor);
+    }
+
+    // The selected box, given by two corners as offsets from the centre in pixels, is fitted to the view
+    fn zoom_to_box(&mut self, first: egui::Vec2, second: egui::Vec2) {
+        let width = (first.x - second.x).abs() as f64;
+        let height = (first.y - second.y).abs() as f64;
+
+        if width < 4.0 || height < 4.0 {
+            return;
+        }
+
+        let factor = (self.image_width as f64 / width).min(self.image_height as f64 / height);
+        let centre = (first + second) * 0.5;
+
+        self.change_view(centre.x as f64, centre.y as f64, factor);
+    }
+
+    fn request_render(&mut self) {
+        if self.image_width == 0 || self.image_height == 0 {
+            return;
+        }
+
+        self.stop_flag.store(true, Ordering::SeqCst);
+        self.stop_flag = Arc::new(AtomicBool::new(false));
+        self.generation += 1;
+
+        let builder = self.get_builder(self.image_width, self.image_height, &self.centre, self.zoom, self.julia_seed.as_ref());
+
+        self.requests.send(RenderRequest {
+            generation: self.generation,
+            builder,
+            stop_flag: self.stop_flag.clone(),
+        }).ok();
+
+        self.rendering = true;
+    }
+
+    fn copy_rgb(&mut self, x: usize, y: usize, width: usize, height: usize, rgb: &[u8]) {
+        for j in 0..height {
+            for i in 0..width {
+                let source = 3 * (j * width + i);
+                let target = 4 * ((y + j) * self.image_width + x + i);
+
+                self.pixels[target..(target + 3)].copy_from_slice(&rgb[source..(source + 3)]);
+                self.pixels[target + 3] = 255;
+            }
+        }
+
+        self.texture_changed = true;
+    }
+
+    fn receive_updates(&mut self) {
+        while let Ok(update) = self.updates.try_recv() {
+            match update {
+                RenderUpdate::Region(generation, region) if generation == self.generation => {
+                    self.copy_rgb(region.x, region.y, region.width, region.height, &region.rgb);
+                },
+                RenderUpdate::Finished(generation, image, render_time) if generation == self.generation => {
+                    if image.image_width == self.image_width && image.image_height == self.image_height {
+                        self.copy_rgb(0, 0, image.image_width, image.image_height, &image.rgb);
+                    }
+
+                    self.rendering = false;
+                    self.render_time = Some(render_time);
+                    self.record_view();
+                },
+                _ => {}
+            }
+        }
+
+        while let Ok(update) = self.preview_updates.try_recv() {
+            if let RenderUpdate::Finished(generation, image, _) = update {
+                if generation == self.preview_generation {
+                    self.preview_pixels = Some(image);
+                }
+            }
+        }
+    }
+
+    fn update_preview_texture(&mut self, frame: &epi::Frame) {
+        if let Some(image) = self.preview_pixels.take() {
+            if let Some(texture) = self.preview_texture.take() {
+                frame.free_texture(texture);
+            }
+
+            let rgba = image.rgb.chunks_exact(3).flat_map(|pixel| vec![pixel[0], pixel[1], pixel[2], 255]).collect::<Vec<u8>>();
+
+            self.preview_texture = Some(frame.alloc_texture(epi::Image::from_rgba_unmultiplied([image.image_width, image.image_height], &rgba)));
+        }
+    }
+
+    fn show_parameters(&mut self, ui: &mut egui::Ui) {
+        let mut changed = false;
+
+        ui.heading("rust-fractal");
+        ui.separator();
+
+        ui.label(format!("Zoom: {}", extended_to_string_short(self.zoom)));
+        ui.label(format!("Real: {:.12}", self.centre.real().to_f64()));
+        ui.label(format!("Imag: {:.12}", self.centre.imag().to_f64()));
+
+        if let Some(seed) = &self.julia_seed {
+            ui.label(format!("Julia seed: {:.12} {:+.12}i", seed.real().to_f64(), seed.imag().to_f64()));
+        }
+
+        ui.separator();
+
+        ui.horizontal(|ui| {
+            ui.label("Iterations");
+            changed |= ui.add(egui::DragValue::new(&mut self.iterations).speed(100.0).clamp_range(10..=1_000_000_000)).changed();
+        });
+
+        let previous_palette = self.palette_name.clone();
+
+        egui::ComboBox::from_label("Palette")
+            .selected_text(self.palette_name.clone())
+            .show_ui(ui, |ui| {
+                for name in PALETTE_NAMES.iter() {
+                    ui.selectable_value(&mut self.palette_name, name.to_string(), *name);
+                }
+            });
+
+        changed |= self.palette_name != previous_palette;
+        changed |= ui.add(egui::Slider::new(&mut self.palette_iteration_span, 1.0..=100000.0).logarithmic(true).text("Palette span")).changed();
+        changed |= ui.add(egui::Slider::new(&mut self.palette_offset, 0.0..=1.0).text("Palette offset")).changed();
+        changed |= ui.add(egui::Slider::new(&mut self.approximation_order, 0..=64).text("Approximation order")).changed();
+
+        ui.label("An approximation order of 0 is chosen automatically.");
+        ui.separator();
+
+        ui.label("Click to zoom in, right click to zoom out, drag to move and shift drag to zoom to a box.");
+        ui.separator();
+
+        if ui.button("Reset view").clicked() {
+            self.reset_view();
+            changed = true;
+        }
+
+        ui.separator();
+
+        // The seed is picked in the Mandelbrot view, the Julia set of the point under the cursor is previewed and
+        // clicking renders it
+        ui.horizontal(|ui| {
+            if ui.add_enabled(self.history_index > 0, egui::Button::new("Back")).clicked() {
+                self.go_back();
+            }
+
+            if ui.add_enabled(self.history_index + 1 < self.history.len(), egui::Button::new("Forward")).clicked() {
+                self.go_forward();
+            }
+
+            if ui.button("Save view").clicked() {
+                self.save_view();
+            }
+        });
+
+        ui.label("Alt and the arrow keys go back and forward, and S saves the view.");
+
+        if let Some(status) = &self.status {
+            ui.label(status.as_str());
+        }
+
+        ui.separator();
+
+        if self.julia_seed.is_some() {
+            if ui.button("Back to Mandelbrot").clicked() {
+                self.show_mandelbrot();
+            }
+        } else {
+            ui.checkbox(&mut self.picking_seed, "Pick Julia seed");
+
+            if let (true, Some(texture)) = (self.picking_seed, self.preview_texture) {
+                ui.image(texture, [PREVIEW_WIDTH as f32 / ui.ctx().pixels_per_point(), PREVIEW_HEIGHT as f32 / ui.ctx().pixels_per_point()]);
+            }
+        }
+
+        ui.separator();
+
+        if self.rendering {
+            ui.label("Rendering...");
+        } else if let Some(render_time) = self.render_time {
+            ui.label(format!("Rendered in {} ms", render_time));
+        }
+
+        if changed {
+            self.request_render();
+        }
+    }
+
+    fn show_viewport(&mut self, ui: &mut egui::Ui, frame: &epi::Frame) {
+        let pixels_per_point = ui.ctx().pixels_per_point();
+        let size = ui.available_size();
+
+        let image_width = (size.x * pixels_per_point) as usize;
+        let image_height = (size.y * pixels_per_point) as usize;
+
+        // The previous image is kept until the new one has been rendered over it
+        if (image_width, image_height) != (self.image_width, self.image_height) && image_width > 1 && image_height > 1 {
+            self.image_width = image_width;
+            self.image_height = image_height;
+            self.pixels = vec![0u8; 4 * image_width * image_height];
+            self.texture_changed = true;
+            self.request_render();
+        }
+
+        if self.texture_changed {
+            if let Some(texture) = self.texture.take() {
+                frame.free_texture(texture);
+            }
+
+            self.texture = Some(frame.alloc_texture(epi::Image::from_rgba_unmultiplied([self.image_width, self.image_height], &self.pixels)));
+            self.texture_changed = false;
+        }
+
+        let (rect, response) = ui.allocate_exact_size(size, egui::Sense::click_and_drag());
+
+        if let Some(texture) = self.texture {
+            let mut mesh = Mesh::with_texture(texture);
+            mesh.add_rect_with_uv(rect.translate(self.drag_offset), egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)), egui::Color32::WHITE);
+
+            ui.painter_at(rect).add(egui::Shape::mesh(mesh));
+        }
+
+        if let Some((start, end)) = self.selection {
+            ui.painter_at(rect).rect_stroke(egui::Rect::from_two_pos(start, end), 0.0, egui::Stroke::new(1.0, egui::Color32::WHITE));
+        }
+
+        let shift = ui.input().modifiers.shift;
+
+        // The image follows the drag, and is rendered at the new centre when it is released. With shift held the drag
+        // selects a box instead.
+        if response.drag_started() && shift {
+            if let Some(position) = response.interact_pointer_pos() {
+                self.selection = Some((position, position));
+            }
+        }
+
+        if response.dragged() {
+            match (&mut self.selection, response.interact_pointer_pos()) {
+                (Some((_, end)), Some(position)) => *end = position,
+                _ => self.drag_offset += response.drag_delta()
+            }
+        }
+
+        if response.drag_released() {
+            if let Some((start, end)) = self.selection.take() {
+                self.zoom_to_box((start - rect.center()) * pixels_per_point, (end - rect.center()) * pixels_per_point);
+            } else {
+                let offset = self.drag_offset * pixels_per_point;
+
+                self.drag_offset = egui::Vec2::ZERO;
+                self.change_view(-offset.x as f64, -offset.y as f64, 1.0);
+            }
+
+            self.request_render();
+        }
+
+        let picking_seed = self.picking_seed && self.julia_seed.is_none();
+
+        if let (Some(position), true) = (response.hover_pos(), picking_seed) {
+            let offset = (position - rect.center()) * pixels_per_point;
+            let seed = self.get_location(offset.x as f64, offset.y as f64);
+
+            if response.clicked() {
+                self.show_julia(seed);
+                return;
+            }
+
+            self.request_preview(seed);
+        }
+
+        // A click zooms in by a factor of two on the point clicked, which becomes the new centre
+        if let (Some(position), true) = (response.interact_pointer_pos(), response.clicked() || response.secondary_clicked()) {
+            let offset = (position - rect.center()) * pixels_per_point;
+            let factor = if response.clicked() { 2.0 } else { 0.5 };
+
+            self.change_view(offset.x as f64, offset.y as f64, factor);
+            self.request_render();
+        }
+
+        let scroll = ui.input().scroll_delta.y;
+
+        if let (Some(position), true) = (response.hover_pos(), scroll != 0.0) {
+            let offset = (position - rect.center()) * pixels_per_point;
+
+            self.zoom_at(2.0f64.powf(scroll as f64 / 100.0), offset.x as f64, offset.y as f64);
+            self.request_render();
+        }
+    }
+}
+
+impl epi::App for Explorer {
+    fn name(&self) -> &str {
+        "rust-fractal"
+    }
+
+    fn update(&mut self, ctx: &egui::CtxRef, frame: &epi::Frame) {
+        self.receive_updates();
+
+        // The shortcuts are ignored while a value is being edited
+        if !ctx.wants_keyboard_input() {
+            let input = ctx.input();
+
+            let back = input.modifiers.alt && input.key_pressed(egui::Key::ArrowLeft);
+            let forward = input.modifiers.alt && input.key_pressed(egui::Key::ArrowRight);
+            let save = input.key_pressed(egui::Key::S);
+
+            if back {
+                self.go_back();
+            } else if forward {
+                self.go_forward();
+            } else if save {
+                self.save_view();
+            }
+        }
+
+        self.update_preview_texture(frame);
+
+        egui::SidePanel::left("parameters").show(ctx, |ui| {
+            self.show_parameters(ui);
+        });
+
+        egui::CentralPanel::default().frame(egui::Frame::none()).show(ctx, |ui| {
+            self.show_viewport(ui, frame);
+        });
+
+        // The regions of the render are shown as they arrive
+        if self.rendering || self.picking_seed {
+            ctx.request_repaint();
+        }
+    }
+}
+
+fn main() {
+    eframe::run_native(Box::new(Explorer::new()), eframe::NativeOptions::default());
+}
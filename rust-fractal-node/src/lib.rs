@@ -0,0 +1,196 @@
+//! Node.js bindings to the rust-fractal renderer. The renders run on the libuv thread pool, so the event loop is free
+//! while a frame is iterated, and the progress reports are delivered to a callback on the main thread.
+//!
+//! ```js
+//! const { RenderJob } = require("rust-fractal");
+//!
+//! const job = new RenderJob({ real: "-0.75", imag: "0.1", zoom: "1E10", iterations: 10000 });
+//! const image = await job.render(progress => console.log(progress.stage, progress.stageFraction));
+//! ```
+//!
+//! The options have the same keys as the options and location files, and any which are not given take their
+//! default values.
+
+#[macro_use]
+extern crate napi_derive;
+
+use rust_fractal_core::{Config, FractalRenderer, ProgressReport, RenderedImage, RenderParameters};
+
+use napi::bindgen_prelude::*;
+use napi::threadsafe_function::{ErrorStrategy, ThreadSafeCallContext, ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::JsFunction;
+
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+// A panic must not unwind into Node, so it is turned into an error which is thrown in JavaScript (or rejects the promise)
+fn catch_panic<T>(work: impl FnOnce() -> Result<T>) -> Result<T> {
+    panic::catch_unwind(AssertUnwindSafe(work)).unwrap_or_else(|error| {
+        let message = error.downcast_ref::<String>().cloned()
+            .or_else(|| error.downcast_ref::<&str>().map(|message| message.to_string()))
+            .unwrap_or_else(|| String::from("the renderer panicked"));
+
+        Err(Error::new(Status::GenericFailure, message))
+    })
+}
+
+#[napi(object)]
+pub struct RenderProgress {
+    pub zoom: String,
+    pub stage: String,
+    pub stage_fraction: f64,
+    pub pixels_iterated: f64,
+    pub total_pixels: f64,
+    pub glitched_remaining: f64,
+    pub elapsed: f64,
+    pub stage_eta: Option<f64>,
+}
+
+impl From<ProgressReport> for RenderProgress {
+    fn from(report: ProgressReport) -> Self {
+        RenderProgress {
+            zoom: report.zoom,
+            stage: format!("{:?}", report.stage).to_ascii_lowercase(),
+            stage_fraction: report.stage_fraction,
+            pixels_iterated: report.pixels_iterated as f64,
+            total_pixels: report.total_pixels as f64,
+            glitched_remaining: report.glitched_remaining as f64,
+            elapsed: report.elapsed.as_millis() as f64,
+            stage_eta: report.stage_eta.map(|eta| eta.as_millis() as f64),
+        }
+    }
+}
+
+// The colour image at the output size in RGB order
+#[napi(object)]
+pub struct RenderResult {
+    pub width: u32,
+    pub height: u32,
+    pub data: Buffer,
+    pub cancelled: bool,
+}
+
+type ProgressCallback = ThreadsafeFunction<RenderProgress, ErrorStrategy::Fatal>;
+
+pub struct RenderTask {
+    parameters: RenderParameters,
+    stop_flag: Arc<AtomicBool>,
+    progress: Option<ProgressCallback>,
+    sequence: bool,
+}
+
+impl RenderTask {
+    fn get_renderer(&self) -> Result<FractalRenderer> {
+        let mut settings = self.parameters.to_config(&Config::default());
+        settings.set("show_output", false).unwrap();
+
+        let mut renderer = FractalRenderer::try_new(settings)
+            .map_err(|validation| Error::new(Status::InvalidArg, format!("invalid render options: {}", validation.errors.join(", "))))?;
+        renderer.stop_flag = self.stop_flag.clone();
+
+        // The reports are forwarded until the renderer is dropped at the end of the task
+        if let Some(progress) = &self.progress {
+            let receiver = renderer.get_progress_receiver();
+            let progress = progress.clone();
+
+            thread::spawn(move || {
+                for report in receiver {
+                    progress.call(RenderProgress::from(report), ThreadsafeFunctionCallMode::NonBlocking);
+                }
+            });
+        }
+
+        Ok(renderer)
+    }
+}
+
+impl Task for RenderTask {
+    type Output = Option<(RenderedImage, bool)>;
+    type JsValue = Option<RenderResult>;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        catch_panic(|| {
+            let mut renderer = self.get_renderer()?;
+
+            if self.sequence {
+                renderer.render();
+                Ok(None)
+            } else {
+                let image = renderer.render_to_buffer(false);
+                Ok(Some((image, renderer.cancelled)))
+            }
+        })
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(output.map(|(image, cancelled)| RenderResult {
+            width: image.image_width as u32,
+            height: image.image_height as u32,
+            data: image.rgb.into(),
+            cancelled,
+        }))
+    }
+}
+
+/// A render with fixed options, which can be started any number of times and cancelled from JavaScript.
+#[napi]
+pub struct RenderJob {
+    parameters: RenderParameters,
+    stop_flag: Arc<AtomicBool>,
+}
+
+#[napi]
+impl RenderJob {
+    #[napi(constructor, ts_args_type = "options: Record<string, unknown>")]
+    pub fn new(options: serde_json::Value) -> Result<Self> {
+        catch_panic(|| {
+            let parameters = serde_json::from_value::<RenderParameters>(options)
+                .map_err(|error| Error::new(Status::InvalidArg, format!("invalid render options: {}", error)))?;
+
+            Ok(RenderJob {
+                parameters,
+                stop_flag: Arc::new(AtomicBool::new(false)),
+            })
+        })
+    }
+
+    /// Renders the first frame into memory without writing any files.
+    #[napi(ts_args_type = "onProgress?: (progress: RenderProgress) => void", ts_return_type = "Promise<RenderResult>")]
+    pub fn render(&self, on_progress: Option<JsFunction>) -> Result<AsyncTask<RenderTask>> {
+        self.start(on_progress, false)
+    }
+
+    /// Renders every frame of the options and writes the outputs, as the command line renderer does.
+    #[napi(ts_args_type = "onProgress?: (progress: RenderProgress) => void", ts_return_type = "Promise<void>")]
+    pub fn render_sequence(&self, on_progress: Option<JsFunction>) -> Result<AsyncTask<RenderTask>> {
+        self.start(on_progress, true)
+    }
+
+    /// Stops the running render, the frame which was being rendered is returned with `cancelled` set.
+    #[napi]
+    pub fn cancel(&self) {
+        self.stop_flag.store(true, Ordering::SeqCst);
+    }
+
+    fn start(&self, on_progress: Option<JsFunction>, sequence: bool) -> Result<AsyncTask<RenderTask>> {
+        catch_panic(|| {
+            let progress = match on_progress {
+                Some(callback) => Some(callback.create_threadsafe_function(0, |context: ThreadSafeCallContext<RenderProgress>| {
+                    Ok(vec![context.value])
+                })?),
+                None => None
+            };
+
+            self.stop_flag.store(false, Ordering::SeqCst);
+
+            Ok(AsyncTask::new(RenderTask {
+                parameters: self.parameters.clone(),
+                stop_flag: self.stop_flag.clone(),
+                progress,
+                sequence,
+            }))
+        })
+    }
+}
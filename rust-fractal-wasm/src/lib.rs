@@ -0,0 +1,116 @@
+//! JavaScript bindings to the rust-fractal renderer, for drawing into a canvas from the browser.
+//!
+//! ```js
+//! import init, { Explorer } from "./rust_fractal_wasm.js";
+//!
+//! await init();
+//!
+//! const explorer = new Explorer(canvas.width, canvas.height);
+//! explorer.setLocation("-1.7490930", "0.0000217", "1E5");
+//! explorer.setIterations(20000);
+//!
+//! const pixels = new ImageData(new Uint8ClampedArray(explorer.render()), canvas.width, canvas.height);
+//! canvas.getContext("2d").putImageData(pixels, 0, 0);
+//! ```
+//!
+//! The core is built without the `mpfr` feature, so the arbitrary precision reference uses its pure Rust floats
+//! rather than GMP and MPFR, which do not build for `wasm32-unknown-unknown`. These are slower, so the reference takes
+//! longer at deep zooms than in the native renderer. Build with `wasm-pack build --target web`. The pixels are
+//! iterated on the calling thread, unless a rayon thread pool has been started from JavaScript (for example with
+//! `wasm-bindgen-rayon`) on a page with cross origin isolation. The colouring plugins are not available.
+
+use rust_fractal_core::{FractalRendererBuilder, PixelOrder};
+
+use wasm_bindgen::prelude::*;
+
+/// A view of the set which is rendered to the size of a canvas.
+#[wasm_bindgen]
+pub struct Explorer {
+    image_width: usize,
+    image_height: usize,
+    real: String,
+    imag: String,
+    zoom: String,
+    iterations: usize,
+    rotate: f64,
+    palette_iteration_span: f64,
+    palette_offset: f64,
+}
+
+#[wasm_bindgen]
+impl Explorer {
+    #[wasm_bindgen(constructor)]
+    pub fn new(image_width: usize, image_height: usize) -> Explorer {
+        Explorer {
+            image_width,
+            image_height,
+            real: String::from("-0.75"),
+            imag: String::from("0.0"),
+            zoom: String::from("1E0"),
+            iterations: 1000,
+            rotate: 0.0,
+            palette_iteration_span: 100.0,
+            palette_offset: 0.0,
+        }
+    }
+
+    /// The size of the canvas, which is used from the next render.
+    #[wasm_bindgen(js_name = setSize)]
+    pub fn set_size(&mut self, image_width: usize, image_height: usize) {
+        self.image_width = image_width;
+        self.image_height = image_height;
+    }
+
+    /// The centre and zoom as strings in the format of the location files, so that no digits are lost in JavaScript.
+    #[wasm_bindgen(js_name = setLocation)]
+    pub fn set_location(&mut self, real: &str, imag: &str, zoom: &str) {
+        self.real = real.to_string();
+        self.imag = imag.to_string();
+        self.zoom = zoom.to_string();
+    }
+
+    #[wasm_bindgen(js_name = setIterations)]
+    pub fn set_iterations(&mut self, iterations: usize) {
+        self.iterations = iterations;
+    }
+
+    /// The rotation in degrees.
+    #[wasm_bindgen(js_name = setRotate)]
+    pub fn set_rotate(&mut self, rotate: f64) {
+        self.rotate = rotate;
+    }
+
+    #[wasm_bindgen(js_name = setPalette)]
+    pub fn set_palette(&mut self, palette_iteration_span: f64, palette_offset: f64) {
+        self.palette_iteration_span = palette_iteration_span;
+        self.palette_offset = palette_offset;
+    }
+
+    /// Renders the view and returns the pixels in RGBA order, which can be given directly to an `ImageData`.
+    pub fn render(&self) -> Vec<u8> {
+        // The rows are iterated in order so that a partial image is filled from the top, and no files are written
+        let mut renderer = FractalRendererBuilder::new()
+            .image_size(self.image_width, self.image_height)
+            .centre(&self.real, &self.imag)
+            .zoom(&self.zoom)
+            .iterations(self.iterations)
+            .rotate(self.rotate)
+            .palette_iteration_span(self.palette_iteration_span)
+            .palette_offset(self.palette_offset)
+            .pixel_order(PixelOrder::Scanline)
+            .setting("reference_cache", false)
+            .setting("checkpoint_interval", 0i64)
+            .build();
+
+        let image = renderer.render_to_buffer(false);
+
+        let mut pixels = Vec::with_capacity(4 * self.image_width * self.image_height);
+
+        for pixel in image.rgb.chunks_exact(3) {
+            pixels.extend_from_slice(pixel);
+            pixels.push(255);
+        }
+
+        pixels
+    }
+}
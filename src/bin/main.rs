@@ -1,46 +1,493 @@
-use rust_fractal::renderer::FractalRenderer;
-use rust_fractal::util::RecolourExr;
-use clap::{crate_version, crate_name, crate_description, App, Arg};
-use config::{Config, File};
+use rust_fractal_core::renderer::FractalRenderer;
+use rust_fractal_core::util::{RecolourExr, KeyframeInterpolator, Batch, Benchmark, Explorer, ImageComparison, TerminalPreview};
+use rust_fractal_core::util::metadata::{read_metadata, apply_metadata};
+use rust_fractal_core::util::kfr::{apply_kfr, get_kfr, save_kfr};
+use rust_fractal_core::util::palettes::is_palette_name;
+use rust_fractal_core::util::history::read_history;
+use rust_fractal_core::util::bookmarks::{Bookmark, Bookmarks};
+use rust_fractal_core::util::presets::{PRESET_LOCATIONS, apply_preset};
+use rust_fractal_core::util::queue::{JobQueue, QueueLock, run_queue};
+use rust_fractal_core::util::log::{log_error, log_event, log_warning};
+use rust_fractal_core::util::distributed::run_worker;
+use rust_fractal_core::util::server::{run_server, run_tile_server};
+use rust_fractal_core::util::script::run_script;
+use rust_fractal_core::util::{ComplexArbitrary, ProgressReport, RenderStage, RenderParameters, Validation, complex_to_pixel, extended_to_string_short, merge_location_file, parse_extended, pixel_to_complex, string_to_extended};
+use rust_fractal_core::util::data_export::{CompletedRegion, get_run_folder};
+use clap::{crate_version, crate_name, crate_description, App, AppSettings, Arg, ArgMatches};
+use config::{Config, File, FileFormat};
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, mpsc};
+use std::thread;
+use std::io::{self, Read};
+use std::time::Duration;
+use indicatif::{ProgressBar, ProgressStyle};
+use crossterm::execute;
+use crossterm::cursor::{Hide, Show};
+use crossterm::terminal::{EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::{Frame, Terminal};
+use ratatui::backend::{Backend, CrosstermBackend};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Style};
+use ratatui::text::{Span, Spans};
+use ratatui::widgets::{BarChart, Block, Borders, Gauge, Paragraph};
 
 
-fn main() {
-    let matches = App::new(crate_name!())
-        .about(crate_description!())
-        .version(crate_version!())
-        .arg(
-            Arg::new("INPUT")
-                .value_name("FILE")
-                .about("Sets the location file to use")
-                .takes_value(true)
-                .required(false)
-        )
-        .arg(
-            Arg::new("options")
-                .short('o')
-                .long("options")
-                .value_name("FILE")
-                .about("Sets the options file to use")
-                .takes_value(true)
-                .required(false)
-        )
-        .arg(
-            Arg::new("palette")
-                .short('p')
-                .long("palette")
-                .value_name("FILE")
-                .about("Sets the palette file to use")
-                .takes_value(true)
-                .required(false)
-        )
-        .arg(
-            Arg::new("colour_exr")
-                .short('c')
-                .long("colour_exr")
-                .about("Colours the EXR files in the output directory")
-                .required(false)
-        ).get_matches();
+// The arguments which build up the settings, shared by every subcommand. The options files, location file and the
+// embedded parameters of an image are merged in that order, then the environment and the flags override single
+// values.
+fn get_settings_args() -> Vec<Arg<'static>> {
+    vec![
+        Arg::new("INPUT")
+            .value_name("FILE")
+            .about("Sets the location file to use, or - to read the location from stdin")
+            .takes_value(true)
+            .required(false),
+        Arg::new("options")
+            .short('o')
+            .long("options")
+            .value_name("FILE")
+            .about("Sets the options file to use")
+            .takes_value(true)
+            .required(false),
+        Arg::new("palette")
+            .short('p')
+            .long("palette")
+            .value_name("FILE")
+            .about("Sets the palette file to use, or the name of a built in palette")
+            .takes_value(true)
+            .required(false),
+        Arg::new("preset")
+            .long("preset")
+            .value_name("NAME")
+            .about("Renders one of the built in locations instead of a location file, listed with the presets subcommand")
+            .takes_value(true)
+            .conflicts_with("INPUT")
+            .required(false),
+        Arg::new("rerender")
+            .short('r')
+            .long("rerender")
+            .value_name("IMAGE")
+            .about("Re-renders a PNG or EXR image using its embedded parameters")
+            .takes_value(true)
+            .required(false),
+        Arg::new("width")
+            .long("width")
+            .value_name("PIXELS")
+            .about("Overrides the image width")
+            .takes_value(true)
+            .required(false),
+        Arg::new("height")
+            .long("height")
+            .value_name("PIXELS")
+            .about("Overrides the image height")
+            .takes_value(true)
+            .required(false),
+        Arg::new("region")
+            .long("region")
+            .value_names(&["X", "Y", "WIDTH", "HEIGHT"])
+            .about("Renders only this rectangle of the view, in pixels from the top left")
+            .takes_value(true)
+            .number_of_values(4)
+            .required(false),
+        Arg::new("real")
+            .long("real")
+            .value_name("VALUE")
+            .about("Overrides the real part of the centre")
+            .takes_value(true)
+            .allow_hyphen_values(true)
+            .required(false),
+        Arg::new("imag")
+            .long("imag")
+            .value_name("VALUE")
+            .about("Overrides the imaginary part of the centre")
+            .takes_value(true)
+            .allow_hyphen_values(true)
+            .required(false),
+        Arg::new("zoom")
+            .long("zoom")
+            .value_name("ZOOM")
+            .about("Overrides the zoom, for example 1E100")
+            .takes_value(true)
+            .required(false),
+        Arg::new("iterations")
+            .long("iterations")
+            .value_name("COUNT")
+            .about("Overrides the maximum iteration count, or raises it automatically with auto")
+            .takes_value(true)
+            .required(false),
+        Arg::new("rotate")
+            .long("rotate")
+            .value_name("DEGREES")
+            .about("Overrides the rotation")
+            .takes_value(true)
+            .allow_hyphen_values(true)
+            .required(false),
+        Arg::new("frames")
+            .long("frames")
+            .value_name("COUNT")
+            .about("Overrides the number of frames in the sequence")
+            .takes_value(true)
+            .required(false),
+        Arg::new("export")
+            .long("export")
+            .value_name("TYPE")
+            .about("Overrides the export type")
+            .takes_value(true)
+            .required(false),
+        Arg::new("set")
+            .long("set")
+            .value_name("KEY=VALUE")
+            .about("Overrides any value of the settings files, can be given more than once")
+            .takes_value(true)
+            .multiple_occurrences(true)
+            .allow_hyphen_values(true)
+            .required(false),
+        Arg::new("threads")
+            .short('t')
+            .long("threads")
+            .value_name("COUNT")
+            .about("Sets the number of threads to render with, using all cores by default")
+            .takes_value(true)
+            .required(false),
+        Arg::new("pin_threads")
+            .long("pin_threads")
+            .about("Pins each render thread to a core, filling one NUMA node at a time")
+            .required(false),
+        Arg::new("force")
+            .short('f')
+            .long("force")
+            .about("Overwrites existing outputs even if overwrite protection is enabled")
+            .required(false),
+        Arg::new("resume")
+            .long("resume")
+            .about("Skips keyframes which have already been rendered and continues partial frames from their checkpoints")
+            .required(false),
+        Arg::new("continue_from")
+            .long("continue_from")
+            .value_name("FILE")
+            .about("Continues a raw EXR export of the same location, iterating only the pixels which reached its iteration limit")
+            .takes_value(true)
+            .required(false),
+        Arg::new("deterministic")
+            .long("deterministic")
+            .about("Fixes all seeds so that the output is bit-identical across runs and thread counts")
+            .required(false),
+        Arg::new("quiet")
+            .short('q')
+            .long("quiet")
+            .about("Disables all progress output")
+            .required(false),
+        Arg::new("verbose")
+            .short('v')
+            .long("verbose")
+            .about("Prints the timings of each stage in a table instead of progress bars")
+            .required(false),
+        Arg::new("tui")
+            .long("tui")
+            .about("Shows a dashboard of the stages, glitches, iteration histogram and a preview while rendering")
+            .required(false),
+        Arg::new("json_progress")
+            .long("json_progress")
+            .about("Prints the progress as line delimited JSON events")
+            .required(false),
+        Arg::new("log_format")
+            .long("log_format")
+            .alias("log-format")
+            .value_name("FORMAT")
+            .about("Sets the format of the log, either text or json for structured events on each line")
+            .takes_value(true)
+            .required(false),
+        Arg::new("watch")
+            .long("watch")
+            .about("Re-renders the first frame at a smaller size whenever the options, palette or location file changes")
+            .required(false),
+    ]
+}
+
+// The modes which were selected with flags before the subcommands were added, these are still accepted
+fn get_legacy_args() -> Vec<Arg<'static>> {
+    vec![
+        Arg::new("interpolate")
+            .short('i')
+            .long("interpolate")
+            .about("Generates video frames by interpolating between the EXR keyframes in the output directory")
+            .required(false),
+        Arg::new("dry_run")
+            .long("dry_run")
+            .alias("dry-run")
+            .about("Estimates the time and disk space for the sequence without rendering it")
+            .required(false),
+        Arg::new("preview_terminal")
+            .long("preview_terminal")
+            .value_name("MODE")
+            .about("Renders a small preview of the location and shows it in the terminal, with half block characters or as a sixel image")
+            .takes_value(true)
+            .possible_values(&["ansi", "sixel"])
+            .min_values(0)
+            .required(false),
+        Arg::new("bench")
+            .long("bench")
+            .value_name("OUTPUT")
+            .about("Renders the built-in benchmark locations and reports the timings, optionally saving them as JSON")
+            .takes_value(true)
+            .min_values(0)
+            .required(false),
+        Arg::new("compare")
+            .long("compare")
+            .value_names(&["FIRST", "SECOND"])
+            .about("Compares two EXR exports or images pixel by pixel and saves a difference image")
+            .takes_value(true)
+            .number_of_values(2)
+            .required(false),
+        Arg::new("coordinator")
+            .long("coordinator")
+            .value_name("ADDRESS")
+            .about("Listens on the address and distributes the frames of the sequence to the workers which connect")
+            .takes_value(true)
+            .required(false),
+        Arg::new("worker")
+            .long("worker")
+            .value_name("ADDRESS")
+            .about("Connects to a coordinator at the address and renders the frames it sends")
+            .takes_value(true)
+            .required(false),
+        Arg::new("serve")
+            .long("serve")
+            .value_name("ADDRESS")
+            .about("Listens on the address for render jobs posted over HTTP, using the other settings as their defaults")
+            .takes_value(true)
+            .required(false),
+        Arg::new("tile_server")
+            .long("tile_server")
+            .value_name("ADDRESS")
+            .about("Listens on the address and renders the tiles of a slippy map of the location on demand")
+            .takes_value(true)
+            .required(false),
+        Arg::new("script")
+            .long("script")
+            .value_name("FILE")
+            .about("Runs a Rhai script which sets up and renders locations, using the other settings as their defaults")
+            .takes_value(true)
+            .required(false),
+        Arg::new("colour_exr")
+            .short('c')
+            .long("colour_exr")
+            .about("Colours the EXR files in the output directory")
+            .required(false),
+    ]
+}
+
+// Each subcommand takes the settings arguments as well as its own. The values of the subcommands have the same
+// names as the legacy flags, so that both are read in the same way, and are given as flags so that the location
+// file stays the only positional argument.
+fn get_subcommands() -> Vec<App<'static>> {
+    let address = |name: &'static str| Arg::new(name).value_name("ADDRESS").takes_value(true).required(true);
+
+    vec![
+        App::new("render")
+            .about("Renders only the first frame of the location")
+            .args(get_settings_args()),
+        App::new("sequence")
+            .about("Renders every frame of the zoom sequence, which is the default without a subcommand")
+            .args(get_settings_args()),
+        App::new("estimate")
+            .about("Estimates the time and disk space for the sequence without rendering it")
+            .args(get_settings_args()),
+        App::new("info")
+            .about("Reports the precision, period, minibrot, skipped iterations and suggested iterations and approximation order of the location without rendering it")
+            .args(get_settings_args())
+            .arg(Arg::new("parameters").long("parameters").about("Prints the parameters after the files and flags have been applied, as JSON").required(false))
+            .arg(Arg::new("json").long("json").about("Prints the report as JSON").required(false)),
+        App::new("coordinate")
+            .about("Converts a pixel of the output image to its location, or a location to its pixel, for the view of the settings")
+            .args(get_settings_args())
+            .arg(Arg::new("pixel").long("pixel").value_names(&["X", "Y"]).takes_value(true).number_of_values(2).allow_hyphen_values(true).required_unless_present("point"))
+            .arg(Arg::new("point").long("point").value_names(&["REAL", "IMAG"]).takes_value(true).number_of_values(2).allow_hyphen_values(true).conflicts_with("pixel")),
+        App::new("explore")
+            .about("Descends at random from the location to find interesting locations, saving each with a thumbnail")
+            .args(get_settings_args())
+            .arg(Arg::new("explore_zoom").long("depth").value_name("ZOOM").takes_value(true).required(false))
+            .arg(Arg::new("explore_candidates").long("count").value_name("COUNT").takes_value(true).required(false))
+            .arg(Arg::new("explore_seed").long("seed").value_name("SEED").takes_value(true).required(false))
+            .arg(Arg::new("explore_minimum_score").long("minimum-score").value_name("SCORE").takes_value(true).required(false)),
+        App::new("kfr")
+            .about("Writes the location and palette as a Kalles Fraktaler .kfr file, or prints it without an output")
+            .args(get_settings_args())
+            .arg(Arg::new("kfr").long("output").value_name("OUTPUT").takes_value(true).required(false)),
+        App::new("history")
+            .about("Lists the frames which have been rendered, or shows or renders one of them again")
+            .args(get_settings_args())
+            .arg(Arg::new("history_last").long("last").value_name("COUNT").takes_value(true).required(false))
+            .arg(Arg::new("history_show").long("show").value_name("ENTRY").takes_value(true).required(false))
+            .arg(Arg::new("history_render").long("render").value_name("ENTRY").takes_value(true).conflicts_with("history_show"))
+            .arg(Arg::new("json").long("json").about("Prints the entries as JSON").required(false)),
+        App::new("bookmark")
+            .about("Keeps named locations with notes and thumbnails, which can be rendered together as a batch")
+            .setting(AppSettings::SubcommandRequiredElseHelp)
+            .subcommand(App::new("add")
+                .about("Adds the location of the settings as a bookmark, replacing any with the same name")
+                .args(get_settings_args())
+                .arg(Arg::new("bookmark_name").long("name").value_name("NAME").takes_value(true).required(true))
+                .arg(Arg::new("bookmark_notes").long("notes").value_name("TEXT").takes_value(true).required(false))
+                .arg(Arg::new("no_thumbnail").long("no_thumbnail").about("Adds the bookmark without rendering a thumbnail").required(false)))
+            .subcommand(App::new("list")
+                .about("Lists the bookmarks with their notes")
+                .args(get_settings_args()))
+            .subcommand(App::new("render")
+                .about("Renders the named bookmarks, or all of them, with the same settings")
+                .args(get_settings_args())
+                .arg(Arg::new("bookmark_name").long("name").value_name("NAME").takes_value(true).multiple_occurrences(true).required(false))
+                .arg(Arg::new("batch_output").long("output").value_name("OUTPUT").takes_value(true).required(false))),
+        App::new("queue")
+            .about("Keeps a queue of renders which a worker renders one at a time, highest priority first")
+            .setting(AppSettings::SubcommandRequiredElseHelp)
+            .subcommand(App::new("submit")
+                .about("Adds the render of the settings to the queue")
+                .args(get_settings_args())
+                .arg(Arg::new("job_priority").long("priority").value_name("PRIORITY").takes_value(true).allow_hyphen_values(true).required(false)))
+            .subcommand(App::new("list")
+                .about("Lists the jobs in the queue")
+                .args(get_settings_args()))
+            .subcommand(App::new("priority")
+                .about("Changes the priority of a queued job, which moves it in the queue")
+                .args(get_settings_args())
+                .arg(Arg::new("job_id").long("id").value_name("ID").takes_value(true).required(true))
+                .arg(Arg::new("job_priority").long("priority").value_name("PRIORITY").takes_value(true).allow_hyphen_values(true).required(true)))
+            .subcommand(App::new("cancel")
+                .about("Cancels a job, stopping it if it is being rendered")
+                .args(get_settings_args())
+                .arg(Arg::new("job_id").long("id").value_name("ID").takes_value(true).required(true)))
+            .subcommand(App::new("run")
+                .about("Renders the jobs in the queue until stopped, waiting for more once it is empty")
+                .args(get_settings_args())),
+        App::new("presets")
+            .about("Lists the built in locations which can be rendered with --preset"),
+        App::new("schema")
+            .about("Prints the JSON schema of the settings files"),
+        App::new("preview")
+            .about("Renders a small preview of the location and shows it in the terminal")
+            .args(get_settings_args())
+            .arg(Arg::new("preview_terminal").long("mode").value_name("MODE").takes_value(true).possible_values(&["ansi", "sixel"]).required(false)),
+        App::new("recolor")
+            .about("Colours the EXR files in the output directory")
+            .args(get_settings_args()),
+        App::new("interpolate")
+            .about("Generates video frames by interpolating between the EXR keyframes in the output directory")
+            .args(get_settings_args()),
+        App::new("bench")
+            .about("Renders the built-in benchmark locations and reports the timings, optionally saving them as JSON")
+            .args(get_settings_args())
+            .arg(Arg::new("bench").long("output").value_name("OUTPUT").takes_value(true).required(false)),
+        App::new("batch")
+            .about("Renders every location in a file with the same settings, optionally saving a report as JSON")
+            .args(get_settings_args())
+            .arg(Arg::new("batch").long("locations").value_name("FILE").takes_value(true).required(true))
+            .arg(Arg::new("batch_output").long("output").value_name("OUTPUT").takes_value(true).required(false)),
+        App::new("compare")
+            .about("Compares two EXR exports or images pixel by pixel and saves a difference image")
+            .args(get_settings_args())
+            .arg(Arg::new("compare").long("images").value_names(&["FIRST", "SECOND"]).takes_value(true).number_of_values(2).required(true)),
+        App::new("serve")
+            .about("Listens on the address for render jobs posted over HTTP")
+            .args(get_settings_args())
+            .arg(address("serve").long("address")),
+        App::new("tiles")
+            .about("Listens on the address and renders the tiles of a slippy map of the location on demand")
+            .args(get_settings_args())
+            .arg(address("tile_server").long("address")),
+        App::new("coordinator")
+            .about("Listens on the address and distributes the frames of the sequence to the workers which connect")
+            .args(get_settings_args())
+            .arg(address("coordinator").long("address")),
+        App::new("worker")
+            .about("Connects to a coordinator at the address and renders the frames it sends")
+            .args(get_settings_args())
+            .arg(address("worker").long("address")),
+        App::new("script")
+            .about("Runs a Rhai script which sets up and renders locations")
+            .args(get_settings_args())
+            .arg(Arg::new("script").long("file").value_name("FILE").takes_value(true).required(true)),
+    ]
+}
+
+// The subcommand which the legacy flags select, rendering the sequence if there are none
+fn get_legacy_mode(matches: &ArgMatches) -> &'static str {
+    let modes = [
+        ("interpolate", "interpolate"),
+        ("colour_exr", "recolor"),
+        ("worker", "worker"),
+        ("serve", "serve"),
+        ("script", "script"),
+        ("tile_server", "tiles"),
+        ("coordinator", "coordinator"),
+        ("compare", "compare"),
+        ("bench", "bench"),
+        ("preview_terminal", "preview"),
+        ("dry_run", "estimate"),
+    ];
+
+    modes.iter()
+        .find(|(flag, _)| matches.is_present(*flag))
+        .map(|(_, mode)| *mode)
+        .unwrap_or("sequence")
+}
+
+// The coordinates and zooms are kept as strings, as they can have more digits than a float
+const STRING_KEYS: [&str; 6] = ["real", "imag", "zoom", "end_zoom", "julia_real", "julia_imag"];
+
+// The values are typed the same way as in the settings files, so that numbers and booleans are not read as strings
+fn set_value(settings: &mut Config, key: &str, value: &str) {
+    if STRING_KEYS.contains(&key) {
+        settings.set(key, value).unwrap();
+    } else if let Ok(value) = value.parse::<i64>() {
+        settings.set(key, value).unwrap();
+    } else if let Ok(value) = value.parse::<f64>() {
+        settings.set(key, value).unwrap();
+    } else if let Ok(value) = value.parse::<bool>() {
+        settings.set(key, value).unwrap();
+    } else {
+        settings.set(key, value).unwrap();
+    }
+}
+
+// A location piped on stdin, either in the format of the location files, as a Kalles Fraktaler location or as lines
+// of "key: value", so that coordinates can be pasted without writing a file
+fn merge_stdin_location(settings: &mut Config) {
+    let mut text = String::new();
+    io::stdin().read_to_string(&mut text).expect("failed to read the location from stdin");
+
+    // The location is checked on its own first, as a source which fails to parse is kept in the settings
+    if Config::default().merge(File::from_str(&text, FileFormat::Toml)).is_ok() {
+        settings.merge(File::from_str(&text, FileFormat::Toml)).unwrap();
+        return;
+    }
+
+    if text.lines().any(|line| line.trim_start().starts_with("Re:")) {
+        apply_kfr(settings, &text);
+        return;
+    }
+
+    for line in text.lines().map(|line| line.trim()).filter(|line| !line.is_empty() && !line.starts_with('#')) {
+        let (key, value) = line.split_once(&[':', '='][..]).unwrap_or_else(|| panic!("{} is not of the form KEY: VALUE", line));
+        let value = value.trim().trim_matches('"');
+
+        let key = key.trim().to_ascii_lowercase();
+
+        set_value(settings, &key, value);
+    }
+}
+
+// Whether File::with_name would find the settings file, which can be given without its extension
+fn settings_file_exists(name: &str) -> bool {
+    Path::new(name).is_file() || ["toml", "json", "yaml", "yml", "hjson", "ini"].iter()
+        .any(|extension| Path::new(&format!("{}.{}", name, extension)).is_file())
+}
 
+fn get_settings(matches: &ArgMatches) -> Config {
     let mut settings = Config::default();
 
     if let Some(p) = matches.value_of("options") {
@@ -48,18 +495,941 @@ fn main() {
     };
 
     if let Some(p) = matches.value_of("palette") {
-        settings.merge(File::with_name(p).required(true)).unwrap();
+        // A file with the same name as a built in palette is still read as the file, as it would be without the name
+        if is_palette_name(p) && !settings_file_exists(p) {
+            settings.set("palette_name", p).unwrap();
+        } else {
+            if is_palette_name(p) {
+                eprintln!("Warning: {} is read from the file rather than as the built in palette", p);
+            }
+
+            settings.merge(File::with_name(p).required(true)).unwrap();
+        }
+    };
+
+    if let Some("-") = matches.value_of("INPUT") {
+        merge_stdin_location(&mut settings);
+    } else if let Some(l) = matches.value_of("INPUT") {
+        merge_location_file(&mut settings, l);
+
+        // The location name is used in the output filename template
+        if let Some(stem) = Path::new(l).file_stem() {
+            settings.set_default("name", stem.to_string_lossy().to_string()).unwrap();
+        }
+    };
+
+    if let Some(preset) = matches.value_of("preset") {
+        apply_preset(&mut settings, preset);
+        settings.set_default("name", preset).unwrap();
     };
 
-    if let Some(l) = matches.value_of("INPUT") {
-        settings.merge(File::with_name(l).required(true)).unwrap();
+    if let Some(i) = matches.value_of("rerender") {
+        apply_metadata(&mut settings, &read_metadata(i));
     };
 
-    if matches.is_present("colour_exr") {
-        let colouring = RecolourExr::new(settings);
-        colouring.colour();
+    RenderParameters::apply_environment(&mut settings);
+
+    // A size which is not a number is kept as text, so that it is reported by the validation
+    if let Some(w) = matches.value_of("width") {
+        set_value(&mut settings, "image_width", w);
+    };
+
+    if let Some(h) = matches.value_of("height") {
+        set_value(&mut settings, "image_height", h);
+    };
+
+    if let Some(values) = matches.values_of("region") {
+        let region = values.map(|value| value.parse::<f64>().expect("region must be four numbers")).collect::<Vec<f64>>();
+        settings.set("region", region).unwrap();
+    };
+
+    if let Some(format) = matches.value_of("log_format") {
+        settings.set("log_format", format).unwrap();
+    };
+
+    // The centre and zoom are kept as strings, as they can have more digits than a float
+    for key in &["real", "imag", "zoom", "export"] {
+        if let Some(value) = matches.value_of(*key) {
+            settings.set(key, value).unwrap();
+        }
+    }
+
+    if let Some(i) = matches.value_of("iterations") {
+        if i.eq_ignore_ascii_case("auto") {
+            settings.set("iterations", "auto").unwrap();
+        } else {
+            settings.set("iterations", i.parse::<i64>().expect("iterations must be an integer or auto")).unwrap();
+        }
+    };
+
+    if let Some(r) = matches.value_of("rotate") {
+        settings.set("rotate", r.parse::<f64>().expect("rotate must be a number")).unwrap();
+    };
+
+    if let Some(f) = matches.value_of("frames") {
+        settings.set("frames", f.parse::<i64>().expect("frames must be an integer")).unwrap();
+    };
+
+    if let Some(values) = matches.values_of("set") {
+        for value in values {
+            let (key, value) = value.split_once('=').unwrap_or_else(|| panic!("{} is not of the form KEY=VALUE", value));
+            set_value(&mut settings, key.trim(), value.trim());
+        }
+    };
+
+    if let Some(t) = matches.value_of("threads") {
+        settings.set("threads", t.parse::<i64>().expect("threads must be an integer")).unwrap();
+    };
+
+    if matches.is_present("pin_threads") {
+        settings.set("pin_threads", true).unwrap();
+    };
+
+    if matches.is_present("force") {
+        settings.set("overwrite_protection", false).unwrap();
+    };
+
+    if matches.is_present("resume") {
+        settings.set("resume", true).unwrap();
+    };
+
+    if let Some(filename) = matches.value_of("continue_from") {
+        settings.set("continue_from", filename).unwrap();
+    };
+
+    if matches.is_present("deterministic") {
+        settings.set("deterministic", true).unwrap();
+    };
+
+    settings
+}
+
+fn main() {
+    let matches = App::new(crate_name!())
+        .about(crate_description!())
+        .version(crate_version!())
+        .args(get_settings_args())
+        .args(get_legacy_args())
+        .subcommands(get_subcommands())
+        .get_matches();
+
+    let (mode, matches) = match matches.subcommand() {
+        // The bookmark commands are one level further down, and have the settings arguments there
+        Some(("bookmark", bookmark)) => match bookmark.subcommand() {
+            Some(("add", subcommand)) => ("bookmark_add", subcommand),
+            Some(("list", subcommand)) => ("bookmark_list", subcommand),
+            Some((_, subcommand)) => ("bookmark_render", subcommand),
+            None => unreachable!()
+        },
+        Some(("queue", queue)) => match queue.subcommand() {
+            Some(("submit", subcommand)) => ("queue_submit", subcommand),
+            Some(("list", subcommand)) => ("queue_list", subcommand),
+            Some(("priority", subcommand)) => ("queue_priority", subcommand),
+            Some(("cancel", subcommand)) => ("queue_cancel", subcommand),
+            Some((_, subcommand)) => ("queue_run", subcommand),
+            None => unreachable!()
+        },
+        Some((name, subcommand)) => (name, subcommand),
+        None => (get_legacy_mode(&matches), &matches)
+    };
+
+    let mut settings = get_settings(matches);
+
+    // The benchmark has its own locations, and the comparison and workers do not use the settings
+    if !["schema", "presets", "compare", "worker", "bench"].contains(&mode) {
+        check_settings(&settings);
+    }
+
+    // Every renderer of the run, such as those of a batch or the bookmarks, saves to the same folder. A submitted job
+    // is given the folder of the run which renders it.
+    let run_folder = get_run_folder();
+
+    if mode != "queue_submit" {
+        FractalRenderer::apply_run_folder(&mut settings, &run_folder);
+    }
+
+    match mode {
+        "interpolate" => {
+            let interpolator = KeyframeInterpolator::new(settings);
+            interpolator.interpolate();
+        },
+        "recolor" => {
+            let colouring = RecolourExr::new(settings);
+            colouring.colour();
+        },
+        "worker" => {
+            if let Err(error) = run_worker(matches.value_of("worker").unwrap()) {
+                eprintln!("{}", error);
+                process::exit(1);
+            }
+        },
+        "serve" => run_server(matches.value_of("serve").unwrap(), settings),
+        "script" => {
+            settings.set("show_output", matches.is_present("verbose")).unwrap();
+
+            if let Err(error) = run_script(matches.value_of("script").unwrap(), settings) {
+                eprintln!("{}", error);
+                process::exit(1);
+            }
+        },
+        "tiles" => {
+            if let Err(error) = run_tile_server(matches.value_of("tile_server").unwrap(), settings) {
+                eprintln!("{}", error);
+                process::exit(1);
+            }
+        },
+        "coordinator" => {
+            let mut renderer = FractalRenderer::new(settings);
+            renderer.render_distributed(matches.value_of("coordinator").unwrap());
+        },
+        "compare" => {
+            let mut files = matches.values_of("compare").unwrap();
+
+            let comparison = ImageComparison::new(files.next().unwrap(), files.next().unwrap()).unwrap_or_else(|error| {
+                eprintln!("{}", error);
+                process::exit(1);
+            });
+
+            let result = comparison.get_result();
+
+            println!("{} of {} pixels mismatched, maximum error {}, mean error {}", 
+                result.mismatched_pixels, 
+                result.total_pixels, 
+                result.maximum_error, 
+                result.mean_error);
+
+            if let Err(error) = comparison.save_difference(&settings.get_str("compare_output").unwrap_or_else(|_| String::from("output/difference.png"))) {
+                eprintln!("{}", error);
+                process::exit(1);
+            }
+
+            // A non-zero exit code lets scripts use the comparison as a test
+            if result.mismatched_pixels > 0 {
+                process::exit(1);
+            }
+        },
+        "bench" => {
+            if let Some(output) = matches.value_of("bench") {
+                settings.set("benchmark_output", output).unwrap();
+            }
+
+            let benchmark = Benchmark::new(settings);
+            benchmark.run();
+        },
+        "batch" => {
+            settings.set("show_output", matches.is_present("verbose")).unwrap();
+
+            if let Some(output) = matches.value_of("batch_output") {
+                settings.set("batch_output", output).unwrap();
+            }
+
+            let batch = Batch::new(matches.value_of("batch").unwrap(), settings);
+
+            // A non-zero exit code lets scripts check that every location was rendered
+            if batch.run().iter().any(|result| result.error.is_some()) {
+                process::exit(1);
+            }
+        },
+        "preview" => {
+            let sixel = matches.value_of("preview_terminal") == Some("sixel");
+
+            let preview = TerminalPreview::new(settings, sixel);
+            preview.show();
+        },
+        "estimate" => {
+            let mut renderer = FractalRenderer::new(settings);
+            renderer.estimate_sequence();
+        },
+        "schema" => println!("{}", RenderParameters::get_schema()),
+        "presets" => {
+            println!(" {:<31}| {:<15}| {}", "Preset", "Zoom", "Description");
+
+            for preset in PRESET_LOCATIONS.iter() {
+                let mut location = Config::default();
+                apply_preset(&mut location, preset.name);
+
+                let zoom = location.get_str("zoom").unwrap_or_else(|_| String::from("1E0"));
+
+                println!(" {:<31}| {:<15}| {}", preset.name, extended_to_string_short(string_to_extended(&zoom.to_ascii_uppercase())), preset.description);
+            }
+        },
+        "coordinate" => {
+            let parameters = RenderParameters::from_config(&settings);
+            let zoom = string_to_extended(&parameters.zoom.to_ascii_uppercase());
+            let precision = FractalRenderer::get_precision(zoom, parameters.image_width, parameters.image_height);
+
+            let centre = ComplexArbitrary::with_val(precision, 
+                ComplexArbitrary::parse(format!("({},{})", parameters.real, parameters.imag)).expect("provided location not valid"));
+            let rotate = parameters.rotate.to_radians();
+
+            if let Some(values) = matches.values_of("pixel") {
+                let values = values.map(|value| value.parse::<f64>().expect("pixel coordinates must be numbers")).collect::<Vec<f64>>();
+                let location = pixel_to_complex(&centre, zoom, parameters.image_width, parameters.image_height, rotate, values[0], values[1]);
+
+                // Printed as a location file, so it can be piped back in to render the feature
+                println!("real = \"{}\"", location.real().to_string_radix(10, None));
+                println!("imag = \"{}\"", location.imag().to_string_radix(10, None));
+                println!("zoom = \"{}\"", parameters.zoom);
+            } else {
+                let values = matches.values_of("point").unwrap().collect::<Vec<&str>>();
+                let location = ComplexArbitrary::with_val(precision, 
+                    ComplexArbitrary::parse(format!("({},{})", values[0], values[1])).expect("provided point not valid"));
+
+                let (x, y) = complex_to_pixel(&centre, zoom, parameters.image_width, parameters.image_height, rotate, &location);
+
+                println!("{} {}", x, y);
+            }
+        },
+        "explore" => {
+            // The depth is kept as a string, as it can be beyond the range of a float
+            if let Some(depth) = matches.value_of("explore_zoom") {
+                settings.set("explore_zoom", depth).unwrap();
+            }
+
+            for key in &["explore_candidates", "explore_seed", "explore_minimum_score"] {
+                if let Some(value) = matches.value_of(*key) {
+                    set_value(&mut settings, key, value);
+                }
+            }
+
+            let mut explorer = Explorer::new(settings);
+            explorer.run();
+        },
+        "kfr" => {
+            let parameters = RenderParameters::from_config(&settings);
+
+            if parameters.julia_real.is_some() {
+                eprintln!("Warning: the julia seed is not written, as Kalles Fraktaler locations are of the Mandelbrot set");
+            }
+
+            match matches.value_of("kfr") {
+                Some(output) => save_kfr(&parameters, output),
+                None => print!("{}", get_kfr(&parameters))
+            }
+        },
+        "info" if matches.is_present("parameters") => {
+            let parameters = RenderParameters::from_config(&settings);
+            let value = serde_json::from_str::<serde_json::Value>(&parameters.to_json()).unwrap();
+
+            println!("{}", serde_json::to_string_pretty(&value).unwrap());
+        },
+        "info" => {
+            settings.set("show_output", false).unwrap();
+
+            let mut renderer = FractalRenderer::new(settings);
+            let analysis = renderer.analyse_location();
+
+            if matches.is_present("json") {
+                println!("{}", serde_json::to_string_pretty(&analysis.to_json()).unwrap());
+            } else {
+                analysis.print();
+            }
+        },
+        "history" => {
+            let filename = settings.get_str("history_file").unwrap_or_else(|_| String::from("output/history.jsonl"));
+            let entries = read_history(&filename);
+
+            let get_entry = |value: &str| {
+                let index = value.parse::<usize>().expect("the entry must be a number from the history");
+                entries.get(index).unwrap_or_else(|| panic!("the history has no entry {}", index))
+            };
+
+            if let Some(value) = matches.value_of("history_render") {
+                let settings = get_entry(value).parameters.to_config(&settings);
+
+                render(settings, matches);
+            } else if let Some(value) = matches.value_of("history_show") {
+                let entry = get_entry(value);
+
+                // Printed as a location file, so it can be piped back in to render the frame
+                println!("real = \"{}\"", entry.parameters.real);
+                println!("imag = \"{}\"", entry.parameters.imag);
+                println!("zoom = \"{}\"", entry.parameters.zoom);
+                println!("iterations = {}", entry.parameters.iterations);
+                println!("rotate = {}", entry.parameters.rotate);
+            } else {
+                let last = matches.value_of("history_last").map(|count| count.parse::<usize>().expect("last must be an integer")).unwrap_or(20);
+                let start = entries.len().saturating_sub(last);
+
+                if matches.is_present("json") {
+                    let values = entries[start..].iter().map(|entry| entry.to_json()).collect::<Vec<serde_json::Value>>();
+
+                    println!("{}", serde_json::to_string_pretty(&values).unwrap());
+                } else {
+                    println!(" {:<6}| {:<21}| {:<15}| {:<15}| {}", "Entry", "Rendered", "Zoom", "Iterations", "Output");
+
+                    for (index, entry) in entries.iter().enumerate().skip(start) {
+                        println!(" {:<6}| {:<21}| {:<15}| {:<15}| {}",
+                            index,
+                            entry.get_date(),
+                            extended_to_string_short(string_to_extended(&entry.parameters.zoom.to_ascii_uppercase())),
+                            entry.parameters.iterations,
+                            entry.output);
+                    }
+                }
+            }
+        },
+        "bookmark_add" | "bookmark_list" | "bookmark_render" => {
+            let filename = settings.get_str("bookmarks_file").unwrap_or_else(|_| String::from("bookmarks.toml"));
+            let mut bookmarks = Bookmarks::load(&filename).unwrap_or_else(|error| {
+                eprintln!("{}", error);
+                process::exit(1);
+            });
+
+            match mode {
+                "bookmark_add" => {
+                    let parameters = RenderParameters::from_config(&settings);
+                    let name = matches.value_of("bookmark_name").unwrap();
+                    let mut bookmark = Bookmark::from_parameters(name, matches.value_of("bookmark_notes").unwrap_or(""), &parameters);
+
+                    if !matches.is_present("no_thumbnail") {
+                        let thumbnail_width = settings.get_int("bookmark_thumbnail_width").unwrap_or(320).max(16) as usize;
+                        bookmarks.render_thumbnail(&mut bookmark, &settings, thumbnail_width);
+                    }
+
+                    bookmarks.add(bookmark);
+                    bookmarks.save();
+
+                    println!("Saved the bookmark {} to {}", name, filename);
+                },
+                "bookmark_list" => {
+                    println!(" {:<31}| {:<15}| {:<15}| {}", "Bookmark", "Zoom", "Iterations", "Notes");
+
+                    for bookmark in &bookmarks.bookmarks {
+                        println!(" {:<31}| {:<15}| {:<15}| {}",
+                            bookmark.name,
+                            parse_extended(&bookmark.zoom).map(extended_to_string_short).unwrap_or_else(|_| bookmark.zoom.clone()),
+                            bookmark.iterations,
+                            bookmark.notes);
+                    }
+                },
+                _ => {
+                    settings.set("show_output", matches.is_present("verbose")).unwrap();
+
+                    if let Some(output) = matches.value_of("batch_output") {
+                        settings.set("batch_output", output).unwrap();
+                    }
+
+                    if bookmarks.bookmarks.is_empty() {
+                        println!("There are no bookmarks in {}", filename);
+                        return;
+                    }
+
+                    // The bookmarks file is a locations file, so the whole collection is rendered as a batch
+                    let mut batch = Batch::new(&filename, settings);
+
+                    if let Some(names) = matches.values_of("bookmark_name") {
+                        let names = names.collect::<Vec<&str>>();
+
+                        for name in names.iter().filter(|name| bookmarks.get(name).is_none()) {
+                            eprintln!("Warning: there is no bookmark named {}", name);
+                        }
+
+                        batch.locations.retain(|location| names.contains(&location.name.as_str()));
+                    }
+
+                    if batch.run().iter().any(|result| result.error.is_some()) {
+                        process::exit(1);
+                    }
+                }
+            }
+        },
+        "queue_submit" | "queue_list" | "queue_priority" | "queue_cancel" | "queue_run" => {
+            let filename = settings.get_str("queue_file").unwrap_or_else(|_| String::from("output/queue.json"));
+
+            // The changes are made under the lock, so that they are not lost to the worker or another submission
+            let lock = if ["queue_submit", "queue_priority", "queue_cancel"].contains(&mode) {
+                Some(QueueLock::acquire(&filename))
+            } else {
+                None
+            };
+
+            // A queue which cannot be read is reported rather than saved over
+            let mut queue = match JobQueue::load(&filename) {
+                Ok(queue) => queue,
+                Err(error) => {
+                    eprintln!("{}", error);
+                    drop(lock);
+                    process::exit(1);
+                }
+            };
+
+            let get_id = || matches.value_of("job_id").unwrap().parse::<usize>().expect("the id must be an integer");
+            let get_priority = || matches.value_of("job_priority").map(|priority| priority.parse::<i64>().expect("the priority must be an integer"));
+
+            match mode {
+                "queue_submit" => {
+                    let name = RenderParameters::from_config(&settings).name;
+                    let id = queue.submit(&settings, &name, get_priority().unwrap_or(0));
+                    queue.save();
+
+                    println!("Submitted job {} ({})", id, name);
+                },
+                "queue_list" => {
+                    let order = queue.get_order();
+
+                    println!(" {:<6}| {:<31}| {:<10}| {:<10}| {}", "Job", "Name", "Priority", "Position", "Status");
+
+                    for job in &queue.jobs {
+                        let position = order.iter().position(|&id| id == job.id).map(|position| (position + 1).to_string()).unwrap_or_else(|| String::from("-"));
+
+                        println!(" {:<6}| {:<31}| {:<10}| {:<10}| {}{}",
+                            job.id,
+                            job.name,
+                            job.priority,
+                            position,
+                            job.status.to_str(),
+                            job.error.as_ref().map(|error| format!(": {}", error)).unwrap_or_default());
+                    }
+                },
+                "queue_priority" | "queue_cancel" => {
+                    let id = get_id();
+
+                    let changed = if mode == "queue_priority" {
+                        queue.set_priority(id, get_priority().unwrap())
+                    } else {
+                        queue.cancel(id)
+                    };
+
+                    if !changed {
+                        eprintln!("Job {} is not in the queue or has already finished", id);
+                        drop(lock);
+                        process::exit(1);
+                    }
+
+                    queue.save();
+                },
+                _ => {
+                    let stop_flag = Arc::new(AtomicBool::new(false));
+                    let handler_flag = stop_flag.clone();
+
+                    // The first interrupt stops the current job and queues it again, a second one exits immediately
+                    ctrlc::set_handler(move || {
+                        if handler_flag.swap(true, Ordering::SeqCst) {
+                            process::exit(130);
+                        }
+                    }).unwrap();
+
+                    if let Err(error) = run_queue(&filename, &run_folder, stop_flag) {
+                        eprintln!("{}", error);
+                        process::exit(1);
+                    }
+                }
+            }
+        },
+        _ if matches.is_present("watch") => watch(matches),
+        _ => {
+            if mode == "render" {
+                settings.set("frames", 1i64).unwrap();
+            }
+
+            render(settings, matches);
+        }
+    }
+}
+
+// Reports every problem with the settings before any work starts, exiting if the render could not run
+fn check_settings(settings: &Config) {
+    let validation = Validation::check(settings);
+    let log_json = settings.get_str("log_format").map(|format| format.eq_ignore_ascii_case("json")).unwrap_or(false);
+
+    for warning in &validation.warnings {
+        log_warning(log_json, warning);
+    }
+
+    for error in &validation.errors {
+        log_error(log_json, error);
+    }
+
+    if !validation.is_valid() {
+        process::exit(1);
+    }
+}
+
+// The files are polled rather than watched, and an edit which leaves the settings invalid is reported without stopping,
+// so the files can be saved part way through a change
+fn watch(matches: &ArgMatches) {
+    let files = ["options", "palette", "INPUT"].iter()
+        .filter_map(|key| matches.value_of(*key))
+        .filter(|filename| *filename != "-")
+        .map(|filename| filename.to_owned())
+        .collect::<Vec<String>>();
+
+    let get_modified = || files.iter()
+        .map(|filename| fs::metadata(filename).and_then(|metadata| metadata.modified()).ok())
+        .collect::<Vec<_>>();
+
+    let mut modified = get_modified();
+
+    loop {
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let mut settings = get_settings(matches);
+            let validation = Validation::check(&settings);
+
+            for warning in &validation.warnings {
+                eprintln!("Warning: {}", warning);
+            }
+
+            for error in &validation.errors {
+                eprintln!("Error: {}", error);
+            }
+
+            if !validation.is_valid() {
+                return;
+            }
+
+            let image_width = settings.get_int("image_width").unwrap_or(1280) as f64;
+            let image_height = settings.get_int("image_height").unwrap_or(720) as f64;
+            let watch_width = (settings.get_int("watch_width").unwrap_or(640) as f64).min(image_width);
+
+            settings.set("image_width", watch_width as i64).unwrap();
+            settings.set("image_height", ((watch_width * image_height / image_width) as i64).max(2)).unwrap();
+            settings.set("frames", 1i64).unwrap();
+            settings.set("show_output", false).unwrap();
+
+            // The preview has its own file next to the outputs, so the frames rendered at full size are not replaced
+            let template = settings.get_str("filename_template").unwrap_or_else(|_| String::from("output/{index}_{zoom}"));
+            let preview = Path::new(&template).with_file_name("{name}_preview");
+            settings.set("filename_template", preview.to_string_lossy().to_string()).unwrap();
+
+            let mut renderer = FractalRenderer::new(settings);
+            renderer.render();
+
+            println!("{} by {} preview rendered in {} ms, waiting for changes", renderer.image_width, renderer.image_height, renderer.render_time);
+        }));
+
+        if result.is_err() {
+            eprintln!("The render failed, waiting for changes");
+        }
+
+        while get_modified() == modified {
+            thread::sleep(Duration::from_millis(250));
+        }
+
+        modified = get_modified();
+    }
+}
+
+fn render(mut settings: Config, matches: &ArgMatches) {
+    // The table of stage timings is only printed in verbose mode, otherwise the progress is reported by the display thread.
+    // The JSON log replaces both, so that only events are written, unless the progress is also wanted as JSON.
+    let log_json = settings.get_str("log_format").map(|format| format.eq_ignore_ascii_case("json")).unwrap_or(false);
+    let verbose = matches.is_present("verbose") && !matches.is_present("quiet") && !matches.is_present("json_progress") && !log_json;
+    settings.set("show_output", verbose).unwrap();
+
+    let mut renderer = FractalRenderer::new(settings);
+    let stop_flag = renderer.get_stop_flag();
+
+    let display = if verbose || matches.is_present("quiet") || (log_json && !matches.is_present("json_progress")) {
+        None
+    } else if matches.is_present("tui") && !log_json {
+        let receiver = renderer.get_progress_receiver();
+        let regions = renderer.get_region_receiver();
+        let image_width = renderer.image_width;
+        let image_height = renderer.image_height;
+
+        Some(thread::spawn(move || display_dashboard(receiver, regions, image_width, image_height)))
     } else {
-        let mut renderer = FractalRenderer::new(settings);
-        renderer.render();
+        let receiver = renderer.get_progress_receiver();
+        let json = matches.is_present("json_progress");
+
+        Some(thread::spawn(move || display_progress(receiver, json, log_json)))
+    };
+
+    // The first interrupt stops the render cooperatively so that the partial output can be saved, a second one exits immediately
+    ctrlc::set_handler(move || {
+        if stop_flag.swap(true, Ordering::SeqCst) {
+            process::exit(130);
+        }
+    }).unwrap();
+
+    renderer.render();
+
+    if renderer.cancelled {
+        renderer.save_partial_output();
+    }
+
+    let cancelled = renderer.cancelled;
+
+    // The progress channel is closed once the renderer is dropped
+    drop(renderer);
+
+    if let Some(display) = display {
+        display.join().unwrap();
+    }
+
+    if cancelled {
+        process::exit(130);
+    }
+}
+
+// Shows a bar for each frame which is restarted for every stage, or prints each report as a line of JSON. With the JSON
+// log the reports are written as progress events, so that every line has the same fields.
+fn display_progress(receiver: mpsc::Receiver<ProgressReport>, json: bool, log_json: bool) {
+    if json {
+        for report in receiver {
+            if log_json {
+                log_event("info", "progress", report.to_json());
+            } else {
+                println!("{}", report.to_json());
+            }
+        }
+
+        return;
+    }
+
+    let style = ProgressStyle::default_bar()
+        .template(" {prefix:<15} {msg:<36} [{bar:40}] {percent:>3}%")
+        .progress_chars("=> ");
+
+    let mut current: Option<(String, RenderStage, ProgressBar)> = None;
+
+    for report in receiver {
+        let same_frame = matches!(&current, Some((zoom, _, _)) if *zoom == report.zoom);
+
+        if same_frame {
+            if let Some((_, stage, bar)) = &mut current {
+                if *stage != report.stage {
+                    *stage = report.stage;
+                    bar.reset();
+                }
+            }
+        } else {
+            if let Some((_, _, bar)) = current.take() {
+                bar.finish();
+            }
+
+            let bar = ProgressBar::new(1000);
+            bar.set_style(style.clone());
+            bar.set_prefix(report.zoom.clone());
+
+            current = Some((report.zoom.clone(), report.stage, bar));
+        }
+
+        if let Some((_, _, bar)) = &current {
+            let eta = match report.stage_eta {
+                Some(eta) => format!("{}s remaining", eta.as_secs()),
+                None => String::new()
+            };
+
+            bar.set_message(format!("{:<20} {}", format!("{:?}", report.stage), eta));
+            bar.set_position((1000.0 * report.stage_fraction) as u64);
+        }
+    }
+
+    if let Some((_, _, bar)) = current {
+        bar.finish();
+    }
+}
+
+const STAGES: [RenderStage; 5] = [RenderStage::Reference, RenderStage::SeriesApproximation, RenderStage::Iteration, RenderStage::Correction, RenderStage::Saving];
+
+// The state of the frame being rendered, which is reset when the reports of the next frame arrive
+struct Dashboard {
+    report: Option<ProgressReport>,
+    completed_frames: usize,
+    image_width: usize,
+    image_height: usize,
+    rgb: Vec<u8>,
+    iterations: Vec<u32>,
+}
+
+impl Dashboard {
+    fn new(image_width: usize, image_height: usize) -> Self {
+        Dashboard {
+            report: None,
+            completed_frames: 0,
+            image_width,
+            image_height,
+            rgb: vec![0u8; 3 * image_width * image_height],
+            iterations: vec![u32::MAX; image_width * image_height],
+        }
+    }
+
+    fn update_report(&mut self, report: ProgressReport) {
+        if let Some(previous) = &self.report {
+            if previous.zoom != report.zoom {
+                self.completed_frames += 1;
+                self.rgb.iter_mut().for_each(|value| *value = 0);
+                self.iterations.iter_mut().for_each(|value| *value = u32::MAX);
+            }
+        }
+
+        self.report = Some(report);
+    }
+
+    fn update_region(&mut self, region: CompletedRegion) {
+        for j in 0..region.height {
+            let start = (region.y + j) * self.image_width + region.x;
+
+            self.rgb[(3 * start)..(3 * (start + region.width))].copy_from_slice(&region.rgb[(3 * j * region.width)..(3 * (j + 1) * region.width)]);
+            self.iterations[start..(start + region.width)].copy_from_slice(&region.iterations[(j * region.width)..((j + 1) * region.width)]);
+        }
+    }
+
+    fn draw<B: Backend>(&self, frame: &mut Frame<B>) {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(4), Constraint::Length(2 + STAGES.len() as u16), Constraint::Min(8)])
+            .split(frame.size());
+
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(rows[2]);
+
+        frame.render_widget(self.get_summary(), rows[0]);
+        self.draw_stages(frame, rows[1]);
+        self.draw_histogram(frame, columns[0]);
+        self.draw_preview(frame, columns[1]);
     }
-}
\ No newline at end of file
+
+    fn get_summary(&self) -> Paragraph {
+        let lines = match &self.report {
+            Some(report) => vec![
+                Spans::from(format!("zoom {}   frame {}   elapsed {}s", report.zoom, self.completed_frames + 1, report.elapsed.as_secs())),
+                Spans::from(format!("pixels {} of {}   glitched {}   reference {} of {}", 
+                    report.pixels_iterated, 
+                    report.total_pixels, 
+                    report.glitched_remaining, 
+                    report.reference_iteration, 
+                    report.reference_maximum)),
+            ],
+            None => vec![Spans::from("starting")]
+        };
+
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title("rust-fractal"))
+    }
+
+    fn draw_stages<B: Backend>(&self, frame: &mut Frame<B>, area: Rect) {
+        let block = Block::default().borders(Borders::ALL).title("stages");
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let current = self.report.as_ref().and_then(|report| STAGES.iter().position(|stage| *stage == report.stage).map(|index| (index, report)));
+
+        for (index, stage) in STAGES.iter().enumerate() {
+            let (ratio, label) = match current {
+                Some((current, _)) if index < current => (1.0, String::from("done")),
+                Some((current, report)) if index == current => {
+                    let eta = report.stage_eta.map(|eta| format!(", {}s remaining", eta.as_secs())).unwrap_or_default();
+                    (report.stage_fraction.max(0.0).min(1.0), format!("{:.1}%{}", 100.0 * report.stage_fraction, eta))
+                },
+                _ => (0.0, String::new())
+            };
+
+            let gauge = Gauge::default()
+                .gauge_style(Style::default().fg(Color::Cyan))
+                .ratio(ratio)
+                .label(format!("{:<20} {}", format!("{:?}", stage), label));
+
+            frame.render_widget(gauge, Rect::new(inner.x, inner.y + index as u16, inner.width, 1));
+        }
+    }
+
+    // A sample of the pixels which have been iterated, the pixels inside the set are left out
+    fn draw_histogram<B: Backend>(&self, frame: &mut Frame<B>, area: Rect) {
+        let samples = self.iterations.iter()
+            .step_by(7)
+            .filter(|&&iteration| iteration != u32::MAX)
+            .copied()
+            .collect::<Vec<u32>>();
+
+        let maximum = samples.iter().copied().max().unwrap_or(0);
+        let escaped = samples.iter().copied().filter(|&iteration| iteration < maximum).collect::<Vec<u32>>();
+
+        let bins = ((area.width.saturating_sub(2)) / 4).max(1) as usize;
+        let minimum = escaped.iter().copied().min().unwrap_or(0);
+        let range = (escaped.iter().copied().max().unwrap_or(0) - minimum) as u64 + 1;
+
+        let mut counts = vec![0u64; bins];
+
+        // The product is taken in 64 bits so that it cannot overflow, and the index is kept within the last bin
+        for iteration in &escaped {
+            let bin = ((*iteration - minimum) as u64 * bins as u64 / range) as usize;
+            counts[bin.min(bins - 1)] += 1;
+        }
+
+        let labels = (0..bins).map(|bin| if bin == 0 { minimum.to_string() } else { String::new() }).collect::<Vec<String>>();
+        let data = labels.iter().zip(counts.iter()).map(|(label, count)| (label.as_str(), *count)).collect::<Vec<(&str, u64)>>();
+
+        let chart = BarChart::default()
+            .block(Block::default().borders(Borders::ALL).title("iterations"))
+            .data(&data)
+            .bar_width(3)
+            .bar_gap(1)
+            .bar_style(Style::default().fg(Color::Yellow));
+
+        frame.render_widget(chart, area);
+    }
+
+    // Each character shows two pixels sampled from the image, with the upper half block in the upper colour
+    fn draw_preview<B: Backend>(&self, frame: &mut Frame<B>, area: Rect) {
+        let block = Block::default().borders(Borders::ALL).title("preview");
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        if inner.width == 0 || inner.height == 0 || self.image_width == 0 || self.image_height == 0 {
+            return;
+        }
+
+        // The preview keeps the aspect of the image
+        let scale = (self.image_width as f64 / inner.width as f64).max(self.image_height as f64 / (2.0 * inner.height as f64));
+        let width = ((self.image_width as f64 / scale) as usize).max(1);
+        let height = ((self.image_height as f64 / (2.0 * scale)) as usize).max(1);
+
+        let colour = |i: usize, j: usize| {
+            let x = ((i as f64 * scale) as usize).min(self.image_width - 1);
+            let y = ((j as f64 * scale) as usize).min(self.image_height - 1);
+            let index = 3 * (y * self.image_width + x);
+
+            Color::Rgb(self.rgb[index], self.rgb[index + 1], self.rgb[index + 2])
+        };
+
+        let lines = (0..height).map(|row| {
+            Spans::from((0..width).map(|column| {
+                Span::styled("\u{2580}", Style::default().fg(colour(column, 2 * row)).bg(colour(column, 2 * row + 1)))
+            }).collect::<Vec<Span>>())
+        }).collect::<Vec<Spans>>();
+
+        frame.render_widget(Paragraph::new(lines), inner);
+    }
+}
+
+// Draws the dashboard on the alternate screen until the progress channel is closed at the end of the render
+fn display_dashboard(receiver: mpsc::Receiver<ProgressReport>, regions: mpsc::Receiver<CompletedRegion>, image_width: usize, image_height: usize) {
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen, Hide).unwrap();
+
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout)).unwrap();
+    let mut dashboard = Dashboard::new(image_width, image_height);
+
+    loop {
+        let mut finished = false;
+
+        loop {
+            match receiver.try_recv() {
+                Ok(report) => dashboard.update_report(report),
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    finished = true;
+                    break;
+                }
+            }
+        }
+
+        for region in regions.try_iter() {
+            dashboard.update_region(region);
+        }
+
+        terminal.draw(|frame| dashboard.draw(frame)).unwrap();
+
+        if finished {
+            break;
+        }
+
+        thread::sleep(Duration::from_millis(200));
+    }
+
+    execute!(terminal.backend_mut(), LeaveAlternateScreen, Show).unwrap();
+}
@@ -1,3 +0,0 @@
-pub mod renderer;
-pub mod util;
-pub mod math;
\ No newline at end of file